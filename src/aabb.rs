@@ -0,0 +1,416 @@
+use crate::mat::{Mat3, Mat4};
+use crate::point::{Point2, Point3};
+use crate::ray::{Ray2, Ray3};
+use crate::vec::{Vec2, Vec3};
+
+/// Slab test for one axis, narrowing `[t_min, t_max]`. Returns false once the interval is empty.
+fn clip_slab(origin: f64, dir: f64, min: f64, max: f64, t_min: &mut f64, t_max: &mut f64) -> bool {
+    if dir == 0.0 {
+        return origin >= min && origin <= max;
+    }
+    let inv = 1.0 / dir;
+    let mut t0 = (min - origin) * inv;
+    let mut t1 = (max - origin) * inv;
+    if t0 > t1 {
+        std::mem::swap(&mut t0, &mut t1);
+    }
+    *t_min = t_min.max(t0);
+    *t_max = t_max.min(t1);
+    t_min <= t_max
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb2 {
+    pub min: Point2,
+    pub max: Point2,
+}
+
+impl Aabb2 {
+    pub fn new(min: Point2, max: Point2) -> Aabb2 {
+        Aabb2 { min, max }
+    }
+
+    /// The empty box: the identity for `union` and `include`.
+    pub fn empty() -> Aabb2 {
+        Aabb2 {
+            min: Point2::new(f64::INFINITY, f64::INFINITY),
+            max: Point2::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    pub fn from_points<I: IntoIterator<Item = Point2>>(points: I) -> Aabb2 {
+        let mut aabb = Aabb2::empty();
+        for p in points {
+            aabb.include(&p);
+        }
+        aabb
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min[0] > self.max[0] || self.min[1] > self.max[1]
+    }
+
+    /// Grows the box so that it contains `p`.
+    pub fn include(&mut self, p: &Point2) {
+        for i in 0..2 {
+            self.min[i] = self.min[i].min(p[i]);
+            self.max[i] = self.max[i].max(p[i]);
+        }
+    }
+
+    pub fn union(&self, rhs: &Aabb2) -> Aabb2 {
+        Aabb2::new(
+            Point2::new(self.min[0].min(rhs.min[0]), self.min[1].min(rhs.min[1])),
+            Point2::new(self.max[0].max(rhs.max[0]), self.max[1].max(rhs.max[1])),
+        )
+    }
+
+    pub fn intersection(&self, rhs: &Aabb2) -> Option<Aabb2> {
+        let aabb = Aabb2::new(
+            Point2::new(self.min[0].max(rhs.min[0]), self.min[1].max(rhs.min[1])),
+            Point2::new(self.max[0].min(rhs.max[0]), self.max[1].min(rhs.max[1])),
+        );
+        if aabb.is_empty() {
+            None
+        } else {
+            Some(aabb)
+        }
+    }
+
+    pub fn contains(&self, p: &Point2) -> bool {
+        (0..2).all(|i| p[i] >= self.min[i] && p[i] <= self.max[i])
+    }
+
+    pub fn contains_aabb(&self, rhs: &Aabb2) -> bool {
+        (0..2).all(|i| rhs.min[i] >= self.min[i] && rhs.max[i] <= self.max[i])
+    }
+
+    pub fn overlaps(&self, rhs: &Aabb2) -> bool {
+        (0..2).all(|i| self.min[i] <= rhs.max[i] && rhs.min[i] <= self.max[i])
+    }
+
+    /// Returns the box grown by `margin` on every side.
+    pub fn expand(&self, margin: f64) -> Aabb2 {
+        Aabb2::new(self.min - Vec2::all(margin), self.max + Vec2::all(margin))
+    }
+
+    pub fn center(&self) -> Point2 {
+        Point2::new(
+            (self.min[0] + self.max[0]) * 0.5,
+            (self.min[1] + self.max[1]) * 0.5,
+        )
+    }
+
+    /// Full side lengths of the box.
+    pub fn size(&self) -> Vec2<f64> {
+        self.max - self.min
+    }
+
+    /// Half side lengths of the box.
+    pub fn extents(&self) -> Vec2<f64> {
+        self.size() * 0.5
+    }
+
+    pub fn area(&self) -> f64 {
+        let s = self.size();
+        s[0] * s[1]
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        let s = self.size();
+        2.0 * (s[0] + s[1])
+    }
+
+    pub fn closest_point(&self, p: &Point2) -> Point2 {
+        Point2::new(
+            p[0].clamp(self.min[0], self.max[0]),
+            p[1].clamp(self.min[1], self.max[1]),
+        )
+    }
+
+    /// Slab intersection. Returns the entry and exit parameters along the ray,
+    /// with the entry clamped to zero when the origin is inside the box.
+    pub fn ray_intersection(&self, ray: &Ray2) -> Option<(f64, f64)> {
+        let mut t_min = 0.0;
+        let mut t_max = f64::INFINITY;
+        for i in 0..2 {
+            if !clip_slab(
+                ray.origin[i],
+                ray.direction[i],
+                self.min[i],
+                self.max[i],
+                &mut t_min,
+                &mut t_max,
+            ) {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
+    /// Bounds the box after an affine transform (Arvo's method).
+    pub fn transform(&self, m: &Mat3<f64>) -> Aabb2 {
+        let mut min = Point2::new(m[0][2], m[1][2]);
+        let mut max = min;
+        for i in 0..2 {
+            for j in 0..2 {
+                let a = m[i][j] * self.min[j];
+                let b = m[i][j] * self.max[j];
+                min[i] += a.min(b);
+                max[i] += a.max(b);
+            }
+        }
+        Aabb2::new(min, max)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb3 {
+    pub min: Point3,
+    pub max: Point3,
+}
+
+impl Aabb3 {
+    pub fn new(min: Point3, max: Point3) -> Aabb3 {
+        Aabb3 { min, max }
+    }
+
+    /// The empty box: the identity for `union` and `include`.
+    pub fn empty() -> Aabb3 {
+        Aabb3 {
+            min: Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    pub fn from_points<I: IntoIterator<Item = Point3>>(points: I) -> Aabb3 {
+        let mut aabb = Aabb3::empty();
+        for p in points {
+            aabb.include(&p);
+        }
+        aabb
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..3).any(|i| self.min[i] > self.max[i])
+    }
+
+    /// Grows the box so that it contains `p`.
+    pub fn include(&mut self, p: &Point3) {
+        for i in 0..3 {
+            self.min[i] = self.min[i].min(p[i]);
+            self.max[i] = self.max[i].max(p[i]);
+        }
+    }
+
+    pub fn union(&self, rhs: &Aabb3) -> Aabb3 {
+        let mut aabb = *self;
+        for i in 0..3 {
+            aabb.min[i] = aabb.min[i].min(rhs.min[i]);
+            aabb.max[i] = aabb.max[i].max(rhs.max[i]);
+        }
+        aabb
+    }
+
+    pub fn intersection(&self, rhs: &Aabb3) -> Option<Aabb3> {
+        let mut aabb = *self;
+        for i in 0..3 {
+            aabb.min[i] = aabb.min[i].max(rhs.min[i]);
+            aabb.max[i] = aabb.max[i].min(rhs.max[i]);
+        }
+        if aabb.is_empty() {
+            None
+        } else {
+            Some(aabb)
+        }
+    }
+
+    pub fn contains(&self, p: &Point3) -> bool {
+        (0..3).all(|i| p[i] >= self.min[i] && p[i] <= self.max[i])
+    }
+
+    pub fn contains_aabb(&self, rhs: &Aabb3) -> bool {
+        (0..3).all(|i| rhs.min[i] >= self.min[i] && rhs.max[i] <= self.max[i])
+    }
+
+    pub fn overlaps(&self, rhs: &Aabb3) -> bool {
+        (0..3).all(|i| self.min[i] <= rhs.max[i] && rhs.min[i] <= self.max[i])
+    }
+
+    /// Returns the box grown by `margin` on every side.
+    pub fn expand(&self, margin: f64) -> Aabb3 {
+        Aabb3::new(self.min - Vec3::all(margin), self.max + Vec3::all(margin))
+    }
+
+    pub fn center(&self) -> Point3 {
+        Point3::new(
+            (self.min[0] + self.max[0]) * 0.5,
+            (self.min[1] + self.max[1]) * 0.5,
+            (self.min[2] + self.max[2]) * 0.5,
+        )
+    }
+
+    /// Full side lengths of the box.
+    pub fn size(&self) -> Vec3<f64> {
+        self.max - self.min
+    }
+
+    /// Half side lengths of the box.
+    pub fn extents(&self) -> Vec3<f64> {
+        self.size() * 0.5
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let s = self.size();
+        2.0 * (s[0] * s[1] + s[1] * s[2] + s[2] * s[0])
+    }
+
+    pub fn volume(&self) -> f64 {
+        let s = self.size();
+        s[0] * s[1] * s[2]
+    }
+
+    /// Index of the longest axis.
+    pub fn major_axis(&self) -> usize {
+        let s = self.size();
+        if s[0] >= s[1] && s[0] >= s[2] {
+            0
+        } else if s[1] >= s[2] {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn closest_point(&self, p: &Point3) -> Point3 {
+        Point3::new(
+            p[0].clamp(self.min[0], self.max[0]),
+            p[1].clamp(self.min[1], self.max[1]),
+            p[2].clamp(self.min[2], self.max[2]),
+        )
+    }
+
+    /// Slab intersection. Returns the entry and exit parameters along the ray,
+    /// with the entry clamped to zero when the origin is inside the box.
+    pub fn ray_intersection(&self, ray: &Ray3) -> Option<(f64, f64)> {
+        let mut t_min = 0.0;
+        let mut t_max = f64::INFINITY;
+        for i in 0..3 {
+            if !clip_slab(
+                ray.origin[i],
+                ray.direction[i],
+                self.min[i],
+                self.max[i],
+                &mut t_min,
+                &mut t_max,
+            ) {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
+    /// Bounds the box after an affine transform (Arvo's method).
+    pub fn transform(&self, m: &Mat4<f64>) -> Aabb3 {
+        let mut min = Point3::new(m[0][3], m[1][3], m[2][3]);
+        let mut max = min;
+        for i in 0..3 {
+            for j in 0..3 {
+                let a = m[i][j] * self.min[j];
+                let b = m[i][j] * self.max[j];
+                min[i] += a.min(b);
+                max[i] += a.max(b);
+            }
+        }
+        Aabb3::new(min, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box() -> Aabb3 {
+        Aabb3::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn test_from_points() {
+        let aabb = Aabb2::from_points(vec![
+            Point2::new(1.0, -1.0),
+            Point2::new(-2.0, 3.0),
+            Point2::new(0.5, 0.5),
+        ]);
+        assert_eq!(
+            aabb,
+            Aabb2::new(Point2::new(-2.0, -1.0), Point2::new(1.0, 3.0))
+        );
+        assert!(Aabb3::from_points(vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_union_intersection() {
+        let a = unit_box();
+        let b = Aabb3::new(Point3::new(0.5, 0.5, 0.5), Point3::new(2.0, 2.0, 2.0));
+        let c = Aabb3::new(Point3::new(3.0, 3.0, 3.0), Point3::new(4.0, 4.0, 4.0));
+        assert_eq!(a.union(&b), Aabb3::new(a.min, b.max));
+        assert_eq!(a.intersection(&b), Some(Aabb3::new(b.min, a.max)));
+        assert_eq!(a.intersection(&c), None);
+        assert!(a.overlaps(&b));
+        assert!(!a.overlaps(&c));
+        assert_eq!(Aabb3::empty().union(&a), a);
+    }
+
+    #[test]
+    fn test_measures() {
+        let a = Aabb3::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 2.0, 3.0));
+        assert_eq!(a.volume(), 6.0);
+        assert_eq!(a.surface_area(), 22.0);
+        assert_eq!(a.center(), Point3::new(0.5, 1.0, 1.5));
+        assert_eq!(a.extents(), Vec3([0.5, 1.0, 1.5]));
+        assert_eq!(a.major_axis(), 2);
+        assert!(a.expand(1.0).contains_aabb(&a));
+    }
+
+    #[test]
+    fn test_closest_point() {
+        let a = unit_box();
+        assert_eq!(
+            a.closest_point(&Point3::new(2.0, 0.5, -1.0)),
+            Point3::new(1.0, 0.5, 0.0)
+        );
+        assert_eq!(
+            a.closest_point(&Point3::new(0.2, 0.5, 0.7)),
+            Point3::new(0.2, 0.5, 0.7)
+        );
+    }
+
+    #[test]
+    fn test_ray_intersection() {
+        let a = unit_box();
+        let hit = Ray3::new(Point3::new(-1.0, 0.5, 0.5), Vec3([1.0, 0.0, 0.0]));
+        assert_eq!(a.ray_intersection(&hit), Some((1.0, 2.0)));
+        let inside = Ray3::new(Point3::new(0.5, 0.5, 0.5), Vec3([0.0, 0.0, 1.0]));
+        assert_eq!(a.ray_intersection(&inside), Some((0.0, 0.5)));
+        let miss = Ray3::new(Point3::new(-1.0, 2.0, 0.5), Vec3([1.0, 0.0, 0.0]));
+        assert_eq!(a.ray_intersection(&miss), None);
+        let behind = Ray3::new(Point3::new(2.0, 0.5, 0.5), Vec3([1.0, 0.0, 0.0]));
+        assert_eq!(a.ray_intersection(&behind), None);
+    }
+
+    #[test]
+    fn test_transform() {
+        // 90 degree rotation about z followed by a translation of (10, 0, 0).
+        let m = Mat4::new([
+            [0.0, -1.0, 0.0, 10.0],
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        let a = Aabb3::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 1.0, 1.0));
+        assert_eq!(
+            a.transform(&m),
+            Aabb3::new(Point3::new(9.0, 0.0, 0.0), Point3::new(10.0, 2.0, 1.0))
+        );
+    }
+}
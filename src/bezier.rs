@@ -0,0 +1,657 @@
+use crate::aabb::{Aabb2, Aabb3};
+use crate::curve::{Curve, CurvePoint};
+use crate::point::{Point2, Point3};
+
+/// Subdivision depth at which flattening stops regardless of the tolerance.
+const FLATTEN_MAX_DEPTH: u32 = 24;
+
+pub type QuadBezier2 = QuadBezier<Point2>;
+pub type QuadBezier3 = QuadBezier<Point3>;
+pub type CubicBezier2 = CubicBezier<Point2>;
+pub type CubicBezier3 = CubicBezier<Point3>;
+pub type RationalQuadBezier2 = RationalQuadBezier<Point2>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuadBezier<P> {
+    pub p0: P,
+    pub p1: P,
+    pub p2: P,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CubicBezier<P> {
+    pub p0: P,
+    pub p1: P,
+    pub p2: P,
+    pub p3: P,
+}
+
+/// A conic section in standard form: the end points have weight one and the control point
+/// has weight `w`. The curve is an ellipse arc for `w < 1`, a parabola for `w == 1` and a
+/// hyperbola arc for `w > 1`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RationalQuadBezier<P> {
+    pub p0: P,
+    pub p1: P,
+    pub p2: P,
+    pub w: f64,
+}
+
+/// Parameters in `(0, 1)` where `a t^2 + b t + c` vanishes.
+fn quadratic_roots(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let roots = if a.abs() <= 1e-12 * (b.abs() + c.abs()) {
+        if b == 0.0 {
+            vec![]
+        } else {
+            vec![-c / b]
+        }
+    } else {
+        let discr = b * b - 4.0 * a * c;
+        if discr < 0.0 {
+            vec![]
+        } else {
+            // Citardauq form for the smaller root avoids cancellation.
+            let q = -0.5 * (b + b.signum() * discr.sqrt());
+            if q == 0.0 {
+                vec![0.0]
+            } else {
+                vec![q / a, c / q]
+            }
+        }
+    };
+    roots.into_iter().filter(|t| *t > 0.0 && *t < 1.0).collect()
+}
+
+/// Component-wise bounds of a list of points.
+fn bounds<P: CurvePoint>(points: impl IntoIterator<Item = P>) -> (P, P) {
+    let mut points = points.into_iter();
+    let first = points.next().unwrap();
+    points.fold((first, first), |(mut min, mut max), p| {
+        for i in 0..P::DIM {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+        (min, max)
+    })
+}
+
+/// Appends the end points of the pieces of `curve` after recursive halving until
+/// `deviation` (an upper bound on the distance to the chord) is within `tolerance`.
+fn flatten_recursive<C, P, F>(
+    curve: &C,
+    tolerance: f64,
+    depth: u32,
+    points: &mut Vec<P>,
+    deviation: &F,
+) where
+    C: Split<P>,
+    P: CurvePoint,
+    F: Fn(&C) -> f64,
+{
+    if depth == 0 || deviation(curve) <= tolerance {
+        points.push(curve.end());
+        return;
+    }
+    let (left, right) = curve.split(0.5);
+    flatten_recursive(&left, tolerance, depth - 1, points, deviation);
+    flatten_recursive(&right, tolerance, depth - 1, points, deviation);
+}
+
+/// Curves that subdivide into two curves of the same kind.
+trait Split<P>: Sized {
+    fn split(&self, t: f64) -> (Self, Self);
+
+    fn end(&self) -> P;
+}
+
+impl<P: CurvePoint> QuadBezier<P> {
+    pub fn new(p0: P, p1: P, p2: P) -> QuadBezier<P> {
+        QuadBezier { p0, p1, p2 }
+    }
+
+    pub fn second_derivative(&self) -> P::Vector {
+        ((self.p2 - self.p1) - (self.p1 - self.p0)) * 2.0
+    }
+
+    /// The two halves on either side of `t`, by de Casteljau.
+    pub fn split(&self, t: f64) -> (QuadBezier<P>, QuadBezier<P>) {
+        let a = self.p0.lerp(&self.p1, t);
+        let b = self.p1.lerp(&self.p2, t);
+        let m = a.lerp(&b, t);
+        (
+            QuadBezier::new(self.p0, a, m),
+            QuadBezier::new(m, b, self.p2),
+        )
+    }
+
+    /// The part of the curve between `t0` and `t1`.
+    pub fn subcurve(&self, t0: f64, t1: f64) -> QuadBezier<P> {
+        let (_, right) = self.split(t0);
+        if t0 >= 1.0 {
+            return right;
+        }
+        right.split((t1 - t0) / (1.0 - t0)).0
+    }
+
+    /// Parameters in `(0, 1)` where some coordinate has a local extremum.
+    pub fn extrema(&self) -> Vec<f64> {
+        let mut ts = vec![];
+        for i in 0..P::DIM {
+            let (a, b) = ((self.p1 - self.p0)[i], (self.p2 - self.p1)[i]);
+            ts.extend(quadratic_roots(0.0, b - a, a));
+        }
+        ts.sort_by(f64::total_cmp);
+        ts
+    }
+
+    /// Tight component-wise bounds, from the end points and the extrema.
+    pub fn bounds(&self) -> (P, P) {
+        let ends = [self.p0, self.p2];
+        bounds(
+            ends.into_iter()
+                .chain(self.extrema().into_iter().map(|t| self.at(t))),
+        )
+    }
+
+    /// The same curve as a cubic.
+    pub fn to_cubic(&self) -> CubicBezier<P> {
+        CubicBezier::new(
+            self.p0,
+            self.p0.lerp(&self.p1, 2.0 / 3.0),
+            self.p2.lerp(&self.p1, 2.0 / 3.0),
+            self.p2,
+        )
+    }
+
+    /// Polyline from `p0` to `p2` within `tolerance` of the curve.
+    pub fn flatten(&self, tolerance: f64) -> Vec<P> {
+        let mut points = vec![self.p0];
+        self.flatten_into(tolerance, &mut points);
+        points
+    }
+
+    /// Appends the points of `flatten` after `p0`.
+    pub fn flatten_into(&self, tolerance: f64, points: &mut Vec<P>) {
+        assert!(tolerance > 0.0, "tolerance must be positive");
+        // The curve is furthest from its chord at `t = 1/2`, a quarter of the way from the
+        // chord's midpoint towards `p1`.
+        let deviation = |c: &QuadBezier<P>| {
+            let d = (c.p1 - c.p0) - (c.p2 - c.p1);
+            P::dot(&d, &d).sqrt() / 4.0
+        };
+        flatten_recursive(self, tolerance, FLATTEN_MAX_DEPTH, points, &deviation);
+    }
+}
+
+impl<P: CurvePoint> Split<P> for QuadBezier<P> {
+    fn split(&self, t: f64) -> (Self, Self) {
+        QuadBezier::split(self, t)
+    }
+
+    fn end(&self) -> P {
+        self.p2
+    }
+}
+
+impl<P: CurvePoint> Curve for QuadBezier<P> {
+    type Point = P;
+
+    fn at(&self, t: f64) -> P {
+        let a = self.p0.lerp(&self.p1, t);
+        let b = self.p1.lerp(&self.p2, t);
+        a.lerp(&b, t)
+    }
+
+    fn derivative(&self, t: f64) -> P::Vector {
+        ((self.p1 - self.p0) * (1.0 - t) + (self.p2 - self.p1) * t) * 2.0
+    }
+}
+
+impl<P: CurvePoint> CubicBezier<P> {
+    pub fn new(p0: P, p1: P, p2: P, p3: P) -> CubicBezier<P> {
+        CubicBezier { p0, p1, p2, p3 }
+    }
+
+    pub fn second_derivative(&self, t: f64) -> P::Vector {
+        let a = (self.p2 - self.p1) - (self.p1 - self.p0);
+        let b = (self.p3 - self.p2) - (self.p2 - self.p1);
+        (a * (1.0 - t) + b * t) * 6.0
+    }
+
+    /// The two halves on either side of `t`, by de Casteljau.
+    pub fn split(&self, t: f64) -> (CubicBezier<P>, CubicBezier<P>) {
+        let a = self.p0.lerp(&self.p1, t);
+        let b = self.p1.lerp(&self.p2, t);
+        let c = self.p2.lerp(&self.p3, t);
+        let ab = a.lerp(&b, t);
+        let bc = b.lerp(&c, t);
+        let m = ab.lerp(&bc, t);
+        (
+            CubicBezier::new(self.p0, a, ab, m),
+            CubicBezier::new(m, bc, c, self.p3),
+        )
+    }
+
+    /// The part of the curve between `t0` and `t1`.
+    pub fn subcurve(&self, t0: f64, t1: f64) -> CubicBezier<P> {
+        let (_, right) = self.split(t0);
+        if t0 >= 1.0 {
+            return right;
+        }
+        right.split((t1 - t0) / (1.0 - t0)).0
+    }
+
+    /// Parameters in `(0, 1)` where some coordinate has a local extremum.
+    pub fn extrema(&self) -> Vec<f64> {
+        let mut ts = vec![];
+        for i in 0..P::DIM {
+            let d0 = (self.p1 - self.p0)[i];
+            let d1 = (self.p2 - self.p1)[i];
+            let d2 = (self.p3 - self.p2)[i];
+            ts.extend(quadratic_roots(d0 - 2.0 * d1 + d2, 2.0 * (d1 - d0), d0));
+        }
+        ts.sort_by(f64::total_cmp);
+        ts
+    }
+
+    /// Tight component-wise bounds, from the end points and the extrema.
+    pub fn bounds(&self) -> (P, P) {
+        let ends = [self.p0, self.p3];
+        bounds(
+            ends.into_iter()
+                .chain(self.extrema().into_iter().map(|t| self.at(t))),
+        )
+    }
+
+    /// Polyline from `p0` to `p3` within `tolerance` of the curve.
+    pub fn flatten(&self, tolerance: f64) -> Vec<P> {
+        let mut points = vec![self.p0];
+        self.flatten_into(tolerance, &mut points);
+        points
+    }
+
+    /// Appends the points of `flatten` after `p0`.
+    pub fn flatten_into(&self, tolerance: f64, points: &mut Vec<P>) {
+        assert!(tolerance > 0.0, "tolerance must be positive");
+        // Willcocks' bound: per coordinate, the curve strays from the chord by at most a
+        // quarter of the larger of these two offsets.
+        let deviation = |c: &CubicBezier<P>| {
+            let u = (c.p1 - c.p0) * 3.0 - (c.p3 - c.p0);
+            let v = (c.p2 - c.p3) * 3.0 - (c.p0 - c.p3);
+            let square: f64 = (0..P::DIM).map(|i| (u[i] * u[i]).max(v[i] * v[i])).sum();
+            square.sqrt() / 4.0
+        };
+        flatten_recursive(self, tolerance, FLATTEN_MAX_DEPTH, points, &deviation);
+    }
+}
+
+impl<P: CurvePoint> Split<P> for CubicBezier<P> {
+    fn split(&self, t: f64) -> (Self, Self) {
+        CubicBezier::split(self, t)
+    }
+
+    fn end(&self) -> P {
+        self.p3
+    }
+}
+
+impl<P: CurvePoint> Curve for CubicBezier<P> {
+    type Point = P;
+
+    fn at(&self, t: f64) -> P {
+        let a = self.p0.lerp(&self.p1, t);
+        let b = self.p1.lerp(&self.p2, t);
+        let c = self.p2.lerp(&self.p3, t);
+        a.lerp(&b, t).lerp(&b.lerp(&c, t), t)
+    }
+
+    fn derivative(&self, t: f64) -> P::Vector {
+        let s = 1.0 - t;
+        ((self.p1 - self.p0) * (s * s)
+            + (self.p2 - self.p1) * (2.0 * s * t)
+            + (self.p3 - self.p2) * (t * t))
+            * 3.0
+    }
+}
+
+impl<P: CurvePoint> RationalQuadBezier<P> {
+    pub fn new(p0: P, p1: P, p2: P, w: f64) -> RationalQuadBezier<P> {
+        assert!(w > 0.0, "weight must be positive");
+        RationalQuadBezier { p0, p1, p2, w }
+    }
+
+    /// Weight of the curve point at `t`.
+    fn denominator(&self, t: f64) -> f64 {
+        let s = 1.0 - t;
+        s * s + 2.0 * self.w * s * t + t * t
+    }
+
+    /// The two halves on either side of `t`, renormalised to standard form.
+    pub fn split(&self, t: f64) -> (RationalQuadBezier<P>, RationalQuadBezier<P>) {
+        let s = 1.0 - t;
+        let m = self.at(t);
+        let root = self.denominator(t).sqrt();
+        let (wa, wb) = (s + t * self.w, s * self.w + t);
+        let a = self.p0.lerp(&self.p1, t * self.w / wa);
+        let b = self.p1.lerp(&self.p2, t / wb);
+        (
+            RationalQuadBezier::new(self.p0, a, m, wa / root),
+            RationalQuadBezier::new(m, b, self.p2, wb / root),
+        )
+    }
+
+    /// Polyline from `p0` to `p2` within `tolerance` of the curve.
+    pub fn flatten(&self, tolerance: f64) -> Vec<P> {
+        let mut points = vec![self.p0];
+        self.flatten_into(tolerance, &mut points);
+        points
+    }
+
+    /// Appends the points of `flatten` after `p0`.
+    pub fn flatten_into(&self, tolerance: f64, points: &mut Vec<P>) {
+        assert!(tolerance > 0.0, "tolerance must be positive");
+        // In standard form the tangent at `t = 1/2` is parallel to the chord, so that point
+        // is the furthest, `w / (1 + w)` of the way from the chord's midpoint to `p1`.
+        let deviation = |c: &RationalQuadBezier<P>| {
+            let d = (c.p1 - c.p0) - (c.p2 - c.p1);
+            P::dot(&d, &d).sqrt() / 2.0 * c.w / (1.0 + c.w)
+        };
+        flatten_recursive(self, tolerance, FLATTEN_MAX_DEPTH, points, &deviation);
+    }
+}
+
+impl<P: CurvePoint> Split<P> for RationalQuadBezier<P> {
+    fn split(&self, t: f64) -> (Self, Self) {
+        RationalQuadBezier::split(self, t)
+    }
+
+    fn end(&self) -> P {
+        self.p2
+    }
+}
+
+impl<P: CurvePoint> Curve for RationalQuadBezier<P> {
+    type Point = P;
+
+    fn at(&self, t: f64) -> P {
+        let s = 1.0 - t;
+        let offset = (self.p1 - self.p0) * (2.0 * self.w * s * t) + (self.p2 - self.p0) * (t * t);
+        self.p0 + offset * (1.0 / self.denominator(t))
+    }
+
+    fn derivative(&self, t: f64) -> P::Vector {
+        let s = 1.0 - t;
+        let d = self.denominator(t);
+        let dd = 2.0 * (t - s) + 2.0 * self.w * (s - t);
+        let n = (self.p1 - self.p0) * (2.0 * self.w * s * t) + (self.p2 - self.p0) * (t * t);
+        let dn = (self.p1 - self.p0) * (2.0 * self.w * (s - t)) + (self.p2 - self.p0) * (2.0 * t);
+        (dn * d - n * dd) * (1.0 / (d * d))
+    }
+}
+
+impl QuadBezier<Point2> {
+    pub fn aabb(&self) -> Aabb2 {
+        let (min, max) = self.bounds();
+        Aabb2::new(min, max)
+    }
+}
+
+impl QuadBezier<Point3> {
+    pub fn aabb(&self) -> Aabb3 {
+        let (min, max) = self.bounds();
+        Aabb3::new(min, max)
+    }
+}
+
+impl CubicBezier<Point2> {
+    pub fn aabb(&self) -> Aabb2 {
+        let (min, max) = self.bounds();
+        Aabb2::new(min, max)
+    }
+}
+
+impl CubicBezier<Point3> {
+    pub fn aabb(&self) -> Aabb3 {
+        let (min, max) = self.bounds();
+        Aabb3::new(min, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segment::Segment2;
+    use crate::test_util::{assert_close, random_values};
+    use crate::vec::Vec2;
+
+    fn random_cubic(c: &[f64]) -> CubicBezier2 {
+        let p = |i: usize| Point2::new(c[2 * i] * 10.0, c[2 * i + 1] * 10.0);
+        CubicBezier2::new(p(0), p(1), p(2), p(3))
+    }
+
+    /// A quarter of the unit circle, from (1, 0) to (0, 1).
+    fn quarter_circle() -> RationalQuadBezier2 {
+        RationalQuadBezier2::new(
+            Point2::new(1.0, 0.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(0.0, 1.0),
+            0.5f64.sqrt(),
+        )
+    }
+
+    #[test]
+    fn test_evaluate_and_split() {
+        let c = CubicBezier3::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 2.0, 0.0),
+            Point3::new(3.0, 2.0, 1.0),
+            Point3::new(4.0, 0.0, 3.0),
+        );
+        for i in 0..=10 {
+            let t = i as f64 / 10.0;
+            let s = 1.0 - t;
+            let bernstein = Point3::from_vec(
+                c.p0.to_vec() * (s * s * s)
+                    + c.p1.to_vec() * (3.0 * s * s * t)
+                    + c.p2.to_vec() * (3.0 * s * t * t)
+                    + c.p3.to_vec() * (t * t * t),
+            );
+            assert!((c.at(t) - bernstein).length() < 1e-12);
+
+            let h = 1e-6;
+            let numeric = (c.at(t + h) - c.at(t - h)) * (0.5 / h);
+            assert!((c.derivative(t) - numeric).length() < 1e-6);
+            let numeric = (c.derivative(t + h) - c.derivative(t - h)) * (0.5 / h);
+            assert!((c.second_derivative(t) - numeric).length() < 1e-6);
+        }
+
+        let (left, right) = c.split(0.3);
+        assert!((left.at(0.5) - c.at(0.15)).length() < 1e-12);
+        assert!((right.at(0.5) - c.at(0.65)).length() < 1e-12);
+        let middle = c.subcurve(0.2, 0.6);
+        assert!((middle.at(0.0) - c.at(0.2)).length() < 1e-12);
+        assert!((middle.at(0.25) - c.at(0.3)).length() < 1e-12);
+        assert!((middle.at(1.0) - c.at(0.6)).length() < 1e-12);
+
+        let q = QuadBezier2::new(
+            Point2::new(0.0, 0.0),
+            Point2::new(2.0, 4.0),
+            Point2::new(4.0, 0.0),
+        );
+        let cubic = q.to_cubic();
+        let (ql, qr) = q.split(0.25);
+        for i in 0..=10 {
+            let t = i as f64 / 10.0;
+            assert!((cubic.at(t) - q.at(t)).length() < 1e-12);
+            assert!((ql.at(t) - q.at(t * 0.25)).length() < 1e-12);
+            assert!((qr.at(t) - q.at(0.25 + t * 0.75)).length() < 1e-12);
+            let numeric = (q.at(t + 1e-6) - q.at(t - 1e-6)) * 5e5;
+            assert!((q.derivative(t) - numeric).length() < 1e-6);
+        }
+        assert_eq!(q.second_derivative(), vec2![0.0, -16.0]);
+    }
+
+    #[test]
+    fn test_rational() {
+        let arc = quarter_circle();
+        let (left, right) = arc.split(0.3);
+        for i in 0..=20 {
+            let t = i as f64 / 20.0;
+            assert_close(arc.at(t).to_vec().length(), 1.0, 1e-12);
+            assert_close(left.at(t).to_vec().length(), 1.0, 1e-12);
+            assert_close(right.at(t).to_vec().length(), 1.0, 1e-12);
+            let numeric = (arc.at(t + 1e-6) - arc.at(t - 1e-6)) * 5e5;
+            assert!((arc.derivative(t) - numeric).length() < 1e-6);
+            // The tangent of a circle is perpendicular to the radius.
+            assert_close(arc.derivative(t).dot(&arc.at(t).to_vec()), 0.0, 1e-12);
+        }
+        assert!((left.p2 - arc.at(0.3)).length() < 1e-12);
+        assert!((right.at(0.5) - arc.at(0.65)).length() > 1e-3);
+        assert_close(arc.length(), std::f64::consts::FRAC_PI_2, 1e-10);
+        assert_close(left.length() + right.length(), arc.length(), 1e-10);
+
+        let points = arc.flatten(1e-3);
+        for pair in points.windows(2) {
+            let mid = Point2::from_vec((pair[0].to_vec() + pair[1].to_vec()) * 0.5);
+            assert!(1.0 - mid.to_vec().length() <= 1e-3);
+        }
+        // A sagitta of 1e-3 on the unit circle needs about 18 segments per quarter.
+        assert!(points.len() > 10 && points.len() < 40, "{}", points.len());
+    }
+
+    #[test]
+    fn test_bounds() {
+        let c = CubicBezier2::new(
+            Point2::new(0.0, 0.0),
+            Point2::new(0.0, 4.0),
+            Point2::new(4.0, -4.0),
+            Point2::new(4.0, 0.0),
+        );
+        let aabb = c.aabb();
+        assert_close(aabb.min[0], 0.0, 1e-12);
+        assert_close(aabb.max[0], 4.0, 1e-12);
+        // y(t) = 12t(1 - t)(1 - 2t) peaks at t = (3 - sqrt(3)) / 6.
+        let t = (3.0 - 3f64.sqrt()) / 6.0;
+        assert_close(aabb.max[1], 12.0 * t * (1.0 - t) * (1.0 - 2.0 * t), 1e-12);
+        assert_close(aabb.min[1], -aabb.max[1], 1e-12);
+
+        let values = random_values(8 * 200, 1);
+        for v in values.chunks(8) {
+            let c = random_cubic(v);
+            let aabb = c.aabb();
+            let mut sampled = Aabb2::empty();
+            for i in 0..=1000 {
+                let p = c.at(i as f64 / 1000.0);
+                assert!(aabb.expand(1e-9).contains(&p));
+                sampled.include(&p);
+            }
+            assert!((aabb.min - sampled.min).length() < 1e-3);
+            assert!((aabb.max - sampled.max).length() < 1e-3);
+        }
+
+        let q = QuadBezier3::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 2.0, -2.0),
+            Point3::new(2.0, 0.0, 0.0),
+        );
+        let aabb = q.aabb();
+        assert_eq!(aabb.min, Point3::new(0.0, 0.0, -1.0));
+        assert_eq!(aabb.max, Point3::new(2.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_arc_length() {
+        // A straight line with uneven speed.
+        let line = CubicBezier2::new(
+            Point2::new(0.0, 0.0),
+            Point2::new(0.5, 0.0),
+            Point2::new(0.7, 0.0),
+            Point2::new(3.0, 0.0),
+        );
+        assert_close(line.length(), 3.0, 1e-12);
+        for s in [0.0, 0.4, 1.5, 2.9, 3.0] {
+            assert_close(line.at(line.parameter_at_length(s))[0], s, 1e-9);
+        }
+        assert_eq!(line.parameter_at_length(5.0), 1.0);
+
+        let values = random_values(8 * 20, 2);
+        for v in values.chunks(8) {
+            let c = random_cubic(v);
+            let mut polyline = 0.0;
+            let mut previous = c.p0;
+            for i in 1..=20000 {
+                let p = c.at(i as f64 / 20000.0);
+                polyline += (p - previous).length();
+                previous = p;
+            }
+            let length = c.length();
+            assert!(length >= polyline && length - polyline < 1e-5 * length.max(1.0));
+
+            let points = c.sample_by_length(9);
+            assert_eq!(points.len(), 9);
+            assert_eq!(points[0], c.p0);
+            assert!((points[8] - c.p3).length() < 1e-12);
+            for (i, p) in points.iter().enumerate() {
+                let t = c.closest_parameter(p);
+                assert_close(c.length_between(0.0, t), length * i as f64 / 8.0, 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_closest_point() {
+        let values = random_values(10 * 50, 3);
+        for v in values.chunks(10) {
+            let c = random_cubic(v);
+            let p = Point2::new(v[8] * 14.0 - 2.0, v[9] * 14.0 - 2.0);
+            let closest = c.closest_point(&p);
+            let best = (0..=5000)
+                .map(|i| (c.at(i as f64 / 5000.0) - p).length())
+                .fold(f64::INFINITY, f64::min);
+            let distance = (closest - p).length();
+            assert!(distance <= best + 1e-12 && best - distance < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_flatten() {
+        let values = random_values(8 * 40, 4);
+        for v in values.chunks(8) {
+            let c = random_cubic(v);
+            for tolerance in [0.1, 1e-3] {
+                let points = c.flatten(tolerance);
+                assert_eq!(points[0], c.p0);
+                assert_eq!(*points.last().unwrap(), c.p3);
+                for i in 0..=500 {
+                    let p = c.at(i as f64 / 500.0);
+                    let distance = points
+                        .windows(2)
+                        .map(|pair| Segment2::new(pair[0], pair[1]).distance(&p))
+                        .fold(f64::INFINITY, f64::min);
+                    assert!(distance <= tolerance + 1e-12);
+                }
+            }
+            let q = QuadBezier2::new(c.p0, c.p1, c.p3);
+            let points = q.flatten(1e-2);
+            let mut extended = vec![q.p0];
+            q.flatten_into(1e-2, &mut extended);
+            assert_eq!(points, extended);
+            for i in 0..=500 {
+                let p = q.at(i as f64 / 500.0);
+                let distance = points
+                    .windows(2)
+                    .map(|pair| Segment2::new(pair[0], pair[1]).distance(&p))
+                    .fold(f64::INFINITY, f64::min);
+                assert!(distance <= 1e-2 + 1e-12);
+            }
+        }
+
+        let line = CubicBezier2::new(
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(2.0, 0.0),
+            Point2::new(3.0, 0.0),
+        );
+        assert_eq!(line.flatten(1e-6), vec![line.p0, line.p3]);
+    }
+}
@@ -0,0 +1,587 @@
+use crate::bezier::CubicBezier2;
+use crate::curve::Curve;
+use crate::point::Point2;
+use crate::segment::Segment2;
+use crate::vec::Vec2;
+
+/// Recursion depth at which Bezier clipping gives up on a pair of pieces.
+const CLIP_MAX_DEPTH: u32 = 64;
+/// Pieces examined by one `intersections` call before it stops, which bounds the work on
+/// curves that overlap along a stretch.
+const CLIP_MAX_PIECES: usize = 4096;
+/// Clipping that keeps more than this fraction of a piece splits the larger piece instead.
+const CLIP_MIN_PROGRESS: f64 = 0.8;
+
+/// A point where two curves, or a curve and a line, meet: `t` is the parameter on the
+/// curve the method was called on and `u` the parameter on the other curve or line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurveIntersection {
+    pub t: f64,
+    pub u: f64,
+    pub point: Point2,
+}
+
+/// Real roots of `a t^3 + b t^2 + c t + d`, falling back to lower degrees when the leading
+/// coefficients vanish.
+fn cubic_roots(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    let scale = b.abs().max(c.abs()).max(d.abs());
+    if a.abs() <= 1e-12 * scale {
+        if b.abs() <= 1e-12 * c.abs().max(d.abs()) {
+            return if c == 0.0 { vec![] } else { vec![-d / c] };
+        }
+        let discr = c * c - 4.0 * b * d;
+        if discr < 0.0 {
+            return vec![];
+        }
+        let q = -0.5 * (c + c.signum() * discr.sqrt());
+        return if q == 0.0 {
+            vec![0.0]
+        } else {
+            vec![q / b, d / q]
+        };
+    }
+    // Depressed cubic `x^3 + p x + q` with `t = x - b / 3a`.
+    let (b, c, d) = (b / a, c / a, d / a);
+    let shift = -b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let discr = q * q / 4.0 + p * p * p / 27.0;
+    let roots = if discr > 0.0 {
+        let s = discr.sqrt();
+        vec![(-q / 2.0 + s).cbrt() + (-q / 2.0 - s).cbrt()]
+    } else if p == 0.0 {
+        vec![0.0]
+    } else {
+        let r = (-p / 3.0).sqrt();
+        let phi = (-q / (2.0 * r * r * r)).clamp(-1.0, 1.0).acos();
+        (0..3)
+            .map(|k| 2.0 * r * ((phi + 2.0 * std::f64::consts::PI * k as f64) / 3.0).cos())
+            .collect()
+    };
+    // The closed forms lose digits near multiple roots, so polish with Newton steps.
+    roots
+        .into_iter()
+        .map(|x| {
+            let mut t = x + shift;
+            for _ in 0..4 {
+                let f = ((t + b) * t + c) * t + d;
+                let df = (3.0 * t + 2.0 * b) * t + c;
+                if df == 0.0 {
+                    break;
+                }
+                t -= f / df;
+            }
+            t
+        })
+        .collect()
+}
+
+/// Power-basis coefficients `a t^3 + b t^2 + c t + d` of a cubic Bezier in one coordinate,
+/// given its control values.
+fn power_basis(v: [f64; 4]) -> [f64; 4] {
+    [
+        -v[0] + 3.0 * v[1] - 3.0 * v[2] + v[3],
+        3.0 * v[0] - 6.0 * v[1] + 3.0 * v[2],
+        3.0 * (v[1] - v[0]),
+        v[0],
+    ]
+}
+
+fn control_points(c: &CubicBezier2) -> [Point2; 4] {
+    [c.p0, c.p1, c.p2, c.p3]
+}
+
+/// Diagonal of the bounding box of the control polygon, which holds the curve.
+fn extent(c: &CubicBezier2) -> f64 {
+    let (mut min, mut max) = (c.p0, c.p0);
+    for p in control_points(c) {
+        for i in 0..2 {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+    }
+    (max - min).length()
+}
+
+/// Largest distance of the inner control points from the chord, which bounds how far the
+/// curve strays from it.
+fn flatness(c: &CubicBezier2) -> f64 {
+    let chord = c.p3 - c.p0;
+    let length = chord.length();
+    if length == 0.0 {
+        return extent(c);
+    }
+    let d1 = chord.perp_dot(&(c.p1 - c.p0)).abs();
+    let d2 = chord.perp_dot(&(c.p2 - c.p0)).abs();
+    d1.max(d2) / length
+}
+
+/// Parameter range of `c` that may lie within the band `lo <= n . (p - origin) <= hi`, from
+/// the convex hull of the control values plotted against `i / 3`.
+fn clip_range(
+    c: &CubicBezier2,
+    origin: &Point2,
+    n: &Vec2<f64>,
+    lo: f64,
+    hi: f64,
+) -> Option<(f64, f64)> {
+    let d = control_points(c).map(|p| n.dot(&(p - *origin)));
+    let mut range: Option<(f64, f64)> = None;
+    let mut include = |x: f64| {
+        range = Some(range.map_or((x, x), |(a, b)| (a.min(x), b.max(x))));
+    };
+    for (i, &y) in d.iter().enumerate() {
+        if (lo..=hi).contains(&y) {
+            include(i as f64 / 3.0);
+        }
+    }
+    // The hull edges are among the segments between pairs of control values.
+    for i in 0..4 {
+        for j in i + 1..4 {
+            for bound in [lo, hi] {
+                if (d[i] - bound) * (d[j] - bound) < 0.0 {
+                    let s = (bound - d[i]) / (d[j] - d[i]);
+                    include((i as f64 + s * (j - i) as f64) / 3.0);
+                }
+            }
+        }
+    }
+    range
+}
+
+struct Clipper {
+    tolerance: f64,
+    pieces: usize,
+    found: Vec<CurveIntersection>,
+}
+
+impl Clipper {
+    /// Resolves two pieces that are straight to within the tolerance, and so cross at most
+    /// once, from the crossing of their chords polished by Newton steps on the curves.
+    /// Returns false for near-parallel pieces, which are left to clipping.
+    fn flat_crossing(
+        &mut self,
+        a: &CubicBezier2,
+        range_a: (f64, f64),
+        b: &CubicBezier2,
+        range_b: (f64, f64),
+        flip: bool,
+    ) -> bool {
+        let (da, db) = (a.p3 - a.p0, b.p3 - b.p0);
+        let (la, lb) = (da.length(), db.length());
+        let denom = da.perp_dot(&db);
+        if denom.abs() <= 1e-9 * la * lb {
+            return false;
+        }
+        let w = b.p0 - a.p0;
+        let (mut s, mut r) = (w.perp_dot(&db) / denom, w.perp_dot(&da) / denom);
+        // The curves stray up to the tolerance from their chords, which moves the crossing
+        // along them by that much over the sine of the angle between them.
+        let sine = denom.abs() / (la * lb);
+        let slack = 2.0 * self.tolerance / sine;
+        if s * la < -slack || (s - 1.0) * la > slack || r * lb < -slack || (r - 1.0) * lb > slack {
+            return true;
+        }
+        (s, r) = (s.clamp(0.0, 1.0), r.clamp(0.0, 1.0));
+        for _ in 0..8 {
+            let f = a.at(s) - b.at(r);
+            let (ja, jb) = (a.derivative(s), b.derivative(r));
+            let det = ja.perp_dot(&jb);
+            if det == 0.0 {
+                break;
+            }
+            s = (s - f.perp_dot(&jb) / det).clamp(0.0, 1.0);
+            r = (r - f.perp_dot(&ja) / det).clamp(0.0, 1.0);
+        }
+        if (a.at(s) - b.at(r)).length() <= self.tolerance {
+            let ta = range_a.0 + s * (range_a.1 - range_a.0);
+            let tb = range_b.0 + r * (range_b.1 - range_b.0);
+            let (t, u) = if flip { (tb, ta) } else { (ta, tb) };
+            let point = a.at(s);
+            self.found.push(CurveIntersection { t, u, point });
+        }
+        true
+    }
+
+    /// Clips `a` against the fat line of `b`, then recurses with the roles swapped.
+    /// `flip` records that `a` is the second of the original curves.
+    fn clip(
+        &mut self,
+        a: &CubicBezier2,
+        range_a: (f64, f64),
+        b: &CubicBezier2,
+        range_b: (f64, f64),
+        flip: bool,
+        depth: u32,
+    ) {
+        self.pieces += 1;
+        if depth > CLIP_MAX_DEPTH || self.pieces > CLIP_MAX_PIECES {
+            return;
+        }
+        let (size_a, size_b) = (extent(a), extent(b));
+        if size_a <= self.tolerance && size_b <= self.tolerance {
+            // Bands along a chord bound a piece in one direction only, so check that the
+            // pieces really meet.
+            if (a.at(0.5) - b.at(0.5)).length() > size_a + size_b + self.tolerance {
+                return;
+            }
+            let (ta, tb) = ((range_a.0 + range_a.1) / 2.0, (range_b.0 + range_b.1) / 2.0);
+            let (t, u) = if flip { (tb, ta) } else { (ta, tb) };
+            let point = a.at(0.5);
+            self.found.push(CurveIntersection { t, u, point });
+            return;
+        }
+
+        if flatness(a) <= self.tolerance
+            && flatness(b) <= self.tolerance
+            && self.flat_crossing(a, range_a, b, range_b, flip)
+        {
+            return;
+        }
+
+        let chord = b.p3 - b.p0;
+        let band = if size_b > self.tolerance && chord.length() > 1e-3 * size_b {
+            // The fat line: the chord of `b`, widened to hold the whole curve.
+            let n = chord.rotate_left_90().normalize();
+            let d1 = n.dot(&(b.p1 - b.p0));
+            let d2 = n.dot(&(b.p2 - b.p0));
+            let factor = if d1 * d2 > 0.0 { 0.75 } else { 4.0 / 9.0 };
+            // Widened a little more so that rounding cannot clip away a crossing.
+            let lo = factor * d1.min(d2).min(0.0) - 0.5 * self.tolerance;
+            let hi = factor * d1.max(d2).max(0.0) + 0.5 * self.tolerance;
+            Some((b.p0, n, lo, hi))
+        } else if size_b <= self.tolerance {
+            // `b` is all but a point: bound it along the chord of `a` instead.
+            let along = a.p3 - a.p0;
+            (along.length() > 0.0).then(|| {
+                let n = along.normalize();
+                let d = control_points(b).map(|p| n.dot(&(p - b.p0)));
+                let lo = d.iter().fold(f64::INFINITY, |m, &x| m.min(x));
+                let hi = d.iter().fold(f64::NEG_INFINITY, |m, &x| m.max(x));
+                (
+                    b.p0,
+                    n,
+                    lo - 0.5 * self.tolerance,
+                    hi + 0.5 * self.tolerance,
+                )
+            })
+        } else {
+            // A closed or folded `b` has no useful chord.
+            None
+        };
+
+        let (a, range_a) = match band {
+            Some((origin, n, lo, hi)) => {
+                let Some((t0, t1)) = clip_range(a, &origin, &n, lo, hi) else {
+                    return;
+                };
+                if t1 - t0 > CLIP_MIN_PROGRESS {
+                    (*a, range_a)
+                } else {
+                    let span = range_a.1 - range_a.0;
+                    let range = (range_a.0 + t0 * span, range_a.0 + t1 * span);
+                    let a = a.subcurve(t0, t1);
+                    self.clip(b, range_b, &a, range, !flip, depth + 1);
+                    return;
+                }
+            }
+            None => (*a, range_a),
+        };
+
+        // Clipping made little progress, so halve the larger piece and try both halves.
+        if extent(&a) >= size_b {
+            let (left, right) = a.split(0.5);
+            let mid = (range_a.0 + range_a.1) / 2.0;
+            self.clip(b, range_b, &left, (range_a.0, mid), !flip, depth + 1);
+            self.clip(b, range_b, &right, (mid, range_a.1), !flip, depth + 1);
+        } else {
+            let (left, right) = b.split(0.5);
+            let mid = (range_b.0 + range_b.1) / 2.0;
+            self.clip(&left, (range_b.0, mid), &a, range_a, !flip, depth + 1);
+            self.clip(&right, (mid, range_b.1), &a, range_a, !flip, depth + 1);
+        }
+    }
+}
+
+/// Sorts by `t` and merges hits that are the same crossing: either their points are close,
+/// or `gap(t, u)`, the distance between the curves, stays small at parameters between the
+/// two. Reported hits are only good to a few `tolerance`, and either side of a shallow
+/// crossing they can lie further apart than that along the curves.
+fn merge(
+    mut hits: Vec<CurveIntersection>,
+    tolerance: f64,
+    gap: impl Fn(f64, f64) -> f64,
+) -> Vec<CurveIntersection> {
+    hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+    let slack = 4.0 * tolerance;
+    let same = |m: &CurveIntersection, hit: &CurveIntersection| {
+        (m.point - hit.point).length() <= slack
+            || [0.25, 0.5, 0.75].iter().all(|&f| {
+                let t = m.t + (hit.t - m.t) * f;
+                let u = m.u + (hit.u - m.u) * f;
+                gap(t, u) <= slack
+            })
+    };
+    let mut merged: Vec<CurveIntersection> = vec![];
+    for hit in hits {
+        if !merged.iter().any(|m| same(m, &hit)) {
+            merged.push(hit);
+        }
+    }
+    merged
+}
+
+impl CubicBezier2 {
+    /// Intersections with `other` by Bezier clipping (Sederberg and Nishita), each accurate
+    /// to about `tolerance`. Curves that overlap along a stretch report scattered points
+    /// from it.
+    pub fn intersections(&self, other: &CubicBezier2, tolerance: f64) -> Vec<CurveIntersection> {
+        assert!(tolerance > 0.0, "tolerance must be positive");
+        let mut clipper = Clipper {
+            tolerance,
+            pieces: 0,
+            found: vec![],
+        };
+        clipper.clip(self, (0.0, 1.0), other, (0.0, 1.0), false, 0);
+        merge(clipper.found, tolerance, |t, u| {
+            (self.at(t) - other.at(u)).length()
+        })
+    }
+
+    /// Intersections with the infinite line through `a` and `b`, where `u` is measured from
+    /// `a` (0) to `b` (1). Points where the curve touches the line within `tolerance`
+    /// without crossing it count as well.
+    pub fn line_intersections(
+        &self,
+        a: &Point2,
+        b: &Point2,
+        tolerance: f64,
+    ) -> Vec<CurveIntersection> {
+        assert!(tolerance > 0.0, "tolerance must be positive");
+        let dir = *b - *a;
+        let n = dir.rotate_left_90().normalize();
+        let d = control_points(self).map(|p| n.dot(&(p - *a)));
+        let [c3, c2, c1, c0] = power_basis(d);
+        let distance = |t: f64| ((c3 * t + c2) * t + c1) * t + c0;
+
+        let mut ts: Vec<f64> = cubic_roots(c3, c2, c1, c0)
+            .into_iter()
+            .filter(|t| (-1e-9..=1.0 + 1e-9).contains(t))
+            .map(|t| t.clamp(0.0, 1.0))
+            .collect();
+        // Tangencies and end points that come close without crossing.
+        let mut extra = cubic_roots(0.0, 3.0 * c3, 2.0 * c2, c1);
+        extra.extend([0.0, 1.0]);
+        ts.extend(
+            extra
+                .into_iter()
+                .filter(|t| (0.0..=1.0).contains(t) && distance(*t).abs() <= tolerance),
+        );
+
+        let square = dir.square_length();
+        let hits = ts
+            .into_iter()
+            .map(|t| {
+                let point = self.at(t);
+                let u = (point - *a).dot(&dir) / square;
+                CurveIntersection { t, u, point }
+            })
+            .collect();
+        merge(hits, tolerance, |t, _| distance(t).abs())
+    }
+
+    /// Intersections with a segment: those of `line_intersections` that fall within it.
+    pub fn segment_intersections(
+        &self,
+        segment: &Segment2,
+        tolerance: f64,
+    ) -> Vec<CurveIntersection> {
+        let slack = tolerance / segment.length();
+        self.line_intersections(&segment.a, &segment.b, tolerance)
+            .into_iter()
+            .filter(|hit| hit.u >= -slack && hit.u <= 1.0 + slack)
+            .map(|hit| CurveIntersection {
+                u: hit.u.clamp(0.0, 1.0),
+                ..hit
+            })
+            .collect()
+    }
+
+    /// The point where the curve crosses itself, with `t < u`. A cubic has at most one
+    /// such loop; loops smaller than `tolerance`, as near a cusp, are ignored.
+    pub fn self_intersection(&self, tolerance: f64) -> Option<CurveIntersection> {
+        // With `B(t) = a t^3 + b t^2 + c t + d`, `(B(t) - B(u)) / (t - u)` vanishes at a
+        // crossing, which in terms of `s = t + u` and `p = t u` reads
+        // `a (s^2 - p) + b s + c = 0` in both coordinates.
+        let xs = power_basis(control_points(self).map(|p| p[0]));
+        let ys = power_basis(control_points(self).map(|p| p[1]));
+        let (a, b, c) = (
+            vec2![xs[0], ys[0]],
+            vec2![xs[1], ys[1]],
+            vec2![xs[2], ys[2]],
+        );
+        let ab = a.perp_dot(&b);
+        if ab == 0.0 {
+            return None;
+        }
+        let s = -a.perp_dot(&c) / ab;
+        let axis = if a[0].abs() >= a[1].abs() { 0 } else { 1 };
+        let q = -(b[axis] * s + c[axis]) / a[axis];
+        let p = s * s - q;
+        let discr = s * s - 4.0 * p;
+        if discr <= 0.0 {
+            return None;
+        }
+        let root = discr.sqrt();
+        let (t, u) = ((s - root) / 2.0, (s + root) / 2.0);
+        if t < 0.0 || u > 1.0 {
+            return None;
+        }
+        let point = self.at(t);
+        let loop_size = (self.at((t + u) / 2.0) - point).length();
+        (loop_size > tolerance).then_some(CurveIntersection { t, u, point })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segment::SegmentIntersection;
+    use crate::test_util::random_values;
+
+    fn cubic(points: [(f64, f64); 4]) -> CubicBezier2 {
+        let p = points.map(|(x, y)| Point2::new(x, y));
+        CubicBezier2::new(p[0], p[1], p[2], p[3])
+    }
+
+    /// Crossings of the two curves flattened very finely.
+    fn polyline_crossings(a: &CubicBezier2, b: &CubicBezier2) -> Vec<Point2> {
+        let (pa, pb) = (a.flatten(1e-4), b.flatten(1e-4));
+        let mut crossings = vec![];
+        for sa in pa.windows(2) {
+            for sb in pb.windows(2) {
+                let (sa, sb) = (Segment2::new(sa[0], sa[1]), Segment2::new(sb[0], sb[1]));
+                if let Some(SegmentIntersection::Point(p)) = sa.intersection(&sb) {
+                    if crossings.iter().all(|q: &Point2| (*q - p).length() > 1e-6) {
+                        crossings.push(p);
+                    }
+                }
+            }
+        }
+        crossings
+    }
+
+    #[test]
+    fn test_cubic_roots() {
+        let mut roots = cubic_roots(2.0, -12.0, 22.0, -12.0);
+        roots.sort_by(f64::total_cmp);
+        assert_eq!(roots.len(), 3);
+        for (r, e) in roots.iter().zip([1.0, 2.0, 3.0]) {
+            assert!((r - e).abs() < 1e-12);
+        }
+        assert_eq!(cubic_roots(1.0, 0.0, 0.0, -8.0), vec![2.0]);
+        assert_eq!(cubic_roots(0.0, 1.0, -3.0, 2.0).len(), 2);
+        assert_eq!(cubic_roots(0.0, 0.0, 2.0, -1.0), vec![0.5]);
+    }
+
+    #[test]
+    fn test_cubic_cubic() {
+        let a = cubic([(0.0, 0.0), (1.0, 4.0), (2.0, -4.0), (3.0, 0.0)]);
+        let b = cubic([(0.0, 0.5), (1.0, -4.0), (2.0, 4.0), (3.0, -0.5)]);
+        let hits = a.intersections(&b, 1e-9);
+        assert_eq!(hits.len(), 3);
+        for hit in &hits {
+            assert!((a.at(hit.t) - hit.point).length() < 1e-8);
+            assert!((b.at(hit.u) - hit.point).length() < 1e-8);
+        }
+        assert!(hits.windows(2).all(|w| w[0].t < w[1].t));
+        // The curves are point-symmetric about (1.5, 0), and so is the middle crossing.
+        assert!((hits[1].point - Point2::new(1.5, 0.0)).length() < 1e-8);
+
+        // Shared end points and disjoint curves.
+        let c = cubic([(3.0, 0.0), (4.0, 1.0), (5.0, 1.0), (6.0, 0.0)]);
+        let hits = a.intersections(&c, 1e-9);
+        assert_eq!(hits.len(), 1);
+        assert!((hits[0].t - 1.0).abs() < 1e-8 && hits[0].u.abs() < 1e-8);
+        let far = cubic([(0.0, 5.0), (1.0, 6.0), (2.0, 6.0), (3.0, 5.0)]);
+        assert!(a.intersections(&far, 1e-9).is_empty());
+
+        let values = random_values(16 * 25, 1);
+        for v in values.chunks(16) {
+            let a = cubic([0, 2, 4, 6].map(|i| (v[i] * 10.0, v[i + 1] * 10.0)));
+            let b = cubic([8, 10, 12, 14].map(|i| (v[i] * 10.0, v[i + 1] * 10.0)));
+            let hits = a.intersections(&b, 1e-9);
+            for hit in &hits {
+                assert!((a.at(hit.t) - b.at(hit.u)).length() < 1e-7);
+            }
+            let expected = polyline_crossings(&a, &b);
+            // Near-tangent crossings are ambiguous in either method, so only compare
+            // clean cases.
+            let separated = expected
+                .iter()
+                .enumerate()
+                .all(|(i, p)| expected[i + 1..].iter().all(|q| (*p - *q).length() > 5e-2));
+            if separated {
+                assert_eq!(hits.len(), expected.len(), "{a:?} {b:?}");
+                for p in expected {
+                    assert!(hits.iter().any(|hit| (hit.point - p).length() < 1e-2));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cubic_line() {
+        let c = cubic([(0.0, -1.0), (1.0, 3.0), (2.0, -3.0), (3.0, 1.0)]);
+        let (a, b) = (Point2::new(-1.0, 0.0), Point2::new(1.0, 0.0));
+        let hits = c.line_intersections(&a, &b, 1e-9);
+        assert_eq!(hits.len(), 3);
+        for hit in &hits {
+            assert!(hit.point[1].abs() < 1e-12);
+            assert!((a + (b - a) * hit.u - hit.point).length() < 1e-12);
+        }
+        // Only the first crossing lies on the segment from -1 to 1.
+        let hits = c.segment_intersections(&Segment2::new(a, b), 1e-9);
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].point[0] < 1.0);
+
+        // A line touching the top of an arch.
+        let arch = cubic([(0.0, 0.0), (1.0, 1.0), (2.0, 1.0), (3.0, 0.0)]);
+        let hits = arch.line_intersections(&Point2::new(0.0, 0.75), &Point2::new(1.0, 0.75), 1e-9);
+        assert_eq!(hits.len(), 1);
+        assert!((hits[0].t - 0.5).abs() < 1e-9);
+        assert!((hits[0].point - Point2::new(1.5, 0.75)).length() < 1e-9);
+        let above = Point2::new(0.0, 0.75 + 1e-6);
+        assert!(arch
+            .line_intersections(&above, &Point2::new(1.0, 0.75 + 1e-6), 1e-9)
+            .is_empty());
+
+        // A straight cubic along the line is reported at its ends.
+        let hits = arch.line_intersections(&Point2::new(0.0, 0.0), &Point2::new(3.0, 0.0), 1e-9);
+        assert_eq!(hits.len(), 2);
+        assert_eq!((hits[0].u, hits[1].u), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_self_intersection() {
+        let looped = cubic([(0.0, 0.0), (3.0, 2.0), (-1.0, 2.0), (2.0, 0.0)]);
+        let hit = looped.self_intersection(1e-9).unwrap();
+        assert!(hit.t < hit.u);
+        assert!((looped.at(hit.t) - looped.at(hit.u)).length() < 1e-12);
+        assert!((hit.point - looped.at(hit.t)).length() < 1e-12);
+        // Symmetric about x = 1.
+        assert!((hit.point[0] - 1.0).abs() < 1e-12);
+        assert!((hit.t + hit.u - 1.0).abs() < 1e-12);
+
+        let arch = cubic([(0.0, 0.0), (1.0, 1.0), (2.0, 1.0), (3.0, 0.0)]);
+        assert_eq!(arch.self_intersection(1e-9), None);
+        let s_curve = cubic([(0.0, 0.0), (1.0, 4.0), (2.0, -4.0), (3.0, 0.0)]);
+        assert_eq!(s_curve.self_intersection(1e-9), None);
+        // A loop that would close beyond the end of the curve.
+        let open = cubic([(0.0, 0.0), (3.0, 2.0), (-1.0, 2.0), (0.5, 1.0)]);
+        assert_eq!(open.self_intersection(1e-9), None);
+        // A cusp has no loop.
+        let cusp = cubic([(0.0, 0.0), (2.0, 1.0), (0.0, 1.0), (2.0, 0.0)]);
+        assert_eq!(cusp.self_intersection(1e-9), None);
+    }
+}
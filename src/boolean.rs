@@ -0,0 +1,514 @@
+use std::collections::HashMap;
+
+use crate::aabb::Aabb2;
+use crate::point::Point2;
+use crate::polygon::{ring_signed_area, ring_winding_number, FillRule, Polygon2};
+use crate::segment::{Segment2, SegmentIntersection};
+use crate::vec::Vec2;
+
+/// Points closer than this fraction of the input extent are merged into one vertex.
+const WELD_TOLERANCE: f64 = 1e-10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOp {
+    Union,
+    Intersection,
+    /// Subject minus clip.
+    Difference,
+    Xor,
+}
+
+/// A directed edge with the polygon it came from (0 for the subject, 1 for the clip).
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    a: Point2,
+    b: Point2,
+    owner: usize,
+}
+
+type PointKey = (u64, u64);
+
+fn key(p: &Point2) -> PointKey {
+    // Adding zero folds -0.0 into 0.0 so that both hash alike.
+    ((p[0] + 0.0).to_bits(), (p[1] + 0.0).to_bits())
+}
+
+/// Merges points that lie within `tolerance` of an earlier point, so that intersection
+/// points computed from different edge pairs land on exactly the same coordinates.
+struct Welder {
+    tolerance: f64,
+    cells: HashMap<(i64, i64), Vec<Point2>>,
+}
+
+impl Welder {
+    fn new(tolerance: f64) -> Welder {
+        Welder {
+            tolerance,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, p: &Point2) -> (i64, i64) {
+        (
+            (p[0] / self.tolerance).floor() as i64,
+            (p[1] / self.tolerance).floor() as i64,
+        )
+    }
+
+    fn weld(&mut self, p: Point2) -> Point2 {
+        let (cx, cy) = self.cell(&p);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if let Some(points) = self.cells.get(&(cx + dx, cy + dy)) {
+                    for q in points {
+                        if (q[0] - p[0]).abs() <= self.tolerance
+                            && (q[1] - p[1]).abs() <= self.tolerance
+                        {
+                            return *q;
+                        }
+                    }
+                }
+            }
+        }
+        self.cells.entry((cx, cy)).or_default().push(p);
+        p
+    }
+}
+
+fn normalized(polygons: &[Polygon2]) -> Vec<Polygon2> {
+    polygons
+        .iter()
+        .map(|p| {
+            let mut p = p.clone();
+            p.normalize_orientation();
+            p
+        })
+        .collect()
+}
+
+fn inside(polygons: &[Polygon2], boxes: &[Aabb2], p: &Point2) -> bool {
+    polygons
+        .iter()
+        .zip(boxes)
+        .any(|(poly, aabb)| aabb.contains(p) && poly.contains(p, FillRule::EvenOdd))
+}
+
+/// Splits every edge at every point where it touches another edge, welding nearby
+/// points together. Returns the resulting sub-edges, dropping those that collapse.
+fn split_edges(edges: &[Edge], tolerance: f64) -> Vec<Edge> {
+    let segments: Vec<Segment2> = edges.iter().map(|e| Segment2::new(e.a, e.b)).collect();
+    let boxes: Vec<Aabb2> = segments.iter().map(|s| s.aabb()).collect();
+    let mut cuts: Vec<Vec<Point2>> = edges.iter().map(|e| vec![e.a, e.b]).collect();
+
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by(|&i, &j| boxes[i].min[0].total_cmp(&boxes[j].min[0]));
+    let mut active: Vec<usize> = vec![];
+    for &i in &order {
+        active.retain(|&j| boxes[j].max[0] >= boxes[i].min[0]);
+        for &j in &active {
+            if !boxes[i].overlaps(&boxes[j]) {
+                continue;
+            }
+            match segments[i].intersection(&segments[j]) {
+                Some(SegmentIntersection::Point(p)) => {
+                    cuts[i].push(p);
+                    cuts[j].push(p);
+                }
+                Some(SegmentIntersection::Overlap(p, q)) => {
+                    cuts[i].extend([p, q]);
+                    cuts[j].extend([p, q]);
+                }
+                None => {}
+            }
+        }
+        active.push(i);
+    }
+
+    // Weld the original vertices first so that they win over nearby intersection points.
+    let mut welder = Welder::new(tolerance);
+    for e in edges {
+        welder.weld(e.a);
+        welder.weld(e.b);
+    }
+
+    let mut pieces = vec![];
+    for (e, points) in edges.iter().zip(cuts) {
+        let d = e.b - e.a;
+        let len2 = d.square_length();
+        let mut points: Vec<(f64, Point2)> = points
+            .into_iter()
+            .map(|p| ((p - e.a).dot(&d) / len2, welder.weld(p)))
+            .collect();
+        points.sort_by(|x, y| x.0.total_cmp(&y.0));
+        points.dedup_by(|x, y| x.1 == y.1);
+        for pair in points.windows(2) {
+            if pair[0].1 != pair[1].1 {
+                pieces.push(Edge {
+                    a: pair[0].1,
+                    b: pair[1].1,
+                    owner: e.owner,
+                });
+            }
+        }
+    }
+    pieces
+}
+
+/// Whether an edge bounding only one operand survives, and if so whether it is reversed.
+fn keep_single(op: BooleanOp, owner: usize, inside_other: bool) -> Option<bool> {
+    match (op, owner) {
+        (BooleanOp::Union, _) => (!inside_other).then_some(false),
+        (BooleanOp::Intersection, _) => inside_other.then_some(false),
+        (BooleanOp::Difference, 0) => (!inside_other).then_some(false),
+        (BooleanOp::Difference, _) => inside_other.then_some(true),
+        (BooleanOp::Xor, _) => Some(inside_other),
+    }
+}
+
+/// Whether a boundary shared by both operands survives. `same` is true when both
+/// interiors lie on the same side of it; the edge keeps the subject's direction.
+fn keep_shared(op: BooleanOp, same: bool) -> bool {
+    matches!(
+        (op, same),
+        (BooleanOp::Union, true) | (BooleanOp::Intersection, true) | (BooleanOp::Difference, false)
+    )
+}
+
+/// Chains directed edges into closed rings, taking the leftmost turn wherever
+/// several edges leave the same vertex so that touching rings come out separate.
+fn connect_edges(edges: &[(Point2, Point2)]) -> Vec<Vec<Point2>> {
+    let mut outgoing: HashMap<PointKey, Vec<usize>> = HashMap::new();
+    for (i, e) in edges.iter().enumerate() {
+        outgoing.entry(key(&e.0)).or_default().push(i);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut rings = vec![];
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+        let origin = key(&edges[start].0);
+        let mut ring = vec![];
+        let mut current = start;
+        let closed = loop {
+            used[current] = true;
+            let (a, b) = edges[current];
+            ring.push(a);
+            if key(&b) == origin {
+                break true;
+            }
+            let incoming = b - a;
+            let next = outgoing.get(&key(&b)).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .copied()
+                    .filter(|&c| !used[c])
+                    .max_by(|&x, &y| {
+                        let tx = turn_angle(&incoming, &(edges[x].1 - edges[x].0));
+                        let ty = turn_angle(&incoming, &(edges[y].1 - edges[y].0));
+                        tx.total_cmp(&ty)
+                    })
+            });
+            match next {
+                Some(n) => current = n,
+                None => break false,
+            }
+        };
+        if closed {
+            rings.push(ring);
+        }
+    }
+    rings
+}
+
+fn turn_angle(from: &Vec2<f64>, to: &Vec2<f64>) -> f64 {
+    from.perp_dot(to).atan2(from.dot(to))
+}
+
+/// Removes repeated, collinear and spike vertices from a closed ring.
+pub(crate) fn simplify_ring(ring: &mut Vec<Point2>) {
+    let mut changed = true;
+    while changed && ring.len() >= 3 {
+        changed = false;
+        let mut i = 0;
+        while i < ring.len() && ring.len() >= 3 {
+            let n = ring.len();
+            let prev = ring[(i + n - 1) % n];
+            let cur = ring[i];
+            let next = ring[(i + 1) % n];
+            let d0 = cur - prev;
+            let d1 = next - cur;
+            let cross = d0.perp_dot(&d1);
+            let scale = d0.length() * d1.length();
+            if cur == prev || cross.abs() <= 1e-12 * scale {
+                ring.remove(i);
+                changed = true;
+            } else {
+                i += 1;
+            }
+        }
+    }
+    if ring.len() < 3 {
+        ring.clear();
+    }
+}
+
+/// Groups counter-clockwise exteriors with the clockwise holes they enclose.
+fn assemble(rings: Vec<Vec<Point2>>) -> Vec<Polygon2> {
+    let (exteriors, holes): (Vec<_>, Vec<_>) =
+        rings.into_iter().partition(|r| ring_signed_area(r) > 0.0);
+    let mut polygons: Vec<Polygon2> = exteriors.into_iter().map(Polygon2::new).collect();
+    let areas: Vec<f64> = polygons
+        .iter()
+        .map(|p| ring_signed_area(&p.exterior))
+        .collect();
+
+    for hole in holes {
+        let mut best: Option<usize> = None;
+        for (i, poly) in polygons.iter().enumerate() {
+            let sample = hole
+                .iter()
+                .find(|v| !poly.exterior.contains(v))
+                .copied()
+                .unwrap_or_else(|| hole[0] + (hole[1] - hole[0]) * 0.5);
+            if ring_winding_number(&poly.exterior, &sample) != 0
+                && best.is_none_or(|b| areas[i] < areas[b])
+            {
+                best = Some(i);
+            }
+        }
+        if let Some(i) = best {
+            polygons[i].holes.push(hole);
+        }
+    }
+    polygons
+}
+
+/// Boolean operation between two sets of polygons with holes. The polygons within each
+/// operand must not overlap each other; ring orientation does not matter.
+///
+/// Edges are split at every intersection and each piece is classified by whether it lies
+/// inside the other operand, in the manner of Martinez–Rueda. Pieces shared by both
+/// operands are resolved by whether the two interiors lie on the same side. The kept
+/// pieces are chained into rings with exteriors counter-clockwise and holes clockwise.
+pub fn boolean(subject: &[Polygon2], clip: &[Polygon2], op: BooleanOp) -> Vec<Polygon2> {
+    let operands = [normalized(subject), normalized(clip)];
+    let boxes: Vec<Vec<Aabb2>> = operands
+        .iter()
+        .map(|polys| polys.iter().map(|p| p.aabb()).collect())
+        .collect();
+
+    let mut edges = vec![];
+    let mut extent = Aabb2::empty();
+    for (owner, polys) in operands.iter().enumerate() {
+        for poly in polys {
+            for (_, seg) in poly.edges() {
+                if seg.a != seg.b {
+                    extent.include(&seg.a);
+                    edges.push(Edge {
+                        a: seg.a,
+                        b: seg.b,
+                        owner,
+                    });
+                }
+            }
+        }
+    }
+    if edges.is_empty() {
+        return vec![];
+    }
+
+    let scale = [extent.min[0], extent.min[1], extent.max[0], extent.max[1]]
+        .iter()
+        .fold(1.0f64, |m, v| m.max(v.abs()));
+    let pieces = split_edges(&edges, WELD_TOLERANCE * scale);
+
+    // Group pieces by their undirected endpoints; the net direction per operand
+    // cancels out zero-width spikes and detects shared boundaries.
+    let mut slots: HashMap<(PointKey, PointKey), usize> = HashMap::new();
+    let mut groups: Vec<(Point2, Point2, [i32; 2])> = vec![];
+    for piece in &pieces {
+        let (ka, kb) = (key(&piece.a), key(&piece.b));
+        let (lo, hi, dir) = if ka < kb {
+            (piece.a, piece.b, 1)
+        } else {
+            (piece.b, piece.a, -1)
+        };
+        let slot = *slots.entry((key(&lo), key(&hi))).or_insert_with(|| {
+            groups.push((lo, hi, [0, 0]));
+            groups.len() - 1
+        });
+        groups[slot].2[piece.owner] += dir;
+    }
+
+    let mut kept = vec![];
+    for (lo, hi, net) in groups {
+        let (da, db) = (net[0].signum(), net[1].signum());
+        let directed = |dir: i32, reverse: bool| {
+            if (dir > 0) != reverse {
+                (lo, hi)
+            } else {
+                (hi, lo)
+            }
+        };
+        if da != 0 && db != 0 {
+            if keep_shared(op, da == db) {
+                kept.push(directed(da, false));
+            }
+            continue;
+        }
+        let (owner, dir) = if da != 0 {
+            (0, da)
+        } else if db != 0 {
+            (1, db)
+        } else {
+            continue;
+        };
+        let mid = lo + (hi - lo) * 0.5;
+        let other = 1 - owner;
+        let inside_other = inside(&operands[other], &boxes[other], &mid);
+        if let Some(reverse) = keep_single(op, owner, inside_other) {
+            kept.push(directed(dir, reverse));
+        }
+    }
+
+    let mut rings = connect_edges(&kept);
+    for ring in &mut rings {
+        simplify_ring(ring);
+    }
+    rings.retain(|r| !r.is_empty() && ring_signed_area(r) != 0.0);
+    assemble(rings)
+}
+
+impl Polygon2 {
+    pub fn union(&self, rhs: &Polygon2) -> Vec<Polygon2> {
+        boolean(
+            std::slice::from_ref(self),
+            std::slice::from_ref(rhs),
+            BooleanOp::Union,
+        )
+    }
+
+    pub fn intersection(&self, rhs: &Polygon2) -> Vec<Polygon2> {
+        boolean(
+            std::slice::from_ref(self),
+            std::slice::from_ref(rhs),
+            BooleanOp::Intersection,
+        )
+    }
+
+    pub fn difference(&self, rhs: &Polygon2) -> Vec<Polygon2> {
+        boolean(
+            std::slice::from_ref(self),
+            std::slice::from_ref(rhs),
+            BooleanOp::Difference,
+        )
+    }
+
+    pub fn xor(&self, rhs: &Polygon2) -> Vec<Polygon2> {
+        boolean(
+            std::slice::from_ref(self),
+            std::slice::from_ref(rhs),
+            BooleanOp::Xor,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{assert_close, rect, total_area};
+
+    #[test]
+    fn test_overlapping_squares() {
+        let a = rect(0.0, 0.0, 2.0, 2.0);
+        let b = rect(1.0, 1.0, 3.0, 3.0);
+        let union = a.union(&b);
+        assert_eq!(union.len(), 1);
+        assert_eq!(union[0].exterior.len(), 8);
+        assert_close(total_area(&union), 7.0, 1e-9);
+        assert_close(total_area(&a.intersection(&b)), 1.0, 1e-9);
+        assert_close(total_area(&a.difference(&b)), 3.0, 1e-9);
+        assert_close(total_area(&b.difference(&a)), 3.0, 1e-9);
+        let xor = a.xor(&b);
+        assert_eq!(xor.len(), 2);
+        assert_close(total_area(&xor), 6.0, 1e-9);
+    }
+
+    #[test]
+    fn test_coincident_edges() {
+        let a = rect(0.0, 0.0, 1.0, 1.0);
+        let b = rect(1.0, 0.0, 2.0, 1.0);
+        let union = a.union(&b);
+        assert_eq!(union.len(), 1);
+        assert_eq!(union[0].exterior.len(), 4);
+        assert_close(union[0].area(), 2.0, 1e-9);
+        assert!(a.intersection(&b).is_empty());
+        assert_close(total_area(&a.difference(&b)), 1.0, 1e-9);
+
+        for op in [BooleanOp::Union, BooleanOp::Intersection] {
+            let same = boolean(std::slice::from_ref(&a), std::slice::from_ref(&a), op);
+            assert_eq!(same.len(), 1);
+            assert_close(same[0].area(), 1.0, 1e-9);
+        }
+        assert!(a.difference(&a).is_empty());
+        assert!(a.xor(&a).is_empty());
+    }
+
+    #[test]
+    fn test_holes() {
+        let outer = rect(0.0, 0.0, 4.0, 4.0);
+        let inner = rect(1.0, 1.0, 3.0, 3.0);
+        let ring = outer.difference(&inner);
+        assert_eq!(ring.len(), 1);
+        assert_eq!(ring[0].holes.len(), 1);
+        assert_close(ring[0].area(), 12.0, 1e-9);
+        assert!(ring[0].signed_area() > 0.0);
+
+        let filled = ring[0].union(&inner);
+        assert_eq!(filled.len(), 1);
+        assert!(filled[0].holes.is_empty());
+        assert_close(filled[0].area(), 16.0, 1e-9);
+
+        // Cutting the ring in half leaves two U shapes.
+        let halves = ring[0].difference(&rect(-1.0, 1.5, 5.0, 2.5));
+        assert_eq!(halves.len(), 2);
+        assert_close(total_area(&halves), 12.0 - 2.0, 1e-9);
+    }
+
+    #[test]
+    fn test_disjoint_and_touching() {
+        let a = rect(0.0, 0.0, 1.0, 1.0);
+        let b = rect(2.0, 0.0, 3.0, 1.0);
+        assert_eq!(a.union(&b).len(), 2);
+        assert!(a.intersection(&b).is_empty());
+
+        // Squares touching at a single corner stay separate rings.
+        let c = rect(1.0, 1.0, 2.0, 2.0);
+        let union = a.union(&c);
+        assert_eq!(union.len(), 2);
+        assert_close(total_area(&union), 2.0, 1e-9);
+    }
+
+    #[test]
+    fn test_degenerate_vertices() {
+        // Repeated and collinear vertices plus a zero-width spike.
+        let a = Polygon2::new(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(2.0, 0.0),
+            Point2::new(2.0, 2.0),
+            Point2::new(1.0, 2.0),
+            Point2::new(1.0, 3.0),
+            Point2::new(1.0, 2.0),
+            Point2::new(0.0, 2.0),
+        ]);
+        let b = rect(1.0, -1.0, 3.0, 1.0);
+        let union = a.union(&b);
+        assert_eq!(union.len(), 1);
+        assert_close(total_area(&union), 4.0 + 4.0 - 1.0, 1e-9);
+        assert!(union[0].is_simple());
+    }
+}
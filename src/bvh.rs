@@ -0,0 +1,574 @@
+use crate::aabb::Aabb3;
+use crate::point::Point3;
+use crate::ray::Ray3;
+use crate::sphere::Sphere;
+use crate::triangle::Triangle3;
+
+/// Number of centroid bins evaluated per split.
+const BINS: usize = 12;
+/// Ranges at or below this size always become leaves.
+const MIN_LEAF: usize = 2;
+/// Ranges above this size are always split, even when SAH prefers a leaf.
+const MAX_LEAF: usize = 8;
+/// Cost of visiting an interior node relative to testing one item.
+const TRAVERSAL_COST: f64 = 1.0;
+
+/// Anything that can be stored in a `Bvh`.
+pub trait Bounded {
+    fn aabb(&self) -> Aabb3;
+}
+
+impl Bounded for Aabb3 {
+    fn aabb(&self) -> Aabb3 {
+        *self
+    }
+}
+
+impl Bounded for Point3 {
+    fn aabb(&self) -> Aabb3 {
+        Aabb3::new(*self, *self)
+    }
+}
+
+impl Bounded for Sphere {
+    fn aabb(&self) -> Aabb3 {
+        Sphere::aabb(self)
+    }
+}
+
+impl Bounded for Triangle3 {
+    fn aabb(&self) -> Aabb3 {
+        Triangle3::aabb(self)
+    }
+}
+
+/// A flattened tree node. Leaves have `count > 0` and own `order[start..start + count]`.
+/// Interior nodes have `count == 0`; their first child is the next node and `start` is
+/// the index of the second, so a depth-first walk reads memory mostly forwards.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    bounds: Aabb3,
+    start: u32,
+    count: u32,
+}
+
+impl Node {
+    fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+}
+
+/// Bounding volume hierarchy over `Aabb3`, built top-down with binned SAH splits.
+/// Items keep their original order; queries report indices into `items()`.
+#[derive(Debug, Clone)]
+pub struct Bvh<T> {
+    items: Vec<T>,
+    order: Vec<u32>,
+    nodes: Vec<Node>,
+}
+
+impl<T> Default for Bvh<T> {
+    fn default() -> Self {
+        Bvh {
+            items: Vec::new(),
+            order: Vec::new(),
+            nodes: Vec::new(),
+        }
+    }
+}
+
+impl<T: Bounded> Bvh<T> {
+    pub fn new(items: Vec<T>) -> Bvh<T> {
+        let bounds: Vec<Aabb3> = items.iter().map(|item| item.aabb()).collect();
+        let centroids: Vec<Point3> = bounds.iter().map(|b| b.center()).collect();
+        let mut bvh = Bvh {
+            order: (0..items.len() as u32).collect(),
+            nodes: Vec::with_capacity(2 * items.len()),
+            items,
+        };
+        if !bvh.items.is_empty() {
+            bvh.build(&bounds, &centroids, 0, bvh.items.len());
+        }
+        bvh
+    }
+
+    fn build(&mut self, bounds: &[Aabb3], centroids: &[Point3], start: usize, end: usize) -> usize {
+        let index = self.nodes.len();
+        let range = &self.order[start..end];
+        let node_bounds = range
+            .iter()
+            .fold(Aabb3::empty(), |acc, &i| acc.union(&bounds[i as usize]));
+        self.nodes.push(Node {
+            bounds: node_bounds,
+            start: start as u32,
+            count: (end - start) as u32,
+        });
+
+        let count = end - start;
+        if count <= MIN_LEAF {
+            return index;
+        }
+        let centroid_bounds = Aabb3::from_points(range.iter().map(|&i| centroids[i as usize]));
+        let axis = centroid_bounds.major_axis();
+        let lo = centroid_bounds.min[axis];
+        let extent = centroid_bounds.max[axis] - lo;
+        if extent <= 0.0 {
+            // Coincident centroids cannot be separated; split by count if the leaf is too big.
+            if count <= MAX_LEAF {
+                return index;
+            }
+            return self.split(bounds, centroids, index, start, start + count / 2, end);
+        }
+
+        let bin_of = |c: &Point3| (((c[axis] - lo) / extent * BINS as f64) as usize).min(BINS - 1);
+        let mut bin_counts = [0usize; BINS];
+        let mut bin_bounds = [Aabb3::empty(); BINS];
+        for &i in range {
+            let b = bin_of(&centroids[i as usize]);
+            bin_counts[b] += 1;
+            bin_bounds[b] = bin_bounds[b].union(&bounds[i as usize]);
+        }
+
+        // Sweep from the right to get suffix costs, then from the left to pick the split.
+        let mut right_cost = [0.0; BINS];
+        let mut acc = Aabb3::empty();
+        let mut acc_count = 0;
+        for b in (1..BINS).rev() {
+            acc = acc.union(&bin_bounds[b]);
+            acc_count += bin_counts[b];
+            right_cost[b] = surface_area(&acc) * acc_count as f64;
+        }
+        let mut best = (f64::INFINITY, 0);
+        let mut acc = Aabb3::empty();
+        let mut acc_count = 0;
+        for b in 0..BINS - 1 {
+            acc = acc.union(&bin_bounds[b]);
+            acc_count += bin_counts[b];
+            if acc_count == 0 || acc_count == count {
+                continue;
+            }
+            let cost = surface_area(&acc) * acc_count as f64 + right_cost[b + 1];
+            if cost < best.0 {
+                best = (cost, b);
+            }
+        }
+
+        let parent_area = surface_area(&node_bounds);
+        let split_cost = TRAVERSAL_COST + best.0 / parent_area.max(f64::MIN_POSITIVE);
+        if count <= MAX_LEAF && split_cost >= count as f64 {
+            return index;
+        }
+
+        let range = &mut self.order[start..end];
+        let mut mid = 0;
+        for k in 0..range.len() {
+            if bin_of(&centroids[range[k] as usize]) <= best.1 {
+                range.swap(k, mid);
+                mid += 1;
+            }
+        }
+        if mid == 0 || mid == count {
+            // Every centroid landed on one side (huge outliers); fall back to a median split.
+            mid = count / 2;
+            range.select_nth_unstable_by(mid, |&a, &b| {
+                centroids[a as usize][axis].total_cmp(&centroids[b as usize][axis])
+            });
+        }
+        self.split(bounds, centroids, index, start, start + mid, end)
+    }
+
+    fn split(
+        &mut self,
+        bounds: &[Aabb3],
+        centroids: &[Point3],
+        index: usize,
+        start: usize,
+        mid: usize,
+        end: usize,
+    ) -> usize {
+        self.build(bounds, centroids, start, mid);
+        let second = self.build(bounds, centroids, mid, end);
+        self.nodes[index].start = second as u32;
+        self.nodes[index].count = 0;
+        index
+    }
+
+    /// Recomputes every node's bounds bottom-up after items have moved. The topology is
+    /// kept, so queries stay correct but slow down as motion accumulates; rebuild then.
+    pub fn refit(&mut self) {
+        for index in (0..self.nodes.len()).rev() {
+            let node = self.nodes[index];
+            let bounds = if node.is_leaf() {
+                self.leaf_items(&node)
+                    .iter()
+                    .fold(Aabb3::empty(), |acc, &i| {
+                        acc.union(&self.items[i as usize].aabb())
+                    })
+            } else {
+                self.nodes[index + 1]
+                    .bounds
+                    .union(&self.nodes[node.start as usize].bounds)
+            };
+            self.nodes[index].bounds = bounds;
+        }
+    }
+
+    /// Indices of the items whose bounding boxes overlap `aabb`.
+    pub fn query_aabb(&self, aabb: &Aabb3) -> Vec<usize> {
+        self.query(|b| b.overlaps(aabb))
+    }
+
+    /// Indices of the items whose bounding boxes overlap `sphere`.
+    pub fn query_sphere(&self, sphere: &Sphere) -> Vec<usize> {
+        self.query(|b| sphere.overlaps_aabb(b))
+    }
+
+    fn query<F: Fn(&Aabb3) -> bool>(&self, overlaps: F) -> Vec<usize> {
+        let mut result = Vec::new();
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !overlaps(&node.bounds) {
+                continue;
+            }
+            if node.is_leaf() {
+                for &i in self.leaf_items(node) {
+                    if overlaps(&self.items[i as usize].aabb()) {
+                        result.push(i as usize);
+                    }
+                }
+            } else {
+                stack.push(node.start as usize);
+                stack.push(index + 1);
+            }
+        }
+        result
+    }
+
+    /// Closest hit along `ray` up to `max_t`. `hit` intersects a single item and returns
+    /// its ray parameter; misses and hits outside `[0, max_t]` are ignored. Children are
+    /// visited near-first and skipped once their entry lies beyond the best hit so far.
+    pub fn cast_ray<F>(&self, ray: &Ray3, max_t: f64, mut hit: F) -> Option<(usize, f64)>
+    where
+        F: FnMut(&T) -> Option<f64>,
+    {
+        let mut best: Option<(usize, f64)> = None;
+        let mut best_t = max_t;
+        let mut stack: Vec<(usize, f64)> = Vec::with_capacity(64);
+        if let Some((t, _)) = self
+            .nodes
+            .first()
+            .and_then(|n| n.bounds.ray_intersection(ray))
+        {
+            stack.push((0, t));
+        }
+        while let Some((index, entry)) = stack.pop() {
+            if entry > best_t {
+                continue;
+            }
+            let node = &self.nodes[index];
+            if node.is_leaf() {
+                for &i in self.leaf_items(node) {
+                    if let Some(t) = hit(&self.items[i as usize]) {
+                        if t >= 0.0 && t <= best_t {
+                            best_t = t;
+                            best = Some((i as usize, t));
+                        }
+                    }
+                }
+                continue;
+            }
+            let near = self.child_entry(index + 1, ray, best_t);
+            let far = self.child_entry(node.start as usize, ray, best_t);
+            let (near, far) = match (near, far) {
+                (Some(a), Some(b)) if b.1 < a.1 => (Some(b), Some(a)),
+                pair => pair,
+            };
+            stack.extend(far);
+            stack.extend(near);
+        }
+        best
+    }
+
+    /// Any hit along `ray` within `[0, max_t]`, for shadow and visibility rays. Returns as
+    /// soon as one item reports a hit, so the index is not necessarily the closest.
+    pub fn any_hit<F>(&self, ray: &Ray3, max_t: f64, mut hit: F) -> Option<usize>
+    where
+        F: FnMut(&T) -> Option<f64>,
+    {
+        let mut stack = Vec::with_capacity(64);
+        if self.child_entry(0, ray, max_t).is_some() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if node.is_leaf() {
+                for &i in self.leaf_items(node) {
+                    if hit(&self.items[i as usize]).is_some_and(|t| t >= 0.0 && t <= max_t) {
+                        return Some(i as usize);
+                    }
+                }
+                continue;
+            }
+            for child in [node.start as usize, index + 1] {
+                if self.child_entry(child, ray, max_t).is_some() {
+                    stack.push(child);
+                }
+            }
+        }
+        None
+    }
+
+    /// Item closest to `p` as measured by `distance`, which must never be smaller than
+    /// the distance from `p` to the item's bounding box.
+    pub fn nearest<F>(&self, p: &Point3, mut distance: F) -> Option<(usize, f64)>
+    where
+        F: FnMut(&T) -> f64,
+    {
+        let box_distance = |b: &Aabb3| (b.closest_point(p) - *p).length();
+        let mut best: Option<(usize, f64)> = None;
+        let mut best_d = f64::INFINITY;
+        let mut stack: Vec<(usize, f64)> = Vec::with_capacity(64);
+        if let Some(root) = self.nodes.first() {
+            stack.push((0, box_distance(&root.bounds)));
+        }
+        while let Some((index, d)) = stack.pop() {
+            if d >= best_d {
+                continue;
+            }
+            let node = &self.nodes[index];
+            if node.is_leaf() {
+                for &i in self.leaf_items(node) {
+                    let d = distance(&self.items[i as usize]);
+                    if d < best_d {
+                        best_d = d;
+                        best = Some((i as usize, d));
+                    }
+                }
+                continue;
+            }
+            let a = (index + 1, box_distance(&self.nodes[index + 1].bounds));
+            let b = (
+                node.start as usize,
+                box_distance(&self.nodes[node.start as usize].bounds),
+            );
+            let (near, far) = if b.1 < a.1 { (b, a) } else { (a, b) };
+            stack.push(far);
+            stack.push(near);
+        }
+        best
+    }
+}
+
+impl<T> Bvh<T> {
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Mutable access to the items. Call `refit` (or rebuild) after moving any of them.
+    pub fn items_mut(&mut self) -> &mut [T] {
+        &mut self.items
+    }
+
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Bounds of the whole hierarchy; empty when there are no items.
+    pub fn bounds(&self) -> Aabb3 {
+        self.nodes.first().map_or(Aabb3::empty(), |n| n.bounds)
+    }
+
+    fn leaf_items(&self, node: &Node) -> &[u32] {
+        &self.order[node.start as usize..(node.start + node.count) as usize]
+    }
+
+    fn child_entry(&self, index: usize, ray: &Ray3, max_t: f64) -> Option<(usize, f64)> {
+        let (entry, _) = self.nodes.get(index)?.bounds.ray_intersection(ray)?;
+        (entry <= max_t).then_some((index, entry))
+    }
+}
+
+/// Surface area that treats empty boxes as zero instead of a NaN or infinity.
+fn surface_area(aabb: &Aabb3) -> f64 {
+    if aabb.is_empty() {
+        0.0
+    } else {
+        aabb.surface_area()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::random_values;
+    use crate::triangle::Culling;
+    use crate::vec::Vec3;
+
+    fn random_points(n: usize, seed: u64) -> Vec<Point3> {
+        random_values(3 * n, seed)
+            .chunks(3)
+            .map(|c| Point3::new(c[0] * 10.0, c[1] * 10.0, c[2] * 10.0))
+            .collect()
+    }
+
+    /// A bumpy height field of `2 * n * n` triangles over `[0, n]^2`.
+    fn terrain(n: usize) -> Vec<Triangle3> {
+        let height = |x: usize, y: usize| ((x * 7 + y * 13) % 5) as f64 * 0.2;
+        let p = |x: usize, y: usize| Point3::new(x as f64, y as f64, height(x, y));
+        let mut triangles = Vec::new();
+        for y in 0..n {
+            for x in 0..n {
+                triangles.push(Triangle3::new(p(x, y), p(x + 1, y), p(x + 1, y + 1)));
+                triangles.push(Triangle3::new(p(x, y), p(x + 1, y + 1), p(x, y + 1)));
+            }
+        }
+        triangles
+    }
+
+    fn hit(ray: &Ray3) -> impl Fn(&Triangle3) -> Option<f64> + '_ {
+        move |tri| tri.ray_intersection(ray, Culling::None).map(|h| h.t)
+    }
+
+    fn assert_valid<T: Bounded>(bvh: &Bvh<T>) {
+        assert!(bvh.nodes.len() < 2 * bvh.len().max(1));
+        let mut seen = vec![false; bvh.len()];
+        for (index, node) in bvh.nodes.iter().enumerate() {
+            if node.is_leaf() {
+                for &i in bvh.leaf_items(node) {
+                    assert!(!seen[i as usize]);
+                    seen[i as usize] = true;
+                    assert!(node.bounds.contains_aabb(&bvh.items[i as usize].aabb()));
+                }
+            } else {
+                assert!(node.start as usize > index + 1);
+                assert!(node.bounds.contains_aabb(&bvh.nodes[index + 1].bounds));
+                assert!(node
+                    .bounds
+                    .contains_aabb(&bvh.nodes[node.start as usize].bounds));
+            }
+        }
+        assert!(seen.iter().all(|&s| s));
+    }
+
+    #[test]
+    fn test_build() {
+        let bvh = Bvh::new(terrain(16));
+        assert_valid(&bvh);
+        assert_eq!(
+            bvh.bounds(),
+            Aabb3::new(Point3::new(0.0, 0.0, 0.0), Point3::new(16.0, 16.0, 0.8))
+        );
+
+        let empty: Bvh<Point3> = Bvh::new(Vec::new());
+        assert!(empty.is_empty());
+        assert!(empty.bounds().is_empty());
+        assert!(empty.query_aabb(&empty.bounds()).is_empty());
+        assert_eq!(empty.nearest(&Point3::new(0.0, 0.0, 0.0), |_| 0.0), None);
+
+        // Coincident items must still terminate and split into bounded leaves.
+        let same = Bvh::new(vec![Point3::new(1.0, 2.0, 3.0); 100]);
+        assert_valid(&same);
+        assert!(same.nodes.iter().all(|n| n.count as usize <= MAX_LEAF));
+    }
+
+    #[test]
+    fn test_cast_ray() {
+        let triangles = terrain(16);
+        let bvh = Bvh::new(triangles.clone());
+        let values = random_values(400, 7);
+        for c in values.chunks(4) {
+            let origin = Point3::new(c[0] * 16.0, c[1] * 16.0, 5.0);
+            let direction = vec3![c[2] - 0.5, c[3] - 0.5, -1.0];
+            let ray = Ray3::new(origin, direction);
+            let expected = triangles
+                .iter()
+                .filter_map(hit(&ray))
+                .filter(|&t| t >= 0.0)
+                .min_by(f64::total_cmp);
+            let closest = bvh.cast_ray(&ray, f64::INFINITY, hit(&ray));
+            assert_eq!(closest.map(|(_, t)| t), expected);
+            if let Some((i, t)) = closest {
+                assert_eq!(hit(&ray)(&triangles[i]), Some(t));
+                assert!(bvh.any_hit(&ray, f64::INFINITY, hit(&ray)).is_some());
+                assert_eq!(bvh.cast_ray(&ray, t * 0.5, hit(&ray)), None);
+                assert_eq!(bvh.any_hit(&ray, t * 0.5, hit(&ray)), None);
+            }
+        }
+
+        let up = Ray3::new(Point3::new(8.0, 8.0, 5.0), vec3![0.0, 0.0, 1.0]);
+        assert_eq!(bvh.cast_ray(&up, f64::INFINITY, hit(&up)), None);
+        assert_eq!(bvh.any_hit(&up, f64::INFINITY, hit(&up)), None);
+    }
+
+    #[test]
+    fn test_overlap_queries() {
+        let points = random_points(500, 3);
+        let bvh = Bvh::new(points.clone());
+        assert_valid(&bvh);
+        for c in random_points(20, 11) {
+            let aabb = Aabb3::new(c, c).expand(1.5);
+            let mut found = bvh.query_aabb(&aabb);
+            found.sort();
+            let expected: Vec<usize> = (0..points.len())
+                .filter(|&i| aabb.contains(&points[i]))
+                .collect();
+            assert_eq!(found, expected);
+
+            let sphere = Sphere::new(c, 2.0);
+            let mut found = bvh.query_sphere(&sphere);
+            found.sort();
+            let expected: Vec<usize> = (0..points.len())
+                .filter(|&i| (points[i] - c).length() <= 2.0)
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn test_nearest() {
+        let points = random_points(500, 5);
+        let bvh = Bvh::new(points.clone());
+        for q in random_points(50, 13) {
+            let (i, d) = bvh.nearest(&q, |p| (*p - q).length()).unwrap();
+            let expected = points
+                .iter()
+                .map(|p| (*p - q).length())
+                .min_by(f64::total_cmp)
+                .unwrap();
+            assert_eq!(d, expected);
+            assert_eq!((points[i] - q).length(), d);
+        }
+    }
+
+    #[test]
+    fn test_refit() {
+        let mut bvh = Bvh::new(terrain(8));
+        let offset = vec3![0.25, -0.5, 3.0];
+        for tri in bvh.items_mut() {
+            tri.b += offset.clone();
+        }
+        bvh.refit();
+        assert_valid(&bvh);
+
+        let moved = bvh.items().to_vec();
+        let ray = Ray3::new(Point3::new(4.3, 3.6, 10.0), vec3![0.0, 0.0, -1.0]);
+        let expected = moved.iter().filter_map(hit(&ray)).min_by(f64::total_cmp);
+        assert!(expected.is_some());
+        assert_eq!(
+            bvh.cast_ray(&ray, f64::INFINITY, hit(&ray)).map(|(_, t)| t),
+            expected
+        );
+    }
+}
@@ -0,0 +1,502 @@
+use crate::aabb::Aabb3;
+use crate::convex::{epa_penetration, gjk_distance, Support};
+use crate::point::Point3;
+use crate::sphere::Sphere;
+use crate::triangle::Triangle3;
+use crate::vec::Vec3;
+
+const MAX_ADVANCEMENT_STEPS: usize = 64;
+
+/// First contact during a linear motion over the unit time interval. `normal` is the
+/// unit surface normal at `point`, pointing from the obstacle towards the moving shape.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Impact {
+    pub time: f64,
+    pub point: Point3,
+    pub normal: Vec3<f64>,
+}
+
+/// Smallest `t >= 0` where `origin + dir * t` is at distance `radius` from `center`,
+/// for an origin outside the sphere.
+fn ray_sphere(origin: &Point3, dir: &Vec3<f64>, center: &Point3, radius: f64) -> Option<f64> {
+    let m = *origin - *center;
+    let a = dir.square_length();
+    let b = m.dot(dir);
+    let c = m.square_length() - radius * radius;
+    let discr = b * b - a * c;
+    if a == 0.0 || b > 0.0 || discr < 0.0 {
+        return None;
+    }
+    Some(((-b - discr.sqrt()) / a).max(0.0))
+}
+
+/// Smallest `t >= 0` where `origin + dir * t` is at distance `radius` from the segment
+/// `ab`, touching it between the endpoints. Returns the time and segment parameter.
+fn ray_cylinder(
+    origin: &Point3,
+    dir: &Vec3<f64>,
+    a: &Point3,
+    b: &Point3,
+    radius: f64,
+) -> Option<(f64, f64)> {
+    let d = *b - *a;
+    let m = *origin - *a;
+    let dd = d.square_length();
+    let md = m.dot(&d);
+    let nd = dir.dot(&d);
+    let qa = dd * dir.square_length() - nd * nd;
+    let qb = dd * m.dot(dir) - nd * md;
+    let qc = dd * (m.square_length() - radius * radius) - md * md;
+    let discr = qb * qb - qa * qc;
+    if qa <= 0.0 || qb > 0.0 || discr < 0.0 {
+        return None;
+    }
+    let t = ((-qb - discr.sqrt()) / qa).max(0.0);
+    let s = (md + t * nd) / dd;
+    (0.0..=1.0).contains(&s).then_some((t, s))
+}
+
+/// Sphere moving by `motion` against a static, double-sided triangle.
+pub fn sweep_sphere_triangle(
+    sphere: &Sphere,
+    motion: &Vec3<f64>,
+    triangle: &Triangle3,
+) -> Option<Impact> {
+    let r = sphere.radius;
+    let c = sphere.center;
+    let closest = triangle.closest_point(&c);
+    if (c - closest).square_length() <= r * r {
+        let d = c - closest;
+        let normal = if d.square_length() > 0.0 {
+            d.normalize()
+        } else {
+            triangle.normal()
+        };
+        return Some(Impact {
+            time: 0.0,
+            point: closest,
+            normal,
+        });
+    }
+
+    // Face: the sphere touches the plane at distance `r` on the side it starts from. A
+    // sphere already cutting the plane can only reach the triangle across an edge.
+    let mut n = triangle.normal();
+    if n.dot(&(c - triangle.a)) < 0.0 {
+        n *= -1.0;
+    }
+    let s0 = n.dot(&(c - triangle.a));
+    let vn = n.dot(motion);
+    if vn < 0.0 && s0 >= r {
+        let t = (s0 - r) / -vn;
+        if t <= 1.0 {
+            let point = c + motion.clone() * t - n.clone() * r;
+            let inside = triangle
+                .barycentric(&point)
+                .is_some_and(|uvw| (0..3).all(|i| uvw[i] >= 0.0));
+            if inside {
+                return Some(Impact {
+                    time: t,
+                    point,
+                    normal: n,
+                });
+            }
+        }
+    }
+
+    // Otherwise the first contact is on an edge or a vertex.
+    let vertices = [triangle.a, triangle.b, triangle.c];
+    let mut best: Option<(f64, Point3)> = None;
+    let mut consider = |t: f64, point: Point3| {
+        if t <= 1.0 && best.is_none_or(|(bt, _)| t < bt) {
+            best = Some((t, point));
+        }
+    };
+    for i in 0..3 {
+        let (a, b) = (vertices[i], vertices[(i + 1) % 3]);
+        if let Some((t, s)) = ray_cylinder(&c, motion, &a, &b, r) {
+            consider(t, a + (b - a) * s);
+        }
+        if let Some(t) = ray_sphere(&c, motion, &a, r) {
+            consider(t, a);
+        }
+    }
+    best.map(|(time, point)| Impact {
+        time,
+        point,
+        normal: (c + motion.clone() * time - point).normalize(),
+    })
+}
+
+/// Two boxes moving by `motion_a` and `motion_b`. The normal is the face normal of `b`
+/// that `a` hits. Boxes that already overlap report time zero with the normal of least
+/// penetration.
+pub fn sweep_aabb(
+    a: &Aabb3,
+    motion_a: &Vec3<f64>,
+    b: &Aabb3,
+    motion_b: &Vec3<f64>,
+) -> Option<Impact> {
+    let v = motion_a.clone() - motion_b.clone();
+    if a.overlaps(b) {
+        let (mut axis, mut depth, mut sign) = (0, f64::INFINITY, 1.0);
+        for i in 0..3 {
+            let push_up = b.max[i] - a.min[i];
+            let push_down = a.max[i] - b.min[i];
+            if push_up < depth {
+                (axis, depth, sign) = (i, push_up, 1.0);
+            }
+            if push_down < depth {
+                (axis, depth, sign) = (i, push_down, -1.0);
+            }
+        }
+        let mut normal = Vec3::all(0.0);
+        normal[axis] = sign;
+        return Some(Impact {
+            time: 0.0,
+            point: a.intersection(b).unwrap_or(*a).center(),
+            normal,
+        });
+    }
+
+    let mut enter = (f64::NEG_INFINITY, 0);
+    let mut exit = f64::INFINITY;
+    for i in 0..3 {
+        if v[i] == 0.0 {
+            if a.max[i] < b.min[i] || a.min[i] > b.max[i] {
+                return None;
+            }
+            continue;
+        }
+        let (t0, t1) = if v[i] > 0.0 {
+            ((b.min[i] - a.max[i]) / v[i], (b.max[i] - a.min[i]) / v[i])
+        } else {
+            ((b.max[i] - a.min[i]) / v[i], (b.min[i] - a.max[i]) / v[i])
+        };
+        if t0 > enter.0 {
+            enter = (t0, i);
+        }
+        exit = exit.min(t1);
+    }
+    let (time, axis) = enter;
+    if time > exit || !(0.0..=1.0).contains(&time) {
+        return None;
+    }
+    let moved_a = Aabb3::new(
+        a.min + motion_a.clone() * time,
+        a.max + motion_a.clone() * time,
+    );
+    let moved_b = Aabb3::new(
+        b.min + motion_b.clone() * time,
+        b.max + motion_b.clone() * time,
+    );
+    // The boxes only touch here, so clamp the contact face to both of them.
+    let mut contact = moved_a;
+    for i in 0..3 {
+        contact.min[i] = moved_a.min[i].max(moved_b.min[i]);
+        contact.max[i] = moved_a.max[i].min(moved_b.max[i]).max(contact.min[i]);
+    }
+    let mut normal = Vec3::all(0.0);
+    normal[axis] = if v[axis] > 0.0 { -1.0 } else { 1.0 };
+    Some(Impact {
+        time,
+        point: contact.center(),
+        normal,
+    })
+}
+
+/// A shape displaced by a fixed offset.
+struct Translated<'a, S> {
+    shape: &'a S,
+    offset: Vec3<f64>,
+}
+
+impl<S: Support> Support for Translated<'_, S> {
+    fn support(&self, dir: &Vec3<f64>) -> Point3 {
+        self.shape.support(dir) + self.offset.clone()
+    }
+}
+
+/// Time of impact of two convex shapes translating by `motion_a` and `motion_b`, by
+/// conservative advancement: each step moves forward by the current gap divided by the
+/// closing speed along the separating direction, which can never overshoot. Contact is
+/// declared once the gap falls below `tolerance`, which must be positive and, for curved
+/// shapes, well above the roughly 1e-8 precision of GJK distances. Shapes that already
+/// overlap report time zero with the EPA contact normal, or with the reversed relative
+/// motion when the overlap is flat. A slow, grazing approach that has not converged after
+/// a fixed number of steps reports the conservative time reached so far.
+pub fn time_of_impact<A: Support, B: Support>(
+    a: &A,
+    motion_a: &Vec3<f64>,
+    b: &B,
+    motion_b: &Vec3<f64>,
+    tolerance: f64,
+) -> Option<Impact> {
+    assert!(tolerance > 0.0, "tolerance must be positive");
+    advance(a, motion_a, b, motion_b, tolerance, MAX_ADVANCEMENT_STEPS)
+}
+
+/// The advancement loop of `time_of_impact`, giving up after `max_steps`.
+fn advance<A: Support, B: Support>(
+    a: &A,
+    motion_a: &Vec3<f64>,
+    b: &B,
+    motion_b: &Vec3<f64>,
+    tolerance: f64,
+    max_steps: usize,
+) -> Option<Impact> {
+    let v = motion_a.clone() - motion_b.clone();
+    let mut t = 0.0;
+    let mut last = None;
+    for _ in 0..max_steps {
+        let moved_a = Translated {
+            shape: a,
+            offset: motion_a.clone() * t,
+        };
+        let moved_b = Translated {
+            shape: b,
+            offset: motion_b.clone() * t,
+        };
+        let Some(separation) = gjk_distance(&moved_a, &moved_b) else {
+            // Only reachable at the start: advancement stops short of any overlap.
+            let (point, normal) = match epa_penetration(&moved_a, &moved_b) {
+                Some(p) => (p.point_a + (p.point_b - p.point_a) * 0.5, p.normal * -1.0),
+                // An overlap without volume has no penetration direction, so the normal
+                // opposes the relative motion instead.
+                None if v.square_length() > 0.0 => (moved_a.support(&v), v.normalize() * -1.0),
+                None => (moved_a.support(&v), vec3![0.0, 0.0, 1.0]),
+            };
+            return Some(Impact {
+                time: t,
+                point,
+                normal,
+            });
+        };
+        let gap = separation.point_a - separation.point_b;
+        let distance = gap.length();
+        let normal = gap * (1.0 / distance);
+        if distance <= tolerance {
+            return Some(Impact {
+                time: t,
+                point: separation.point_b,
+                normal,
+            });
+        }
+        let closing = -v.dot(&normal);
+        if closing <= 0.0 {
+            return None;
+        }
+        // Aim for half the tolerance so that the shapes never end up exactly touching,
+        // where the separating direction is ill-defined.
+        let step = (distance - 0.5 * tolerance) / closing;
+        t += step;
+        if t > 1.0 {
+            return None;
+        }
+        last = Some((separation.point_b + motion_b.clone() * step, normal));
+    }
+    // Still closing in after every step: the time reached is before contact, so report it
+    // rather than letting the shapes pass through each other.
+    last.map(|(point, normal)| Impact {
+        time: t,
+        point,
+        normal,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{assert_close, random_values};
+
+    fn floor() -> Triangle3 {
+        Triangle3::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(4.0, 0.0, 0.0),
+            Point3::new(0.0, 4.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn test_sphere_triangle() {
+        // Falling onto the face.
+        let sphere = Sphere::new(Point3::new(1.0, 1.0, 5.0), 1.0);
+        let hit = sweep_sphere_triangle(&sphere, &vec3![0.0, 0.0, -8.0], &floor()).unwrap();
+        assert_close(hit.time, 0.5, 1e-12);
+        assert_eq!(hit.point, Point3::new(1.0, 1.0, 0.0));
+        assert_eq!(hit.normal, vec3![0.0, 0.0, 1.0]);
+
+        // A thin wall does not let a fast sphere tunnel through from below either.
+        let sphere = Sphere::new(Point3::new(1.0, 1.0, -3.0), 0.5);
+        let hit = sweep_sphere_triangle(&sphere, &vec3![0.0, 0.0, 100.0], &floor()).unwrap();
+        assert_close(hit.time, 0.025, 1e-12);
+        assert_eq!(hit.normal, vec3![0.0, 0.0, -1.0]);
+
+        // Sliding sideways into the edge along x = 0.
+        let sphere = Sphere::new(Point3::new(-3.0, 1.0, 0.0), 1.0);
+        let hit = sweep_sphere_triangle(&sphere, &vec3![4.0, 0.0, 0.0], &floor()).unwrap();
+        assert_close(hit.time, 0.5, 1e-12);
+        assert!((hit.point - Point3::new(0.0, 1.0, 0.0)).length() < 1e-12);
+        assert!((hit.normal - vec3![-1.0, 0.0, 0.0]).length() < 1e-12);
+
+        // Grazing the corner at the origin.
+        let sphere = Sphere::new(Point3::new(-2.0, -2.0, 0.0), 1.0);
+        let hit = sweep_sphere_triangle(&sphere, &vec3![2.0, 2.0, 0.0], &floor()).unwrap();
+        assert_close(hit.time, 1.0 - 0.5f64.sqrt() / 2.0, 1e-12);
+        assert_eq!(hit.point, Point3::new(0.0, 0.0, 0.0));
+
+        // Starting in contact, moving away, and stopping short.
+        let sphere = Sphere::new(Point3::new(1.0, 1.0, 0.5), 1.0);
+        let hit = sweep_sphere_triangle(&sphere, &vec3![0.0, 0.0, 1.0], &floor()).unwrap();
+        assert_eq!(hit.time, 0.0);
+        assert_eq!(hit.normal, vec3![0.0, 0.0, 1.0]);
+        let sphere = Sphere::new(Point3::new(1.0, 1.0, 3.0), 1.0);
+        assert!(sweep_sphere_triangle(&sphere, &vec3![0.0, 0.0, 1.0], &floor()).is_none());
+        assert!(sweep_sphere_triangle(&sphere, &vec3![0.0, 0.0, -1.9], &floor()).is_none());
+    }
+
+    #[test]
+    fn test_sphere_triangle_matches_advancement() {
+        let triangle = Triangle3::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(3.0, 0.5, 1.0),
+            Point3::new(0.5, 2.5, -1.0),
+        );
+        let values = random_values(7 * 300, 1);
+        let mut hits = 0;
+        for c in values.chunks(7) {
+            let sphere = Sphere::new(
+                Point3::new(c[0] * 8.0 - 3.0, c[1] * 8.0 - 3.0, c[2] * 8.0 - 4.0),
+                0.2 + c[3],
+            );
+            let motion = vec3![c[4] * 8.0 - 4.0, c[5] * 8.0 - 4.0, c[6] * 8.0 - 4.0];
+            let swept = sweep_sphere_triangle(&sphere, &motion, &triangle);
+            let advanced = time_of_impact(&sphere, &motion, &triangle, &Vec3::all(0.0), 1e-6);
+            assert_eq!(
+                swept.is_some(),
+                advanced.is_some(),
+                "{swept:?} {advanced:?} {sphere:?} {motion:?}"
+            );
+            if let (Some(s), Some(a)) = (swept, advanced) {
+                hits += 1;
+                // Grazing contacts pin the time down only loosely, so compare the gaps.
+                let gap = |t: f64| {
+                    let center = sphere.center + motion.clone() * t;
+                    (triangle.closest_point(&center) - center).length() - sphere.radius
+                };
+                if s.time == 0.0 {
+                    assert_eq!(a.time, 0.0);
+                    continue;
+                }
+                assert!(gap(s.time).abs() < 1e-9);
+                assert!(gap(a.time).abs() < 1e-5);
+                assert_close(a.time, s.time, 1e-3);
+                assert!((s.point - a.point).length() < 1e-3);
+            }
+        }
+        assert!(hits > 20);
+    }
+
+    #[test]
+    fn test_sweep_aabb() {
+        let a = Aabb3::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0));
+        let b = Aabb3::new(Point3::new(3.0, 0.5, 0.5), Point3::new(4.0, 2.0, 2.0));
+        let hit = sweep_aabb(&a, &vec3![4.0, 0.0, 0.0], &b, &Vec3::all(0.0)).unwrap();
+        assert_close(hit.time, 0.5, 1e-12);
+        assert_eq!(hit.normal, vec3![-1.0, 0.0, 0.0]);
+        assert_eq!(hit.point, Point3::new(3.0, 0.75, 0.75));
+
+        // Both moving: b comes down onto a.
+        let b = Aabb3::new(Point3::new(0.5, 0.5, 3.0), Point3::new(1.5, 1.5, 4.0));
+        let hit = sweep_aabb(&a, &vec3![0.0, 0.0, 1.0], &b, &vec3![0.0, 0.0, -3.0]).unwrap();
+        assert_close(hit.time, 0.5, 1e-12);
+        assert_eq!(hit.normal, vec3![0.0, 0.0, -1.0]);
+        assert_eq!(hit.point, Point3::new(0.75, 0.75, 1.5));
+
+        // Missing sideways, stopping short, and starting in overlap.
+        assert!(sweep_aabb(&a, &vec3![0.0, 5.0, 0.0], &b, &Vec3::all(0.0)).is_none());
+        assert!(sweep_aabb(&a, &vec3![0.0, 0.0, 1.5], &b, &Vec3::all(0.0)).is_none());
+        let c = Aabb3::new(Point3::new(0.8, 0.0, 0.0), Point3::new(2.0, 1.0, 1.0));
+        let hit = sweep_aabb(&a, &Vec3::all(0.0), &c, &Vec3::all(0.0)).unwrap();
+        assert_eq!(hit.time, 0.0);
+        assert_eq!(hit.normal, vec3![-1.0, 0.0, 0.0]);
+
+        let values = random_values(9 * 300, 2);
+        for k in values.chunks(9) {
+            let p = Point3::new(k[0] * 6.0, k[1] * 6.0, k[2] * 6.0);
+            let b = Aabb3::new(p, p).expand(0.5 + k[3]);
+            let motion = vec3![k[4] * 12.0 - 6.0, k[5] * 12.0 - 6.0, k[6] * 12.0 - 6.0];
+            let a = Aabb3::new(
+                Point3::new(2.0, 2.0, 2.0),
+                Point3::new(3.0, 3.0 + k[7], 3.0 + k[8]),
+            );
+            if a.overlaps(&b) {
+                continue;
+            }
+            let swept = sweep_aabb(&a, &motion, &b, &Vec3::all(0.0));
+            let advanced = time_of_impact(&a, &motion, &b, &Vec3::all(0.0), 1e-9);
+            assert_eq!(swept.is_some(), advanced.is_some());
+            if let (Some(s), Some(ad)) = (swept, advanced) {
+                assert_close(s.time, ad.time, 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_time_of_impact() {
+        let a = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0);
+        let b = Sphere::new(Point3::new(10.0, 0.0, 0.0), 1.0);
+        let hit =
+            time_of_impact(&a, &vec3![8.0, 0.0, 0.0], &b, &vec3![-8.0, 0.0, 0.0], 1e-9).unwrap();
+        assert_close(hit.time, 0.5, 1e-9);
+        assert!((hit.point - Point3::new(5.0, 0.0, 0.0)).length() < 1e-6);
+        assert!((hit.normal.clone() - vec3![-1.0, 0.0, 0.0]).length() < 1e-6);
+        assert!(time_of_impact(&a, &vec3![3.0, 0.0, 0.0], &b, &Vec3::all(0.0), 1e-9).is_none());
+        assert!(time_of_impact(&a, &vec3![-3.0, 0.0, 0.0], &b, &Vec3::all(0.0), 1e-9).is_none());
+
+        let overlapping = Sphere::new(Point3::new(1.5, 0.0, 0.0), 1.0);
+        let hit = time_of_impact(&a, &Vec3::all(0.0), &overlapping, &Vec3::all(0.0), 1e-9).unwrap();
+        assert_eq!(hit.time, 0.0);
+        assert!((hit.normal - vec3![-1.0, 0.0, 0.0]).length() < 1e-2);
+
+        // Overlapping coplanar triangles have no penetration depth.
+        let shifted = Triangle3::new(
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(5.0, 1.0, 0.0),
+            Point3::new(1.0, 5.0, 0.0),
+        );
+        let hit = time_of_impact(
+            &floor(),
+            &vec3![0.0, 2.0, 0.0],
+            &shifted,
+            &Vec3::all(0.0),
+            1e-9,
+        )
+        .unwrap();
+        assert_eq!(hit.time, 0.0);
+        assert!((hit.normal - vec3![0.0, -1.0, 0.0]).length() < 1e-12);
+        let hit =
+            time_of_impact(&floor(), &Vec3::all(0.0), &shifted, &Vec3::all(0.0), 1e-9).unwrap();
+        assert_close(hit.normal.length(), 1.0, 1e-12);
+    }
+
+    #[test]
+    fn test_advancement_out_of_steps() {
+        // A sphere grazing another takes over a dozen steps to close the last gap.
+        let a = Sphere::new(Point3::new(-10.0, 1.9999999, 0.0), 1.0);
+        let b = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0);
+        let motion = vec3![20.0, 0.0, 0.0];
+        let still = Vec3::all(0.0);
+        let full = advance(&a, &motion, &b, &still, 1e-9, MAX_ADVANCEMENT_STEPS).unwrap();
+        // Cut short, it still reports an impact, no later than the real one and with the
+        // shapes apart.
+        let early = advance(&a, &motion, &b, &still, 1e-9, 4).unwrap();
+        assert!(0.0 < early.time && early.time < full.time);
+        let moved = Sphere::new(a.center + motion.clone() * early.time, 1.0);
+        let gap = gjk_distance(&moved, &b).unwrap().distance;
+        assert!(gap > 1e-9);
+        assert_close(early.normal.length(), 1.0, 1e-12);
+        assert!(early.normal.dot(&(moved.center - b.center)) > 0.0);
+        assert_close(early.point.to_vec().length(), 1.0, 1e-6);
+    }
+}
@@ -0,0 +1,773 @@
+use crate::aabb::Aabb3;
+use crate::hull::ConvexHull3;
+use crate::obb::Obb3;
+use crate::point::Point3;
+use crate::sphere::Sphere;
+use crate::triangle::Triangle3;
+use crate::vec::Vec3;
+
+const GJK_MAX_ITERATIONS: usize = 128;
+/// GJK stops once the squared distance estimate improves by less than this fraction.
+const GJK_TOLERANCE: f64 = 1e-12;
+const EPA_MAX_ITERATIONS: usize = 128;
+/// EPA stops once a support point lies this little beyond the closest face (relative).
+const EPA_TOLERANCE: f64 = 1e-9;
+
+/// A convex shape described by its support mapping.
+pub trait Support {
+    /// A point of the shape that is furthest along `dir`. `dir` need not be normalised.
+    fn support(&self, dir: &Vec3<f64>) -> Point3;
+}
+
+impl Support for Point3 {
+    fn support(&self, _: &Vec3<f64>) -> Point3 {
+        *self
+    }
+}
+
+impl Support for Sphere {
+    fn support(&self, dir: &Vec3<f64>) -> Point3 {
+        let len = dir.length();
+        if len == 0.0 {
+            return self.center + vec3![self.radius, 0.0, 0.0];
+        }
+        self.center + dir.clone() * (self.radius / len)
+    }
+}
+
+impl Support for Aabb3 {
+    fn support(&self, dir: &Vec3<f64>) -> Point3 {
+        let pick = |i: usize| {
+            if dir[i] >= 0.0 {
+                self.max[i]
+            } else {
+                self.min[i]
+            }
+        };
+        Point3::new(pick(0), pick(1), pick(2))
+    }
+}
+
+impl Support for Obb3 {
+    fn support(&self, dir: &Vec3<f64>) -> Point3 {
+        let mut p = self.center;
+        for i in 0..3 {
+            let sign = if dir.dot(&self.axes[i]) >= 0.0 {
+                1.0
+            } else {
+                -1.0
+            };
+            p += self.axes[i].clone() * (sign * self.half_extents[i]);
+        }
+        p
+    }
+}
+
+impl Support for Triangle3 {
+    fn support(&self, dir: &Vec3<f64>) -> Point3 {
+        furthest([self.a, self.b, self.c].iter(), dir)
+    }
+}
+
+impl Support for ConvexHull3 {
+    /// Linear scan over the hull vertices.
+    fn support(&self, dir: &Vec3<f64>) -> Point3 {
+        furthest(self.vertices.iter(), dir)
+    }
+}
+
+impl<S: Support + ?Sized> Support for &S {
+    fn support(&self, dir: &Vec3<f64>) -> Point3 {
+        (**self).support(dir)
+    }
+}
+
+fn furthest<'a, I: Iterator<Item = &'a Point3>>(points: I, dir: &Vec3<f64>) -> Point3 {
+    let mut best = Point3::default();
+    let mut best_dot = f64::NEG_INFINITY;
+    for p in points {
+        let d = p.to_vec().dot(dir);
+        if d > best_dot {
+            best_dot = d;
+            best = *p;
+        }
+    }
+    best
+}
+
+/// A line segment swept by a sphere.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Capsule {
+    pub a: Point3,
+    pub b: Point3,
+    pub radius: f64,
+}
+
+impl Capsule {
+    pub fn new(a: Point3, b: Point3, radius: f64) -> Capsule {
+        Capsule { a, b, radius }
+    }
+
+    pub fn aabb(&self) -> Aabb3 {
+        Aabb3::from_points([self.a, self.b]).expand(self.radius)
+    }
+}
+
+impl Support for Capsule {
+    fn support(&self, dir: &Vec3<f64>) -> Point3 {
+        let end = if (self.b - self.a).dot(dir) >= 0.0 {
+            self.b
+        } else {
+            self.a
+        };
+        Sphere::new(end, self.radius).support(dir)
+    }
+}
+
+/// Closest points of two disjoint shapes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Separation {
+    pub distance: f64,
+    pub point_a: Point3,
+    pub point_b: Point3,
+}
+
+/// Overlap of two intersecting shapes. Translating the second shape by
+/// `normal * depth` brings them into touching contact; `point_a` and `point_b` are the
+/// deepest points of each shape's surface along the normal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Penetration {
+    pub depth: f64,
+    pub normal: Vec3<f64>,
+    pub point_a: Point3,
+    pub point_b: Point3,
+}
+
+/// A point of the Minkowski difference `A - B` with the support points it came from.
+#[derive(Debug, Clone)]
+struct Vertex {
+    w: Vec3<f64>,
+    a: Point3,
+    b: Point3,
+}
+
+impl Vertex {
+    fn new<A: Support, B: Support>(a: &A, b: &B, dir: &Vec3<f64>) -> Vertex {
+        let pa = a.support(dir);
+        let pb = b.support(&(dir.clone() * -1.0));
+        Vertex {
+            w: pa - pb,
+            a: pa,
+            b: pb,
+        }
+    }
+}
+
+enum Gjk {
+    Separated(Vec<Vertex>, Vec<f64>),
+    Intersecting(Vec<Vertex>),
+}
+
+/// Gilbert-Johnson-Keerthi descent towards the origin over the Minkowski difference,
+/// with van den Bergen's termination tests. With `early_out` the search stops as soon
+/// as a separating direction is found, which is all a boolean query needs.
+fn gjk<A: Support, B: Support>(a: &A, b: &B, early_out: bool) -> Gjk {
+    let mut simplex = vec![Vertex::new(a, b, &vec3![1.0, 0.0, 0.0])];
+    let mut weights = vec![1.0];
+    let mut v = simplex[0].w.clone();
+    for _ in 0..GJK_MAX_ITERATIONS {
+        let vv = v.square_length();
+        let scale = simplex
+            .iter()
+            .map(|s| s.w.square_length())
+            .fold(0.0, f64::max);
+        if vv <= f64::EPSILON * scale {
+            return Gjk::Intersecting(simplex);
+        }
+        let w = Vertex::new(a, b, &(v.clone() * -1.0));
+        let vw = v.dot(&w.w);
+        if (early_out && vw > 0.0)
+            || vv - vw <= GJK_TOLERANCE * vv
+            || simplex.iter().any(|s| s.w == w.w)
+        {
+            return Gjk::Separated(simplex, weights);
+        }
+        let previous = (simplex.clone(), weights.clone());
+        simplex.push(w);
+        (simplex, weights) = reduce(simplex);
+        if simplex.len() == 4 {
+            return Gjk::Intersecting(simplex);
+        }
+        v = combine(&simplex, &weights, |s| s.w.clone());
+        // Rounding on nearly degenerate simplices can move away from the origin and
+        // then cycle, so stop at the closest simplex seen.
+        if !early_out && v.square_length() >= vv {
+            return Gjk::Separated(previous.0, previous.1);
+        }
+    }
+    Gjk::Separated(simplex, weights)
+}
+
+fn combine<T, F>(simplex: &[Vertex], weights: &[f64], get: F) -> T
+where
+    T: std::ops::Add<Vec3<f64>, Output = T> + Default,
+    F: Fn(&Vertex) -> Vec3<f64>,
+{
+    simplex
+        .iter()
+        .zip(weights)
+        .fold(T::default(), |acc, (s, &l)| acc + get(s) * l)
+}
+
+/// Closest point of the simplex to the origin: drops the vertices that do not support
+/// it and returns the barycentric weights of the rest.
+fn reduce(simplex: Vec<Vertex>) -> (Vec<Vertex>, Vec<f64>) {
+    let w: Vec<Vec3<f64>> = simplex.iter().map(|s| s.w.clone()).collect();
+    let weights = match simplex.len() {
+        1 => vec![1.0],
+        2 => segment_weights(&w[0], &w[1]).to_vec(),
+        3 => triangle_weights(&w[0], &w[1], &w[2]).to_vec(),
+        _ => tetrahedron_weights(&w),
+    };
+    let kept: Vec<(Vertex, f64)> = simplex
+        .into_iter()
+        .zip(weights)
+        .filter(|(_, l)| *l > 0.0)
+        .collect();
+    kept.into_iter().unzip()
+}
+
+fn segment_weights(a: &Vec3<f64>, b: &Vec3<f64>) -> [f64; 2] {
+    let ab = b.clone() - a.clone();
+    let len2 = ab.square_length();
+    if len2 == 0.0 {
+        return [1.0, 0.0];
+    }
+    let t = (-a.dot(&ab) / len2).clamp(0.0, 1.0);
+    [1.0 - t, t]
+}
+
+/// Voronoi-region walk of Ericson's closest point on triangle, with the origin as query.
+fn triangle_weights(a: &Vec3<f64>, b: &Vec3<f64>, c: &Vec3<f64>) -> [f64; 3] {
+    let ab = b.clone() - a.clone();
+    let ac = c.clone() - a.clone();
+    let d1 = -ab.dot(a);
+    let d2 = -ac.dot(a);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return [1.0, 0.0, 0.0];
+    }
+    let d3 = -ab.dot(b);
+    let d4 = -ac.dot(b);
+    if d3 >= 0.0 && d4 <= d3 {
+        return [0.0, 1.0, 0.0];
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let t = d1 / (d1 - d3);
+        return [1.0 - t, t, 0.0];
+    }
+    let d5 = -ab.dot(c);
+    let d6 = -ac.dot(c);
+    if d6 >= 0.0 && d5 <= d6 {
+        return [0.0, 0.0, 1.0];
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let t = d2 / (d2 - d6);
+        return [1.0 - t, 0.0, t];
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        let t = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return [0.0, 1.0 - t, t];
+    }
+    let sum = va + vb + vc;
+    if sum <= 0.0 || !sum.is_finite() {
+        // Degenerate triangle: fall back to the best edge.
+        let candidates = [
+            (0, 1, segment_weights(a, b)),
+            (0, 2, segment_weights(a, c)),
+            (1, 2, segment_weights(b, c)),
+        ];
+        let w = [a, b, c];
+        let (i, j, l) = candidates
+            .into_iter()
+            .min_by(|x, y| {
+                let px = w[x.0].clone() * x.2[0] + w[x.1].clone() * x.2[1];
+                let py = w[y.0].clone() * y.2[0] + w[y.1].clone() * y.2[1];
+                px.square_length().total_cmp(&py.square_length())
+            })
+            .unwrap();
+        let mut weights = [0.0; 3];
+        weights[i] = l[0];
+        weights[j] = l[1];
+        return weights;
+    }
+    let v = vb / sum;
+    let t = vc / sum;
+    [1.0 - v - t, v, t]
+}
+
+/// Closest point of a tetrahedron to the origin: zero inside, otherwise the best of the
+/// faces the origin lies beyond. Inside-ness comes from the signed volumes of the
+/// sub-tetrahedra; a nearly flat tetrahedron has no reliable inside, so all of its faces
+/// are tried instead.
+fn tetrahedron_weights(w: &[Vec3<f64>]) -> Vec<f64> {
+    const FACES: [([usize; 3], usize); 4] = [
+        ([0, 1, 2], 3),
+        ([0, 3, 1], 2),
+        ([0, 2, 3], 1),
+        ([1, 3, 2], 0),
+    ];
+    let volume = |p: &Vec3<f64>, q: &Vec3<f64>, r: &Vec3<f64>, s: &Vec3<f64>| {
+        (q.clone() - p.clone()).dot(&(r.clone() - p.clone()).cross(&(s.clone() - p.clone())))
+    };
+    let origin = Vec3::all(0.0);
+    let total = volume(&w[0], &w[1], &w[2], &w[3]);
+    let lambdas = [
+        volume(&origin, &w[1], &w[2], &w[3]) / total,
+        volume(&w[0], &origin, &w[2], &w[3]) / total,
+        volume(&w[0], &w[1], &origin, &w[3]) / total,
+        volume(&w[0], &w[1], &w[2], &origin) / total,
+    ];
+    let size = (1..4)
+        .map(|i| (w[i].clone() - w[0].clone()).length())
+        .fold(0.0, f64::max);
+    let flat = total.abs() <= 1e-10 * size * size * size;
+    if !flat && lambdas.iter().all(|&l| l > 0.0) {
+        return lambdas.to_vec();
+    }
+
+    let mut best: Option<(f64, Vec<f64>)> = None;
+    for (face, opposite) in FACES {
+        if !flat && lambdas[opposite] > 0.0 {
+            continue;
+        }
+        let [i, j, k] = face;
+        let l = triangle_weights(&w[i], &w[j], &w[k]);
+        let p = w[i].clone() * l[0] + w[j].clone() * l[1] + w[k].clone() * l[2];
+        let d = p.square_length();
+        if best.as_ref().is_none_or(|(bd, _)| d < *bd) {
+            let mut weights = vec![0.0; 4];
+            weights[i] = l[0];
+            weights[j] = l[1];
+            weights[k] = l[2];
+            best = Some((d, weights));
+        }
+    }
+    // Unless every lambda is positive (handled above) at least one is zero, negative or
+    // NaN, and a flat tetrahedron skips no face, so some face is always tried.
+    best.unwrap().1
+}
+
+/// Whether two convex shapes touch or overlap.
+pub fn gjk_intersects<A: Support, B: Support>(a: &A, b: &B) -> bool {
+    matches!(gjk(a, b, true), Gjk::Intersecting(_))
+}
+
+/// Distance and closest points between two convex shapes, or `None` when they
+/// intersect. Curved shapes converge to within a small relative tolerance.
+pub fn gjk_distance<A: Support, B: Support>(a: &A, b: &B) -> Option<Separation> {
+    match gjk(a, b, false) {
+        Gjk::Intersecting(_) => None,
+        Gjk::Separated(simplex, weights) => {
+            let point_a: Point3 = combine(&simplex, &weights, |s| s.a.to_vec());
+            let point_b: Point3 = combine(&simplex, &weights, |s| s.b.to_vec());
+            Some(Separation {
+                distance: (point_a - point_b).length(),
+                point_a,
+                point_b,
+            })
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Face {
+    v: [usize; 3],
+    normal: Vec3<f64>,
+    distance: f64,
+}
+
+/// Penetration depth and contact normal of two intersecting convex shapes by the
+/// expanding polytope algorithm, seeded with GJK's final simplex. Returns `None` when the
+/// shapes are disjoint, or when their overlap has no volume (such as two coplanar
+/// triangles).
+pub fn epa_penetration<A: Support, B: Support>(a: &A, b: &B) -> Option<Penetration> {
+    let Gjk::Intersecting(simplex) = gjk(a, b, false) else {
+        return None;
+    };
+    let mut vertices = blow_up(a, b, simplex)?;
+
+    let centroid = combine::<Vec3<f64>, _>(&vertices, &[0.25; 4], |s| s.w.clone());
+    let make_face = |vertices: &[Vertex], v: [usize; 3]| -> Option<Face> {
+        let [p, q, r] = v.map(|i| vertices[i].w.clone());
+        let n = (q - p.clone()).cross(&(r - p.clone()));
+        let len = n.length();
+        if len == 0.0 {
+            return None;
+        }
+        let normal = n * (1.0 / len);
+        let distance = normal.dot(&p);
+        Some(Face {
+            v,
+            normal,
+            distance,
+        })
+    };
+    let mut faces = Vec::new();
+    for v in [[0, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]] {
+        let mut face = make_face(&vertices, v)?;
+        if face
+            .normal
+            .dot(&(vertices[v[0]].w.clone() - centroid.clone()))
+            < 0.0
+        {
+            face = make_face(&vertices, [v[0], v[2], v[1]])?;
+        }
+        faces.push(face);
+    }
+
+    for _ in 0..EPA_MAX_ITERATIONS {
+        let closest = faces
+            .iter()
+            .enumerate()
+            .min_by(|x, y| x.1.distance.total_cmp(&y.1.distance))
+            .map(|(i, _)| i)?;
+        let face = faces[closest].clone();
+        let w = Vertex::new(a, b, &face.normal);
+        let reach = face.normal.dot(&w.w);
+        if reach - face.distance <= EPA_TOLERANCE * reach.abs().max(1.0) {
+            break;
+        }
+
+        // Replace every face the new point sees by a fan from the horizon to it.
+        let index = vertices.len();
+        vertices.push(w);
+        let mut horizon: Vec<[usize; 2]> = Vec::new();
+        faces.retain(|f| {
+            let visible = f
+                .normal
+                .dot(&(vertices[index].w.clone() - vertices[f.v[0]].w.clone()))
+                > 0.0;
+            if visible {
+                for k in 0..3 {
+                    let edge = [f.v[k], f.v[(k + 1) % 3]];
+                    match horizon.iter().position(|e| *e == [edge[1], edge[0]]) {
+                        Some(p) => {
+                            horizon.swap_remove(p);
+                        }
+                        None => horizon.push(edge),
+                    }
+                }
+            }
+            !visible
+        });
+        for [i, j] in horizon {
+            if let Some(face) = make_face(&vertices, [i, j, index]) {
+                faces.push(face);
+            }
+        }
+    }
+
+    let face = faces
+        .iter()
+        .min_by(|x, y| x.distance.total_cmp(&y.distance))?;
+    let [p, q, r] = face.v.map(|i| &vertices[i]);
+    let l = triangle_weights(
+        &(p.w.clone() - face.normal.clone() * face.distance),
+        &(q.w.clone() - face.normal.clone() * face.distance),
+        &(r.w.clone() - face.normal.clone() * face.distance),
+    );
+    let support = [p.clone(), q.clone(), r.clone()];
+    Some(Penetration {
+        depth: face.distance,
+        normal: face.normal.clone(),
+        point_a: combine(&support, &l, |s| s.a.to_vec()),
+        point_b: combine(&support, &l, |s| s.b.to_vec()),
+    })
+}
+
+/// Grows GJK's terminating simplex into a tetrahedron when the origin was found on a
+/// vertex, edge or face of a smaller one.
+fn blow_up<A: Support, B: Support>(a: &A, b: &B, mut simplex: Vec<Vertex>) -> Option<Vec<Vertex>> {
+    let axes = [
+        vec3![1.0, 0.0, 0.0],
+        vec3![0.0, 1.0, 0.0],
+        vec3![0.0, 0.0, 1.0],
+    ];
+    let scale = simplex
+        .iter()
+        .map(|s| s.w.square_length())
+        .fold(0.0, f64::max)
+        .max(f64::MIN_POSITIVE);
+    let eps = 1e-20 * scale;
+
+    if simplex.len() == 1 {
+        let found = axes
+            .iter()
+            .flat_map(|axis| [axis.clone(), axis.clone() * -1.0])
+            .map(|dir| Vertex::new(a, b, &dir))
+            .find(|w| (w.w.clone() - simplex[0].w.clone()).square_length() > eps)?;
+        simplex.push(found);
+    }
+    if simplex.len() == 2 {
+        let d = (simplex[1].w.clone() - simplex[0].w.clone()).normalize();
+        let least = (0..3)
+            .min_by(|&i, &j| d[i].abs().total_cmp(&d[j].abs()))
+            .unwrap();
+        let dir0 = d.cross(&axes[least]).normalize();
+        let step = d.cross(&dir0);
+        // Probe every 60 degrees around the segment so no side of it goes unchecked.
+        let found = (0..6).find_map(|k| {
+            let (sin, cos) = (k as f64 * std::f64::consts::FRAC_PI_3).sin_cos();
+            let dir = dir0.clone() * cos + step.clone() * sin;
+            let w = Vertex::new(a, b, &dir);
+            let off = (w.w.clone() - simplex[0].w.clone()).cross(&d);
+            (off.square_length() > eps).then_some(w)
+        })?;
+        simplex.push(found);
+    }
+    if simplex.len() == 3 {
+        let [p, q, r] = [0, 1, 2].map(|i| simplex[i].w.clone());
+        let n = (q - p.clone()).cross(&(r - p.clone()));
+        let found = [n.clone(), n.clone() * -1.0]
+            .into_iter()
+            .map(|dir| Vertex::new(a, b, &dir))
+            .find(|w| n.dot(&(w.w.clone() - p.clone())).abs() > eps.sqrt() * n.length())?;
+        simplex.push(found);
+    }
+    Some(simplex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mat::Mat3;
+    use crate::test_util::{assert_close, random_values};
+
+    fn rotation(a: f64, b: f64) -> Mat3<f64> {
+        let (sa, ca) = a.sin_cos();
+        let (sb, cb) = b.sin_cos();
+        let rz = Mat3::new([[ca, -sa, 0.0], [sa, ca, 0.0], [0.0, 0.0, 1.0]]);
+        let rx = Mat3::new([[1.0, 0.0, 0.0], [0.0, cb, -sb], [0.0, sb, cb]]);
+        rz.mul_mat(&rx)
+    }
+
+    fn random_obb(c: &[f64]) -> Obb3 {
+        Obb3::new(
+            Point3::new(c[0] * 4.0, c[1] * 4.0, c[2] * 4.0),
+            rotation(c[3] * 6.3, c[4] * 6.3),
+            vec3![0.2 + c[5], 0.2 + c[6], 0.2 + c[7]],
+        )
+    }
+
+    fn shifted(obb: &Obb3, offset: Vec3<f64>) -> Obb3 {
+        Obb3::new(
+            obb.center + offset,
+            obb.axes.clone(),
+            obb.half_extents.clone(),
+        )
+    }
+
+    #[test]
+    fn test_support() {
+        let sphere = Sphere::new(Point3::new(1.0, 0.0, 0.0), 2.0);
+        assert_eq!(
+            sphere.support(&vec3![0.0, 3.0, 0.0]),
+            Point3::new(1.0, 2.0, 0.0)
+        );
+        let aabb = Aabb3::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 2.0, 3.0));
+        assert_eq!(
+            aabb.support(&vec3![1.0, -1.0, 1.0]),
+            Point3::new(1.0, 0.0, 3.0)
+        );
+        let capsule = Capsule::new(Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, 4.0), 1.0);
+        let p = capsule.support(&vec3![3.0, 0.0, 4.0]);
+        assert!((p - Point3::new(0.6, 0.0, 4.8)).length() < 1e-12);
+        assert_eq!(capsule.aabb().max, Point3::new(1.0, 1.0, 5.0));
+        let triangle = Triangle3::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        );
+        assert_eq!(
+            triangle.support(&vec3![-1.0, 2.0, 0.0]),
+            Point3::new(0.0, 1.0, 0.0)
+        );
+        let obb = Obb3::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Mat3::identity(),
+            vec3![1.0, 2.0, 3.0],
+        );
+        assert_eq!(
+            obb.support(&vec3![-1.0, 1.0, -1.0]),
+            Point3::new(-1.0, 2.0, -3.0)
+        );
+    }
+
+    #[test]
+    fn test_distance() {
+        let a = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0);
+        let b = Sphere::new(Point3::new(3.0, 4.0, 0.0), 2.0);
+        let s = gjk_distance(&a, &b).unwrap();
+        assert_close(s.distance, 2.0, 1e-6);
+        assert!((s.point_a - Point3::new(0.6, 0.8, 0.0)).length() < 1e-3);
+        assert!((s.point_b - Point3::new(1.8, 2.4, 0.0)).length() < 1e-3);
+
+        // A point against a triangle matches the direct closest-point query.
+        let triangle = Triangle3::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(4.0, 0.0, 1.0),
+            Point3::new(0.0, 3.0, 2.0),
+        );
+        for c in random_values(60, 1).chunks(3) {
+            let p = Point3::new(c[0] * 8.0 - 2.0, c[1] * 8.0 - 2.0, c[2] * 8.0 - 2.0);
+            let s = gjk_distance(&triangle, &p).unwrap();
+            let closest = triangle.closest_point(&p);
+            assert_close(s.distance, (closest - p).length(), 1e-9);
+            assert!((s.point_a - closest).length() < 1e-6);
+        }
+
+        // Capsule against box: the gap is along z between the rounded end and the top face.
+        let capsule = Capsule::new(Point3::new(0.0, 0.0, 3.0), Point3::new(0.0, 0.0, 6.0), 0.5);
+        let aabb = Aabb3::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+        let s = gjk_distance(&capsule, &aabb).unwrap();
+        assert_close(s.distance, 1.5, 1e-6);
+        assert_close(s.point_b[2], 1.0, 1e-9);
+
+        let hull = crate::hull::convex_hull_3d(&aabb_corners(&aabb));
+        let s = gjk_distance(&hull, &Point3::new(3.0, 3.0, 0.0)).unwrap();
+        assert_close(s.distance, 8.0f64.sqrt(), 1e-9);
+
+        assert!(gjk_distance(&a, &Point3::new(0.5, 0.0, 0.0)).is_none());
+        assert!(gjk_intersects(
+            &a,
+            &Sphere::new(Point3::new(2.9, 0.0, 0.0), 2.0)
+        ));
+        assert!(!gjk_intersects(&a, &b));
+    }
+
+    fn aabb_corners(aabb: &Aabb3) -> Vec<Point3> {
+        (0..8)
+            .map(|n| {
+                let pick = |i: usize| {
+                    if n & (1 << i) == 0 {
+                        aabb.min[i]
+                    } else {
+                        aabb.max[i]
+                    }
+                };
+                Point3::new(pick(0), pick(1), pick(2))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_intersects_matches_sat() {
+        let values = random_values(16 * 500, 2);
+        let mut hits = 0;
+        for c in values.chunks(16) {
+            let a = random_obb(&c[..8]);
+            let b = random_obb(&c[8..]);
+            let expected = a.overlaps(&b);
+            assert_eq!(gjk_intersects(&a, &b), expected);
+            assert_eq!(gjk_distance(&a, &b).is_none(), expected);
+            hits += expected as usize;
+        }
+        assert!(hits > 50 && hits < 450);
+    }
+
+    #[test]
+    fn test_tetrahedron_weights_degenerate() {
+        let flat = [
+            vec3![1.0, 0.0, 1.0],
+            vec3![-1.0, 1.0, 1.0],
+            vec3![-1.0, -1.0, 1.0],
+            vec3![0.0, 0.0, 1.0],
+        ];
+        let collapsed = [
+            vec3![1.0, 2.0, 3.0],
+            vec3![1.0, 2.0, 3.0],
+            vec3![1.0, 2.0, 3.0],
+            vec3![1.0, 2.0, 3.0],
+        ];
+        let origin_corner = [
+            vec3![0.0, 0.0, 0.0],
+            vec3![1.0, 0.0, 0.0],
+            vec3![0.0, 1.0, 0.0],
+            vec3![0.0, 0.0, 1.0],
+        ];
+        for (w, distance) in [(flat, 1.0), (collapsed, 14f64.sqrt()), (origin_corner, 0.0)] {
+            let weights = tetrahedron_weights(&w);
+            assert_close(weights.iter().sum(), 1.0, 1e-12);
+            let p = w
+                .iter()
+                .zip(&weights)
+                .fold(Vec3::all(0.0), |p, (w, l)| p + w.clone() * *l);
+            assert_close(p.length(), distance, 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_penetration() {
+        let a = Aabb3::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 2.0, 2.0));
+        let b = Aabb3::new(Point3::new(1.7, 0.5, 0.5), Point3::new(3.0, 1.5, 1.5));
+        let p = epa_penetration(&a, &b).unwrap();
+        assert_close(p.depth, 0.3, 1e-9);
+        assert!((p.normal.clone() - vec3![1.0, 0.0, 0.0]).length() < 1e-9);
+        assert_close(p.point_a[0], 2.0, 1e-9);
+        assert_close(p.point_b[0], 1.7, 1e-9);
+        assert!(epa_penetration(
+            &a,
+            &Aabb3::new(Point3::new(2.5, 0.0, 0.0), Point3::new(3.0, 1.0, 1.0))
+        )
+        .is_none());
+
+        let s1 = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0);
+        let s2 = Sphere::new(Point3::new(0.0, 1.5, 0.0), 1.0);
+        let p = epa_penetration(&s1, &s2).unwrap();
+        assert_close(p.depth, 0.5, 1e-3);
+        assert!((p.normal - vec3![0.0, 1.0, 0.0]).length() < 1e-2);
+
+        // Concentric shapes: GJK ends on the origin itself and EPA must grow the simplex.
+        let p = epa_penetration(
+            &a,
+            &Aabb3::new(Point3::new(0.5, 0.5, 0.5), Point3::new(1.5, 1.5, 1.5)),
+        )
+        .unwrap();
+        assert_close(p.depth, 1.5, 1e-9);
+
+        // A thin shape whose off-segment vertices all lie behind the first probe
+        // direction: growing the segment has to look on the far side of it.
+        let hull = crate::hull::convex_hull_3d(&[
+            Point3::new(-1.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.2, 0.5, -1.0),
+            Point3::new(-0.2, 1.0, -0.5),
+        ]);
+        let origin = Point3::new(0.0, 0.0, 0.0);
+        let segment = vec![
+            Vertex::new(&hull, &origin, &vec3![-1.0, 0.0, 0.0]),
+            Vertex::new(&hull, &origin, &vec3![1.0, 0.0, 0.0]),
+        ];
+        assert_eq!(blow_up(&hull, &origin, segment).unwrap().len(), 4);
+
+        // Separating along the normal by the depth leaves the boxes just touching.
+        let values = random_values(16 * 300, 3);
+        for c in values.chunks(16) {
+            let a = random_obb(&c[..8]);
+            let b = random_obb(&c[8..]);
+            let Some(p) = epa_penetration(&a, &b) else {
+                continue;
+            };
+            assert!(p.depth >= 0.0);
+            assert_close(p.normal.length(), 1.0, 1e-9);
+            assert_close((p.point_a - p.point_b).dot(&p.normal), p.depth, 1e-6);
+            let out = shifted(&b, p.normal.clone() * (p.depth + 1e-6));
+            let inside = shifted(&b, p.normal.clone() * (p.depth - 1e-6).max(0.0));
+            assert!(!out.overlaps(&a));
+            assert!(inside.overlaps(&a));
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use std::fmt::Debug;
+use std::ops::{Add, Index, IndexMut, Mul, Sub};
+
+use crate::point::{Point2, Point3};
+use crate::vec::{Vec2, Vec3};
+
+/// Maximum halvings of a parameter interval when integrating arc length.
+const LENGTH_MAX_DEPTH: u32 = 24;
+/// Relative accuracy of arc lengths.
+const LENGTH_TOLERANCE: f64 = 1e-12;
+/// Spans sampled to bracket the closest point before refining it.
+const CLOSEST_SAMPLES: usize = 32;
+
+/// Points a curve can pass through: an affine space whose differences are `Vector`.
+pub trait CurvePoint:
+    Copy
+    + Debug
+    + PartialEq
+    + Index<usize, Output = f64>
+    + IndexMut<usize>
+    + Sub<Output = Self::Vector>
+    + Add<Self::Vector, Output = Self>
+{
+    type Vector: Clone
+        + Debug
+        + Default
+        + PartialEq
+        + Index<usize, Output = f64>
+        + Add<Output = Self::Vector>
+        + Sub<Output = Self::Vector>
+        + Mul<f64, Output = Self::Vector>;
+
+    const DIM: usize;
+
+    fn dot(a: &Self::Vector, b: &Self::Vector) -> f64;
+
+    fn lerp(&self, rhs: &Self, t: f64) -> Self {
+        *self + (*rhs - *self) * t
+    }
+}
+
+impl CurvePoint for Point2 {
+    type Vector = Vec2<f64>;
+
+    const DIM: usize = 2;
+
+    fn dot(a: &Vec2<f64>, b: &Vec2<f64>) -> f64 {
+        a.dot(b)
+    }
+}
+
+impl CurvePoint for Point3 {
+    type Vector = Vec3<f64>;
+
+    const DIM: usize = 3;
+
+    fn dot(a: &Vec3<f64>, b: &Vec3<f64>) -> f64 {
+        a.dot(b)
+    }
+}
+
+/// A parametric curve over `domain()`. Arc length, arc-length sampling and closest points
+/// are derived from `at` and `derivative`.
+pub trait Curve {
+    type Point: CurvePoint;
+
+    fn at(&self, t: f64) -> Self::Point;
+
+    fn derivative(&self, t: f64) -> <Self::Point as CurvePoint>::Vector;
+
+    fn domain(&self) -> (f64, f64) {
+        (0.0, 1.0)
+    }
+
+    fn speed(&self, t: f64) -> f64 {
+        let d = self.derivative(t);
+        Self::Point::dot(&d, &d).sqrt()
+    }
+
+    fn length(&self) -> f64 {
+        let (t0, t1) = self.domain();
+        self.length_between(t0, t1)
+    }
+
+    /// Arc length from `t0` to `t1` by adaptive Gauss-Legendre quadrature of the speed.
+    fn length_between(&self, t0: f64, t1: f64) -> f64 {
+        let speed = |t: f64| self.speed(t);
+        let whole = gauss_legendre(&speed, t0, t1);
+        adaptive_length(&speed, t0, t1, whole, LENGTH_MAX_DEPTH)
+    }
+
+    /// Parameter at arc length `s` from the start of the domain, clamped to the curve.
+    fn parameter_at_length(&self, s: f64) -> f64 {
+        let (t0, _) = self.domain();
+        parameter_after(self, t0, s)
+    }
+
+    /// `count` points spaced evenly by arc length, including both ends.
+    fn sample_by_length(&self, count: usize) -> Vec<Self::Point> {
+        assert!(count >= 2, "need at least the two end points");
+        let (t0, t1) = self.domain();
+        let step = self.length() / (count - 1) as f64;
+        let mut points = vec![self.at(t0)];
+        let mut t = t0;
+        for _ in 1..count - 1 {
+            t = parameter_after(self, t, step);
+            points.push(self.at(t));
+        }
+        points.push(self.at(t1));
+        points
+    }
+
+    /// Parameter of the point closest to `p`. The domain is sampled, and every sampled
+    /// local minimum is narrowed down by golden-section search, since a curve that loops
+    /// back can pass close to `p` more than once.
+    fn closest_parameter(&self, p: &Self::Point) -> f64 {
+        let (t0, t1) = self.domain();
+        let square_distance = |t: f64| {
+            let d = self.at(t) - *p;
+            Self::Point::dot(&d, &d)
+        };
+        let step = (t1 - t0) / CLOSEST_SAMPLES as f64;
+        let samples: Vec<f64> = (0..=CLOSEST_SAMPLES)
+            .map(|i| square_distance(t0 + step * i as f64))
+            .collect();
+        // Golden section never lands exactly on the ends, so they compete on their own.
+        let mut best = if samples[0] <= samples[CLOSEST_SAMPLES] {
+            (t0, samples[0])
+        } else {
+            (t1, samples[CLOSEST_SAMPLES])
+        };
+        for i in 0..=CLOSEST_SAMPLES {
+            let left = i.saturating_sub(1);
+            let right = (i + 1).min(CLOSEST_SAMPLES);
+            if samples[i] > samples[left] || samples[i] > samples[right] {
+                continue;
+            }
+            let lo = t0 + step * left as f64;
+            let hi = t0 + step * right as f64;
+            let t = golden_section(&square_distance, lo, hi, 1e-12 * (t1 - t0));
+            let d = square_distance(t);
+            if d < best.1 {
+                best = (t, d);
+            }
+        }
+        best.0
+    }
+
+    fn closest_point(&self, p: &Self::Point) -> Self::Point {
+        self.at(self.closest_parameter(p))
+    }
+}
+
+/// Minimum of `f` on `[lo, hi]`, assuming a single one, to within `tolerance`.
+fn golden_section(f: &dyn Fn(f64) -> f64, mut lo: f64, mut hi: f64, tolerance: f64) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let mut a = hi - ratio * (hi - lo);
+    let mut b = lo + ratio * (hi - lo);
+    let (mut fa, mut fb) = (f(a), f(b));
+    while hi - lo > tolerance {
+        if fa < fb {
+            hi = b;
+            (b, fb) = (a, fa);
+            a = hi - ratio * (hi - lo);
+            fa = f(a);
+        } else {
+            lo = a;
+            (a, fa) = (b, fb);
+            b = lo + ratio * (hi - lo);
+            fb = f(b);
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+/// Five-point Gauss-Legendre rule on `[a, b]`.
+fn gauss_legendre(f: &dyn Fn(f64) -> f64, a: f64, b: f64) -> f64 {
+    const NODES: [(f64, f64); 5] = [
+        (0.0, 0.568_888_888_888_888_9),
+        (0.538_469_310_105_683, 0.478_628_670_499_366_47),
+        (-0.538_469_310_105_683, 0.478_628_670_499_366_47),
+        (0.906_179_845_938_664, 0.236_926_885_056_189_08),
+        (-0.906_179_845_938_664, 0.236_926_885_056_189_08),
+    ];
+    let (mid, half) = ((a + b) / 2.0, (b - a) / 2.0);
+    half * NODES
+        .iter()
+        .map(|&(x, w)| w * f(mid + half * x))
+        .sum::<f64>()
+}
+
+fn adaptive_length(f: &dyn Fn(f64) -> f64, a: f64, b: f64, whole: f64, depth: u32) -> f64 {
+    let mid = (a + b) / 2.0;
+    let left = gauss_legendre(f, a, mid);
+    let right = gauss_legendre(f, mid, b);
+    if depth == 0 || (left + right - whole).abs() <= LENGTH_TOLERANCE * whole.abs().max(1e-300) {
+        return left + right;
+    }
+    adaptive_length(f, a, mid, left, depth - 1) + adaptive_length(f, mid, b, right, depth - 1)
+}
+
+/// Parameter at arc length `s` past `start`: Newton steps on the length, kept inside a
+/// bisection bracket so that stationary points cannot throw it off.
+fn parameter_after<C: Curve + ?Sized>(curve: &C, start: f64, s: f64) -> f64 {
+    let (_, end) = curve.domain();
+    if s <= 0.0 {
+        return start;
+    }
+    let total = curve.length_between(start, end);
+    if s >= total {
+        return end;
+    }
+    let (mut lo, mut hi) = (start, end);
+    let mut t = start + (end - start) * s / total;
+    for _ in 0..64 {
+        let error = curve.length_between(start, t) - s;
+        if error.abs() <= LENGTH_TOLERANCE * total {
+            break;
+        }
+        if error > 0.0 {
+            hi = t;
+        } else {
+            lo = t;
+        }
+        let speed = curve.speed(t);
+        let next = t - error / speed;
+        t = if speed > 0.0 && next > lo && next < hi {
+            next
+        } else {
+            (lo + hi) / 2.0
+        };
+    }
+    t
+}
@@ -0,0 +1,618 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::aabb::Aabb2;
+use crate::point::Point2;
+use crate::polygon::Polygon2;
+use crate::predicates::{incircle, orient2d};
+use crate::triangle::Triangle2;
+use crate::vec::Vec2;
+
+const NONE: usize = usize::MAX;
+
+/// Whether `x` and `y` are non-zero with opposite signs.
+fn opposite_signs(x: f64, y: f64) -> bool {
+    (x < 0.0 && y > 0.0) || (x > 0.0 && y < 0.0)
+}
+
+/// Monotonic stand-in for the angle of `d`, in `[0, 1)`.
+fn pseudo_angle(d: &Vec2<f64>) -> f64 {
+    let p = d[0] / (d[0].abs() + d[1].abs());
+    (if d[1] > 0.0 { 3.0 - p } else { 1.0 + p }) / 4.0
+}
+
+/// A triangulation of a point set. Triangles index into `points` and wind
+/// counter-clockwise; points not used by any triangle (duplicates, or all points
+/// of collinear input) are kept so indices stay stable.
+#[derive(Debug, Clone, Default)]
+pub struct Triangulation {
+    pub points: Vec<Point2>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+/// Half-edge state of the sweep-hull triangulator. Half-edge `e` runs from
+/// `triangles[e]` to the next vertex of its triangle; `halfedges[e]` is its twin.
+struct Sweep<'a> {
+    points: &'a [Point2],
+    triangles: Vec<usize>,
+    halfedges: Vec<usize>,
+    hull_prev: Vec<usize>,
+    hull_next: Vec<usize>,
+    /// The hull half-edge starting at each hull vertex.
+    hull_tri: Vec<usize>,
+    stack: Vec<usize>,
+}
+
+impl Sweep<'_> {
+    fn link(&mut self, a: usize, b: usize) {
+        self.halfedges[a] = b;
+        if b != NONE {
+            self.halfedges[b] = a;
+        } else {
+            self.hull_tri[self.triangles[a]] = a;
+        }
+    }
+
+    fn add_triangle(&mut self, v: [usize; 3], twins: [usize; 3]) -> usize {
+        let t = self.triangles.len();
+        self.triangles.extend(v);
+        self.halfedges.extend([NONE; 3]);
+        for (k, twin) in twins.into_iter().enumerate() {
+            self.link(t + k, twin);
+        }
+        t
+    }
+
+    /// Flips edges until every edge reachable from `a` is locally Delaunay.
+    fn legalize(&mut self, a: usize) {
+        self.stack.push(a);
+        while let Some(a) = self.stack.pop() {
+            let b = self.halfedges[a];
+            if b == NONE {
+                continue;
+            }
+            let a0 = a - a % 3;
+            let (al, ar) = (a0 + (a + 1) % 3, a0 + (a + 2) % 3);
+            let b0 = b - b % 3;
+            let (br, bl) = (b0 + (b + 1) % 3, b0 + (b + 2) % 3);
+            let [p0, pr, pl, p1] = [ar, a, al, bl].map(|e| self.points[self.triangles[e]]);
+            if incircle(&p0, &pr, &pl, &p1) <= 0.0 {
+                continue;
+            }
+
+            self.triangles[a] = self.triangles[bl];
+            self.triangles[b] = self.triangles[ar];
+            let (hbl, har) = (self.halfedges[bl], self.halfedges[ar]);
+            self.link(a, hbl);
+            self.link(b, har);
+            self.link(ar, bl);
+            self.stack.push(br);
+            self.stack.push(a);
+        }
+    }
+}
+
+/// Index of the point closest to `p` among those accepted by `filter`.
+fn closest(points: &[Point2], p: &Point2, filter: impl Fn(usize) -> bool) -> Option<usize> {
+    (0..points.len()).filter(|&i| filter(i)).min_by(|&i, &j| {
+        (points[i] - *p)
+            .square_length()
+            .total_cmp(&(points[j] - *p).square_length())
+    })
+}
+
+/// Delaunay triangulation by radial sweep (Delaunator's algorithm): points are added in
+/// order of distance from a seed triangle, each connected to the visible part of the
+/// convex hull, and new edges are legalized by Lawson flips.
+pub fn delaunay(points: &[Point2]) -> Triangulation {
+    let mut result = Triangulation {
+        points: points.to_vec(),
+        triangles: vec![],
+    };
+
+    let bounds = Aabb2::from_points(points.iter().copied());
+    let Some(i0) = closest(points, &bounds.center(), |_| true) else {
+        return result;
+    };
+    let p0 = points[i0];
+    let Some(mut i1) = closest(points, &p0, |i| points[i] != p0) else {
+        return result;
+    };
+    let radius = |i: usize| {
+        Triangle2::new(p0, points[i1], points[i])
+            .circumcircle()
+            .map_or(f64::INFINITY, |(_, r)| r)
+    };
+    let mut i2 = (0..points.len())
+        .min_by(|&i, &j| radius(i).total_cmp(&radius(j)))
+        .unwrap();
+    let Some((center, _)) = Triangle2::new(p0, points[i1], points[i2]).circumcircle() else {
+        // Every point is collinear.
+        return result;
+    };
+    if orient2d(&p0, &points[i1], &points[i2]) < 0.0 {
+        std::mem::swap(&mut i1, &mut i2);
+    }
+
+    let n = points.len();
+    let mut ids: Vec<usize> = (0..n).collect();
+    ids.sort_by(|&i, &j| {
+        (points[i] - center)
+            .square_length()
+            .total_cmp(&(points[j] - center).square_length())
+    });
+
+    let hash_size = (n as f64).sqrt().ceil() as usize;
+    let hash_key =
+        |p: &Point2| (pseudo_angle(&(*p - center)) * hash_size as f64).floor() as usize % hash_size;
+    let mut hash = vec![NONE; hash_size];
+
+    let mut sweep = Sweep {
+        points,
+        triangles: Vec::with_capacity(6 * n),
+        halfedges: Vec::with_capacity(6 * n),
+        hull_prev: vec![NONE; n],
+        hull_next: vec![NONE; n],
+        hull_tri: vec![NONE; n],
+        stack: vec![],
+    };
+    sweep.add_triangle([i0, i1, i2], [NONE; 3]);
+    for (v, next) in [(i0, i1), (i1, i2), (i2, i0)] {
+        sweep.hull_next[v] = next;
+        sweep.hull_prev[next] = v;
+        hash[hash_key(&points[v])] = v;
+    }
+
+    for (k, &i) in ids.iter().enumerate() {
+        let p = points[i];
+        if i == i0 || i == i1 || i == i2 || (k > 0 && p == points[ids[k - 1]]) {
+            continue;
+        }
+
+        // Find a hull edge visible from `p`, starting near it in angle.
+        let key = hash_key(&p);
+        let mut start = NONE;
+        for j in 0..hash_size {
+            let s = hash[(key + j) % hash_size];
+            if s != NONE && s != sweep.hull_next[s] {
+                start = s;
+                break;
+            }
+        }
+        start = sweep.hull_prev[start];
+        let mut e = start;
+        loop {
+            let q = sweep.hull_next[e];
+            if orient2d(&points[e], &points[q], &p) < 0.0 {
+                break;
+            }
+            e = q;
+            if e == start {
+                e = NONE;
+                break;
+            }
+        }
+        if e == NONE {
+            // Coincides with a point already in the triangulation.
+            continue;
+        }
+
+        let first = sweep.hull_next[e];
+        let t = sweep.add_triangle([e, i, first], [NONE, NONE, sweep.hull_tri[e]]);
+        sweep.legalize(t + 2);
+
+        // Walk forward and backward along the hull, fanning out from `p`.
+        let mut next = first;
+        loop {
+            let q = sweep.hull_next[next];
+            if orient2d(&points[next], &points[q], &p) >= 0.0 {
+                break;
+            }
+            let t = sweep.add_triangle(
+                [next, i, q],
+                [sweep.hull_tri[i], NONE, sweep.hull_tri[next]],
+            );
+            sweep.legalize(t + 2);
+            sweep.hull_next[next] = next;
+            next = q;
+        }
+        if e == start {
+            loop {
+                let q = sweep.hull_prev[e];
+                if orient2d(&points[q], &points[e], &p) >= 0.0 {
+                    break;
+                }
+                let t = sweep.add_triangle([q, i, e], [NONE, sweep.hull_tri[e], sweep.hull_tri[q]]);
+                sweep.legalize(t + 2);
+                sweep.hull_next[e] = e;
+                e = q;
+            }
+        }
+
+        sweep.hull_prev[i] = e;
+        sweep.hull_next[e] = i;
+        sweep.hull_prev[next] = i;
+        sweep.hull_next[i] = next;
+        hash[hash_key(&p)] = i;
+        hash[hash_key(&points[e])] = e;
+    }
+
+    result.triangles = sweep
+        .triangles
+        .chunks(3)
+        .map(|t| [t[0], t[1], t[2]])
+        .collect();
+    result
+}
+
+/// Constrained Delaunay triangulation: the Delaunay triangulation of `points` modified
+/// so that every constraint `[a, b]` appears as an edge. Each constraint is forced in by
+/// flipping the edges it crosses, after which the new edges are flipped back towards
+/// Delaunay (Sloan's algorithm). Constraints must not cross each other; one passing
+/// through another point is split there.
+pub fn constrained_delaunay(points: &[Point2], constraints: &[[usize; 2]]) -> Triangulation {
+    let mut triangulation = delaunay(points);
+
+    // Constraints may name a duplicate that the triangulation skipped.
+    let mut canonical = HashMap::new();
+    for &v in triangulation.triangles.iter().flatten() {
+        canonical.insert((points[v][0].to_bits(), points[v][1].to_bits()), v);
+    }
+    let vertex = |i: usize| {
+        canonical
+            .get(&(points[i][0].to_bits(), points[i][1].to_bits()))
+            .copied()
+    };
+
+    for &[a, b] in constraints {
+        if let (Some(a), Some(b)) = (vertex(a), vertex(b)) {
+            triangulation.insert_constraint(a, b);
+        }
+    }
+    triangulation
+}
+
+/// The vertex of `triangle` opposite its directed edge starting at `u`.
+fn opposite(triangle: &[usize; 3], u: usize) -> usize {
+    let k = triangle.iter().position(|&v| v == u).unwrap();
+    triangle[(k + 2) % 3]
+}
+
+impl Triangulation {
+    /// Unique undirected edges, each stored with the smaller index first.
+    pub fn edges(&self) -> Vec<[usize; 2]> {
+        let mut edges: Vec<[usize; 2]> = self
+            .triangles
+            .iter()
+            .flat_map(|t| [[t[0], t[1]], [t[1], t[2]], [t[2], t[0]]])
+            .map(|[a, b]| [a.min(b), a.max(b)])
+            .collect();
+        edges.sort();
+        edges.dedup();
+        edges
+    }
+
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+        self.triangles.iter().any(|t| {
+            (0..3).any(|k| (t[k], t[(k + 1) % 3]) == (a, b) || (t[k], t[(k + 1) % 3]) == (b, a))
+        })
+    }
+
+    fn insert_constraint(&mut self, a: usize, b: usize) {
+        let (pa, pb) = (self.points[a], self.points[b]);
+        if pa == pb || self.has_edge(a, b) {
+            return;
+        }
+
+        // A vertex exactly on the segment splits the constraint.
+        let vertices: HashSet<usize> = self.triangles.iter().flatten().copied().collect();
+        let dir = pb - pa;
+        let on_segment = vertices
+            .iter()
+            .copied()
+            .filter(|&v| {
+                let t = (self.points[v] - pa).dot(&dir);
+                v != a
+                    && v != b
+                    && orient2d(&pa, &pb, &self.points[v]) == 0.0
+                    && t > 0.0
+                    && t < dir.square_length()
+            })
+            .min_by(|&u, &v| {
+                (self.points[u] - pa)
+                    .dot(&dir)
+                    .total_cmp(&(self.points[v] - pa).dot(&dir))
+            });
+        if let Some(v) = on_segment {
+            self.insert_constraint(a, v);
+            self.insert_constraint(v, b);
+            return;
+        }
+
+        let crosses = |points: &[Point2], p: usize, q: usize| {
+            let (pp, pq) = (points[p], points[q]);
+            p != a
+                && p != b
+                && q != a
+                && q != b
+                && opposite_signs(orient2d(&pa, &pb, &pp), orient2d(&pa, &pb, &pq))
+                && opposite_signs(orient2d(&pp, &pq, &pa), orient2d(&pp, &pq, &pb))
+        };
+        let mut edge_map = HashMap::new();
+        for (i, t) in self.triangles.iter().enumerate() {
+            for k in 0..3 {
+                edge_map.insert((t[k], t[(k + 1) % 3]), i);
+            }
+        }
+
+        // Flip crossed edges whose quadrilateral is convex until none cross.
+        let mut queue: VecDeque<(usize, usize)> = self
+            .edges()
+            .into_iter()
+            .filter(|&[u, v]| crosses(&self.points, u, v))
+            .map(|[u, v]| (u, v))
+            .collect();
+        let mut created = vec![];
+        while let Some((u, v)) = queue.pop_front() {
+            match self.flip(&mut edge_map, u, v, |p, q, r, s| {
+                orient2d(p, q, r) > 0.0 && orient2d(r, s, p) > 0.0
+            }) {
+                Some(e) if crosses(&self.points, e.0, e.1) => queue.push_back(e),
+                Some(e) => created.push(e),
+                None => queue.push_back((u, v)),
+            }
+        }
+
+        // Restore the Delaunay property on the new edges, except the constraint itself.
+        let mut swapped = true;
+        while swapped {
+            swapped = false;
+            for e in created.iter_mut() {
+                if *e == (a, b) || *e == (b, a) {
+                    continue;
+                }
+                let flipped = self.flip(&mut edge_map, e.0, e.1, |p, q, r, s| {
+                    // `q, s, p` and `s, q, r` are the current triangles on either side.
+                    incircle(s, q, r, p) > 0.0
+                });
+                if let Some(flipped) = flipped {
+                    *e = flipped;
+                    swapped = true;
+                }
+            }
+        }
+    }
+
+    /// Flips the edge `u -> v` when `accept` approves the quadrilateral `p, u, q, v`
+    /// (counter-clockwise, with `p` and `q` opposite the edge), returning the new edge
+    /// `p -> q`.
+    fn flip(
+        &mut self,
+        edge_map: &mut HashMap<(usize, usize), usize>,
+        u: usize,
+        v: usize,
+        accept: impl Fn(&Point2, &Point2, &Point2, &Point2) -> bool,
+    ) -> Option<(usize, usize)> {
+        let (t1, t2) = (edge_map[&(u, v)], edge_map[&(v, u)]);
+        let p = opposite(&self.triangles[t1], u);
+        let q = opposite(&self.triangles[t2], v);
+        let [pp, pu, pq, pv] = [p, u, q, v].map(|i| self.points[i]);
+        if !accept(&pp, &pu, &pq, &pv) {
+            return None;
+        }
+        self.triangles[t1] = [p, u, q];
+        self.triangles[t2] = [q, v, p];
+        edge_map.remove(&(u, v));
+        edge_map.remove(&(v, u));
+        for t in [t1, t2] {
+            let tri = self.triangles[t];
+            for k in 0..3 {
+                edge_map.insert((tri[k], tri[(k + 1) % 3]), t);
+            }
+        }
+        Some((p, q))
+    }
+
+    /// Voronoi cell of every point, clipped to `bounds`. Cells are the duals of the
+    /// Delaunay triangulation, built by cutting `bounds` with the bisector of each
+    /// Delaunay neighbour; duplicates and points whose cell misses `bounds` get an
+    /// empty polygon.
+    pub fn voronoi_cells(&self, bounds: &Aabb2) -> Vec<Polygon2> {
+        let n = self.points.len();
+        let mut neighbours = vec![vec![]; n];
+        let mut present = vec![false; n];
+        if self.triangles.is_empty() {
+            // Collinear input: cells are slabs between consecutive points.
+            let mut order: Vec<usize> = (0..n).collect();
+            order.sort_by(|&i, &j| {
+                let (p, q) = (self.points[i], self.points[j]);
+                p[0].total_cmp(&q[0]).then(p[1].total_cmp(&q[1]))
+            });
+            order.dedup_by(|i, j| self.points[*i] == self.points[*j]);
+            for w in order.windows(2) {
+                neighbours[w[0]].push(w[1]);
+                neighbours[w[1]].push(w[0]);
+            }
+            for &i in &order {
+                present[i] = true;
+            }
+        } else {
+            for [u, v] in self.edges() {
+                neighbours[u].push(v);
+                neighbours[v].push(u);
+                present[u] = true;
+                present[v] = true;
+            }
+        }
+
+        let (lo, hi) = (bounds.min, bounds.max);
+        let frame = vec![lo, Point2::new(hi[0], lo[1]), hi, Point2::new(lo[0], hi[1])];
+        (0..n)
+            .map(|i| {
+                if !present[i] {
+                    return Polygon2::default();
+                }
+                let p = self.points[i];
+                let mut cell = frame.clone();
+                for &j in &neighbours[i] {
+                    let q = self.points[j];
+                    let mid = p + (q - p) * 0.5;
+                    cell = clip_half_plane(&cell, mid, &(q - p));
+                }
+                if cell.len() < 3 {
+                    cell.clear();
+                }
+                Polygon2::new(cell)
+            })
+            .collect()
+    }
+}
+
+/// Sutherland–Hodgman clip of a convex ring to `(x - origin) . normal <= 0`.
+fn clip_half_plane(ring: &[Point2], origin: Point2, normal: &Vec2<f64>) -> Vec<Point2> {
+    let mut out = vec![];
+    for k in 0..ring.len() {
+        let (p, q) = (ring[k], ring[(k + 1) % ring.len()]);
+        let dp = normal.dot(&(p - origin));
+        let dq = normal.dot(&(q - origin));
+        if dp <= 0.0 {
+            out.push(p);
+        }
+        if (dp < 0.0 && dq > 0.0) || (dp > 0.0 && dq < 0.0) {
+            out.push(p + (q - p) * (dp / (dp - dq)));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hull::convex_hull_2d;
+    use crate::polygon::ring_signed_area;
+
+    fn random_points(n: usize, seed: u64) -> Vec<Point2> {
+        let mut state = seed;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        (0..n)
+            .map(|_| Point2::new(next() * 10.0, next() * 10.0))
+            .collect()
+    }
+
+    fn assert_valid(t: &Triangulation, distinct: usize) {
+        let hull = convex_hull_2d(&t.points);
+        let mut area = 0.0;
+        for tri in &t.triangles {
+            let [a, b, c] = tri.map(|i| t.points[i]);
+            assert!(orient2d(&a, &b, &c) > 0.0);
+            area += Triangle2::new(a, b, c).area();
+        }
+        assert!((area - ring_signed_area(&hull)).abs() < 1e-9);
+        // Euler: a triangulation of n points with h on the hull has 2n - 2 - h triangles,
+        // counting points on hull edges as hull vertices.
+        let boundary = t.edges().len() * 2 - 3 * t.triangles.len();
+        assert_eq!(t.triangles.len(), 2 * distinct - 2 - boundary);
+    }
+
+    fn assert_delaunay(t: &Triangulation) {
+        for tri in &t.triangles {
+            let [a, b, c] = tri.map(|i| t.points[i]);
+            for (i, p) in t.points.iter().enumerate() {
+                if !tri.contains(&i) {
+                    assert!(incircle(&a, &b, &c, p) <= 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_delaunay_random() {
+        let points = random_points(300, 7);
+        let t = delaunay(&points);
+        assert_valid(&t, 300);
+        assert_delaunay(&t);
+    }
+
+    #[test]
+    fn test_delaunay_grid_and_duplicates() {
+        let mut points: Vec<Point2> = (0..5)
+            .flat_map(|i| (0..5).map(move |j| Point2::new(i as f64, j as f64)))
+            .collect();
+        points.extend(points.clone());
+        let t = delaunay(&points);
+        assert_eq!(t.triangles.len(), 32);
+        assert_valid(&t, 25);
+        assert_delaunay(&t);
+    }
+
+    #[test]
+    fn test_delaunay_degenerate() {
+        let collinear: Vec<Point2> = (0..5)
+            .map(|i| Point2::new(i as f64, 2.0 * i as f64))
+            .collect();
+        assert!(delaunay(&collinear).triangles.is_empty());
+        assert!(delaunay(&[Point2::new(1.0, 1.0); 4]).triangles.is_empty());
+        assert!(delaunay(&[]).triangles.is_empty());
+    }
+
+    #[test]
+    fn test_constrained() {
+        // A zigzag between two rows; the long horizontal edge crosses many Delaunay edges.
+        let mut points = vec![Point2::new(0.0, 0.0), Point2::new(10.0, 0.0)];
+        for i in 1..10 {
+            let y = if i % 2 == 0 { 0.5 } else { 0.4 };
+            points.push(Point2::new(i as f64, y));
+            points.push(Point2::new(i as f64 + 0.5, -y));
+        }
+        assert!(!delaunay(&points).has_edge(0, 1));
+        let t = constrained_delaunay(&points, &[[0, 1]]);
+        assert!(t.has_edge(0, 1));
+        assert_valid(&t, points.len());
+
+        // A diagonal through grid points is split at each one.
+        let grid: Vec<Point2> = (0..4)
+            .flat_map(|i| (0..4).map(move |j| Point2::new(i as f64, j as f64 * 0.5)))
+            .collect();
+        let t = constrained_delaunay(&grid, &[[0, 15]]);
+        assert!(t.has_edge(0, 5) && t.has_edge(5, 10) && t.has_edge(10, 15));
+        assert_valid(&t, 16);
+    }
+
+    #[test]
+    fn test_voronoi() {
+        let points = vec![
+            Point2::new(-1.0, -1.0),
+            Point2::new(1.0, -1.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(-1.0, 1.0),
+        ];
+        let bounds = Aabb2::new(Point2::new(-2.0, -2.0), Point2::new(2.0, 2.0));
+        let cells = delaunay(&points).voronoi_cells(&bounds);
+        for (cell, p) in cells.iter().zip(&points) {
+            assert!((cell.area() - 4.0).abs() < 1e-12);
+            assert!(cell.aabb().contains(p));
+        }
+
+        let mut points = random_points(100, 3);
+        points.push(points[0]);
+        let bounds = Aabb2::new(Point2::new(0.0, 0.0), Point2::new(10.0, 10.0));
+        let cells = delaunay(&points).voronoi_cells(&bounds);
+        let area: f64 = cells.iter().map(|c| c.area()).sum();
+        assert!((area - 100.0).abs() < 1e-9);
+        assert!(cells[100].exterior.is_empty());
+        for (cell, p) in cells.iter().zip(&points).take(100) {
+            assert_eq!(cell.winding_number(p), 1);
+        }
+
+        // Collinear sites give slabs.
+        let line: Vec<Point2> = (0..3)
+            .map(|i| Point2::new(i as f64 * 4.0 - 4.0, 0.0))
+            .collect();
+        let cells = delaunay(&line).voronoi_cells(&bounds.expand(10.0));
+        assert!((cells[1].area() - 4.0 * 30.0).abs() < 1e-9);
+    }
+}
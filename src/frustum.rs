@@ -0,0 +1,181 @@
+use crate::aabb::{Aabb2, Aabb3};
+use crate::mat::{Mat3, Mat4};
+use crate::point::{Point2, Point3};
+use crate::sphere::Sphere;
+use crate::vec::{Vec2, Vec3};
+
+/// A 2D view region: the half-planes `n . p + d >= 0` where a projective 2D camera
+/// maps points into the clip square `-w <= x, y <= w`.
+#[derive(Debug, Clone)]
+pub struct Frustum2 {
+    pub planes: [(Vec2<f64>, f64); 4],
+}
+
+impl Frustum2 {
+    /// Extracts the left, right, bottom and top planes of a view-projection matrix
+    /// acting on column vectors (Gribb and Hartmann).
+    pub fn from_matrix(m: &Mat3<f64>) -> Frustum2 {
+        let plane = |s: f64, row: usize| {
+            let n = vec2![m[2][0] + s * m[row][0], m[2][1] + s * m[row][1]];
+            let d = m[2][2] + s * m[row][2];
+            let len = n.length();
+            (n * (1.0 / len), d / len)
+        };
+        Frustum2 {
+            planes: [plane(1.0, 0), plane(-1.0, 0), plane(1.0, 1), plane(-1.0, 1)],
+        }
+    }
+
+    pub fn contains(&self, p: &Point2) -> bool {
+        self.planes
+            .iter()
+            .all(|(n, d)| n.dot(&p.to_vec()) + d >= 0.0)
+    }
+
+    /// Conservative box test: false only when the box lies entirely outside one plane.
+    pub fn intersects_aabb(&self, aabb: &Aabb2) -> bool {
+        self.planes.iter().all(|(n, d)| {
+            let corner = vec2![
+                if n[0] >= 0.0 {
+                    aabb.max[0]
+                } else {
+                    aabb.min[0]
+                },
+                if n[1] >= 0.0 {
+                    aabb.max[1]
+                } else {
+                    aabb.min[1]
+                }
+            ];
+            n.dot(&corner) + d >= 0.0
+        })
+    }
+}
+
+/// A view frustum: the half-spaces `n . p + d >= 0` that a projection maps into the
+/// OpenGL clip cube `-w <= x, y, z <= w`. Normals are unit length and point inwards.
+#[derive(Debug, Clone)]
+pub struct Frustum3 {
+    pub planes: [(Vec3<f64>, f64); 6],
+}
+
+impl Frustum3 {
+    /// Extracts the left, right, bottom, top, near and far planes of a view-projection
+    /// matrix acting on column vectors (Gribb and Hartmann).
+    pub fn from_matrix(m: &Mat4<f64>) -> Frustum3 {
+        let plane = |s: f64, row: usize| {
+            let n = vec3![
+                m[3][0] + s * m[row][0],
+                m[3][1] + s * m[row][1],
+                m[3][2] + s * m[row][2]
+            ];
+            let d = m[3][3] + s * m[row][3];
+            let len = n.length();
+            (n * (1.0 / len), d / len)
+        };
+        Frustum3 {
+            planes: [
+                plane(1.0, 0),
+                plane(-1.0, 0),
+                plane(1.0, 1),
+                plane(-1.0, 1),
+                plane(1.0, 2),
+                plane(-1.0, 2),
+            ],
+        }
+    }
+
+    pub fn contains(&self, p: &Point3) -> bool {
+        self.planes
+            .iter()
+            .all(|(n, d)| n.dot(&p.to_vec()) + d >= 0.0)
+    }
+
+    /// Conservative box test: false only when the box lies entirely outside one plane.
+    pub fn intersects_aabb(&self, aabb: &Aabb3) -> bool {
+        self.planes.iter().all(|(n, d)| {
+            let corner = vec3![
+                if n[0] >= 0.0 {
+                    aabb.max[0]
+                } else {
+                    aabb.min[0]
+                },
+                if n[1] >= 0.0 {
+                    aabb.max[1]
+                } else {
+                    aabb.min[1]
+                },
+                if n[2] >= 0.0 {
+                    aabb.max[2]
+                } else {
+                    aabb.min[2]
+                }
+            ];
+            n.dot(&corner) + d >= 0.0
+        })
+    }
+
+    /// Conservative sphere test: false only when the sphere lies entirely outside one plane.
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        self.planes
+            .iter()
+            .all(|(n, d)| n.dot(&sphere.center.to_vec()) + d >= -sphere.radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// OpenGL-style perspective looking down -z with a 90 degree field of view.
+    fn perspective(near: f64, far: f64) -> Mat4<f64> {
+        Mat4::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [
+                0.0,
+                0.0,
+                (far + near) / (near - far),
+                2.0 * far * near / (near - far),
+            ],
+            [0.0, 0.0, -1.0, 0.0],
+        ])
+    }
+
+    #[test]
+    fn test_frustum3() {
+        let f = Frustum3::from_matrix(&perspective(1.0, 10.0));
+        assert!(f.contains(&Point3::new(0.0, 0.0, -5.0)));
+        assert!(f.contains(&Point3::new(4.9, -4.9, -5.0)));
+        assert!(!f.contains(&Point3::new(5.1, 0.0, -5.0)));
+        assert!(!f.contains(&Point3::new(0.0, 0.0, -0.5)));
+        assert!(!f.contains(&Point3::new(0.0, 0.0, -11.0)));
+        assert!(!f.contains(&Point3::new(0.0, 0.0, 5.0)));
+
+        let inside = Aabb3::new(Point3::new(-1.0, -1.0, -3.0), Point3::new(1.0, 1.0, -2.0));
+        let straddling = Aabb3::new(Point3::new(4.0, 0.0, -5.0), Point3::new(6.0, 1.0, -4.0));
+        let behind = Aabb3::new(Point3::new(-1.0, -1.0, 1.0), Point3::new(1.0, 1.0, 2.0));
+        assert!(f.intersects_aabb(&inside));
+        assert!(f.intersects_aabb(&straddling));
+        assert!(!f.intersects_aabb(&behind));
+
+        assert!(f.intersects_sphere(&Sphere::new(Point3::new(0.0, 0.0, -10.5), 1.0)));
+        assert!(!f.intersects_sphere(&Sphere::new(Point3::new(0.0, 0.0, -11.5), 1.0)));
+    }
+
+    #[test]
+    fn test_frustum2() {
+        // A camera centred on (10, 20) showing 8 x 4 units, rotated by 90 degrees.
+        let m = Mat3::new([[0.0, 0.25, -5.0], [-0.5, 0.0, 5.0], [0.0, 0.0, 1.0]]);
+        let f = Frustum2::from_matrix(&m);
+        assert!(f.contains(&Point2::new(10.0, 20.0)));
+        assert!(f.contains(&Point2::new(11.9, 23.9)));
+        assert!(!f.contains(&Point2::new(12.1, 20.0)));
+        assert!(!f.contains(&Point2::new(10.0, 24.1)));
+
+        let near = Aabb2::new(Point2::new(11.0, 10.0), Point2::new(13.0, 17.0));
+        let far = Aabb2::new(Point2::new(13.0, 10.0), Point2::new(15.0, 30.0));
+        assert!(f.intersects_aabb(&near));
+        assert!(!f.intersects_aabb(&far));
+    }
+}
@@ -0,0 +1,480 @@
+use std::collections::HashMap;
+
+use crate::point::{Point2, Point3};
+use crate::predicates::orient2d;
+use crate::vec::Vec3;
+
+/// Convex hull by Andrew's monotone chain. Returns the hull vertices counter-clockwise,
+/// starting from the lowest-leftmost point. Duplicates and points lying on hull edges
+/// are dropped; collinear input yields its two endpoints.
+pub fn convex_hull_2d(points: &[Point2]) -> Vec<Point2> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let turns_left = |o: &Point2, a: &Point2, b: &Point2| orient2d(o, a, b) > 0.0;
+
+    let mut hull: Vec<Point2> = Vec::with_capacity(sorted.len() + 1);
+    for p in sorted.iter() {
+        while hull.len() >= 2 && !turns_left(&hull[hull.len() - 2], &hull[hull.len() - 1], p) {
+            hull.pop();
+        }
+        hull.push(*p);
+    }
+    let lower_len = hull.len() + 1;
+    for p in sorted.iter().rev().skip(1) {
+        while hull.len() >= lower_len
+            && !turns_left(&hull[hull.len() - 2], &hull[hull.len() - 1], p)
+        {
+            hull.pop();
+        }
+        hull.push(*p);
+    }
+    hull.pop();
+    hull
+}
+
+/// A closed triangulated convex hull. Faces wind counter-clockwise seen from outside.
+///
+/// Degenerate input produces a degenerate hull: coplanar points give a flat polygon
+/// with faces on both sides, collinear points give a single edge, and a single
+/// distinct point gives no edges at all.
+#[derive(Debug, Clone, Default)]
+pub struct ConvexHull3 {
+    pub vertices: Vec<Point3>,
+    pub faces: Vec<[usize; 3]>,
+    /// Unique undirected edges, each stored with the smaller index first.
+    pub edges: Vec<[usize; 2]>,
+    /// Outward unit normal of each face.
+    pub normals: Vec<Vec3<f64>>,
+}
+
+impl ConvexHull3 {
+    pub fn volume(&self) -> f64 {
+        self.faces
+            .iter()
+            .map(|f| {
+                let [a, b, c] = f.map(|i| self.vertices[i].to_vec());
+                a.dot(&b.cross(&c)) / 6.0
+            })
+            .sum()
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        self.faces
+            .iter()
+            .map(|f| {
+                let [a, b, c] = f.map(|i| self.vertices[i]);
+                (b - a).cross(&(c - a)).length() * 0.5
+            })
+            .sum()
+    }
+
+    /// Whether `p` lies inside or on the hull, allowing `tolerance` outside each face.
+    pub fn contains(&self, p: &Point3, tolerance: f64) -> bool {
+        !self.faces.is_empty()
+            && self
+                .faces
+                .iter()
+                .zip(&self.normals)
+                .all(|(f, n)| n.dot(&(*p - self.vertices[f[0]])) <= tolerance)
+    }
+
+    fn from_faces(points: &[Point3], faces: Vec<[usize; 3]>) -> ConvexHull3 {
+        // Compact the vertex list down to the points the faces use.
+        let mut remap = HashMap::new();
+        let mut vertices = vec![];
+        let faces: Vec<[usize; 3]> = faces
+            .into_iter()
+            .map(|f| {
+                f.map(|i| {
+                    *remap.entry(i).or_insert_with(|| {
+                        vertices.push(points[i]);
+                        vertices.len() - 1
+                    })
+                })
+            })
+            .collect();
+        let normals = faces
+            .iter()
+            .map(|f| {
+                let [a, b, c] = f.map(|i| vertices[i]);
+                (b - a).cross(&(c - a)).normalize()
+            })
+            .collect();
+        let mut edges: Vec<[usize; 2]> = faces
+            .iter()
+            .flat_map(|f| [[f[0], f[1]], [f[1], f[2]], [f[2], f[0]]])
+            .map(|[a, b]| [a.min(b), a.max(b)])
+            .collect();
+        edges.sort();
+        edges.dedup();
+        ConvexHull3 {
+            vertices,
+            faces,
+            edges,
+            normals,
+        }
+    }
+}
+
+struct Face {
+    vertices: [usize; 3],
+    normal: Vec3<f64>,
+    offset: f64,
+    outside: Vec<usize>,
+    alive: bool,
+}
+
+impl Face {
+    fn new(points: &[Point3], vertices: [usize; 3]) -> Face {
+        let [a, b, c] = vertices.map(|i| points[i]);
+        let normal = (b - a).cross(&(c - a)).normalize();
+        let offset = normal.dot(&a.to_vec());
+        Face {
+            vertices,
+            normal,
+            offset,
+            outside: vec![],
+            alive: true,
+        }
+    }
+
+    fn distance(&self, p: &Point3) -> f64 {
+        self.normal.dot(&p.to_vec()) - self.offset
+    }
+
+    fn directed_edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.vertices;
+        [(a, b), (b, c), (c, a)]
+    }
+}
+
+/// Index and value of the point maximizing `dist`.
+fn farthest(points: &[Point3], dist: impl Fn(&Point3) -> f64) -> (usize, f64) {
+    points.iter().enumerate().map(|(i, p)| (i, dist(p))).fold(
+        (0, f64::NEG_INFINITY),
+        |best, cur| if cur.1 > best.1 { cur } else { best },
+    )
+}
+
+/// Convex hull of a 3D point set by quickhull.
+pub fn convex_hull_3d(points: &[Point3]) -> ConvexHull3 {
+    if points.is_empty() {
+        return ConvexHull3::default();
+    }
+
+    // Tolerance scaled to the magnitude of the input, as in Barber et al.
+    let max_abs = (0..3)
+        .map(|k| points.iter().fold(0.0f64, |m, p| m.max(p[k].abs())))
+        .sum::<f64>();
+    let eps = 3.0 * f64::EPSILON * max_abs.max(f64::MIN_POSITIVE);
+
+    // Initial simplex: the widest pair of axis extremes, then the farthest points from
+    // their line and from the resulting plane.
+    let mut i0 = 0;
+    let mut i1 = 0;
+    let mut widest = -1.0;
+    for k in 0..3 {
+        let (lo, _) = farthest(points, |p| -p[k]);
+        let (hi, _) = farthest(points, |p| p[k]);
+        let d = (points[hi] - points[lo]).square_length();
+        if d > widest {
+            widest = d;
+            i0 = lo;
+            i1 = hi;
+        }
+    }
+    if widest.sqrt() <= eps {
+        return ConvexHull3 {
+            vertices: vec![points[0]],
+            ..ConvexHull3::default()
+        };
+    }
+
+    let axis = (points[i1] - points[i0]).normalize();
+    let (i2, line_dist) = farthest(points, |p| {
+        let d = *p - points[i0];
+        (d.clone() - axis.clone() * d.dot(&axis)).length()
+    });
+    if line_dist <= eps {
+        return ConvexHull3 {
+            vertices: vec![points[i0], points[i1]],
+            edges: vec![[0, 1]],
+            ..ConvexHull3::default()
+        };
+    }
+
+    let plane = Face::new(points, [i0, i1, i2]);
+    let (i3, _) = farthest(points, |p| plane.distance(p).abs());
+    if plane.distance(&points[i3]).abs() <= eps {
+        return planar_hull(points, &plane.normal);
+    }
+
+    let mut faces: Vec<Face> = vec![];
+    let mut edge_face: HashMap<(usize, usize), usize> = HashMap::new();
+    let add_face = |faces: &mut Vec<Face>, edge_face: &mut HashMap<(usize, usize), usize>, v| {
+        let face = Face::new(points, v);
+        for e in face.directed_edges() {
+            edge_face.insert(e, faces.len());
+        }
+        faces.push(face);
+        faces.len() - 1
+    };
+
+    let simplex = if plane.distance(&points[i3]) < 0.0 {
+        [[i0, i1, i2], [i0, i3, i1], [i1, i3, i2], [i2, i3, i0]]
+    } else {
+        [[i0, i2, i1], [i0, i1, i3], [i1, i2, i3], [i2, i0, i3]]
+    };
+    for v in simplex {
+        add_face(&mut faces, &mut edge_face, v);
+    }
+
+    let assign = |faces: &mut Vec<Face>, targets: &[usize], point: usize| {
+        for &f in targets {
+            if faces[f].distance(&points[point]) > eps {
+                faces[f].outside.push(point);
+                return;
+            }
+        }
+    };
+    let initial: Vec<usize> = (0..faces.len()).collect();
+    for i in 0..points.len() {
+        if ![i0, i1, i2, i3].contains(&i) {
+            assign(&mut faces, &initial, i);
+        }
+    }
+
+    while let Some(start) =
+        (0..faces.len()).find(|&f| faces[f].alive && !faces[f].outside.is_empty())
+    {
+        let face = &faces[start];
+        let eye = *face
+            .outside
+            .iter()
+            .max_by(|&&a, &&b| {
+                face.distance(&points[a])
+                    .total_cmp(&face.distance(&points[b]))
+            })
+            .unwrap();
+        let eye_point = points[eye];
+
+        // Flood-fill the faces visible from the eye point.
+        let mut visible = vec![start];
+        let mut is_visible: HashMap<usize, bool> = HashMap::from([(start, true)]);
+        let mut k = 0;
+        while k < visible.len() {
+            let f = visible[k];
+            k += 1;
+            for (a, b) in faces[f].directed_edges() {
+                let Some(&g) = edge_face.get(&(b, a)) else {
+                    continue;
+                };
+                if is_visible.contains_key(&g) {
+                    continue;
+                }
+                let seen = faces[g].distance(&eye_point) > eps;
+                is_visible.insert(g, seen);
+                if seen {
+                    visible.push(g);
+                }
+            }
+        }
+
+        // Horizon edges border a visible face on one side only.
+        let mut horizon = vec![];
+        for &f in &visible {
+            for (a, b) in faces[f].directed_edges() {
+                let neighbour = edge_face.get(&(b, a)).copied();
+                if neighbour.is_none_or(|g| is_visible.get(&g) != Some(&true)) {
+                    horizon.push((a, b));
+                }
+            }
+        }
+
+        let mut orphans = vec![];
+        for &f in &visible {
+            faces[f].alive = false;
+            orphans.append(&mut faces[f].outside);
+            for e in faces[f].directed_edges() {
+                if edge_face.get(&e) == Some(&f) {
+                    edge_face.remove(&e);
+                }
+            }
+        }
+
+        let new_faces: Vec<usize> = horizon
+            .iter()
+            .map(|&(a, b)| add_face(&mut faces, &mut edge_face, [a, b, eye]))
+            .collect();
+        for p in orphans {
+            if p != eye {
+                assign(&mut faces, &new_faces, p);
+            }
+        }
+    }
+
+    let alive: Vec<[usize; 3]> = faces
+        .iter()
+        .filter(|f| f.alive)
+        .map(|f| f.vertices)
+        .collect();
+    ConvexHull3::from_faces(points, alive)
+}
+
+/// Flat hull of coplanar points: the 2D hull in the plane, as two opposing fans.
+fn planar_hull(points: &[Point3], normal: &Vec3<f64>) -> ConvexHull3 {
+    // Build an in-plane basis and hull the projected points.
+    let helper: Vec3<f64> = if normal[0].abs() < 0.9 {
+        vec3![1.0, 0.0, 0.0]
+    } else {
+        vec3![0.0, 1.0, 0.0]
+    };
+    let u = normal.cross(&helper).normalize();
+    let v = normal.cross(&u);
+    let projected: Vec<Point2> = points
+        .iter()
+        .map(|p| Point2::new(u.dot(&p.to_vec()), v.dot(&p.to_vec())))
+        .collect();
+    let ring: Vec<usize> = convex_hull_2d(&projected)
+        .iter()
+        .map(|q| projected.iter().position(|p| p == q).unwrap())
+        .collect();
+
+    // The projected ring is counter-clockwise about u x v = normal.
+    let mut faces = vec![];
+    for k in 1..ring.len() - 1 {
+        faces.push([ring[0], ring[k], ring[k + 1]]);
+        faces.push([ring[0], ring[k + 1], ring[k]]);
+    }
+    ConvexHull3::from_faces(points, faces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hull_2d() {
+        let mut points = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(2.0, 0.0),
+            Point2::new(2.0, 2.0),
+            Point2::new(0.0, 2.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(2.0, 1.0),
+        ];
+        points.extend(points.clone());
+        let hull = convex_hull_2d(&points);
+        assert_eq!(
+            hull,
+            vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(2.0, 0.0),
+                Point2::new(2.0, 2.0),
+                Point2::new(0.0, 2.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hull_2d_degenerate() {
+        let collinear: Vec<Point2> = (0..5).map(|i| Point2::new(i as f64, i as f64)).collect();
+        assert_eq!(
+            convex_hull_2d(&collinear),
+            vec![Point2::new(0.0, 0.0), Point2::new(4.0, 4.0)]
+        );
+        assert_eq!(
+            convex_hull_2d(&[Point2::new(1.0, 1.0); 3]),
+            vec![Point2::new(1.0, 1.0)]
+        );
+        assert!(convex_hull_2d(&[]).is_empty());
+    }
+
+    fn cube_with_extras() -> Vec<Point3> {
+        let mut points = vec![];
+        for &x in &[-1.0, 1.0] {
+            for &y in &[-1.0, 1.0] {
+                for &z in &[-1.0, 1.0] {
+                    points.push(Point3::new(x, y, z));
+                }
+            }
+        }
+        // Interior points, face centres, edge midpoints and duplicates.
+        points.push(Point3::new(0.0, 0.0, 0.0));
+        points.push(Point3::new(0.3, -0.2, 0.5));
+        points.push(Point3::new(1.0, 0.0, 0.0));
+        points.push(Point3::new(0.0, 0.0, -1.0));
+        points.push(Point3::new(1.0, 1.0, 0.0));
+        points.push(Point3::new(-1.0, -1.0, -1.0));
+        points
+    }
+
+    #[test]
+    fn test_hull_3d_cube() {
+        let points = cube_with_extras();
+        let hull = convex_hull_3d(&points);
+        assert_eq!(hull.vertices.len(), 8);
+        assert_eq!(hull.faces.len(), 12);
+        assert_eq!(hull.edges.len(), 18);
+        assert!((hull.volume() - 8.0).abs() < 1e-12);
+        assert!((hull.surface_area() - 24.0).abs() < 1e-12);
+        for (f, n) in hull.faces.iter().zip(&hull.normals) {
+            // Outward normals point away from the centre.
+            assert!(n.dot(&hull.vertices[f[0]].to_vec()) > 0.0);
+            assert!((n.length() - 1.0).abs() < 1e-12);
+        }
+        assert!(points.iter().all(|p| hull.contains(p, 1e-12)));
+        assert!(!hull.contains(&Point3::new(1.1, 0.0, 0.0), 1e-12));
+    }
+
+    #[test]
+    fn test_hull_3d_sphere_points() {
+        // Points on a deterministic spiral over the sphere plus interior points.
+        let n = 200;
+        let mut points: Vec<Point3> = (0..n)
+            .map(|i| {
+                let z = 1.0 - 2.0 * (i as f64 + 0.5) / n as f64;
+                let r = (1.0 - z * z).sqrt();
+                let phi = i as f64 * 2.399963229728653;
+                Point3::new(r * phi.cos(), r * phi.sin(), z)
+            })
+            .collect();
+        points.extend((0..50).map(|i| Point3::new(0.01 * i as f64, 0.0, 0.0)));
+        let hull = convex_hull_3d(&points);
+        assert_eq!(hull.vertices.len(), n);
+        // Euler's formula for a closed triangulated surface.
+        assert_eq!(hull.vertices.len() + hull.faces.len(), hull.edges.len() + 2);
+        assert!(points.iter().all(|p| hull.contains(p, 1e-9)));
+    }
+
+    #[test]
+    fn test_hull_3d_degenerate() {
+        let coplanar: Vec<Point3> = (0..4)
+            .flat_map(|i| (0..4).map(move |j| Point3::new(i as f64, j as f64, 2.0)))
+            .collect();
+        let hull = convex_hull_3d(&coplanar);
+        assert_eq!(hull.vertices.len(), 4);
+        assert_eq!(hull.faces.len(), 4);
+        assert_eq!(hull.edges.len(), 5);
+        assert_eq!(hull.volume(), 0.0);
+        assert!((hull.surface_area() - 18.0).abs() < 1e-12);
+
+        let collinear: Vec<Point3> = (0..4).map(|i| Point3::new(i as f64, 0.0, 0.0)).collect();
+        let hull = convex_hull_3d(&collinear);
+        assert_eq!(
+            hull.vertices,
+            vec![Point3::new(0.0, 0.0, 0.0), Point3::new(3.0, 0.0, 0.0)]
+        );
+        assert_eq!(hull.edges, vec![[0, 1]]);
+
+        let single = convex_hull_3d(&[Point3::new(1.0, 2.0, 3.0); 4]);
+        assert_eq!(single.vertices.len(), 1);
+        assert!(single.faces.is_empty());
+    }
+}
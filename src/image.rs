@@ -0,0 +1,541 @@
+use std::fmt::Debug;
+
+/// A pixel format. Conversions between formats go through straight (not premultiplied)
+/// RGBA with channels in `[0, 1]`.
+pub trait Pixel: Copy + Debug + Default + PartialEq {
+    fn to_rgba(&self) -> [f32; 4];
+
+    /// The pixel nearest to `rgba`, clamping channels outside the format's range.
+    fn from_rgba(rgba: [f32; 4]) -> Self;
+
+    /// `self` composited over `dst` with its alpha scaled by `coverage` (Porter-Duff source
+    /// over).
+    fn over(&self, dst: &Self, coverage: f32) -> Self {
+        let (s, d) = (self.to_rgba(), dst.to_rgba());
+        let a = s[3] * coverage.clamp(0.0, 1.0);
+        let alpha = a + d[3] * (1.0 - a);
+        if alpha <= 0.0 {
+            return Self::from_rgba([0.0; 4]);
+        }
+        let c = |i: usize| (s[i] * a + d[i] * d[3] * (1.0 - a)) / alpha;
+        Self::from_rgba([c(0), c(1), c(2), alpha])
+    }
+}
+
+/// Eight bits per channel, mapping `0..=255` linearly onto `[0, 1]`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgba8(pub [u8; 4]);
+
+impl Rgba8 {
+    pub const TRANSPARENT: Rgba8 = Rgba8([0, 0, 0, 0]);
+    pub const BLACK: Rgba8 = Rgba8([0, 0, 0, 255]);
+    pub const WHITE: Rgba8 = Rgba8([255, 255, 255, 255]);
+
+    pub fn new(r: u8, g: u8, b: u8, a: u8) -> Rgba8 {
+        Rgba8([r, g, b, a])
+    }
+}
+
+impl Pixel for Rgba8 {
+    fn to_rgba(&self) -> [f32; 4] {
+        self.0.map(|c| c as f32 / 255.0)
+    }
+
+    fn from_rgba(rgba: [f32; 4]) -> Rgba8 {
+        // `as` saturates, and NaN becomes 0.
+        Rgba8(rgba.map(|c| (c * 255.0).round() as u8))
+    }
+}
+
+/// A float per channel. Values outside `[0, 1]` are kept until converted to another format.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Rgba32F(pub [f32; 4]);
+
+impl Rgba32F {
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> Rgba32F {
+        Rgba32F([r, g, b, a])
+    }
+}
+
+impl Pixel for Rgba32F {
+    fn to_rgba(&self) -> [f32; 4] {
+        self.0
+    }
+
+    fn from_rgba(rgba: [f32; 4]) -> Rgba32F {
+        Rgba32F(rgba)
+    }
+}
+
+/// A rectangle of pixels stored row by row from the top left. Coordinates are signed so
+/// that drawing code can pass points off the image, which reads as `None` and writes
+/// nothing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image<P> {
+    width: usize,
+    height: usize,
+    pixels: Vec<P>,
+}
+
+/// The usual drawing target.
+pub type Canvas = Image<Rgba8>;
+
+/// A read-only rectangle within an image.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageView<'a, P> {
+    pixels: &'a [P],
+    stride: usize,
+    width: usize,
+    height: usize,
+}
+
+/// A writable rectangle within an image.
+#[derive(Debug)]
+pub struct ImageViewMut<'a, P> {
+    pixels: &'a mut [P],
+    stride: usize,
+    width: usize,
+    height: usize,
+}
+
+/// Index of `(x, y)` in rows of `stride` pixels, if it lies within `width` by `height`.
+fn index(x: i32, y: i32, width: usize, height: usize, stride: usize) -> Option<usize> {
+    let (x, y) = (usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+    (x < width && y < height).then_some(y * stride + x)
+}
+
+/// The part of a rectangle that lies within bounds: where it starts in the rectangle and
+/// in the bounds, and its size.
+struct Clipped {
+    src: (usize, usize),
+    dst: (usize, usize),
+    size: (usize, usize),
+}
+
+/// Clips a `width` by `height` rectangle at `(x, y)` to a `bound_width` by `bound_height`
+/// one at the origin.
+fn clip_rect(
+    x: i32,
+    y: i32,
+    width: usize,
+    height: usize,
+    bound_width: usize,
+    bound_height: usize,
+) -> Option<Clipped> {
+    let clip = |start: i32, size: usize, bound: usize| {
+        let (start, size, bound) = (start as i64, size as i64, bound as i64);
+        let lo = start.max(0);
+        let hi = (start + size).min(bound);
+        (lo < hi).then(|| ((lo - start) as usize, lo as usize, (hi - lo) as usize))
+    };
+    let (sx, dx, w) = clip(x, width, bound_width)?;
+    let (sy, dy, h) = clip(y, height, bound_height)?;
+    Some(Clipped {
+        src: (sx, sy),
+        dst: (dx, dy),
+        size: (w, h),
+    })
+}
+
+impl<P: Pixel> Image<P> {
+    /// An image of default pixels, which for the formats here are transparent black.
+    pub fn new(width: usize, height: usize) -> Image<P> {
+        Image::filled(width, height, P::default())
+    }
+
+    pub fn filled(width: usize, height: usize, pixel: P) -> Image<P> {
+        Image {
+            width,
+            height,
+            pixels: vec![pixel; width * height],
+        }
+    }
+
+    /// Wraps pixels stored row by row, `width * height` of them.
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<P>) -> Image<P> {
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "pixel count must match the size"
+        );
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[P] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [P] {
+        &mut self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<P> {
+        self.pixels
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        index(x, y, self.width, self.height, self.width).is_some()
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> Option<P> {
+        index(x, y, self.width, self.height, self.width).map(|i| self.pixels[i])
+    }
+
+    pub fn get_mut(&mut self, x: i32, y: i32) -> Option<&mut P> {
+        index(x, y, self.width, self.height, self.width).map(|i| &mut self.pixels[i])
+    }
+
+    /// Writes the pixel if it lies on the image, and tells whether it did.
+    pub fn set(&mut self, x: i32, y: i32, pixel: P) -> bool {
+        self.get_mut(x, y).map(|p| *p = pixel).is_some()
+    }
+
+    /// Composites `pixel` over the one at `(x, y)` with its alpha scaled by `coverage`, if it
+    /// lies on the image, and tells whether it did.
+    pub fn blend(&mut self, x: i32, y: i32, pixel: P, coverage: f32) -> bool {
+        self.get_mut(x, y)
+            .map(|p| *p = pixel.over(p, coverage))
+            .is_some()
+    }
+
+    pub fn fill(&mut self, pixel: P) {
+        self.pixels.fill(pixel);
+    }
+
+    /// Fills the part of the `width` by `height` rectangle at `(x, y)` that is on the image.
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: usize, height: usize, pixel: P) {
+        self.as_view_mut().fill_rect(x, y, width, height, pixel);
+    }
+
+    /// Copies `src` with its top left corner at `(x, y)`, dropping what falls off the image.
+    pub fn blit(&mut self, src: &ImageView<P>, x: i32, y: i32) {
+        self.as_view_mut().blit(src, x, y);
+    }
+
+    pub fn as_view(&self) -> ImageView<'_, P> {
+        ImageView {
+            pixels: &self.pixels,
+            stride: self.width,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub fn as_view_mut(&mut self) -> ImageViewMut<'_, P> {
+        ImageViewMut {
+            pixels: &mut self.pixels,
+            stride: self.width,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// The `width` by `height` rectangle at `(x, y)`, which must lie within the image.
+    pub fn view(&self, x: usize, y: usize, width: usize, height: usize) -> ImageView<'_, P> {
+        self.as_view().view(x, y, width, height)
+    }
+
+    /// A writable `width` by `height` rectangle at `(x, y)`, which must lie within the image.
+    pub fn view_mut(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> ImageViewMut<'_, P> {
+        self.as_view_mut().into_view(x, y, width, height)
+    }
+
+    /// The image in another pixel format.
+    pub fn convert<Q: Pixel>(&self) -> Image<Q> {
+        Image {
+            width: self.width,
+            height: self.height,
+            pixels: self
+                .pixels
+                .iter()
+                .map(|p| Q::from_rgba(p.to_rgba()))
+                .collect(),
+        }
+    }
+}
+
+/// Checks that a `width` by `height` rectangle at `(x, y)` fits in the parent and returns
+/// the offset of its first pixel.
+fn sub_rect(
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    parent: (usize, usize),
+    stride: usize,
+) -> usize {
+    assert!(
+        x + width <= parent.0 && y + height <= parent.1,
+        "view must lie within the image"
+    );
+    y * stride + x
+}
+
+/// The pixels a `width` by `height` view starting at `offset` spans in its parent.
+fn span(offset: usize, width: usize, height: usize, stride: usize) -> std::ops::Range<usize> {
+    if width == 0 || height == 0 {
+        offset..offset
+    } else {
+        offset..offset + (height - 1) * stride + width
+    }
+}
+
+impl<'a, P: Pixel> ImageView<'a, P> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> Option<P> {
+        index(x, y, self.width, self.height, self.stride).map(|i| self.pixels[i])
+    }
+
+    /// The pixels of row `y`.
+    pub fn row(&self, y: usize) -> &'a [P] {
+        assert!(y < self.height, "row must lie within the view");
+        &self.pixels[y * self.stride..y * self.stride + self.width]
+    }
+
+    /// A rectangle within this view, relative to its top left corner.
+    pub fn view(&self, x: usize, y: usize, width: usize, height: usize) -> ImageView<'a, P> {
+        let offset = sub_rect(x, y, width, height, (self.width, self.height), self.stride);
+        ImageView {
+            pixels: &self.pixels[span(offset, width, height, self.stride)],
+            stride: self.stride,
+            width,
+            height,
+        }
+    }
+
+    /// Copies the view out into an image of its own.
+    pub fn to_image(&self) -> Image<P> {
+        let pixels = (0..self.height)
+            .flat_map(|y| self.row(y).iter().copied())
+            .collect();
+        Image::from_pixels(self.width, self.height, pixels)
+    }
+}
+
+impl<'a, P: Pixel> ImageViewMut<'a, P> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> Option<P> {
+        index(x, y, self.width, self.height, self.stride).map(|i| self.pixels[i])
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, pixel: P) -> bool {
+        match index(x, y, self.width, self.height, self.stride) {
+            Some(i) => {
+                self.pixels[i] = pixel;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn as_view(&self) -> ImageView<'_, P> {
+        ImageView {
+            pixels: &*self.pixels,
+            stride: self.stride,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// A writable rectangle within this view, relative to its top left corner.
+    pub fn into_view(self, x: usize, y: usize, width: usize, height: usize) -> ImageViewMut<'a, P> {
+        let offset = sub_rect(x, y, width, height, (self.width, self.height), self.stride);
+        ImageViewMut {
+            pixels: &mut self.pixels[span(offset, width, height, self.stride)],
+            stride: self.stride,
+            width,
+            height,
+        }
+    }
+
+    pub fn fill(&mut self, pixel: P) {
+        let (width, height) = (self.width, self.height);
+        self.fill_rect(0, 0, width, height, pixel);
+    }
+
+    /// Fills the part of the `width` by `height` rectangle at `(x, y)` that is in the view.
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: usize, height: usize, pixel: P) {
+        let Some(Clipped {
+            dst: (dx, dy),
+            size: (w, h),
+            ..
+        }) = clip_rect(x, y, width, height, self.width, self.height)
+        else {
+            return;
+        };
+        for row in dy..dy + h {
+            let start = row * self.stride + dx;
+            self.pixels[start..start + w].fill(pixel);
+        }
+    }
+
+    /// Copies `src` with its top left corner at `(x, y)`, dropping what falls off the view.
+    pub fn blit(&mut self, src: &ImageView<P>, x: i32, y: i32) {
+        let Some(Clipped {
+            src: (sx, sy),
+            dst: (dx, dy),
+            size: (w, h),
+        }) = clip_rect(x, y, src.width, src.height, self.width, self.height)
+        else {
+            return;
+        };
+        for row in 0..h {
+            let from = &src.row(sy + row)[sx..sx + w];
+            let start = (dy + row) * self.stride + dx;
+            self.pixels[start..start + w].copy_from_slice(from);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An image whose pixels encode their own coordinates.
+    fn numbered(width: usize, height: usize) -> Image<Rgba8> {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Rgba8::new(x as u8, y as u8, 0, 255)))
+            .collect();
+        Image::from_pixels(width, height, pixels)
+    }
+
+    #[test]
+    fn test_get_set_fill() {
+        let mut image: Canvas = Image::new(4, 3);
+        assert_eq!(image.get(0, 0), Some(Rgba8::TRANSPARENT));
+        assert!(image.set(3, 2, Rgba8::WHITE));
+        assert_eq!(image.get(3, 2), Some(Rgba8::WHITE));
+        assert_eq!(image.pixels()[11], Rgba8::WHITE);
+        for (x, y) in [(-1, 0), (0, -1), (4, 0), (0, 3), (i32::MIN, i32::MAX)] {
+            assert!(!image.contains(x, y));
+            assert_eq!(image.get(x, y), None);
+            assert!(!image.set(x, y, Rgba8::BLACK));
+        }
+        image.fill_rect(-2, 1, 4, 10, Rgba8::BLACK);
+        let expected = |x: i32, y: i32| {
+            if x < 2 && y >= 1 {
+                Rgba8::BLACK
+            } else if (x, y) == (3, 2) {
+                Rgba8::WHITE
+            } else {
+                Rgba8::TRANSPARENT
+            }
+        };
+        for y in 0..3 {
+            for x in 0..4 {
+                assert_eq!(image.get(x, y), Some(expected(x, y)));
+            }
+        }
+        image.fill(Rgba8::WHITE);
+        assert!(image.pixels().iter().all(|&p| p == Rgba8::WHITE));
+    }
+
+    #[test]
+    fn test_blit_and_views() {
+        let src = numbered(5, 4);
+        let view = src.view(1, 1, 3, 2);
+        assert_eq!((view.width(), view.height()), (3, 2));
+        assert_eq!(view.get(0, 0), Some(Rgba8::new(1, 1, 0, 255)));
+        assert_eq!(view.get(3, 0), None);
+        assert_eq!(view.row(1)[2], Rgba8::new(3, 2, 0, 255));
+        // Views of views stay relative to their parent.
+        let inner = view.view(1, 1, 2, 1);
+        assert_eq!(inner.to_image(), numbered(5, 4).view(2, 2, 2, 1).to_image());
+        assert_eq!(inner.get(1, 0), Some(Rgba8::new(3, 2, 0, 255)));
+
+        // Blits clip on every side.
+        for (x, y) in [(-2, -1), (3, 2), (-10, 0), (0, 1)] {
+            let mut dst: Canvas = Image::new(4, 3);
+            dst.blit(&view, x, y);
+            for dy in 0..3 {
+                for dx in 0..4 {
+                    let expected = view.get(dx - x, dy - y).unwrap_or_default();
+                    assert_eq!(dst.get(dx, dy), Some(expected), "{x} {y} at {dx} {dy}");
+                }
+            }
+        }
+
+        // Writes through a mutable view land in the parent, clipped to the view.
+        let mut dst: Canvas = Image::new(4, 4);
+        {
+            let mut window = dst.view_mut(1, 1, 2, 2);
+            assert!(window.set(1, 1, Rgba8::WHITE));
+            assert!(!window.set(2, 0, Rgba8::WHITE));
+            window.blit(&src.as_view(), -1, -1);
+            assert_eq!(window.get(0, 0), Some(Rgba8::new(1, 1, 0, 255)));
+            let mut corner = window.into_view(1, 0, 1, 2);
+            corner.fill(Rgba8::BLACK);
+            assert_eq!(corner.as_view().to_image().pixels(), &[Rgba8::BLACK; 2]);
+        }
+        assert_eq!(dst.get(1, 1), Some(Rgba8::new(1, 1, 0, 255)));
+        assert_eq!(dst.get(2, 2), Some(Rgba8::BLACK));
+        assert_eq!(dst.get(1, 2), Some(Rgba8::new(1, 2, 0, 255)));
+        assert_eq!(dst.get(3, 1), Some(Rgba8::TRANSPARENT));
+        assert_eq!(dst.get(0, 0), Some(Rgba8::TRANSPARENT));
+    }
+
+    #[test]
+    fn test_blend() {
+        let mut image = Image::filled(2, 1, Rgba32F::new(0.0, 0.0, 1.0, 1.0));
+        assert!(image.blend(0, 0, Rgba32F::new(1.0, 0.0, 0.0, 1.0), 0.25));
+        assert!(!image.blend(2, 0, Rgba32F::new(1.0, 0.0, 0.0, 1.0), 1.0));
+        assert_eq!(image.get(0, 0), Some(Rgba32F::new(0.25, 0.0, 0.75, 1.0)));
+        // Over a transparent pixel only the source colour remains.
+        let half = Rgba32F::new(0.0, 1.0, 0.0, 0.5);
+        assert_eq!(half.over(&Rgba32F::default(), 1.0), half);
+        assert_eq!(half.over(&Rgba32F::default(), 0.0), Rgba32F::default());
+        let blended = half.over(&Rgba32F::new(1.0, 0.0, 0.0, 0.5), 1.0);
+        assert_eq!(blended, Rgba32F::new(1.0 / 3.0, 2.0 / 3.0, 0.0, 0.75));
+    }
+
+    #[test]
+    fn test_convert() {
+        let pixels = (0..=255u8)
+            .map(|c| Rgba8::new(c, 255 - c, c / 2, 255))
+            .collect();
+        let image = Image::from_pixels(16, 16, pixels);
+        let float: Image<Rgba32F> = image.convert();
+        assert_eq!(
+            float.get(15, 15),
+            Some(Rgba32F::new(1.0, 0.0, 127.0 / 255.0, 1.0))
+        );
+        assert_eq!(float.convert::<Rgba8>(), image);
+        let out_of_range = Image::filled(1, 1, Rgba32F::new(-0.5, 2.0, 0.502, f32::NAN));
+        assert_eq!(
+            out_of_range.convert::<Rgba8>().get(0, 0),
+            Some(Rgba8::new(0, 255, 128, 0))
+        );
+    }
+}
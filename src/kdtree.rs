@@ -0,0 +1,412 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::point::{Point2, Point3};
+
+/// Points a `KdTree` can index. Coordinates are any `Scalar` that converts to `f64`, in
+/// which squared distances and the search bounds are computed, so integer coordinates
+/// cannot overflow.
+pub trait KdPoint: Copy {
+    type Scalar: Copy + Into<f64>;
+
+    const DIM: usize;
+
+    fn coord(&self, axis: usize) -> Self::Scalar;
+
+    fn square_distance(&self, other: &Self) -> f64;
+}
+
+impl KdPoint for Point2 {
+    type Scalar = f64;
+
+    const DIM: usize = 2;
+
+    fn coord(&self, axis: usize) -> f64 {
+        self[axis]
+    }
+
+    fn square_distance(&self, other: &Point2) -> f64 {
+        (*other - *self).square_length()
+    }
+}
+
+impl KdPoint for Point3 {
+    type Scalar = f64;
+
+    const DIM: usize = 3;
+
+    fn coord(&self, axis: usize) -> f64 {
+        self[axis]
+    }
+
+    fn square_distance(&self, other: &Point3) -> f64 {
+        (*other - *self).square_length()
+    }
+}
+
+/// A query result: the index of the point as it was passed to the constructor and its
+/// squared distance to the query point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Neighbour {
+    pub index: usize,
+    pub square_distance: f64,
+}
+
+impl Eq for Neighbour {}
+
+impl PartialOrd for Neighbour {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Neighbour {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.square_distance
+            .total_cmp(&other.square_distance)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+/// Static k-d tree with an optional payload per point. The tree is stored implicitly:
+/// the median of every range is its node, split along the axis of largest spread.
+#[derive(Debug, Clone)]
+pub struct KdTree<P, D = ()> {
+    points: Vec<P>,
+    data: Vec<D>,
+    tree: Vec<(P, u32)>,
+    axes: Vec<u8>,
+}
+
+pub type KdTree2<D = ()> = KdTree<Point2, D>;
+pub type KdTree3<D = ()> = KdTree<Point3, D>;
+
+impl<P: KdPoint> KdTree<P> {
+    pub fn new(points: Vec<P>) -> KdTree<P> {
+        let data = vec![(); points.len()];
+        KdTree::build(points, data)
+    }
+}
+
+impl<P: KdPoint, D> KdTree<P, D> {
+    /// Builds a tree whose points carry a payload, retrievable with `data`.
+    pub fn with_data(entries: Vec<(P, D)>) -> KdTree<P, D> {
+        let (points, data) = entries.into_iter().unzip();
+        KdTree::build(points, data)
+    }
+
+    fn build(points: Vec<P>, data: Vec<D>) -> KdTree<P, D> {
+        let mut tree: Vec<(P, u32)> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (*p, i as u32))
+            .collect();
+        let mut axes = vec![0; points.len()];
+        split(&mut tree, &mut axes);
+        KdTree {
+            points,
+            data,
+            tree,
+            axes,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Points in construction order.
+    pub fn points(&self) -> &[P] {
+        &self.points
+    }
+
+    pub fn point(&self, index: usize) -> &P {
+        &self.points[index]
+    }
+
+    pub fn data(&self, index: usize) -> &D {
+        &self.data[index]
+    }
+
+    pub fn nearest(&self, query: &P) -> Option<Neighbour> {
+        self.k_nearest(query, 1).pop()
+    }
+
+    /// The `k` points closest to `query`, nearest first.
+    pub fn k_nearest(&self, query: &P, k: usize) -> Vec<Neighbour> {
+        self.k_nearest_approx(query, k, 0.0)
+    }
+
+    /// Like `k_nearest`, but subtrees are skipped unless they could improve the current
+    /// k-th distance by more than a factor `1 + epsilon`. Every returned distance is then
+    /// within `1 + epsilon` of the true distance of the neighbour with the same rank.
+    pub fn k_nearest_approx(&self, query: &P, k: usize, epsilon: f64) -> Vec<Neighbour> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        let scale = (1.0 + epsilon) * (1.0 + epsilon);
+        self.search_k(query, k, scale, 0, self.tree.len(), &mut heap);
+        heap.into_sorted_vec()
+    }
+
+    fn search_k(
+        &self,
+        query: &P,
+        k: usize,
+        scale: f64,
+        lo: usize,
+        hi: usize,
+        heap: &mut BinaryHeap<Neighbour>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let (p, index) = self.tree[mid];
+        let candidate = Neighbour {
+            index: index as usize,
+            square_distance: query.square_distance(&p),
+        };
+        if heap.len() < k {
+            heap.push(candidate);
+        } else if candidate < *heap.peek().unwrap() {
+            heap.pop();
+            heap.push(candidate);
+        }
+
+        let axis = self.axes[mid] as usize;
+        let diff = query.coord(axis).into() - p.coord(axis).into();
+        let (near, far) = if diff < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.search_k(query, k, scale, near.0, near.1, heap);
+        let worst = if heap.len() < k {
+            f64::INFINITY
+        } else {
+            heap.peek().unwrap().square_distance
+        };
+        if diff * diff * scale <= worst {
+            self.search_k(query, k, scale, far.0, far.1, heap);
+        }
+    }
+
+    /// All points within `radius` of `query` (inclusive), nearest first.
+    pub fn within_radius(&self, query: &P, radius: f64) -> Vec<Neighbour> {
+        let mut result = Vec::new();
+        let mut stack = vec![(0, self.tree.len())];
+        let r2 = radius * radius;
+        while let Some((lo, hi)) = stack.pop() {
+            if lo >= hi {
+                continue;
+            }
+            let mid = (lo + hi) / 2;
+            let (p, index) = self.tree[mid];
+            let square_distance = query.square_distance(&p);
+            if square_distance <= r2 {
+                result.push(Neighbour {
+                    index: index as usize,
+                    square_distance,
+                });
+            }
+            let axis = self.axes[mid] as usize;
+            let diff = query.coord(axis).into() - p.coord(axis).into();
+            if diff <= radius {
+                stack.push((lo, mid));
+            }
+            if diff >= -radius {
+                stack.push((mid + 1, hi));
+            }
+        }
+        result.sort();
+        result
+    }
+}
+
+/// Recursively places the median along the widest axis in the middle of `tree`, with
+/// smaller coordinates before it and larger ones after.
+fn split<P: KdPoint>(tree: &mut [(P, u32)], axes: &mut [u8]) {
+    if tree.len() <= 1 {
+        return;
+    }
+    let mut axis = 0;
+    let mut widest = f64::NEG_INFINITY;
+    for a in 0..P::DIM {
+        let (lo, hi) = tree
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (p, _)| {
+                let c = p.coord(a).into();
+                (lo.min(c), hi.max(c))
+            });
+        if hi - lo > widest {
+            widest = hi - lo;
+            axis = a;
+        }
+    }
+    let mid = tree.len() / 2;
+    tree.select_nth_unstable_by(mid, |a, b| {
+        let (a, b): (f64, f64) = (a.0.coord(axis).into(), b.0.coord(axis).into());
+        a.total_cmp(&b)
+    });
+    axes[mid] = axis as u8;
+    let (left, right) = tree.split_at_mut(mid);
+    let (left_axes, right_axes) = axes.split_at_mut(mid);
+    split(left, left_axes);
+    split(&mut right[1..], &mut right_axes[1..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::random_values;
+
+    fn random_points3(n: usize, seed: u64) -> Vec<Point3> {
+        random_values(3 * n, seed)
+            .chunks(3)
+            .map(|c| Point3::new(c[0] * 10.0, c[1] * 10.0, c[2] * 10.0))
+            .collect()
+    }
+
+    fn brute_force<P: KdPoint>(points: &[P], query: &P) -> Vec<Neighbour> {
+        let mut all: Vec<Neighbour> = points
+            .iter()
+            .enumerate()
+            .map(|(index, p)| Neighbour {
+                index,
+                square_distance: query.square_distance(p),
+            })
+            .collect();
+        all.sort();
+        all
+    }
+
+    #[test]
+    fn test_k_nearest() {
+        let points = random_points3(1000, 1);
+        let tree = KdTree3::new(points.clone());
+        for q in random_points3(50, 2) {
+            let expected = brute_force(&points, &q);
+            assert_eq!(tree.k_nearest(&q, 10), expected[..10]);
+            assert_eq!(tree.nearest(&q), Some(expected[0]));
+        }
+        assert_eq!(tree.k_nearest(&points[0], 2000).len(), 1000);
+        assert!(tree.k_nearest(&points[0], 0).is_empty());
+
+        // A lattice has many equidistant points and duplicate split coordinates.
+        let mut grid: Vec<Point2> = (0..100)
+            .map(|i| Point2::new((i % 10) as f64, (i / 10) as f64))
+            .collect();
+        grid.extend(grid.clone());
+        let tree = KdTree2::new(grid.clone());
+        for q in [
+            Point2::new(4.5, 4.5),
+            Point2::new(0.0, 0.0),
+            Point2::new(-3.0, 7.0),
+        ] {
+            let expected = brute_force(&grid, &q);
+            assert_eq!(tree.k_nearest(&q, 12), expected[..12]);
+        }
+
+        let empty: KdTree2 = KdTree::new(Vec::new());
+        assert!(empty.is_empty());
+        assert_eq!(empty.nearest(&Point2::new(0.0, 0.0)), None);
+        assert!(empty.within_radius(&Point2::new(0.0, 0.0), 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_k_nearest_approx() {
+        let points = random_points3(2000, 3);
+        let tree = KdTree3::new(points.clone());
+        let epsilon = 0.5;
+        for q in random_points3(50, 4) {
+            let expected = brute_force(&points, &q);
+            let found = tree.k_nearest_approx(&q, 5, epsilon);
+            assert_eq!(found.len(), 5);
+            for (f, e) in found.iter().zip(&expected) {
+                let bound = e.square_distance * (1.0 + epsilon) * (1.0 + epsilon);
+                assert!(f.square_distance <= bound + 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_within_radius() {
+        let points = random_points3(1000, 5);
+        let tree = KdTree3::new(points.clone());
+        for q in random_points3(30, 6) {
+            let expected: Vec<Neighbour> = brute_force(&points, &q)
+                .into_iter()
+                .filter(|n| n.square_distance <= 4.0)
+                .collect();
+            assert_eq!(tree.within_radius(&q, 2.0), expected);
+        }
+
+        // The boundary is inclusive.
+        let tree = KdTree2::new(vec![Point2::new(0.0, 0.0), Point2::new(3.0, 4.0)]);
+        assert_eq!(tree.within_radius(&Point2::new(0.0, 0.0), 5.0).len(), 2);
+    }
+
+    #[test]
+    fn test_payload() {
+        let entries: Vec<(Point2, &str)> = vec![
+            (Point2::new(0.0, 0.0), "origin"),
+            (Point2::new(5.0, 0.0), "east"),
+            (Point2::new(0.0, 5.0), "north"),
+        ];
+        let tree = KdTree2::with_data(entries);
+        let n = tree.nearest(&Point2::new(4.0, 1.0)).unwrap();
+        assert_eq!(*tree.data(n.index), "east");
+        assert_eq!(*tree.point(n.index), Point2::new(5.0, 0.0));
+        assert_eq!(n.square_distance, 2.0);
+        assert_eq!(tree.points().len(), 3);
+    }
+    /// Integer grid cells, whose differences could overflow `i32`.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Cell(i32, i32);
+
+    impl KdPoint for Cell {
+        type Scalar = i32;
+
+        const DIM: usize = 2;
+
+        fn coord(&self, axis: usize) -> i32 {
+            [self.0, self.1][axis]
+        }
+
+        fn square_distance(&self, other: &Cell) -> f64 {
+            let dx = f64::from(self.0) - f64::from(other.0);
+            let dy = f64::from(self.1) - f64::from(other.1);
+            dx * dx + dy * dy
+        }
+    }
+
+    #[test]
+    fn test_integer_coordinates() {
+        let cells: Vec<Cell> = (0..400)
+            .map(|i| Cell((i % 20) * 3 - 30, (i / 20) * 7 - 70))
+            .chain([Cell(i32::MIN, i32::MAX), Cell(i32::MAX, i32::MIN)])
+            .collect();
+        let tree = KdTree::new(cells.clone());
+        for q in [
+            Cell(0, 0),
+            Cell(-31, 69),
+            Cell(i32::MAX, 0),
+            Cell(i32::MIN, i32::MAX),
+        ] {
+            let expected = brute_force(&cells, &q);
+            assert_eq!(tree.k_nearest(&q, 5), expected[..5]);
+        }
+        let near = tree.within_radius(&Cell(0, 0), 4.0);
+        let expected: Vec<Neighbour> = brute_force(&cells, &Cell(0, 0))
+            .into_iter()
+            .take_while(|n| n.square_distance <= 16.0)
+            .collect();
+        assert_eq!(near, expected);
+    }
+}
@@ -2,3 +2,37 @@
 pub mod vec;
 pub mod mat;
 pub mod point;
+pub mod ray;
+pub mod aabb;
+pub mod sphere;
+pub mod obb;
+pub mod triangle;
+pub mod segment;
+pub mod polygon;
+pub mod boolean;
+pub mod offset;
+pub mod hull;
+pub mod delaunay;
+pub mod triangulate;
+pub mod predicates;
+pub mod bvh;
+pub mod kdtree;
+pub mod frustum;
+pub mod octree;
+pub mod spatial_hash;
+pub mod convex;
+pub mod ccd;
+pub mod curve;
+pub mod bezier;
+pub mod spline;
+pub mod bezier_intersection;
+pub mod path;
+pub mod image;
+pub mod line_raster;
+pub mod scanline;
+pub mod triangle_raster;
+pub mod pipeline;
+pub mod texture;
+
+#[cfg(test)]
+mod test_util;
@@ -0,0 +1,577 @@
+use crate::aabb::Aabb2;
+use crate::image::{Image, Pixel};
+use crate::path::LineCap;
+use crate::point::Point2;
+use crate::vec::Vec2;
+
+/// The pixels of the integer line from `start` towards `end`, both included. It steps one
+/// pixel at a time along the major axis and rounds the minor coordinate to the nearest
+/// pixel, halves towards `end`.
+#[derive(Debug, Clone)]
+pub struct Bresenham {
+    start: [i64; 2],
+    /// Index of the major axis.
+    major: usize,
+    /// Direction of travel along each axis.
+    step: [i64; 2],
+    d_major: i64,
+    d_minor: i64,
+    /// Current step and the last one.
+    k: i64,
+    last: i64,
+    /// Minor offset at step `k` and the remainder `(2 k d_minor + d_major) mod 2 d_major`.
+    minor: i64,
+    rem: i64,
+}
+
+impl Bresenham {
+    pub fn new(start: &Vec2<i32>, end: &Vec2<i32>) -> Bresenham {
+        let d = [
+            end[0] as i64 - start[0] as i64,
+            end[1] as i64 - start[1] as i64,
+        ];
+        let major = if d[0].abs() >= d[1].abs() { 0 } else { 1 };
+        let mut line = Bresenham {
+            start: [start[0] as i64, start[1] as i64],
+            major,
+            step: d.map(|v| if v < 0 { -1 } else { 1 }),
+            // A single point still needs a non-zero denominator.
+            d_major: d[major].abs().max(1),
+            d_minor: d[1 - major].abs(),
+            k: 0,
+            last: d[major].abs(),
+            minor: 0,
+            rem: 0,
+        };
+        line.seek(0);
+        line
+    }
+
+    /// Keeps only the pixels within a `width` by `height` image. They are found by solving
+    /// for the range of steps, so they match those of the whole line however far off the
+    /// image it starts.
+    pub fn clip(mut self, width: usize, height: usize) -> Bresenham {
+        let size = [width as i128, height as i128];
+        let (major, minor) = (self.major, 1 - self.major);
+        // Offsets along an axis that stay within `0..size` from `start`.
+        let offsets = |axis: usize| {
+            let (s, n) = (self.start[axis] as i128, size[axis]);
+            if self.step[axis] > 0 {
+                (-s, n - 1 - s)
+            } else {
+                (s - (n - 1), s)
+            }
+        };
+        let (lo, hi) = offsets(major);
+        let (mut k0, mut k1) = (lo.max(self.k as i128), hi.min(self.last as i128));
+        let (m_lo, m_hi) = offsets(minor);
+        let (dm, dn) = (self.d_major as i128, self.d_minor as i128);
+        if dn == 0 {
+            if m_lo > 0 || m_hi < 0 {
+                k1 = k0 - 1;
+            }
+        } else {
+            // The minor offset at step `k` is `floor((2 k dn + dm) / 2 dm)`.
+            k0 = k0.max(div_ceil(2 * dm * m_lo - dm, 2 * dn));
+            k1 = k1.min(div_ceil(2 * dm * (m_hi + 1) - dm, 2 * dn) - 1);
+        }
+        if k0 > k1 {
+            self.last = self.k - 1;
+        } else {
+            self.last = k1 as i64;
+            self.seek(k0 as i64);
+        }
+        self
+    }
+
+    fn seek(&mut self, k: i64) {
+        let n = 2 * k as i128 * self.d_minor as i128 + self.d_major as i128;
+        let d = 2 * self.d_major as i128;
+        self.k = k;
+        self.minor = n.div_euclid(d) as i64;
+        self.rem = n.rem_euclid(d) as i64;
+    }
+}
+
+/// `a / b` rounded up, for positive `b`.
+fn div_ceil(a: i128, b: i128) -> i128 {
+    -(-a).div_euclid(b)
+}
+
+impl Iterator for Bresenham {
+    type Item = Vec2<i32>;
+
+    fn next(&mut self) -> Option<Vec2<i32>> {
+        if self.k > self.last {
+            return None;
+        }
+        let (major, minor) = (self.major, 1 - self.major);
+        let mut p = [0; 2];
+        p[major] = self.start[major] + self.step[major] * self.k;
+        p[minor] = self.start[minor] + self.step[minor] * self.minor;
+        self.k += 1;
+        self.rem += 2 * self.d_minor;
+        if self.rem >= 2 * self.d_major {
+            self.rem -= 2 * self.d_major;
+            self.minor += 1;
+        }
+        Some(vec2![p[0] as i32, p[1] as i32])
+    }
+}
+
+/// Draws the Bresenham line from `a` to `b`, clipped to the image.
+pub fn draw_line<P: Pixel>(image: &mut Image<P>, a: &Vec2<i32>, b: &Vec2<i32>, color: P) {
+    for p in Bresenham::new(a, b).clip(image.width(), image.height()) {
+        image.set(p[0], p[1], color);
+    }
+}
+
+/// Draws an anti-aliased line from `a` to `b` by Xiaolin Wu's algorithm, blending `color`
+/// into the two pixels nearest the line in each column (or row, for steep lines) by their
+/// closeness. Pixel `(x, y)` is centred on `(x + 0.5, y + 0.5)`.
+pub fn draw_line_aa<P: Pixel>(image: &mut Image<P>, a: &Point2, b: &Point2, color: P) {
+    let (w, h) = (image.width() as f64, image.height() as f64);
+    let bounds = Aabb2::new(Point2::new(-1.0, -1.0), Point2::new(w + 1.0, h + 1.0));
+    let Some((a, b)) = clip_liang_barsky(a, b, &bounds) else {
+        return;
+    };
+    // Work in coordinates where pixel centres are whole numbers.
+    let (mut x0, mut y0, mut x1, mut y1) = (a[0] - 0.5, a[1] - 0.5, b[0] - 0.5, b[1] - 0.5);
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+    if steep {
+        (x0, y0, x1, y1) = (y0, x0, y1, x1);
+    }
+    if x0 > x1 {
+        (x0, y0, x1, y1) = (x1, y1, x0, y0);
+    }
+    let mut plot = |x: f64, y: f64, coverage: f64| {
+        let (x, y) = if steep { (y, x) } else { (x, y) };
+        image.blend(x as i32, y as i32, color, coverage as f32);
+    };
+    let dx = x1 - x0;
+    let gradient = if dx == 0.0 { 1.0 } else { (y1 - y0) / dx };
+
+    // The end pixels are weighted by how much of their column the line covers.
+    let x_start = x0.round();
+    let y_start = y0 + gradient * (x_start - x0);
+    let gap = 1.0 - (x0 + 0.5).fract_floor();
+    plot(
+        x_start,
+        y_start.floor(),
+        (1.0 - y_start.fract_floor()) * gap,
+    );
+    plot(x_start, y_start.floor() + 1.0, y_start.fract_floor() * gap);
+
+    let x_end = x1.round();
+    let y_end = y1 + gradient * (x_end - x1);
+    let gap = (x1 + 0.5).fract_floor();
+    plot(x_end, y_end.floor(), (1.0 - y_end.fract_floor()) * gap);
+    plot(x_end, y_end.floor() + 1.0, y_end.fract_floor() * gap);
+
+    let mut y = y_start + gradient;
+    let mut x = x_start + 1.0;
+    while x < x_end {
+        plot(x, y.floor(), 1.0 - y.fract_floor());
+        plot(x, y.floor() + 1.0, y.fract_floor());
+        y += gradient;
+        x += 1.0;
+    }
+}
+
+/// The fractional part measured from the floor, so that it lies in `[0, 1)` for negative
+/// values too.
+trait FractFloor {
+    fn fract_floor(self) -> f64;
+}
+
+impl FractFloor for f64 {
+    fn fract_floor(self) -> f64 {
+        self - self.floor()
+    }
+}
+
+/// Draws a line `width` wide from `a` to `b`, filling the pixels whose centres lie inside it.
+/// The sides and ends are half-open so that lines sharing an edge do not both fill it.
+pub fn draw_thick_line<P: Pixel>(
+    image: &mut Image<P>,
+    a: &Point2,
+    b: &Point2,
+    width: f64,
+    cap: LineCap,
+    color: P,
+) {
+    let h = width / 2.0;
+    if h <= 0.0 {
+        return;
+    }
+    let d = *b - *a;
+    let length = d.length();
+    let dir = if length > 0.0 {
+        d / length
+    } else {
+        vec2![1.0, 0.0]
+    };
+    let inside = |c: Point2| {
+        let v = c - *a;
+        let (along, across) = (v.dot(&dir), dir.perp_dot(&v));
+        let band = -h <= across && across < h;
+        match cap {
+            LineCap::Butt => band && 0.0 <= along && along < length,
+            LineCap::Square => band && -h <= along && along < length + h,
+            LineCap::Round => {
+                (band && 0.0 <= along && along < length) || v.length() < h || (c - *b).length() < h
+            }
+        }
+    };
+
+    // Square caps reach furthest, to the corners of a box `h` past either end.
+    let reach = h * std::f64::consts::SQRT_2;
+    let columns = pixel_range(
+        a[0].min(b[0]) - reach,
+        a[0].max(b[0]) + reach,
+        image.width(),
+    );
+    let rows = pixel_range(
+        a[1].min(b[1]) - reach,
+        a[1].max(b[1]) + reach,
+        image.height(),
+    );
+    for y in rows {
+        for x in columns.clone() {
+            if inside(Point2::new(x as f64 + 0.5, y as f64 + 0.5)) {
+                image.set(x, y, color);
+            }
+        }
+    }
+}
+
+/// Pixels of a `size` long axis whose centres may lie in `[lo, hi]`.
+fn pixel_range(lo: f64, hi: f64, size: usize) -> std::ops::Range<i32> {
+    let lo = (lo - 0.5).floor().max(0.0);
+    let hi = ((hi - 0.5).ceil() + 1.0).min(size as f64);
+    if lo < hi {
+        lo as i32..hi as i32
+    } else {
+        0..0
+    }
+}
+
+const X_MIN: u8 = 1;
+const X_MAX: u8 = 2;
+const Y_MIN: u8 = 4;
+const Y_MAX: u8 = 8;
+
+/// Which sides of `bounds` the point lies beyond.
+fn outcode(p: &Point2, bounds: &Aabb2) -> u8 {
+    let mut code = 0;
+    if p[0] < bounds.min[0] {
+        code |= X_MIN;
+    } else if p[0] > bounds.max[0] {
+        code |= X_MAX;
+    }
+    if p[1] < bounds.min[1] {
+        code |= Y_MIN;
+    } else if p[1] > bounds.max[1] {
+        code |= Y_MAX;
+    }
+    code
+}
+
+/// The part of the segment from `a` to `b` inside `bounds` by Cohen-Sutherland clipping,
+/// which moves an outside end onto one side of the box at a time.
+pub fn clip_cohen_sutherland(a: &Point2, b: &Point2, bounds: &Aabb2) -> Option<(Point2, Point2)> {
+    let (mut a, mut b) = (*a, *b);
+    let (mut code_a, mut code_b) = (outcode(&a, bounds), outcode(&b, bounds));
+    loop {
+        if code_a | code_b == 0 {
+            return Some((a, b));
+        }
+        if code_a & code_b != 0 {
+            return None;
+        }
+        // Move an outside end onto the first side it lies beyond. Its coordinate across that
+        // side is set exactly, so the side is never reported again.
+        let code = if code_a != 0 { code_a } else { code_b };
+        let d = b - a;
+        let p = if code & (X_MIN | X_MAX) != 0 {
+            let x = if code & X_MIN != 0 {
+                bounds.min[0]
+            } else {
+                bounds.max[0]
+            };
+            Point2::new(x, a[1] + d[1] * (x - a[0]) / d[0])
+        } else {
+            let y = if code & Y_MIN != 0 {
+                bounds.min[1]
+            } else {
+                bounds.max[1]
+            };
+            Point2::new(a[0] + d[0] * (y - a[1]) / d[1], y)
+        };
+        if code == code_a {
+            a = p;
+            code_a = outcode(&a, bounds);
+        } else {
+            b = p;
+            code_b = outcode(&b, bounds);
+        }
+    }
+}
+
+/// The part of the segment from `a` to `b` inside `bounds` by Liang-Barsky clipping, which
+/// narrows the parameter range against each side in turn.
+pub fn clip_liang_barsky(a: &Point2, b: &Point2, bounds: &Aabb2) -> Option<(Point2, Point2)> {
+    let d = *b - *a;
+    let (mut t0, mut t1) = (0.0f64, 1.0f64);
+    for (p, q) in [
+        (-d[0], a[0] - bounds.min[0]),
+        (d[0], bounds.max[0] - a[0]),
+        (-d[1], a[1] - bounds.min[1]),
+        (d[1], bounds.max[1] - a[1]),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let r = q / p;
+        if p < 0.0 {
+            t0 = t0.max(r);
+        } else {
+            t1 = t1.min(r);
+        }
+        if t0 > t1 {
+            return None;
+        }
+    }
+    let start = if t0 > 0.0 { *a + d.clone() * t0 } else { *a };
+    let end = if t1 < 1.0 { *a + d * t1 } else { *b };
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::{Canvas, Rgba32F, Rgba8};
+    use crate::test_util::random_values;
+
+    /// The image as text, one line per row: `.` for untouched pixels, `#` for opaque ones
+    /// and a digit for tenths of coverage in between.
+    fn render(image: &Canvas) -> String {
+        let mut text = String::new();
+        for y in 0..image.height() as i32 {
+            for x in 0..image.width() as i32 {
+                text.push(match image.get(x, y).unwrap().0[3] {
+                    0 => '.',
+                    255 => '#',
+                    a => char::from(b'0' + (a as u32 * 10 / 255) as u8),
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    fn golden(text: &str) -> String {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| format!("{line}\n"))
+            .collect()
+    }
+
+    #[test]
+    fn test_bresenham() {
+        let mut image = Canvas::new(11, 7);
+        let center = vec2![5, 3];
+        for end in [vec2![10, 5], vec2![7, 0], vec2![0, 2], vec2![3, 6]] {
+            draw_line(&mut image, &center, &end, Rgba8::WHITE);
+        }
+        // Off the image on both ends.
+        draw_line(&mut image, &vec2![-4, 8], &vec2![12, 4], Rgba8::WHITE);
+        let expected = golden(
+            "
+            .......#...
+            ......#....
+            ###...#....
+            ...####....
+            ....#..##.#
+            ....#.#####
+            ..####.....
+            ",
+        );
+        assert_eq!(render(&image), expected);
+
+        // Steps of one along the major axis, the minor axis rounded to nearest, and
+        // clipping that keeps exactly the pixels on the image.
+        let values = random_values(4 * 300, 2);
+        for v in values.chunks(4) {
+            let a = vec2![(v[0] * 60.0) as i32 - 20, (v[1] * 60.0) as i32 - 20];
+            let b = vec2![(v[2] * 60.0) as i32 - 20, (v[3] * 60.0) as i32 - 20];
+            let pixels: Vec<Vec2<i32>> = Bresenham::new(&a, &b).collect();
+            let (dx, dy) = ((b[0] - a[0]) as f64, (b[1] - a[1]) as f64);
+            assert_eq!(pixels.len() as f64, dx.abs().max(dy.abs()) + 1.0);
+            assert_eq!((&pixels[0], pixels.last().unwrap()), (&a, &b));
+            for (k, p) in pixels.iter().enumerate() {
+                let t = k as f64 / dx.abs().max(dy.abs()).max(1.0);
+                let exact = [a[0] as f64 + dx * t, a[1] as f64 + dy * t];
+                assert!((p[0] as f64 - exact[0]).abs() <= 0.5);
+                assert!((p[1] as f64 - exact[1]).abs() <= 0.5);
+            }
+            let clipped: Vec<Vec2<i32>> = Bresenham::new(&a, &b).clip(17, 9).collect();
+            let expected: Vec<Vec2<i32>> = pixels
+                .into_iter()
+                .filter(|p| (0..17).contains(&p[0]) && (0..9).contains(&p[1]))
+                .collect();
+            assert_eq!(clipped, expected);
+        }
+        let far: Vec<Vec2<i32>> = Bresenham::new(&vec2![i32::MIN, 3], &vec2![i32::MAX, 3])
+            .clip(4, 4)
+            .collect();
+        assert_eq!(
+            far,
+            vec![vec2![0, 3], vec2![1, 3], vec2![2, 3], vec2![3, 3]]
+        );
+    }
+
+    #[test]
+    fn test_wu() {
+        let mut image = Canvas::new(10, 5);
+        draw_line_aa(
+            &mut image,
+            &Point2::new(0.5, 0.5),
+            &Point2::new(9.5, 3.5),
+            Rgba8::WHITE,
+        );
+        draw_line_aa(
+            &mut image,
+            &Point2::new(9.0, 0.0),
+            &Point2::new(9.0, 10.0),
+            Rgba8::WHITE,
+        );
+        let expected = golden(
+            "
+            563.....55
+            .36#63..55
+            ....36#665
+            .......387
+            ........55
+            ",
+        );
+        assert_eq!(render(&image), expected);
+
+        // Away from the ends every column of a shallow line has full coverage in total.
+        let mut image = Image::filled(40, 20, Rgba32F::default());
+        let (a, b) = (Point2::new(-5.0, 3.2), Point2::new(50.0, 15.7));
+        draw_line_aa(&mut image, &a, &b, Rgba32F::new(1.0, 1.0, 1.0, 1.0));
+        for x in 0..40 {
+            let total: f32 = (0..20).map(|y| image.get(x, y).unwrap().0[3]).sum();
+            assert!((total - 1.0).abs() < 1e-5, "{x}: {total}");
+        }
+    }
+
+    #[test]
+    fn test_thick_line() {
+        let mut image = Canvas::new(14, 9);
+        let (a, b) = (Point2::new(3.0, 2.0), Point2::new(10.0, 2.0));
+        draw_thick_line(&mut image, &a, &b, 2.0, LineCap::Butt, Rgba8::WHITE);
+        let (a, b) = (Point2::new(3.0, 6.0), Point2::new(10.0, 6.0));
+        draw_thick_line(&mut image, &a, &b, 4.0, LineCap::Round, Rgba8::WHITE);
+        let expected = golden(
+            "
+            ..............
+            ...#######....
+            ...#######....
+            ..............
+            ..#########...
+            .###########..
+            .###########..
+            ..#########...
+            ..............
+            ",
+        );
+        assert_eq!(render(&image), expected);
+
+        let mut image = Canvas::new(9, 9);
+        let (a, b) = (Point2::new(2.0, 2.0), Point2::new(7.0, 7.0));
+        draw_thick_line(&mut image, &a, &b, 2.0, LineCap::Square, Rgba8::WHITE);
+        // A lone point with square caps is a square, and butt caps draw nothing.
+        draw_thick_line(
+            &mut image,
+            &Point2::new(7.0, 1.0),
+            &Point2::new(7.0, 1.0),
+            2.0,
+            LineCap::Square,
+            Rgba8::WHITE,
+        );
+        draw_thick_line(
+            &mut image,
+            &Point2::new(1.0, 7.0),
+            &Point2::new(1.0, 7.0),
+            2.0,
+            LineCap::Butt,
+            Rgba8::WHITE,
+        );
+        let expected = golden(
+            "
+            ......##.
+            .##...##.
+            .###.....
+            ..###....
+            ...###...
+            ....###..
+            .....###.
+            ......##.
+            .........
+            ",
+        );
+        assert_eq!(render(&image), expected);
+    }
+
+    #[test]
+    fn test_clipping() {
+        let bounds = Aabb2::new(Point2::new(1.0, 2.0), Point2::new(6.0, 5.0));
+        let (a, b) = (Point2::new(0.0, 0.0), Point2::new(8.0, 8.0));
+        for clip in [clip_cohen_sutherland, clip_liang_barsky] {
+            let (p, q) = clip(&a, &b, &bounds).unwrap();
+            assert!((p - Point2::new(2.0, 2.0)).length() < 1e-12);
+            assert!((q - Point2::new(5.0, 5.0)).length() < 1e-12);
+            assert_eq!(
+                clip(&Point2::new(0.0, 0.0), &Point2::new(3.0, 0.0), &bounds),
+                None
+            );
+            assert_eq!(
+                clip(&Point2::new(0.0, 4.5), &Point2::new(1.5, 6.0), &bounds),
+                None
+            );
+            let inside = (Point2::new(2.0, 3.0), Point2::new(4.0, 4.0));
+            assert_eq!(clip(&inside.0, &inside.1, &bounds), Some(inside));
+        }
+
+        // Both methods agree, and what they keep is the part of the segment in the box.
+        let values = random_values(4 * 500, 4);
+        for v in values.chunks(4) {
+            let a = Point2::new(v[0] * 9.0 - 1.5, v[1] * 9.0 - 1.5);
+            let b = Point2::new(v[2] * 9.0 - 1.5, v[3] * 9.0 - 1.5);
+            let cs = clip_cohen_sutherland(&a, &b, &bounds);
+            let lb = clip_liang_barsky(&a, &b, &bounds);
+            match (cs, lb) {
+                (Some((p, q)), Some((r, s))) => {
+                    assert!((p - r).length() < 1e-9 && (q - s).length() < 1e-9);
+                    for t in [0.0, 0.5, 1.0] {
+                        let m = p + (q - p) * t;
+                        assert!(bounds.expand(1e-9).contains(&m));
+                    }
+                }
+                (None, None) => {
+                    for i in 0..=100 {
+                        let m = a + (b - a) * (i as f64 / 100.0);
+                        assert!(!bounds.expand(-1e-9).contains(&m));
+                    }
+                }
+                other => panic!("{a:?} {b:?}: {other:?}"),
+            }
+        }
+    }
+}
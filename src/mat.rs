@@ -1,6 +1,7 @@
-use std::ops::{Add, Div, Index, Mul, Sub};
+use std::ops::{Add, Div, Index, IndexMut, Mul, Sub};
 use std::slice::SliceIndex;
 
+use crate::point::{Point2, Point3};
 use crate::vec::{Vec2, Vec3, Vec4};
 
 macro_rules! mat2 {
@@ -45,7 +46,7 @@ macro_rules! mat4 {
     };
 }
 
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct Mat2<T>(Vec2<Vec2<T>>);
 
 impl<T, I: SliceIndex<[Vec2<T>]>> Index<I> for Mat2<T> {
@@ -56,6 +57,19 @@ impl<T, I: SliceIndex<[Vec2<T>]>> Index<I> for Mat2<T> {
     }
 }
 
+impl<T, I: SliceIndex<[Vec2<T>]>> IndexMut<I> for Mat2<T> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        IndexMut::index_mut(&mut self.0, index)
+    }
+}
+
+impl<T> Mat2<T> {
+    pub fn new(rows: [[T; 2]; 2]) -> Mat2<T> {
+        let [r0, r1] = rows;
+        Mat2(Vec2([Vec2(r0), Vec2(r1)]))
+    }
+}
+
 impl<T: Add<Output = T> + Copy, R: Into<T> + Copy> Add<R> for Mat2<T> {
     type Output = Self;
 
@@ -144,7 +158,7 @@ impl<T: Div<Output = T> + Copy> Div<Mat2<T>> for Mat2<T> {
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct Mat3<T>(Vec3<Vec3<T>>);
 
 impl<T, I: SliceIndex<[Vec3<T>]>> Index<I> for Mat3<T> {
@@ -155,6 +169,19 @@ impl<T, I: SliceIndex<[Vec3<T>]>> Index<I> for Mat3<T> {
     }
 }
 
+impl<T, I: SliceIndex<[Vec3<T>]>> IndexMut<I> for Mat3<T> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        IndexMut::index_mut(&mut self.0, index)
+    }
+}
+
+impl<T> Mat3<T> {
+    pub fn new(rows: [[T; 3]; 3]) -> Mat3<T> {
+        let [r0, r1, r2] = rows;
+        Mat3(Vec3([Vec3(r0), Vec3(r1), Vec3(r2)]))
+    }
+}
+
 impl<T: Add<Output = T> + Copy, R: Into<T> + Copy> Add<R> for Mat3<T> {
     type Output = Self;
 
@@ -251,7 +278,7 @@ impl<T: Div<Output = T> + Copy> Div<Mat2<T>> for Mat3<T> {
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct Mat4<T>(Vec4<Vec4<T>>);
 
 impl<T, I: SliceIndex<[Vec4<T>]>> Index<I> for Mat4<T> {
@@ -262,6 +289,19 @@ impl<T, I: SliceIndex<[Vec4<T>]>> Index<I> for Mat4<T> {
     }
 }
 
+impl<T, I: SliceIndex<[Vec4<T>]>> IndexMut<I> for Mat4<T> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        IndexMut::index_mut(&mut self.0, index)
+    }
+}
+
+impl<T> Mat4<T> {
+    pub fn new(rows: [[T; 4]; 4]) -> Mat4<T> {
+        let [r0, r1, r2, r3] = rows;
+        Mat4(Vec4([Vec4(r0), Vec4(r1), Vec4(r2), Vec4(r3)]))
+    }
+}
+
 impl<T: Add<Output = T> + Copy, R: Into<T> + Copy> Add<R> for Mat4<T> {
     type Output = Self;
 
@@ -366,13 +406,244 @@ impl<T: Div<Output = T> + Copy> Div<Mat2<T>> for Mat4<T> {
     }
 }
 
+impl Mat2<f64> {
+    pub fn identity() -> Mat2<f64> {
+        Mat2::new([[1.0, 0.0], [0.0, 1.0]])
+    }
+}
+
+impl Mat3<f64> {
+    pub fn identity() -> Mat3<f64> {
+        Mat3::new([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    /// Treats the matrix as a 2D affine transform acting on column vectors.
+    pub fn transform_point(&self, p: &Point2) -> Point2 {
+        let m = self;
+        Point2::new(
+            m[0][0] * p[0] + m[0][1] * p[1] + m[0][2],
+            m[1][0] * p[0] + m[1][1] * p[1] + m[1][2],
+        )
+    }
+
+    pub fn transform_vector(&self, v: &Vec2<f64>) -> Vec2<f64> {
+        let m = self;
+        vec2![
+            m[0][0] * v[0] + m[0][1] * v[1],
+            m[1][0] * v[0] + m[1][1] * v[1]
+        ]
+    }
+
+    pub fn mul_vec(&self, v: &Vec3<f64>) -> Vec3<f64> {
+        let m = self;
+        vec3![m[0].dot(v), m[1].dot(v), m[2].dot(v)]
+    }
+
+    pub fn mul_mat(&self, rhs: &Mat3<f64>) -> Mat3<f64> {
+        let mut out = Mat3::default();
+        for i in 0..3 {
+            for j in 0..3 {
+                out[i][j] = (0..3).map(|k| self[i][k] * rhs[k][j]).sum();
+            }
+        }
+        out
+    }
+
+    pub fn transpose(&self) -> Mat3<f64> {
+        let m = self;
+        Mat3::new([
+            [m[0][0], m[1][0], m[2][0]],
+            [m[0][1], m[1][1], m[2][1]],
+            [m[0][2], m[1][2], m[2][2]],
+        ])
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self[0].dot(&self[1].cross(&self[2]))
+    }
+
+    pub fn inverse(&self) -> Option<Mat3<f64>> {
+        let det = self.determinant();
+        if det == 0.0 {
+            return None;
+        }
+        // Columns of the inverse are the cross products of the rows.
+        let c0 = self[1].cross(&self[2]) / det;
+        let c1 = self[2].cross(&self[0]) / det;
+        let c2 = self[0].cross(&self[1]) / det;
+        Some(Mat3::new([c0.0, c1.0, c2.0]).transpose())
+    }
+
+    /// Eigen-decomposition of a symmetric matrix by cyclic Jacobi rotations.
+    /// Returns the eigenvalues and a matrix whose rows are the matching unit eigenvectors.
+    pub fn symmetric_eigen(&self) -> (Vec3<f64>, Mat3<f64>) {
+        let mut a = self.clone();
+        let mut v = Mat3::identity();
+        for _ in 0..50 {
+            let off = a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2];
+            if off < 1e-24 {
+                break;
+            }
+            for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                let mut j = Mat3::identity();
+                j[p][p] = c;
+                j[q][q] = c;
+                j[p][q] = s;
+                j[q][p] = -s;
+                a = j.transpose().mul_mat(&a).mul_mat(&j);
+                v = v.mul_mat(&j);
+            }
+        }
+        (vec3![a[0][0], a[1][1], a[2][2]], v.transpose())
+    }
+}
+
+impl Mat4<f64> {
+    pub fn identity() -> Mat4<f64> {
+        Mat4::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Treats the matrix as a 3D affine transform acting on column vectors.
+    pub fn transform_point(&self, p: &Point3) -> Point3 {
+        let m = self;
+        Point3::new(
+            m[0][0] * p[0] + m[0][1] * p[1] + m[0][2] * p[2] + m[0][3],
+            m[1][0] * p[0] + m[1][1] * p[1] + m[1][2] * p[2] + m[1][3],
+            m[2][0] * p[0] + m[2][1] * p[1] + m[2][2] * p[2] + m[2][3],
+        )
+    }
+
+    pub fn transform_vector(&self, v: &Vec3<f64>) -> Vec3<f64> {
+        let m = self;
+        vec3![
+            m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+            m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+            m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2]
+        ]
+    }
+
+    pub fn mul_vec(&self, v: &Vec4<f64>) -> Vec4<f64> {
+        let m = self;
+        vec4![m[0].dot(v), m[1].dot(v), m[2].dot(v), m[3].dot(v)]
+    }
+
+    pub fn mul_mat(&self, rhs: &Mat4<f64>) -> Mat4<f64> {
+        let mut out = Mat4::default();
+        for i in 0..4 {
+            for j in 0..4 {
+                out[i][j] = (0..4).map(|k| self[i][k] * rhs[k][j]).sum();
+            }
+        }
+        out
+    }
+
+    /// Perspective projection looking down `-z` with `y` up, mapping the view frustum to
+    /// clip space with `-w <= x, y, z <= w`.
+    pub fn perspective(fov_y: f64, aspect: f64, near: f64, far: f64) -> Mat4<f64> {
+        let f = 1.0 / (fov_y / 2.0).tan();
+        Mat4::new([
+            [f / aspect, 0.0, 0.0, 0.0],
+            [0.0, f, 0.0, 0.0],
+            [
+                0.0,
+                0.0,
+                (far + near) / (near - far),
+                2.0 * far * near / (near - far),
+            ],
+            [0.0, 0.0, -1.0, 0.0],
+        ])
+    }
+
+    /// Maps normalized device coordinates onto a `width` by `height` rectangle of pixels at
+    /// `(x, y)`, with `y` turned to point down and depth taken from `[-1, 1]` to `[0, 1]`.
+    pub fn viewport(x: f64, y: f64, width: f64, height: f64) -> Mat4<f64> {
+        Mat4::new([
+            [width / 2.0, 0.0, 0.0, x + width / 2.0],
+            [0.0, -height / 2.0, 0.0, y + height / 2.0],
+            [0.0, 0.0, 0.5, 0.5],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_mat_default() {
         let m: Mat2<f64> = Mat2::default();
         assert_eq!(m[0][0] == 0.0, true)
     }
+
+    #[test]
+    fn test_mat_transform_point() {
+        let mut m = Mat4::identity();
+        m[0][3] = 1.0;
+        m[1][1] = 2.0;
+        let p = m.transform_point(&Point3::new(1.0, 1.0, 1.0));
+        assert_eq!(p, Point3::new(2.0, 2.0, 1.0));
+    }
+
+    #[test]
+    fn test_mat3_inverse() {
+        let m = Mat3::new([[2.0, 0.0, 1.0], [0.0, 1.0, 0.0], [1.0, 0.0, 1.0]]);
+        let inv = m.inverse().unwrap();
+        let id = m.mul_mat(&inv);
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((id[i][j] - expected).abs() < 1e-12);
+            }
+        }
+        assert!(Mat3::<f64>::default().inverse().is_none());
+    }
+
+    #[test]
+    fn test_mat4_projection() {
+        let projection = Mat4::perspective(std::f64::consts::FRAC_PI_2, 2.0, 1.0, 10.0);
+        let viewport = Mat4::viewport(0.0, 0.0, 200.0, 100.0);
+        let m = viewport.mul_mat(&projection);
+        for (p, expected) in [
+            (vec4![0.0, 0.0, -1.0, 1.0], [100.0, 50.0, 0.0]),
+            (vec4![-20.0, 10.0, -10.0, 1.0], [0.0, 0.0, 1.0]),
+            (vec4![2.0, -1.0, -1.0, 1.0], [200.0, 100.0, 0.0]),
+        ] {
+            let clip = m.mul_vec(&p);
+            assert!((clip[3] + p[2]).abs() < 1e-12);
+            for i in 0..3 {
+                assert!((clip[i] / clip[3] - expected[i]).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mat3_symmetric_eigen() {
+        let m = Mat3::new([[2.0, 1.0, 0.0], [1.0, 2.0, 0.0], [0.0, 0.0, 5.0]]);
+        let (values, vectors) = m.symmetric_eigen();
+        for i in 0..3 {
+            let av = m.mul_vec(&vectors[i]);
+            let lv = vectors[i].clone() * values[i];
+            assert!((av - lv).length() < 1e-9);
+            assert!((vectors[i].length() - 1.0).abs() < 1e-9);
+        }
+        let mut sorted = values.0;
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert!((sorted[0] - 1.0).abs() < 1e-9);
+        assert!((sorted[1] - 3.0).abs() < 1e-9);
+        assert!((sorted[2] - 5.0).abs() < 1e-9);
+    }
 }
@@ -0,0 +1,238 @@
+use crate::mat::Mat3;
+use crate::point::Point3;
+use crate::vec::Vec3;
+
+/// Added to the absolute rotation terms of the separating-axis test so that
+/// near-parallel edge pairs do not produce a spurious separating axis.
+const SAT_EPSILON: f64 = 1e-9;
+
+/// Oriented bounding box. Row `i` of `axes` is the unit direction of local axis `i`.
+#[derive(Debug, Clone)]
+pub struct Obb3 {
+    pub center: Point3,
+    pub axes: Mat3<f64>,
+    pub half_extents: Vec3<f64>,
+}
+
+impl Obb3 {
+    pub fn new(center: Point3, axes: Mat3<f64>, half_extents: Vec3<f64>) -> Obb3 {
+        Obb3 {
+            center,
+            axes,
+            half_extents,
+        }
+    }
+
+    /// Fits a box whose axes are the principal components of the point covariance.
+    pub fn from_points_pca(points: &[Point3]) -> Obb3 {
+        if points.is_empty() {
+            return Obb3::new(Point3::default(), Mat3::identity(), Vec3::default());
+        }
+        let (_, axes) = covariance(points).symmetric_eigen();
+        Obb3::from_points_with_axes(points, axes)
+    }
+
+    /// Fits the tightest box with the given (orthonormal, row-major) axes.
+    pub fn from_points_with_axes(points: &[Point3], axes: Mat3<f64>) -> Obb3 {
+        let mut lo = Vec3::all(f64::INFINITY);
+        let mut hi = Vec3::all(f64::NEG_INFINITY);
+        for p in points {
+            let local = axes.mul_vec(&p.to_vec());
+            for i in 0..3 {
+                lo[i] = lo[i].min(local[i]);
+                hi[i] = hi[i].max(local[i]);
+            }
+        }
+        let mid = (lo.clone() + hi.clone()) * 0.5;
+        let center = Point3::from_vec(axes.transpose().mul_vec(&mid));
+        Obb3::new(center, axes, (hi - lo) * 0.5)
+    }
+
+    /// The eight corners of the box.
+    pub fn corners(&self) -> [Point3; 8] {
+        let mut corners = [self.center; 8];
+        for (n, corner) in corners.iter_mut().enumerate() {
+            for i in 0..3 {
+                let sign = if n & (1 << i) == 0 { -1.0 } else { 1.0 };
+                *corner += self.axes[i].clone() * (sign * self.half_extents[i]);
+            }
+        }
+        corners
+    }
+
+    /// Box enclosing both boxes, refit along the principal axes of their corners.
+    pub fn merge(&self, rhs: &Obb3) -> Obb3 {
+        let mut points = self.corners().to_vec();
+        points.extend_from_slice(&rhs.corners());
+        Obb3::from_points_pca(&points)
+    }
+
+    fn to_local(&self, p: &Point3) -> Vec3<f64> {
+        self.axes.mul_vec(&(*p - self.center))
+    }
+
+    pub fn contains(&self, p: &Point3) -> bool {
+        let local = self.to_local(p);
+        (0..3).all(|i| local[i].abs() <= self.half_extents[i])
+    }
+
+    pub fn closest_point(&self, p: &Point3) -> Point3 {
+        let local = self.to_local(p);
+        let mut q = self.center;
+        for i in 0..3 {
+            let h = self.half_extents[i];
+            q += self.axes[i].clone() * local[i].clamp(-h, h);
+        }
+        q
+    }
+
+    pub fn volume(&self) -> f64 {
+        8.0 * self.half_extents[0] * self.half_extents[1] * self.half_extents[2]
+    }
+
+    /// Separating-axis test over the 15 candidate axes (Gottschalk, via Ericson).
+    pub fn overlaps(&self, rhs: &Obb3) -> bool {
+        let a = &self.half_extents;
+        let b = &rhs.half_extents;
+
+        // Rotation expressing rhs in self's frame, and the translation in self's frame.
+        let mut r = [[0.0; 3]; 3];
+        let mut abs_r = [[0.0; 3]; 3];
+        for i in 0..3 {
+            for j in 0..3 {
+                r[i][j] = self.axes[i].dot(&rhs.axes[j]);
+                abs_r[i][j] = r[i][j].abs() + SAT_EPSILON;
+            }
+        }
+        let t = self.to_local(&rhs.center);
+
+        // Axes of self.
+        for i in 0..3 {
+            let ra = a[i];
+            let rb = b[0] * abs_r[i][0] + b[1] * abs_r[i][1] + b[2] * abs_r[i][2];
+            if t[i].abs() > ra + rb {
+                return false;
+            }
+        }
+
+        // Axes of rhs.
+        for j in 0..3 {
+            let ra = a[0] * abs_r[0][j] + a[1] * abs_r[1][j] + a[2] * abs_r[2][j];
+            let rb = b[j];
+            let proj = t[0] * r[0][j] + t[1] * r[1][j] + t[2] * r[2][j];
+            if proj.abs() > ra + rb {
+                return false;
+            }
+        }
+
+        // Cross products of each pair of axes.
+        for i in 0..3 {
+            let (i1, i2) = ((i + 1) % 3, (i + 2) % 3);
+            for j in 0..3 {
+                let (j1, j2) = ((j + 1) % 3, (j + 2) % 3);
+                let ra = a[i1] * abs_r[i2][j] + a[i2] * abs_r[i1][j];
+                let rb = b[j1] * abs_r[i][j2] + b[j2] * abs_r[i][j1];
+                let proj = t[i2] * r[i1][j] - t[i1] * r[i2][j];
+                if proj.abs() > ra + rb {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// Covariance matrix of a point set about its centroid.
+pub fn covariance(points: &[Point3]) -> Mat3<f64> {
+    let n = points.len() as f64;
+    let mut mean = Vec3::all(0.0);
+    for p in points {
+        mean += p.to_vec();
+    }
+    mean /= n;
+
+    let mut cov = Mat3::default();
+    for p in points {
+        let d = p.to_vec() - mean.clone();
+        for i in 0..3 {
+            for j in 0..3 {
+                cov[i][j] += d[i] * d[j] / n;
+            }
+        }
+    }
+    cov
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rotated_z(angle: f64) -> Mat3<f64> {
+        let (s, c) = angle.sin_cos();
+        Mat3::new([[c, s, 0.0], [-s, c, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    #[test]
+    fn test_pca_fits_rotated_box() {
+        let axes = rotated_z(0.5);
+        let obb = Obb3::new(Point3::new(1.0, 2.0, 3.0), axes, vec3![4.0, 1.0, 0.5]);
+        let corners = obb.corners();
+        let fit = Obb3::from_points_pca(&corners);
+        assert!((fit.volume() - obb.volume()).abs() < 1e-6);
+        assert!((fit.center - obb.center).length() < 1e-9);
+        for p in &corners {
+            let q = fit.closest_point(p);
+            assert!((q - *p).length() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_contains() {
+        let obb = Obb3::new(
+            Point3::default(),
+            rotated_z(std::f64::consts::FRAC_PI_4),
+            vec3![2.0, 0.1, 1.0],
+        );
+        assert!(obb.contains(&Point3::new(1.0, 1.0, 0.0)));
+        assert!(!obb.contains(&Point3::new(1.0, -1.0, 0.0)));
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let a = Obb3::new(Point3::default(), Mat3::identity(), vec3![1.0, 1.0, 1.0]);
+        let b = Obb3::new(
+            Point3::new(2.3, 0.0, 0.0),
+            rotated_z(std::f64::consts::FRAC_PI_4),
+            vec3![1.0, 1.0, 1.0],
+        );
+        // The rotated box reaches sqrt(2) towards `a` along x.
+        assert!(a.overlaps(&b));
+        let c = Obb3::new(
+            Point3::new(2.5, 0.0, 0.0),
+            Mat3::identity(),
+            vec3![1.0, 1.0, 1.0],
+        );
+        assert!(!a.overlaps(&c));
+        let d = Obb3::new(
+            Point3::new(2.0, 2.0, 0.0),
+            rotated_z(std::f64::consts::FRAC_PI_4),
+            vec3![0.5, 0.5, 1.0],
+        );
+        assert!(!a.overlaps(&d));
+    }
+
+    #[test]
+    fn test_merge() {
+        let a = Obb3::new(Point3::default(), Mat3::identity(), vec3![1.0, 1.0, 1.0]);
+        let b = Obb3::new(
+            Point3::new(5.0, 0.0, 0.0),
+            Mat3::identity(),
+            vec3![1.0, 1.0, 1.0],
+        );
+        let m = a.merge(&b);
+        for p in a.corners().iter().chain(b.corners().iter()) {
+            assert!((m.closest_point(p) - *p).length() < 1e-9);
+        }
+    }
+}
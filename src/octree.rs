@@ -0,0 +1,630 @@
+use std::fmt::Write;
+
+use crate::aabb::{Aabb2, Aabb3};
+use crate::frustum::{Frustum2, Frustum3};
+use crate::point::{Point2, Point3};
+
+/// Marks a node without children; the root is never anyone's child.
+const NO_CHILDREN: usize = 0;
+const NO_PARENT: usize = usize::MAX;
+
+/// Box types a `LooseTree` can subdivide: `Aabb2` into quadrants and `Aabb3` into octants.
+pub trait TreeBounds: Copy {
+    type Point: Copy;
+    type Frustum;
+    const CHILDREN: usize;
+
+    fn from_point(p: &Self::Point) -> Self;
+    fn center(&self) -> Self::Point;
+    fn contains_aabb(&self, rhs: &Self) -> bool;
+    fn overlaps(&self, rhs: &Self) -> bool;
+    fn frustum_overlaps(&self, frustum: &Self::Frustum) -> bool;
+    /// Index of the child cell containing `p`, one bit per axis set when `p` is past the centre.
+    fn child_index(&self, p: &Self::Point) -> usize;
+    fn child(&self, index: usize) -> Self;
+    /// The box grown by half its size on every side, doubling each extent.
+    fn loosen(&self) -> Self;
+    fn corners(&self) -> (&[f64], &[f64]);
+}
+
+impl TreeBounds for Aabb2 {
+    type Point = Point2;
+    type Frustum = Frustum2;
+    const CHILDREN: usize = 4;
+
+    fn from_point(p: &Point2) -> Aabb2 {
+        Aabb2::new(*p, *p)
+    }
+
+    fn center(&self) -> Point2 {
+        Aabb2::center(self)
+    }
+
+    fn contains_aabb(&self, rhs: &Aabb2) -> bool {
+        Aabb2::contains_aabb(self, rhs)
+    }
+
+    fn overlaps(&self, rhs: &Aabb2) -> bool {
+        Aabb2::overlaps(self, rhs)
+    }
+
+    fn frustum_overlaps(&self, frustum: &Frustum2) -> bool {
+        frustum.intersects_aabb(self)
+    }
+
+    fn child_index(&self, p: &Point2) -> usize {
+        let c = Aabb2::center(self);
+        (0..2).filter(|&i| p[i] >= c[i]).map(|i| 1 << i).sum()
+    }
+
+    fn child(&self, index: usize) -> Aabb2 {
+        let c = Aabb2::center(self);
+        let mut child = Aabb2::new(self.min, c);
+        for i in 0..2 {
+            if index & (1 << i) != 0 {
+                child.min[i] = c[i];
+                child.max[i] = self.max[i];
+            }
+        }
+        child
+    }
+
+    fn loosen(&self) -> Aabb2 {
+        let e = self.extents();
+        Aabb2::new(self.min - e.clone(), self.max + e)
+    }
+
+    fn corners(&self) -> (&[f64], &[f64]) {
+        (&self.min.0, &self.max.0)
+    }
+}
+
+impl TreeBounds for Aabb3 {
+    type Point = Point3;
+    type Frustum = Frustum3;
+    const CHILDREN: usize = 8;
+
+    fn from_point(p: &Point3) -> Aabb3 {
+        Aabb3::new(*p, *p)
+    }
+
+    fn center(&self) -> Point3 {
+        Aabb3::center(self)
+    }
+
+    fn contains_aabb(&self, rhs: &Aabb3) -> bool {
+        Aabb3::contains_aabb(self, rhs)
+    }
+
+    fn overlaps(&self, rhs: &Aabb3) -> bool {
+        Aabb3::overlaps(self, rhs)
+    }
+
+    fn frustum_overlaps(&self, frustum: &Frustum3) -> bool {
+        frustum.intersects_aabb(self)
+    }
+
+    fn child_index(&self, p: &Point3) -> usize {
+        let c = Aabb3::center(self);
+        (0..3).filter(|&i| p[i] >= c[i]).map(|i| 1 << i).sum()
+    }
+
+    fn child(&self, index: usize) -> Aabb3 {
+        let c = Aabb3::center(self);
+        let mut child = Aabb3::new(self.min, c);
+        for i in 0..3 {
+            if index & (1 << i) != 0 {
+                child.min[i] = c[i];
+                child.max[i] = self.max[i];
+            }
+        }
+        child
+    }
+
+    fn loosen(&self) -> Aabb3 {
+        let e = self.extents();
+        Aabb3::new(self.min - e.clone(), self.max + e)
+    }
+
+    fn corners(&self) -> (&[f64], &[f64]) {
+        (&self.min.0, &self.max.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node<B> {
+    cell: B,
+    loose: B,
+    depth: usize,
+    parent: usize,
+    /// Index of the first of `B::CHILDREN` consecutive children, or `NO_CHILDREN`.
+    children: usize,
+    items: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry<B, T> {
+    bounds: B,
+    value: T,
+    node: usize,
+}
+
+/// Loose quadtree or octree over a fixed world box. Every node's loose bounds are its
+/// cell doubled in size, and an object is stored in the deepest node whose cell contains
+/// its centre and whose loose bounds contain all of it, so nothing straddles siblings.
+/// Objects outside the world box are kept in the root. Nodes split once they hold more
+/// than `bucket_size` objects, down to `max_depth`, and merge back as objects leave.
+///
+/// Objects are identified by the id returned from `insert`. Ids of removed objects are
+/// reused by later insertions.
+#[derive(Debug, Clone)]
+pub struct LooseTree<B, T> {
+    nodes: Vec<Node<B>>,
+    free_blocks: Vec<usize>,
+    entries: Vec<Option<Entry<B, T>>>,
+    free_ids: Vec<usize>,
+    len: usize,
+    max_depth: usize,
+    bucket_size: usize,
+}
+
+pub type Quadtree<T> = LooseTree<Aabb2, T>;
+pub type Octree<T> = LooseTree<Aabb3, T>;
+
+impl<B: TreeBounds, T> LooseTree<B, T> {
+    pub fn new(world: B, max_depth: usize, bucket_size: usize) -> LooseTree<B, T> {
+        LooseTree {
+            nodes: vec![Node {
+                cell: world,
+                loose: world.loosen(),
+                depth: 0,
+                parent: NO_PARENT,
+                children: NO_CHILDREN,
+                items: Vec::new(),
+            }],
+            free_blocks: Vec::new(),
+            entries: Vec::new(),
+            free_ids: Vec::new(),
+            len: 0,
+            max_depth,
+            bucket_size: bucket_size.max(1),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, bounds: B, value: T) -> usize {
+        let entry = Some(Entry {
+            bounds,
+            value,
+            node: 0,
+        });
+        let id = match self.free_ids.pop() {
+            Some(id) => {
+                self.entries[id] = entry;
+                id
+            }
+            None => {
+                self.entries.push(entry);
+                self.entries.len() - 1
+            }
+        };
+        self.len += 1;
+        self.place(id, 0);
+        id
+    }
+
+    pub fn insert_point(&mut self, p: &B::Point, value: T) -> usize {
+        self.insert(B::from_point(p), value)
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<T> {
+        let entry = self.entries.get_mut(id)?.take()?;
+        self.detach(id, entry.node);
+        self.free_ids.push(id);
+        self.len -= 1;
+        self.collapse(entry.node);
+        Some(entry.value)
+    }
+
+    /// Moves an object to new bounds. Small motions that keep it inside its node's loose
+    /// bounds cost nothing; otherwise it is reinserted. Returns false for unknown ids.
+    pub fn update(&mut self, id: usize, bounds: B) -> bool {
+        let Some(entry) = self.entries.get_mut(id).and_then(|e| e.as_mut()) else {
+            return false;
+        };
+        entry.bounds = bounds;
+        let node = entry.node;
+        let fits = node == 0 || self.nodes[node].loose.contains_aabb(&bounds);
+        if fits && self.fitting_child(node, &bounds).is_none() {
+            return true;
+        }
+        self.detach(id, node);
+        // Reinsert from the lowest ancestor that still holds the object, then tidy up.
+        let mut start = node;
+        while start != 0 && !self.nodes[start].loose.contains_aabb(&bounds) {
+            start = self.nodes[start].parent;
+        }
+        self.place(id, start);
+        self.collapse(node);
+        true
+    }
+
+    pub fn update_point(&mut self, id: usize, p: &B::Point) -> bool {
+        self.update(id, B::from_point(p))
+    }
+
+    pub fn get(&self, id: usize) -> Option<&T> {
+        self.entry(id).map(|e| &e.value)
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut T> {
+        self.entries
+            .get_mut(id)
+            .and_then(|e| e.as_mut())
+            .map(|e| &mut e.value)
+    }
+
+    pub fn bounds(&self, id: usize) -> Option<B> {
+        self.entry(id).map(|e| e.bounds)
+    }
+
+    /// All objects as `(id, bounds, value)` in id order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &B, &T)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(id, e)| e.as_ref().map(|e| (id, &e.bounds, &e.value)))
+    }
+
+    /// Ids of the objects whose bounds overlap `range`.
+    pub fn query(&self, range: &B) -> Vec<usize> {
+        self.collect(|b| b.overlaps(range))
+    }
+
+    /// Ids of the objects whose bounds may be visible in `frustum`. Like the frustum box
+    /// test itself this is conservative near the frustum's corners.
+    pub fn query_frustum(&self, frustum: &B::Frustum) -> Vec<usize> {
+        self.collect(|b| b.frustum_overlaps(frustum))
+    }
+
+    /// One line per node, indented by depth, with its cell and object count.
+    pub fn dump(&self) -> String {
+        let mut out = String::new();
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let (min, max) = node.cell.corners();
+            let indent = "  ".repeat(node.depth);
+            writeln!(out, "{indent}{min:?}..{max:?}: {} items", node.items.len()).unwrap();
+            if node.children != NO_CHILDREN {
+                stack.extend((node.children..node.children + B::CHILDREN).rev());
+            }
+        }
+        out
+    }
+
+    fn entry(&self, id: usize) -> Option<&Entry<B, T>> {
+        self.entries.get(id).and_then(|e| e.as_ref())
+    }
+
+    fn fitting_child(&self, node: usize, bounds: &B) -> Option<usize> {
+        let n = &self.nodes[node];
+        if n.children == NO_CHILDREN {
+            return None;
+        }
+        let child = n.children + n.cell.child_index(&bounds.center());
+        self.nodes[child]
+            .loose
+            .contains_aabb(bounds)
+            .then_some(child)
+    }
+
+    /// Stores `id` in the deepest fitting node below `start`, splitting it when full.
+    fn place(&mut self, id: usize, start: usize) {
+        let bounds = self.entries[id].as_ref().unwrap().bounds;
+        let mut node = start;
+        while let Some(child) = self.fitting_child(node, &bounds) {
+            node = child;
+        }
+        self.nodes[node].items.push(id);
+        self.entries[id].as_mut().unwrap().node = node;
+        let n = &self.nodes[node];
+        if n.children == NO_CHILDREN && n.items.len() > self.bucket_size && n.depth < self.max_depth
+        {
+            self.split(node);
+        }
+    }
+
+    fn split(&mut self, node: usize) {
+        let cell = self.nodes[node].cell;
+        let depth = self.nodes[node].depth + 1;
+        let children = (0..B::CHILDREN).map(|i| {
+            let cell = cell.child(i);
+            Node {
+                cell,
+                loose: cell.loosen(),
+                depth,
+                parent: node,
+                children: NO_CHILDREN,
+                items: Vec::new(),
+            }
+        });
+        let first = match self.free_blocks.pop() {
+            Some(first) => {
+                for (slot, child) in self.nodes[first..first + B::CHILDREN]
+                    .iter_mut()
+                    .zip(children)
+                {
+                    *slot = child;
+                }
+                first
+            }
+            None => {
+                self.nodes.extend(children);
+                self.nodes.len() - B::CHILDREN
+            }
+        };
+        self.nodes[node].children = first;
+        let items = std::mem::take(&mut self.nodes[node].items);
+        for id in items {
+            self.place(id, node);
+        }
+    }
+
+    fn detach(&mut self, id: usize, node: usize) {
+        let items = &mut self.nodes[node].items;
+        let position = items.iter().position(|&i| i == id).unwrap();
+        items.swap_remove(position);
+    }
+
+    /// Merges children back into their parent, walking up from `node`, while a whole
+    /// subtree of leaves fits in one bucket.
+    fn collapse(&mut self, node: usize) {
+        let mut node = if self.nodes[node].children == NO_CHILDREN {
+            self.nodes[node].parent
+        } else {
+            node
+        };
+        while node != NO_PARENT {
+            let first = self.nodes[node].children;
+            let children = first..first + B::CHILDREN;
+            let all_leaves = children
+                .clone()
+                .all(|c| self.nodes[c].children == NO_CHILDREN);
+            let total = self.nodes[node].items.len()
+                + children
+                    .clone()
+                    .map(|c| self.nodes[c].items.len())
+                    .sum::<usize>();
+            if !all_leaves || total > self.bucket_size {
+                return;
+            }
+            for c in children {
+                let items = std::mem::take(&mut self.nodes[c].items);
+                for &id in &items {
+                    self.entries[id].as_mut().unwrap().node = node;
+                }
+                self.nodes[node].items.extend(items);
+            }
+            self.nodes[node].children = NO_CHILDREN;
+            self.free_blocks.push(first);
+            node = self.nodes[node].parent;
+        }
+    }
+
+    fn collect<F: Fn(&B) -> bool>(&self, overlaps: F) -> Vec<usize> {
+        let mut result = Vec::new();
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            // The root also holds objects outside the world box, so it is never culled.
+            if index != 0 && !overlaps(&node.loose) {
+                continue;
+            }
+            for &id in &node.items {
+                if overlaps(&self.entries[id].as_ref().unwrap().bounds) {
+                    result.push(id);
+                }
+            }
+            if node.children != NO_CHILDREN {
+                stack.extend(node.children..node.children + B::CHILDREN);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mat::Mat4;
+    use crate::test_util::random_values;
+    use crate::vec::Vec3;
+
+    fn random_boxes(n: usize, seed: u64) -> Vec<Aabb3> {
+        random_values(4 * n, seed)
+            .chunks(4)
+            .map(|c| {
+                let p = Point3::new(c[0] * 100.0, c[1] * 100.0, c[2] * 100.0);
+                Aabb3::new(p, p).expand(c[3] * c[3] * 10.0)
+            })
+            .collect()
+    }
+
+    /// Checks the placement invariants and that every live object sits in its node.
+    fn assert_valid<B: TreeBounds, T>(tree: &LooseTree<B, T>) {
+        let mut count = 0;
+        for (index, node) in tree.nodes.iter().enumerate() {
+            if index != 0
+                && tree
+                    .free_blocks
+                    .iter()
+                    .any(|&f| (f..f + B::CHILDREN).contains(&index))
+            {
+                continue;
+            }
+            for &id in &node.items {
+                let entry = tree.entries[id].as_ref().unwrap();
+                assert_eq!(entry.node, index);
+                assert!(index == 0 || node.loose.contains_aabb(&entry.bounds));
+                assert!(tree.fitting_child(index, &entry.bounds).is_none());
+                count += 1;
+            }
+        }
+        assert_eq!(count, tree.len());
+    }
+
+    fn brute_force(boxes: &[Option<Aabb3>], range: &Aabb3) -> Vec<usize> {
+        (0..boxes.len())
+            .filter(|&i| boxes[i].is_some_and(|b| b.overlaps(range)))
+            .collect()
+    }
+
+    #[test]
+    fn test_octree_queries() {
+        let world = Aabb3::new(Point3::new(0.0, 0.0, 0.0), Point3::new(100.0, 100.0, 100.0));
+        let mut tree = Octree::new(world, 6, 4);
+        let mut boxes: Vec<Option<Aabb3>> = Vec::new();
+        for (i, b) in random_boxes(500, 1).into_iter().enumerate() {
+            assert_eq!(tree.insert(b, i), i);
+            boxes.push(Some(b));
+        }
+        // An object outside the world box lands in the root but is still found.
+        let outside = Aabb3::new(Point3::new(150.0, 0.0, 0.0), Point3::new(160.0, 5.0, 5.0));
+        assert_eq!(tree.insert(outside, 500), 500);
+        boxes.push(Some(outside));
+        assert_valid(&tree);
+        assert!(tree.nodes.len() > 9);
+
+        for range in random_boxes(30, 2) {
+            let range = range.expand(5.0);
+            let mut found = tree.query(&range);
+            found.sort();
+            assert_eq!(found, brute_force(&boxes, &range));
+        }
+        assert_eq!(tree.query(&outside.expand(-1.0)), vec![500]);
+
+        // A camera at z = 120 above the world's centre looking down -z, with its near and
+        // far planes at z = 113 and z = 50. Compare against the box test on every object.
+        let view = Mat4::new([
+            [2.0, 0.0, 0.0, -100.0],
+            [0.0, 2.0, 0.0, -100.0],
+            [0.0, 0.0, -1.2, 130.0],
+            [0.0, 0.0, -1.0, 120.0],
+        ]);
+        let frustum = Frustum3::from_matrix(&view);
+        let mut found = tree.query_frustum(&frustum);
+        found.sort();
+        let expected: Vec<usize> = (0..boxes.len())
+            .filter(|&i| frustum.intersects_aabb(&boxes[i].unwrap()))
+            .collect();
+        assert_eq!(found, expected);
+        assert!(!found.is_empty() && found.len() < boxes.len());
+    }
+
+    #[test]
+    fn test_octree_remove_and_update() {
+        let world = Aabb3::new(Point3::new(0.0, 0.0, 0.0), Point3::new(100.0, 100.0, 100.0));
+        let mut tree = Octree::new(world, 5, 2);
+        let mut boxes: Vec<Option<Aabb3>> = random_boxes(300, 3).into_iter().map(Some).collect();
+        for (i, b) in boxes.iter().enumerate() {
+            tree.insert(b.unwrap(), i);
+        }
+
+        let moves = random_values(600, 4);
+        for (i, c) in moves.chunks(2).enumerate() {
+            let b = boxes[i].unwrap();
+            // Alternate between small jitter and jumps across the world.
+            let offset = if i % 2 == 0 {
+                vec3![c[0] - 0.5, c[1] - 0.5, 0.0]
+            } else {
+                vec3![c[0] * 80.0 - 40.0, c[1] * 80.0 - 40.0, 10.0]
+            };
+            let moved = Aabb3::new(b.min + offset.clone(), b.max + offset);
+            assert!(tree.update(i, moved));
+            boxes[i] = Some(moved);
+        }
+        assert_valid(&tree);
+
+        for i in (0..300).step_by(3) {
+            assert_eq!(tree.remove(i), Some(i));
+            assert_eq!(tree.remove(i), None);
+            boxes[i] = None;
+        }
+        assert_valid(&tree);
+        assert_eq!(tree.len(), 200);
+        for range in random_boxes(20, 5) {
+            let range = range.expand(10.0);
+            let mut found = tree.query(&range);
+            found.sort();
+            assert_eq!(found, brute_force(&boxes, &range));
+        }
+
+        // Removed ids are reused, and emptying the tree merges every node back into the root.
+        assert_eq!(tree.insert(world, 1000), 297);
+        for i in 0..300 {
+            tree.remove(i);
+        }
+        assert!(tree.is_empty());
+        assert!(!tree.update(0, world));
+        assert_eq!(
+            tree.dump(),
+            "[0.0, 0.0, 0.0]..[100.0, 100.0, 100.0]: 0 items\n"
+        );
+    }
+
+    #[test]
+    fn test_quadtree_points() {
+        let world = Aabb2::new(Point2::new(0.0, 0.0), Point2::new(8.0, 8.0));
+        let mut tree = Quadtree::new(world, 2, 1);
+        let a = tree.insert_point(&Point2::new(1.0, 1.0), "a");
+        let b = tree.insert_point(&Point2::new(7.0, 1.0), "b");
+        let c = tree.insert_point(&Point2::new(7.0, 7.0), "c");
+        let d = tree.insert_point(&Point2::new(7.5, 7.5), "d");
+        assert_valid(&tree);
+        assert_eq!(
+            tree.dump(),
+            "[0.0, 0.0]..[8.0, 8.0]: 0 items\n\
+             \x20 [0.0, 0.0]..[4.0, 4.0]: 1 items\n\
+             \x20 [4.0, 0.0]..[8.0, 4.0]: 1 items\n\
+             \x20 [0.0, 4.0]..[4.0, 8.0]: 0 items\n\
+             \x20 [4.0, 4.0]..[8.0, 8.0]: 0 items\n\
+             \x20   [4.0, 4.0]..[6.0, 6.0]: 0 items\n\
+             \x20   [6.0, 4.0]..[8.0, 6.0]: 0 items\n\
+             \x20   [4.0, 6.0]..[6.0, 8.0]: 0 items\n\
+             \x20   [6.0, 6.0]..[8.0, 8.0]: 2 items\n"
+        );
+
+        let range = Aabb2::new(Point2::new(5.0, 0.0), Point2::new(8.0, 7.0));
+        let mut found = tree.query(&range);
+        found.sort();
+        assert_eq!(found, vec![b, c]);
+
+        assert!(tree.update_point(a, &Point2::new(6.5, 6.5)));
+        assert_eq!(
+            tree.bounds(a),
+            Some(Aabb2::from_point(&Point2::new(6.5, 6.5)))
+        );
+        *tree.get_mut(d).unwrap() = "e";
+        let mut values: Vec<&str> = tree.iter().map(|(_, _, v)| *v).collect();
+        values.sort();
+        assert_eq!(values, vec!["a", "b", "c", "e"]);
+        assert_eq!(tree.get(b), Some(&"b"));
+        assert_valid(&tree);
+
+        // A camera showing x in [4, 8] and y in [0, 8] sees everything but nothing on the left.
+        let camera = crate::mat::Mat3::new([[0.5, 0.0, -3.0], [0.0, 0.25, -1.0], [0.0, 0.0, 1.0]]);
+        let mut found = tree.query_frustum(&Frustum2::from_matrix(&camera));
+        found.sort();
+        assert_eq!(found, vec![a, b, c, d]);
+        tree.update_point(a, &Point2::new(1.0, 1.0));
+        assert_eq!(tree.query_frustum(&Frustum2::from_matrix(&camera)).len(), 3);
+    }
+}
@@ -0,0 +1,260 @@
+use crate::boolean::{boolean, BooleanOp};
+use crate::point::Point2;
+use crate::polygon::Polygon2;
+use crate::vec::Vec2;
+
+/// How corners are filled where the offset boundary turns away from the polygon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinStyle {
+    /// Sharp corners, squared off where the tip would lie further than
+    /// `limit * distance` from the original vertex.
+    Miter(f64),
+    /// Arcs approximated by chords deviating at most the given positive tolerance from the
+    /// true arc.
+    Round(f64),
+    /// Corners cut square at `distance` from the original vertex.
+    Square,
+}
+
+/// The join region at a convex corner `v`, where `n1` and `n2` are the unit normals
+/// of the incoming and outgoing edges on the growing side.
+fn join_shape(v: Point2, n1: &Vec2<f64>, n2: &Vec2<f64>, d: f64, join: JoinStyle) -> Vec<Point2> {
+    let s1 = v + n1.clone() * d;
+    let s2 = v + n2.clone() * d;
+    match join {
+        JoinStyle::Round(tolerance) => {
+            let angle = n1.perp_dot(n2).atan2(n1.dot(n2));
+            let step = if tolerance < d {
+                2.0 * (1.0 - tolerance / d).acos()
+            } else {
+                std::f64::consts::FRAC_PI_2
+            };
+            let steps = (angle.abs() / step).ceil().max(1.0) as usize;
+            let mut shape = vec![v];
+            for k in 0..=steps {
+                let (sin, cos) = (angle * k as f64 / steps as f64).sin_cos();
+                let dir = vec2![n1[0] * cos - n1[1] * sin, n1[0] * sin + n1[1] * cos];
+                shape.push(v + dir * d);
+            }
+            shape
+        }
+        JoinStyle::Miter(limit) => {
+            let tip = v + (n1.clone() + n2.clone()) * (d / (1.0 + n1.dot(n2)));
+            let limit = limit.max(1.0) * d;
+            if (tip - v).length() <= limit {
+                vec![v, s1, tip, s2]
+            } else {
+                squared(v, n1, n2, d, tip, limit)
+            }
+        }
+        JoinStyle::Square => {
+            let tip = v + (n1.clone() + n2.clone()) * (d / (1.0 + n1.dot(n2)));
+            squared(v, n1, n2, d, tip, d)
+        }
+    }
+}
+
+/// The mitered corner `v, s1, tip, s2` cut by a line perpendicular to the bisector at
+/// distance `r` from `v`.
+fn squared(v: Point2, n1: &Vec2<f64>, n2: &Vec2<f64>, d: f64, tip: Point2, r: f64) -> Vec<Point2> {
+    let s1 = v + n1.clone() * d;
+    let s2 = v + n2.clone() * d;
+    let bisector = (n1.clone() + n2.clone()).normalize();
+    let base = d * n1.dot(&bisector);
+    let t = (r - base) / ((tip - v).length() - base);
+    vec![v, s1, s1 + (tip - s1) * t, s2 + (tip - s2) * t, s2]
+}
+
+fn without_repeats(ring: &[Point2]) -> Vec<Point2> {
+    let mut ring = ring.to_vec();
+    ring.dedup();
+    while ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+    ring
+}
+
+/// Unions a list of mutually overlapping polygons by merging them pairwise.
+fn union_all(mut parts: Vec<Vec<Polygon2>>) -> Vec<Polygon2> {
+    while parts.len() > 1 {
+        let mut merged = Vec::with_capacity(parts.len().div_ceil(2));
+        let mut iter = parts.into_iter();
+        while let Some(a) = iter.next() {
+            match iter.next() {
+                Some(b) => merged.push(boolean(&a, &b, BooleanOp::Union)),
+                None => merged.push(a),
+            }
+        }
+        parts = merged;
+    }
+    parts.pop().unwrap_or_default()
+}
+
+/// Grows (positive `distance`) or shrinks (negative `distance`) polygons with holes.
+///
+/// The band of points within `distance` of the boundary is built from one rectangle per
+/// edge plus a join at every corner that turns away from the growing side. The band is
+/// then added to or subtracted from the input, which removes the self-intersections a
+/// naive edge-by-edge offset produces around concave corners and narrow features.
+pub fn offset(polygons: &[Polygon2], distance: f64, join: JoinStyle) -> Vec<Polygon2> {
+    if let JoinStyle::Round(tolerance) = join {
+        assert!(tolerance > 0.0, "tolerance must be positive");
+    }
+    if distance == 0.0 || polygons.is_empty() {
+        return boolean(polygons, &[], BooleanOp::Union);
+    }
+    let d = distance.abs();
+    let outward = distance > 0.0;
+    let normal = |dir: Vec2<f64>| {
+        if outward {
+            dir.rotate_right_90()
+        } else {
+            dir.rotate_left_90()
+        }
+    };
+
+    let mut band = vec![];
+    for polygon in polygons {
+        let mut polygon = polygon.clone();
+        polygon.normalize_orientation();
+        for ring in polygon.rings() {
+            let ring = without_repeats(ring);
+            let n = ring.len();
+            if n < 2 {
+                continue;
+            }
+            let normals: Vec<Vec2<f64>> = (0..n)
+                .map(|i| normal((ring[(i + 1) % n] - ring[i]).normalize()))
+                .collect();
+            for i in 0..n {
+                let (a, b) = (ring[i], ring[(i + 1) % n]);
+                let offset = normals[i].clone() * d;
+                band.push(vec![Polygon2::new(vec![
+                    a - offset.clone(),
+                    b - offset.clone(),
+                    b + offset.clone(),
+                    a + offset,
+                ])]);
+
+                let incoming = &normals[(i + n - 1) % n];
+                let turn = (ring[i] - ring[(i + n - 1) % n]).perp_dot(&(b - a));
+                if (outward && turn > 0.0) || (!outward && turn < 0.0) {
+                    band.push(vec![Polygon2::new(join_shape(
+                        a,
+                        incoming,
+                        &normals[i],
+                        d,
+                        join,
+                    ))]);
+                }
+            }
+        }
+    }
+
+    let band = union_all(band);
+    let op = if outward {
+        BooleanOp::Union
+    } else {
+        BooleanOp::Difference
+    };
+    boolean(polygons, &band, op)
+}
+
+impl Polygon2 {
+    pub fn offset(&self, distance: f64, join: JoinStyle) -> Vec<Polygon2> {
+        offset(std::slice::from_ref(self), distance, join)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{rect, total_area};
+
+    #[test]
+    fn test_grow_joins() {
+        let square = rect(0.0, 0.0, 2.0, 2.0);
+
+        let miter = square.offset(1.0, JoinStyle::Miter(2.0));
+        assert_eq!(miter.len(), 1);
+        assert_eq!(miter[0].exterior.len(), 4);
+        assert!((miter[0].area() - 16.0).abs() < 1e-9);
+
+        let cut = (2f64.sqrt() - 1.0).powi(2);
+        let square_join = square.offset(1.0, JoinStyle::Square);
+        assert_eq!(square_join[0].exterior.len(), 8);
+        assert!((total_area(&square_join) - (16.0 - 4.0 * cut)).abs() < 1e-9);
+        // A miter limit of 1 squares corners off at the same distance.
+        let limited = square.offset(1.0, JoinStyle::Miter(1.0));
+        assert!((total_area(&limited) - total_area(&square_join)).abs() < 1e-9);
+
+        let round = square.offset(1.0, JoinStyle::Round(1e-3));
+        let exact = 4.0 + 8.0 + std::f64::consts::PI;
+        assert!(total_area(&round) < exact);
+        assert!(total_area(&round) > exact - 0.01);
+    }
+
+    #[test]
+    #[should_panic(expected = "tolerance must be positive")]
+    fn test_round_zero_tolerance() {
+        rect(0.0, 0.0, 2.0, 2.0).offset(0.5, JoinStyle::Round(0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "tolerance must be positive")]
+    fn test_round_negative_tolerance() {
+        rect(0.0, 0.0, 2.0, 2.0).offset(0.5, JoinStyle::Round(-0.1));
+    }
+
+    #[test]
+    fn test_shrink() {
+        let square = rect(0.0, 0.0, 2.0, 2.0);
+        let shrunk = square.offset(-0.5, JoinStyle::Miter(2.0));
+        assert_eq!(shrunk.len(), 1);
+        assert!((shrunk[0].area() - 1.0).abs() < 1e-9);
+        assert!(square.offset(-1.5, JoinStyle::Round(1e-3)).is_empty());
+
+        // The hole grows while the exterior shrinks.
+        let framed = Polygon2::with_holes(
+            rect(0.0, 0.0, 10.0, 10.0).exterior,
+            vec![rect(4.0, 4.0, 6.0, 6.0).exterior],
+        );
+        let shrunk = framed.offset(-1.0, JoinStyle::Miter(2.0));
+        assert_eq!(shrunk.len(), 1);
+        assert_eq!(shrunk[0].holes.len(), 1);
+        assert!((shrunk[0].area() - (64.0 - 16.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_grow_concave() {
+        // An L shape: the reflex corner needs no join when growing.
+        let l_shape = Polygon2::new(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(2.0, 0.0),
+            Point2::new(2.0, 1.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(1.0, 2.0),
+            Point2::new(0.0, 2.0),
+        ]);
+        let grown = l_shape.offset(0.25, JoinStyle::Miter(2.0));
+        assert_eq!(grown.len(), 1);
+        assert_eq!(grown[0].exterior.len(), 6);
+        assert!((grown[0].area() - (2.5 * 2.5 - 1.0 * 1.0)).abs() < 1e-9);
+
+        // Growing closes a narrow notch completely.
+        let notched = Polygon2::new(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(1.1, 1.0),
+            Point2::new(1.1, 0.0),
+            Point2::new(2.0, 0.0),
+            Point2::new(2.0, 2.0),
+            Point2::new(0.0, 2.0),
+        ]);
+        let grown = notched.offset(0.1, JoinStyle::Miter(2.0));
+        assert_eq!(grown.len(), 1);
+        assert!(grown[0].holes.is_empty());
+        assert!((grown[0].area() - 2.2 * 2.2).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,1174 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::fmt;
+
+use crate::bezier::{CubicBezier2, QuadBezier2};
+use crate::curve::CurvePoint;
+use crate::mat::Mat3;
+use crate::point::Point2;
+use crate::vec::Vec2;
+
+/// One drawing command of a path, in absolute coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    MoveTo(Point2),
+    LineTo(Point2),
+    QuadTo(Point2, Point2),
+    CubicTo(Point2, Point2, Point2),
+    /// Elliptical arc to `to` as in SVG: radii `rx` and `ry`, the ellipse's x axis turned by
+    /// `rotation` radians, and flags picking one of the four arcs through both end points.
+    ArcTo {
+        rx: f64,
+        ry: f64,
+        rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Point2,
+    },
+    Close,
+}
+
+/// A flattened subpath. Closed polylines do not repeat their first point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline2 {
+    pub points: Vec<Point2>,
+    pub closed: bool,
+}
+
+/// A sequence of subpaths, each begun by a move and optionally closed. Drawing commands
+/// before the first move start from the origin.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path2 {
+    commands: Vec<PathCommand>,
+}
+
+/// Why SVG path data failed to parse, and the byte offset where it did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePathError {
+    pub position: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for ParsePathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParsePathError {}
+
+/// How the open ends of a stroke are finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineCap {
+    /// Square ends flush with the end points.
+    Butt,
+    /// Half discs around the end points.
+    Round,
+    /// Square ends extended by half the width past the end points.
+    Square,
+}
+
+/// How the outside of a stroke's corners is filled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Sharp corners, beveled once they would pass the miter limit.
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f64,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    /// Longest miter, as a multiple of the width, before a miter join becomes a bevel.
+    pub miter_limit: f64,
+    /// Alternating dash and gap lengths, repeated twice over when odd in number as in SVG.
+    /// Empty for a solid stroke.
+    pub dashes: Vec<f64>,
+    /// Distance into the dash pattern at which every subpath starts.
+    pub dash_offset: f64,
+}
+
+impl StrokeStyle {
+    /// A solid stroke with butt caps and miter joins limited at 4, the SVG defaults.
+    pub fn new(width: f64) -> StrokeStyle {
+        StrokeStyle {
+            width,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+            dashes: vec![],
+            dash_offset: 0.0,
+        }
+    }
+}
+
+/// Cubic pieces of the SVG elliptical arc from `from` to `to`, each turning at most a quarter
+/// of the way round. Radii too small to span the end points are scaled up as SVG specifies,
+/// a zero radius gives a straight line, and coinciding end points give no arc at all.
+pub fn arc_to_cubics(
+    from: Point2,
+    rx: f64,
+    ry: f64,
+    rotation: f64,
+    large_arc: bool,
+    sweep: bool,
+    to: Point2,
+) -> Vec<CubicBezier2> {
+    arc_cubics(
+        from,
+        (rx, ry),
+        rotation,
+        large_arc,
+        sweep,
+        to,
+        f64::INFINITY,
+    )
+}
+
+/// `arc_to_cubics` with pieces short enough to stay within `tolerance` of the ellipse.
+fn arc_cubics(
+    from: Point2,
+    (rx, ry): (f64, f64),
+    rotation: f64,
+    large_arc: bool,
+    sweep: bool,
+    to: Point2,
+    tolerance: f64,
+) -> Vec<CubicBezier2> {
+    if from == to {
+        return vec![];
+    }
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0.0 || ry == 0.0 {
+        return vec![CubicBezier2::new(
+            from,
+            from.lerp(&to, 1.0 / 3.0),
+            from.lerp(&to, 2.0 / 3.0),
+            to,
+        )];
+    }
+    // The end points relative to their midpoint, in the frame of the ellipse's axes.
+    let (sin, cos) = rotation.sin_cos();
+    let half = (from - to) * 0.5;
+    let x1 = cos * half[0] + sin * half[1];
+    let y1 = -sin * half[0] + cos * half[1];
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let (rx2, ry2) = (rx * rx, ry * ry);
+    let den = rx2 * y1 * y1 + ry2 * x1 * x1;
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let coef = sign * ((rx2 * ry2 - den) / den).max(0.0).sqrt();
+    let (cx, cy) = (coef * rx * y1 / ry, -coef * ry * x1 / rx);
+    let mid = from.lerp(&to, 0.5);
+    let center = mid + vec2![cos * cx - sin * cy, sin * cx + cos * cy];
+
+    let start = (y1 - cy) / ry;
+    let start = start.atan2((x1 - cx) / rx);
+    let u = vec2![(x1 - cx) / rx, (y1 - cy) / ry];
+    let v = vec2![(-x1 - cx) / rx, (-y1 - cy) / ry];
+    let mut delta = u.perp_dot(&v).atan2(u.dot(&v));
+    if !sweep && delta > 0.0 {
+        delta -= 2.0 * PI;
+    } else if sweep && delta < 0.0 {
+        delta += 2.0 * PI;
+    }
+
+    // Points and tangents on the unit circle, mapped onto the ellipse.
+    let map =
+        |x: f64, y: f64| center + vec2![cos * rx * x - sin * ry * y, sin * rx * x + cos * ry * y];
+    // A quarter turn strays up to 2.7e-4 times the radius, shrinking with the sixth power
+    // of the angle.
+    let max_angle = FRAC_PI_2 * (tolerance / (2.7e-4 * rx.max(ry))).powf(1.0 / 6.0).min(1.0);
+    let count = (delta.abs() / max_angle - 1e-9).ceil().max(1.0) as usize;
+    let step = delta / count as f64;
+    let k = 4.0 / 3.0 * (step / 4.0).tan();
+    let mut cubics: Vec<CubicBezier2> = (0..count)
+        .map(|i| {
+            let (s0, c0) = (start + step * i as f64).sin_cos();
+            let (s1, c1) = (start + step * (i + 1) as f64).sin_cos();
+            CubicBezier2::new(
+                map(c0, s0),
+                map(c0 - k * s0, s0 + k * c0),
+                map(c1 + k * s1, s1 - k * c1),
+                map(c1, s1),
+            )
+        })
+        .collect();
+    cubics[0].p0 = from;
+    cubics[count - 1].p3 = to;
+    cubics
+}
+
+impl Path2 {
+    pub fn new() -> Path2 {
+        Path2::default()
+    }
+
+    pub fn from_commands(commands: Vec<PathCommand>) -> Path2 {
+        Path2 { commands }
+    }
+
+    pub fn commands(&self) -> &[PathCommand] {
+        &self.commands
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn move_to(&mut self, p: Point2) {
+        self.commands.push(PathCommand::MoveTo(p));
+    }
+
+    pub fn line_to(&mut self, p: Point2) {
+        self.commands.push(PathCommand::LineTo(p));
+    }
+
+    pub fn quad_to(&mut self, c: Point2, p: Point2) {
+        self.commands.push(PathCommand::QuadTo(c, p));
+    }
+
+    pub fn cubic_to(&mut self, c1: Point2, c2: Point2, p: Point2) {
+        self.commands.push(PathCommand::CubicTo(c1, c2, p));
+    }
+
+    pub fn arc_to(
+        &mut self,
+        rx: f64,
+        ry: f64,
+        rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Point2,
+    ) {
+        self.commands.push(PathCommand::ArcTo {
+            rx,
+            ry,
+            rotation,
+            large_arc,
+            sweep,
+            to,
+        });
+    }
+
+    pub fn close(&mut self) {
+        self.commands.push(PathCommand::Close);
+    }
+
+    /// Parses the `d` attribute of an SVG `path` element. Relative, horizontal, vertical and
+    /// smooth commands become their absolute general forms.
+    pub fn parse(d: &str) -> Result<Path2, ParsePathError> {
+        Parser {
+            bytes: d.as_bytes(),
+            pos: 0,
+        }
+        .parse()
+    }
+
+    /// SVG path data with one absolute command per path command, arc rotations in degrees.
+    pub fn to_svg(&self) -> String {
+        let parts: Vec<String> = self
+            .commands
+            .iter()
+            .map(|command| match *command {
+                PathCommand::MoveTo(p) => format!("M {} {}", p[0], p[1]),
+                PathCommand::LineTo(p) => format!("L {} {}", p[0], p[1]),
+                PathCommand::QuadTo(c, p) => format!("Q {} {} {} {}", c[0], c[1], p[0], p[1]),
+                PathCommand::CubicTo(c1, c2, p) => format!(
+                    "C {} {} {} {} {} {}",
+                    c1[0], c1[1], c2[0], c2[1], p[0], p[1]
+                ),
+                PathCommand::ArcTo {
+                    rx,
+                    ry,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                } => format!(
+                    "A {} {} {} {} {} {} {}",
+                    rx,
+                    ry,
+                    rotation.to_degrees(),
+                    large_arc as u8,
+                    sweep as u8,
+                    to[0],
+                    to[1]
+                ),
+                PathCommand::Close => "Z".to_string(),
+            })
+            .collect();
+        parts.join(" ")
+    }
+
+    /// The path with every arc replaced by its cubic pieces.
+    pub fn arcs_to_cubics(&self) -> Path2 {
+        let mut path = Path2::new();
+        let (mut current, mut start) = (Point2::default(), Point2::default());
+        for command in &self.commands {
+            match *command {
+                PathCommand::ArcTo {
+                    rx,
+                    ry,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                } => {
+                    for c in arc_to_cubics(current, rx, ry, rotation, large_arc, sweep, to) {
+                        path.cubic_to(c.p1, c.p2, c.p3);
+                    }
+                }
+                command => path.commands.push(command),
+            }
+            (current, start) = advance(command, start);
+        }
+        path
+    }
+
+    /// The path under the affine transform `m`. Arcs become cubics first, since the image of
+    /// an arc is generally an arc of another ellipse.
+    pub fn transform(&self, m: &Mat3<f64>) -> Path2 {
+        let t = |p: Point2| m.transform_point(&p);
+        let commands = self
+            .arcs_to_cubics()
+            .commands
+            .into_iter()
+            .map(|command| match command {
+                PathCommand::MoveTo(p) => PathCommand::MoveTo(t(p)),
+                PathCommand::LineTo(p) => PathCommand::LineTo(t(p)),
+                PathCommand::QuadTo(c, p) => PathCommand::QuadTo(t(c), t(p)),
+                PathCommand::CubicTo(c1, c2, p) => PathCommand::CubicTo(t(c1), t(c2), t(p)),
+                command => command,
+            })
+            .collect();
+        Path2 { commands }
+    }
+
+    /// Polylines within `tolerance` of each subpath. Subpaths of a lone move draw nothing
+    /// and are left out.
+    pub fn flatten(&self, tolerance: f64) -> Vec<Polyline2> {
+        let mut polylines = vec![];
+        let mut points: Vec<Point2> = vec![];
+        let mut drawn = false;
+        let (mut current, mut start) = (Point2::default(), Point2::default());
+        for command in &self.commands {
+            if !matches!(command, PathCommand::MoveTo(_)) && points.is_empty() {
+                points.push(current);
+            }
+            match *command {
+                PathCommand::MoveTo(p) => {
+                    if drawn {
+                        polylines.push(Polyline2 {
+                            points: std::mem::take(&mut points),
+                            closed: false,
+                        });
+                    }
+                    points = vec![p];
+                    drawn = false;
+                }
+                PathCommand::LineTo(p) => points.push(p),
+                PathCommand::QuadTo(c, p) => {
+                    QuadBezier2::new(current, c, p).flatten_into(tolerance, &mut points)
+                }
+                PathCommand::CubicTo(c1, c2, p) => {
+                    CubicBezier2::new(current, c1, c2, p).flatten_into(tolerance, &mut points)
+                }
+                PathCommand::ArcTo {
+                    rx,
+                    ry,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                } => {
+                    let half = tolerance / 2.0;
+                    for c in arc_cubics(current, (rx, ry), rotation, large_arc, sweep, to, half) {
+                        c.flatten_into(half, &mut points);
+                    }
+                }
+                PathCommand::Close => {
+                    if points.len() > 1 && points.last() == points.first() {
+                        points.pop();
+                    }
+                    polylines.push(Polyline2 {
+                        points: std::mem::take(&mut points),
+                        closed: true,
+                    });
+                    drawn = false;
+                }
+            }
+            drawn |= !matches!(command, PathCommand::MoveTo(_) | PathCommand::Close);
+            (current, start) = advance(command, start);
+        }
+        if drawn {
+            polylines.push(Polyline2 {
+                points,
+                closed: false,
+            });
+        }
+        polylines
+    }
+
+    /// Rings whose union under the non-zero fill rule is the stroke of the path, with curves
+    /// and round caps and joins within `tolerance`. The rings overlap each other and
+    /// themselves wherever the stroke does.
+    pub fn stroke(&self, style: &StrokeStyle, tolerance: f64) -> Vec<Vec<Point2>> {
+        assert!(tolerance > 0.0, "tolerance must be positive");
+        let half = style.width / 2.0;
+        if half <= 0.0 {
+            return vec![];
+        }
+        let stroker = Stroker {
+            half,
+            style,
+            tolerance,
+        };
+        let dashes = dash_pattern(&style.dashes);
+        let mut rings = vec![];
+        for polyline in self.flatten(tolerance) {
+            let mut points = polyline.points;
+            points.dedup();
+            if polyline.closed && points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+            match &dashes {
+                Some(pattern) => {
+                    for dash in dash(&points, polyline.closed, pattern, style.dash_offset) {
+                        stroker.open(&dash, &mut rings);
+                    }
+                }
+                None if polyline.closed && points.len() > 1 => stroker.closed(&points, &mut rings),
+                None => stroker.open(&points, &mut rings),
+            }
+        }
+        rings
+    }
+}
+
+/// The current point and subpath start after `command`.
+fn advance(command: &PathCommand, start: Point2) -> (Point2, Point2) {
+    match *command {
+        PathCommand::MoveTo(p) => (p, p),
+        PathCommand::LineTo(p) | PathCommand::QuadTo(_, p) | PathCommand::CubicTo(_, _, p) => {
+            (p, start)
+        }
+        PathCommand::ArcTo { to, .. } => (to, start),
+        PathCommand::Close => (start, start),
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> ParsePathError {
+        ParsePathError {
+            position: self.pos,
+            message,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while self.pos < self.bytes.len()
+            && (self.bytes[self.pos].is_ascii_whitespace() || self.bytes[self.pos] == b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    /// Whether an argument follows rather than a command or the end.
+    fn at_number(&mut self) -> bool {
+        self.skip_separators();
+        self.pos < self.bytes.len()
+            && matches!(self.bytes[self.pos], b'0'..=b'9' | b'.' | b'-' | b'+')
+    }
+
+    fn number(&mut self) -> Result<f64, ParsePathError> {
+        self.skip_separators();
+        let start = self.pos;
+        let digits = |parser: &mut Self| {
+            let from = parser.pos;
+            while parser.pos < parser.bytes.len() && parser.bytes[parser.pos].is_ascii_digit() {
+                parser.pos += 1;
+            }
+            parser.pos > from
+        };
+        if self.pos < self.bytes.len() && matches!(self.bytes[self.pos], b'-' | b'+') {
+            self.pos += 1;
+        }
+        let mut any = digits(self);
+        if self.pos < self.bytes.len() && self.bytes[self.pos] == b'.' {
+            self.pos += 1;
+            any |= digits(self);
+        }
+        if !any {
+            self.pos = start;
+            return Err(self.error("expected a number"));
+        }
+        if self.pos < self.bytes.len() && matches!(self.bytes[self.pos], b'e' | b'E') {
+            let mark = self.pos;
+            self.pos += 1;
+            if self.pos < self.bytes.len() && matches!(self.bytes[self.pos], b'-' | b'+') {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                self.pos = mark;
+            }
+        }
+        // Only ASCII digits, signs, points and exponents were consumed.
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap();
+        text.parse().map_err(|_| ParsePathError {
+            position: start,
+            message: "invalid number",
+        })
+    }
+
+    /// Arc flags are single digits and need no separator after them.
+    fn flag(&mut self) -> Result<bool, ParsePathError> {
+        self.skip_separators();
+        let flag = match self.bytes.get(self.pos) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(self.error("expected a flag")),
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+
+    fn point(&mut self, relative: bool, current: Point2) -> Result<Point2, ParsePathError> {
+        let (x, y) = (self.number()?, self.number()?);
+        Ok(if relative {
+            current + vec2![x, y]
+        } else {
+            Point2::new(x, y)
+        })
+    }
+
+    fn parse(mut self) -> Result<Path2, ParsePathError> {
+        let mut path = Path2::new();
+        let (mut current, mut start) = (Point2::default(), Point2::default());
+        // The control point a smooth curve reflects, from the previous command if it was a
+        // cubic (`true`) or a quadratic (`false`).
+        let mut last_control: Option<(bool, Point2)> = None;
+        self.skip_separators();
+        while self.pos < self.bytes.len() {
+            let letter = self.bytes[self.pos];
+            if !letter.is_ascii_alphabetic() {
+                return Err(self.error("expected a command"));
+            }
+            if path.is_empty() && !matches!(letter, b'M' | b'm') {
+                return Err(self.error("path data must begin with a move"));
+            }
+            self.pos += 1;
+            let relative = letter.is_ascii_lowercase();
+            let mut first = true;
+            loop {
+                let command = match letter.to_ascii_uppercase() {
+                    b'M' if first => PathCommand::MoveTo(self.point(relative, current)?),
+                    b'M' | b'L' => PathCommand::LineTo(self.point(relative, current)?),
+                    b'H' => {
+                        let x = self.number()?;
+                        let x = if relative { current[0] + x } else { x };
+                        PathCommand::LineTo(Point2::new(x, current[1]))
+                    }
+                    b'V' => {
+                        let y = self.number()?;
+                        let y = if relative { current[1] + y } else { y };
+                        PathCommand::LineTo(Point2::new(current[0], y))
+                    }
+                    b'C' => PathCommand::CubicTo(
+                        self.point(relative, current)?,
+                        self.point(relative, current)?,
+                        self.point(relative, current)?,
+                    ),
+                    b'S' => {
+                        let c1 = match last_control {
+                            Some((true, c)) => current + (current - c),
+                            _ => current,
+                        };
+                        let c2 = self.point(relative, current)?;
+                        PathCommand::CubicTo(c1, c2, self.point(relative, current)?)
+                    }
+                    b'Q' => PathCommand::QuadTo(
+                        self.point(relative, current)?,
+                        self.point(relative, current)?,
+                    ),
+                    b'T' => {
+                        let c = match last_control {
+                            Some((false, c)) => current + (current - c),
+                            _ => current,
+                        };
+                        PathCommand::QuadTo(c, self.point(relative, current)?)
+                    }
+                    b'A' => PathCommand::ArcTo {
+                        rx: self.number()?,
+                        ry: self.number()?,
+                        rotation: self.number()?.to_radians(),
+                        large_arc: self.flag()?,
+                        sweep: self.flag()?,
+                        to: self.point(relative, current)?,
+                    },
+                    b'Z' => PathCommand::Close,
+                    _ => {
+                        self.pos -= 1;
+                        return Err(self.error("unknown command"));
+                    }
+                };
+                last_control = match command {
+                    PathCommand::CubicTo(_, c2, _) => Some((true, c2)),
+                    PathCommand::QuadTo(c, _) => Some((false, c)),
+                    _ => None,
+                };
+                path.commands.push(command);
+                (current, start) = advance(&command, start);
+                first = false;
+                if matches!(command, PathCommand::Close) || !self.at_number() {
+                    break;
+                }
+            }
+            self.skip_separators();
+        }
+        Ok(path)
+    }
+}
+
+/// The dash pattern with an even number of entries, or `None` for a solid stroke.
+fn dash_pattern(dashes: &[f64]) -> Option<Vec<f64>> {
+    let total: f64 = dashes.iter().sum();
+    if dashes.is_empty() || dashes.iter().any(|&d| d < 0.0) || total <= 0.0 {
+        return None;
+    }
+    let mut pattern = dashes.to_vec();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_slice(dashes);
+    }
+    Some(pattern)
+}
+
+/// The dashes along `points` as open polylines. On a closed polyline a dash running over
+/// the start joins up with the one leaving it.
+fn dash(points: &[Point2], closed: bool, pattern: &[f64], offset: f64) -> Vec<Vec<Point2>> {
+    let Some(&first) = points.first() else {
+        return vec![];
+    };
+    let total: f64 = pattern.iter().sum();
+    let mut phase = offset.rem_euclid(total);
+    let mut index = 0;
+    while phase >= pattern[index] {
+        phase -= pattern[index];
+        index = (index + 1) % pattern.len();
+    }
+    let mut left = pattern[index] - phase;
+    let starts_on = index % 2 == 0;
+    let mut on = starts_on;
+
+    let mut dashes = vec![];
+    let mut current = if on { vec![first] } else { vec![] };
+    let closing = closed.then(|| [points[points.len() - 1], first]);
+    let segments = points
+        .windows(2)
+        .map(|w| [w[0], w[1]])
+        .chain(closing.filter(|_| points.len() > 1));
+    for [a, b] in segments {
+        let length = (b - a).length();
+        let mut pos = 0.0;
+        while length - pos > left {
+            pos += left;
+            let p = a.lerp(&b, pos / length);
+            if on {
+                current.push(p);
+                dashes.push(std::mem::take(&mut current));
+            } else {
+                current = vec![p];
+            }
+            on = !on;
+            index = (index + 1) % pattern.len();
+            left = pattern[index];
+        }
+        left -= length - pos;
+        if on {
+            current.push(b);
+        }
+    }
+    if on {
+        if closed && starts_on && !dashes.is_empty() {
+            let head = dashes.remove(0);
+            current.extend(head.into_iter().skip(1));
+        }
+        dashes.push(current);
+    }
+    dashes
+}
+
+struct Stroker<'a> {
+    half: f64,
+    style: &'a StrokeStyle,
+    tolerance: f64,
+}
+
+impl Stroker<'_> {
+    /// One ring: the left side forward, the end cap, the right side back and the start cap.
+    /// A polyline of a single point is a dot shaped by the cap.
+    fn open(&self, points: &[Point2], rings: &mut Vec<Vec<Point2>>) {
+        let mut points = points.to_vec();
+        points.dedup();
+        let Some(&first) = points.first() else {
+            return;
+        };
+        let h = self.half;
+        if points.len() == 1 {
+            match self.style.cap {
+                LineCap::Butt => {}
+                LineCap::Square => rings.push(vec![
+                    first + vec2![-h, -h],
+                    first + vec2![-h, h],
+                    first + vec2![h, h],
+                    first + vec2![h, -h],
+                ]),
+                LineCap::Round => {
+                    let mut ring = vec![first + vec2![0.0, h]];
+                    self.arc(&mut ring, first, vec2![0.0, 1.0], -2.0 * PI);
+                    rings.push(ring);
+                }
+            }
+            return;
+        }
+        let n = points.len();
+        let mut ring = vec![];
+        self.side(&points, false, &mut ring);
+        self.cap(
+            &mut ring,
+            points[n - 1],
+            (points[n - 1] - points[n - 2]).normalize(),
+        );
+        points.reverse();
+        self.side(&points, false, &mut ring);
+        self.cap(
+            &mut ring,
+            points[n - 1],
+            (points[n - 1] - points[n - 2]).normalize(),
+        );
+        rings.push(ring);
+    }
+
+    /// Two rings, one along either side of the polyline.
+    fn closed(&self, points: &[Point2], rings: &mut Vec<Vec<Point2>>) {
+        let mut ring = vec![];
+        self.side(points, true, &mut ring);
+        rings.push(ring);
+        let reversed: Vec<Point2> = points.iter().rev().copied().collect();
+        let mut ring = vec![];
+        self.side(&reversed, true, &mut ring);
+        rings.push(ring);
+    }
+
+    /// Appends the left side of the polyline offset by half the width. Where the side lies
+    /// inside a corner it runs through the vertex itself, which keeps the fill of the
+    /// overlapping offsets to the union of the segments' rectangles; outside a corner it
+    /// gets the join.
+    fn side(&self, points: &[Point2], closed: bool, ring: &mut Vec<Point2>) {
+        let n = points.len();
+        let direction = |i: usize| (points[(i + 1) % n] - points[i]).normalize();
+        let h = self.half;
+        if !closed {
+            ring.push(points[0] + direction(0).rotate_left_90() * h);
+        }
+        let corners = if closed { 0..n } else { 1..n - 1 };
+        for i in corners {
+            let (d0, d1) = (direction((i + n - 1) % n), direction(i));
+            let (n0, n1) = (d0.rotate_left_90(), d1.rotate_left_90());
+            let v = points[i];
+            ring.push(v + n0.clone() * h);
+            let cross = d0.perp_dot(&d1);
+            if cross > 0.0 || (cross == 0.0 && d0.dot(&d1) > 0.0) {
+                ring.push(v);
+            } else {
+                self.join(ring, v, &n0, &n1);
+            }
+            ring.push(v + n1 * h);
+        }
+        if !closed {
+            ring.push(points[n - 1] + direction(n - 2).rotate_left_90() * h);
+        }
+    }
+
+    /// Appends the join between the offsets along unit normals `n0` and `n1` at the outside
+    /// of a right turn at `v`.
+    fn join(&self, ring: &mut Vec<Point2>, v: Point2, n0: &Vec2<f64>, n1: &Vec2<f64>) {
+        let cos = n0.dot(n1);
+        match self.style.join {
+            // The miter is `1 / sqrt((1 + cos) / 2)` times the width for normals at
+            // angle `acos(cos)`.
+            LineJoin::Miter
+                if 1.0 + cos >= 2.0 / (self.style.miter_limit * self.style.miter_limit) =>
+            {
+                ring.push(v + (n0.clone() + n1.clone()) * (self.half / (1.0 + cos)));
+            }
+            LineJoin::Miter | LineJoin::Bevel => {}
+            LineJoin::Round => {
+                let mut angle = n0.perp_dot(n1).atan2(cos);
+                // A full reversal sweeps round the front of the corner.
+                if angle > 0.0 {
+                    angle -= 2.0 * PI;
+                }
+                self.arc(ring, v, n0.clone(), angle);
+            }
+        }
+    }
+
+    /// Appends the cap at the end `v` of a polyline leaving along unit `d`, between the left
+    /// and right offsets.
+    fn cap(&self, ring: &mut Vec<Point2>, v: Point2, d: Vec2<f64>) {
+        let n = d.rotate_left_90();
+        let h = self.half;
+        match self.style.cap {
+            LineCap::Butt => {}
+            LineCap::Square => {
+                ring.push(v + (n.clone() + d.clone()) * h);
+                ring.push(v + (d - n) * h);
+            }
+            LineCap::Round => self.arc(ring, v, n, -PI),
+        }
+    }
+
+    /// Appends the inner points of the arc of radius half the width around `center`,
+    /// turning `angle` radians from the unit direction `from`.
+    fn arc(&self, ring: &mut Vec<Point2>, center: Point2, from: Vec2<f64>, angle: f64) {
+        let h = self.half;
+        let step = if self.tolerance < h {
+            2.0 * (1.0 - self.tolerance / h).acos()
+        } else {
+            FRAC_PI_2
+        };
+        let steps = (angle.abs() / step).ceil().max(1.0) as usize;
+        for k in 1..steps {
+            let (sin, cos) = (angle * k as f64 / steps as f64).sin_cos();
+            let dir = vec2![from[0] * cos - from[1] * sin, from[0] * sin + from[1] * cos];
+            ring.push(center + dir * h);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::Curve;
+    use crate::polygon::ring_winding_number;
+    use crate::segment::Segment2;
+    use crate::test_util::{assert_close, random_values};
+
+    fn assert_same_path(a: &Path2, b: &Path2) {
+        assert_eq!(a.commands().len(), b.commands().len());
+        let points = |c: &PathCommand| match *c {
+            PathCommand::MoveTo(p) | PathCommand::LineTo(p) => vec![p],
+            PathCommand::QuadTo(c, p) => vec![c, p],
+            PathCommand::CubicTo(c1, c2, p) => vec![c1, c2, p],
+            PathCommand::ArcTo {
+                rx,
+                ry,
+                rotation,
+                to,
+                ..
+            } => vec![Point2::new(rx, ry), Point2::new(rotation, 0.0), to],
+            PathCommand::Close => vec![],
+        };
+        for (ca, cb) in a.commands().iter().zip(b.commands()) {
+            assert_eq!(std::mem::discriminant(ca), std::mem::discriminant(cb));
+            for (pa, pb) in points(ca).into_iter().zip(points(cb)) {
+                assert!((pa - pb).length() < 1e-12, "{ca:?} != {cb:?}");
+            }
+        }
+    }
+
+    fn inside(rings: &[Vec<Point2>], p: &Point2) -> bool {
+        rings
+            .iter()
+            .map(|ring| ring_winding_number(ring, p))
+            .sum::<i32>()
+            != 0
+    }
+
+    fn distance_to_polyline(points: &[Point2], closed: bool, p: &Point2) -> f64 {
+        let mut segments: Vec<Segment2> = points
+            .windows(2)
+            .map(|w| Segment2::new(w[0], w[1]))
+            .collect();
+        if closed {
+            segments.push(Segment2::new(points[points.len() - 1], points[0]));
+        }
+        segments
+            .iter()
+            .map(|s| s.distance(p))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn test_parse_and_serialize() {
+        let path = Path2::parse(
+            "m10-20l.5.5 1e1,0 h-5v5 c1 1 2 2 3 3s4 4 5 5 q1 0 1 1t1 1 a5 3 30 1 0 2 2z L1 1",
+        )
+        .unwrap();
+        let expected = Path2::from_commands(vec![
+            PathCommand::MoveTo(Point2::new(10.0, -20.0)),
+            PathCommand::LineTo(Point2::new(10.5, -19.5)),
+            PathCommand::LineTo(Point2::new(20.5, -19.5)),
+            PathCommand::LineTo(Point2::new(15.5, -19.5)),
+            PathCommand::LineTo(Point2::new(15.5, -14.5)),
+            PathCommand::CubicTo(
+                Point2::new(16.5, -13.5),
+                Point2::new(17.5, -12.5),
+                Point2::new(18.5, -11.5),
+            ),
+            // The first control point reflects the previous second one.
+            PathCommand::CubicTo(
+                Point2::new(19.5, -10.5),
+                Point2::new(22.5, -7.5),
+                Point2::new(23.5, -6.5),
+            ),
+            PathCommand::QuadTo(Point2::new(24.5, -6.5), Point2::new(24.5, -5.5)),
+            PathCommand::QuadTo(Point2::new(24.5, -4.5), Point2::new(25.5, -4.5)),
+            PathCommand::ArcTo {
+                rx: 5.0,
+                ry: 3.0,
+                rotation: 30f64.to_radians(),
+                large_arc: true,
+                sweep: false,
+                to: Point2::new(27.5, -2.5),
+            },
+            PathCommand::Close,
+            // After a close, drawing resumes from the start of the subpath.
+            PathCommand::LineTo(Point2::new(1.0, 1.0)),
+        ]);
+        assert_same_path(&path, &expected);
+        assert_same_path(&Path2::parse(&path.to_svg()).unwrap(), &path);
+        // Extra pairs after a move are lines; flags need no separators.
+        let path = Path2::parse("M0 0 1 1 2 0A1 1 0 015 0").unwrap();
+        assert_eq!(
+            path.commands()[2],
+            PathCommand::LineTo(Point2::new(2.0, 0.0))
+        );
+        assert!(matches!(
+            path.commands()[3],
+            PathCommand::ArcTo {
+                large_arc: false,
+                sweep: true,
+                ..
+            }
+        ));
+        assert_eq!(Path2::parse("  ").unwrap(), Path2::new());
+
+        let error = |d: &str| Path2::parse(d).unwrap_err();
+        assert_eq!(error("L 1 2").message, "path data must begin with a move");
+        assert_eq!(error("M 1").position, 3);
+        assert_eq!(error("M 1 2 X 3").message, "unknown command");
+        assert_eq!(error("M 1 2 A 1 1 0 2 0 3 3").message, "expected a flag");
+        assert_eq!(error("M 1 2 Z 3").message, "expected a command");
+    }
+
+    #[test]
+    fn test_arc_to_cubics() {
+        let on_ellipse =
+            |cubics: &[CubicBezier2], center: Point2, rx: f64, ry: f64, rotation: f64| {
+                let (sin, cos) = rotation.sin_cos();
+                for c in cubics {
+                    for i in 0..=8 {
+                        let d = c.at(i as f64 / 8.0) - center;
+                        let (x, y) = (cos * d[0] + sin * d[1], -sin * d[0] + cos * d[1]);
+                        assert_close((x / rx).hypot(y / ry), 1.0, 1e-3);
+                    }
+                }
+            };
+        let (a, b) = (Point2::new(0.0, 0.0), Point2::new(10.0, 0.0));
+        // Radii too small to reach are scaled up to a half circle about the midpoint.
+        let cubics = arc_to_cubics(a, 1.0, 1.0, 0.0, false, true, b);
+        assert_eq!(cubics.len(), 2);
+        on_ellipse(&cubics, Point2::new(5.0, 0.0), 5.0, 5.0, 0.0);
+        assert_eq!((cubics[0].p0, cubics[1].p3), (a, b));
+        // Sweeping towards positive angles from `a` passes below the chord in y-up axes.
+        assert!(cubics[0].p3[1] < 0.0);
+        let flipped = arc_to_cubics(a, 1.0, 1.0, 0.0, false, false, b);
+        assert!(flipped[0].p3[1] > 0.0);
+
+        // The four arcs between two points on circles of radius 10.
+        let h = (100.0f64 - 25.0).sqrt();
+        for (large_arc, sweep, center, count) in [
+            (false, false, Point2::new(5.0, -h), 1),
+            (false, true, Point2::new(5.0, h), 1),
+            (true, false, Point2::new(5.0, h), 4),
+            (true, true, Point2::new(5.0, -h), 4),
+        ] {
+            let cubics = arc_to_cubics(a, 10.0, 10.0, 0.0, large_arc, sweep, b);
+            assert_eq!(cubics.len(), count);
+            on_ellipse(&cubics, center, 10.0, 10.0, 0.0);
+        }
+
+        // A rotated ellipse about the origin, from the end of one axis to the end of the other.
+        let rotation = 0.6f64;
+        let (sin, cos) = rotation.sin_cos();
+        let from = Point2::new(8.0 * cos, 8.0 * sin);
+        let to = Point2::new(-4.0 * sin, 4.0 * cos);
+        let cubics = arc_to_cubics(from, 8.0, 4.0, rotation, true, false, to);
+        assert_eq!(cubics.len(), 3);
+        on_ellipse(&cubics, Point2::new(0.0, 0.0), 8.0, 4.0, rotation);
+        for pair in cubics.windows(2) {
+            assert_eq!(pair[0].p3, pair[1].p0);
+            let (d0, d1) = (pair[0].derivative(1.0), pair[1].derivative(0.0));
+            assert!(d0.normalize().perp_dot(&d1.normalize()).abs() < 1e-9);
+        }
+
+        assert!(arc_to_cubics(a, 1.0, 1.0, 0.0, false, true, a).is_empty());
+        let line = arc_to_cubics(a, 0.0, 1.0, 0.0, false, true, b);
+        assert_eq!(line.len(), 1);
+        assert_close(line[0].at(0.5)[1], 0.0, 1e-12);
+    }
+
+    #[test]
+    fn test_flatten_and_transform() {
+        let path =
+            Path2::parse("M0 0 Q 5 10 10 0 L 10 -5 Z M 20 0 A 5 5 0 1 1 30 0 M 40 40").unwrap();
+        let polylines = path.flatten(1e-3);
+        assert_eq!(polylines.len(), 2);
+        assert!(polylines[0].closed && !polylines[1].closed);
+        assert_eq!(polylines[0].points[0], Point2::new(0.0, 0.0));
+        assert_ne!(polylines[0].points.last(), polylines[0].points.first());
+        let quad = QuadBezier2::new(
+            Point2::new(0.0, 0.0),
+            Point2::new(5.0, 10.0),
+            Point2::new(10.0, 0.0),
+        );
+        for p in &polylines[0].points {
+            if p[1] >= 0.0 {
+                assert!((quad.closest_point(p) - *p).length() < 1e-9);
+            }
+        }
+        for p in &polylines[1].points {
+            assert!(((*p - Point2::new(25.0, 0.0)).length() - 5.0).abs() < 1e-3);
+        }
+
+        // Scale x by 2, y by 3 and translate: the circle becomes an axis-aligned ellipse.
+        let m = Mat3::new([[2.0, 0.0, 1.0], [0.0, 3.0, -1.0], [0.0, 0.0, 1.0]]);
+        let transformed = path.transform(&m);
+        assert!(transformed
+            .commands()
+            .iter()
+            .all(|c| !matches!(c, PathCommand::ArcTo { .. })));
+        let center = m.transform_point(&Point2::new(25.0, 0.0));
+        for p in &transformed.flatten(1e-3)[1].points {
+            let d = *p - center;
+            assert_close((d[0] / 10.0).hypot(d[1] / 15.0), 1.0, 1e-3);
+        }
+        let start = transformed.flatten(1e-3)[0].points[0];
+        assert_eq!(start, Point2::new(1.0, -1.0));
+    }
+
+    #[test]
+    fn test_stroke_round() {
+        // With round caps and joins the stroke is exactly the points within half the width.
+        let values = random_values(2000, 3);
+        let path =
+            Path2::parse("M 0 0 L 10 0 L 4 1 L 8 8 Q 0 12 -4 4 M 20 0 L 30 0 L 25 8 Z").unwrap();
+        let mut style = StrokeStyle::new(2.0);
+        style.cap = LineCap::Round;
+        style.join = LineJoin::Round;
+        let tolerance = 1e-3;
+        let rings = path.stroke(&style, tolerance);
+        let polylines = path.flatten(tolerance);
+        for v in values.chunks(2) {
+            let p = Point2::new(v[0] * 40.0 - 8.0, v[1] * 20.0 - 4.0);
+            let distance = polylines
+                .iter()
+                .map(|pl| distance_to_polyline(&pl.points, pl.closed, &p))
+                .fold(f64::INFINITY, f64::min);
+            if (distance - 1.0).abs() > 2.0 * tolerance {
+                assert_eq!(inside(&rings, &p), distance < 1.0, "{p:?} at {distance}");
+            }
+        }
+        // A lone closed point with round caps is a dot.
+        let dot = Path2::parse("M 5 5 Z").unwrap().stroke(&style, tolerance);
+        assert!(inside(&dot, &Point2::new(5.5, 5.5)));
+        assert!(!inside(&dot, &Point2::new(5.8, 5.8)));
+    }
+
+    #[test]
+    fn test_stroke_caps_and_joins() {
+        let path = Path2::parse("M 0 0 L 10 0 L 10 10").unwrap();
+        let mut style = StrokeStyle::new(2.0);
+        let rings = path.stroke(&style, 1e-3);
+        // The miter fills the outer corner; butt caps stop at the ends.
+        assert!(inside(&rings, &Point2::new(10.9, -0.9)));
+        assert!(inside(&rings, &Point2::new(5.0, 0.9)));
+        assert!(!inside(&rings, &Point2::new(-0.1, 0.0)));
+        assert!(!inside(&rings, &Point2::new(10.0, 10.1)));
+        // The right angle's miter is sqrt(2) times the width, past a limit of 1.4.
+        style.miter_limit = 1.4;
+        let rings = path.stroke(&style, 1e-3);
+        assert!(!inside(&rings, &Point2::new(10.9, -0.9)));
+        assert!(inside(&rings, &Point2::new(10.4, -0.4)));
+        style.cap = LineCap::Square;
+        let rings = path.stroke(&style, 1e-3);
+        assert!(inside(&rings, &Point2::new(-0.9, 0.9)));
+        assert!(inside(&rings, &Point2::new(10.9, 10.9)));
+        assert!(!inside(&rings, &Point2::new(-1.1, 0.0)));
+
+        // A sharp spike keeps the union of the segments under the non-zero rule.
+        let spike = Path2::parse("M 0 0 L 10 0.5 L 0 1").unwrap();
+        let rings = spike.stroke(&StrokeStyle::new(0.4), 1e-3);
+        assert!(inside(&rings, &Point2::new(5.0, 0.25)));
+        assert!(inside(&rings, &Point2::new(5.0, 0.75)));
+        assert!(!inside(&rings, &Point2::new(5.0, 0.5)));
+        assert!(inside(&rings, &Point2::new(9.0, 0.5)));
+        assert!(!inside(&rings, &Point2::new(5.0, 1.2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "tolerance must be positive")]
+    fn test_stroke_zero_tolerance() {
+        let path = Path2::parse("M 0 0 L 10 0 L 10 10").unwrap();
+        let mut style = StrokeStyle::new(2.0);
+        style.cap = LineCap::Round;
+        style.join = LineJoin::Round;
+        path.stroke(&style, 0.0);
+    }
+
+    #[test]
+    fn test_stroke_dashes() {
+        let path = Path2::parse("M 0 0 L 10 0").unwrap();
+        let mut style = StrokeStyle::new(1.0);
+        style.dashes = vec![2.0, 1.0];
+        style.dash_offset = 0.5;
+        let rings = path.stroke(&style, 1e-3);
+        // Dashes start at -0.5, 2.5, 5.5 and 8.5 and are 2 long.
+        assert_eq!(rings.len(), 4);
+        for (x, on) in [
+            (0.5, true),
+            (1.7, false),
+            (2.0, false),
+            (3.0, true),
+            (5.2, false),
+            (9.9, true),
+        ] {
+            assert_eq!(inside(&rings, &Point2::new(x, 0.0)), on, "{x}");
+        }
+        // An odd pattern repeats: dash 1, gap 1, dash 1, ...
+        style.dashes = vec![1.0];
+        style.dash_offset = 0.0;
+        assert_eq!(path.stroke(&style, 1e-3).len(), 5);
+        // On a closed square the dash over the start continues round the corner.
+        let square = Path2::parse("M 0 0 L 4 0 L 4 4 L 0 4 Z").unwrap();
+        style.dashes = vec![3.0, 2.0];
+        style.dash_offset = 1.0;
+        let rings = square.stroke(&style, 1e-3);
+        assert_eq!(rings.len(), 3);
+        assert!(inside(&rings, &Point2::new(0.0, 0.5)));
+        assert!(inside(&rings, &Point2::new(1.5, 0.0)));
+        assert!(!inside(&rings, &Point2::new(2.5, 0.0)));
+    }
+}
@@ -0,0 +1,652 @@
+use crate::image::{Image, Pixel};
+use crate::mat::Mat4;
+use crate::triangle_raster::{rasterize_triangle, Fragment, Varyings};
+use crate::vec::Vec4;
+
+/// Turns an input vertex into its clip-space position and the values interpolated across the
+/// triangles it is part of.
+pub trait VertexShader<V> {
+    type Varyings: Varyings + Clone;
+
+    fn shade(&self, vertex: &V) -> (Vec4<f64>, Self::Varyings);
+}
+
+/// Colours a fragment from the interpolated varyings, as straight-alpha RGBA, or discards it
+/// by returning `None`.
+pub trait FragmentShader<V> {
+    fn shade(&self, varyings: &V, fragment: &Fragment) -> Option<[f32; 4]>;
+}
+
+/// Which triangles are dropped by the way they face.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CullMode {
+    None,
+    Front,
+    Back,
+}
+
+/// Winding of front-facing triangles in normalized device coordinates, with `y` up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontFace {
+    CounterClockwise,
+    Clockwise,
+}
+
+/// Test a fragment's depth must pass against the depth already stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Never,
+    Less,
+    LessEqual,
+    Equal,
+    NotEqual,
+    GreaterEqual,
+    Greater,
+    Always,
+}
+
+impl CompareOp {
+    pub fn test(self, value: f64, stored: f64) -> bool {
+        match self {
+            CompareOp::Never => false,
+            CompareOp::Less => value < stored,
+            CompareOp::LessEqual => value <= stored,
+            CompareOp::Equal => value == stored,
+            CompareOp::NotEqual => value != stored,
+            CompareOp::GreaterEqual => value >= stored,
+            CompareOp::Greater => value > stored,
+            CompareOp::Always => true,
+        }
+    }
+}
+
+/// What the source or destination colour is multiplied by before the two are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendFactor {
+    Zero,
+    One,
+    SrcColor,
+    OneMinusSrcColor,
+    SrcAlpha,
+    OneMinusSrcAlpha,
+    DstColor,
+    OneMinusDstColor,
+    DstAlpha,
+    OneMinusDstAlpha,
+}
+
+impl BlendFactor {
+    fn weight(self, src: &[f32; 4], dst: &[f32; 4], channel: usize) -> f32 {
+        match self {
+            BlendFactor::Zero => 0.0,
+            BlendFactor::One => 1.0,
+            BlendFactor::SrcColor => src[channel],
+            BlendFactor::OneMinusSrcColor => 1.0 - src[channel],
+            BlendFactor::SrcAlpha => src[3],
+            BlendFactor::OneMinusSrcAlpha => 1.0 - src[3],
+            BlendFactor::DstColor => dst[channel],
+            BlendFactor::OneMinusDstColor => 1.0 - dst[channel],
+            BlendFactor::DstAlpha => dst[3],
+            BlendFactor::OneMinusDstAlpha => 1.0 - dst[3],
+        }
+    }
+}
+
+/// How the weighted source and destination are combined. `Min` and `Max` ignore the factors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendOp {
+    Add,
+    Subtract,
+    ReverseSubtract,
+    Min,
+    Max,
+}
+
+impl BlendOp {
+    fn apply(self, src: f32, src_weight: f32, dst: f32, dst_weight: f32) -> f32 {
+        match self {
+            BlendOp::Add => src * src_weight + dst * dst_weight,
+            BlendOp::Subtract => src * src_weight - dst * dst_weight,
+            BlendOp::ReverseSubtract => dst * dst_weight - src * src_weight,
+            BlendOp::Min => src.min(dst),
+            BlendOp::Max => src.max(dst),
+        }
+    }
+}
+
+/// Blending of fragment colours into the target, separately for colour and alpha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Blend {
+    pub src_color: BlendFactor,
+    pub dst_color: BlendFactor,
+    pub color_op: BlendOp,
+    pub src_alpha: BlendFactor,
+    pub dst_alpha: BlendFactor,
+    pub alpha_op: BlendOp,
+}
+
+impl Blend {
+    /// Source over destination for straight alpha.
+    pub const ALPHA: Blend = Blend {
+        src_color: BlendFactor::SrcAlpha,
+        dst_color: BlendFactor::OneMinusSrcAlpha,
+        color_op: BlendOp::Add,
+        src_alpha: BlendFactor::One,
+        dst_alpha: BlendFactor::OneMinusSrcAlpha,
+        alpha_op: BlendOp::Add,
+    };
+
+    pub const ADDITIVE: Blend = Blend {
+        src_color: BlendFactor::One,
+        dst_color: BlendFactor::One,
+        color_op: BlendOp::Add,
+        src_alpha: BlendFactor::One,
+        dst_alpha: BlendFactor::One,
+        alpha_op: BlendOp::Add,
+    };
+
+    /// The blended colour, unclamped: the target's pixel format decides what it keeps.
+    pub fn apply(&self, src: &[f32; 4], dst: &[f32; 4]) -> [f32; 4] {
+        std::array::from_fn(|i| {
+            let (s, d, op) = if i < 3 {
+                (self.src_color, self.dst_color, self.color_op)
+            } else {
+                (self.src_alpha, self.dst_alpha, self.alpha_op)
+            };
+            op.apply(src[i], s.weight(src, dst, i), dst[i], d.weight(src, dst, i))
+        })
+    }
+}
+
+/// Depth per pixel, smaller being nearer under the default [`CompareOp::Less`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthBuffer {
+    width: usize,
+    height: usize,
+    values: Vec<f64>,
+}
+
+impl DepthBuffer {
+    /// A buffer cleared to the far plane.
+    pub fn new(width: usize, height: usize) -> DepthBuffer {
+        DepthBuffer {
+            width,
+            height,
+            values: vec![1.0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<f64> {
+        (x < self.width && y < self.height).then(|| self.values[y * self.width + x])
+    }
+
+    pub fn clear(&mut self, depth: f64) {
+        self.values.fill(depth);
+    }
+}
+
+/// The colour and depth images a pipeline draws into.
+#[derive(Debug, Clone)]
+pub struct RenderTarget<P: Pixel> {
+    pub color: Image<P>,
+    pub depth: DepthBuffer,
+}
+
+impl<P: Pixel> RenderTarget<P> {
+    pub fn new(width: usize, height: usize) -> RenderTarget<P> {
+        RenderTarget {
+            color: Image::new(width, height),
+            depth: DepthBuffer::new(width, height),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.color.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.color.height()
+    }
+
+    pub fn clear(&mut self, color: P, depth: f64) {
+        self.color.fill(color);
+        self.depth.clear(depth);
+    }
+}
+
+/// Fixed-function state of a software rendering pipeline, running on the calling thread.
+///
+/// Vertices are shaded, triangles are clipped to the view volume `-w <= x, y, z <= w` in
+/// homogeneous coordinates, divided by `w` and mapped to pixels by the `viewport` matrix.
+/// Culled triangles are dropped, and each fragment that passes the depth test is shaded and
+/// blended into the target.
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    pub viewport: Mat4<f64>,
+    pub cull: CullMode,
+    pub front_face: FrontFace,
+    /// The depth test, or `None` to neither test nor write depth.
+    pub depth_compare: Option<CompareOp>,
+    pub depth_write: bool,
+    /// How colours are blended, or `None` to replace them.
+    pub blend: Option<Blend>,
+}
+
+impl Pipeline {
+    /// A pipeline drawing over the whole of a `width` by `height` target, culling back faces
+    /// wound clockwise and keeping the nearest fragments.
+    pub fn new(width: usize, height: usize) -> Pipeline {
+        Pipeline {
+            viewport: Mat4::viewport(0.0, 0.0, width as f64, height as f64),
+            cull: CullMode::Back,
+            front_face: FrontFace::CounterClockwise,
+            depth_compare: Some(CompareOp::Less),
+            depth_write: true,
+            blend: None,
+        }
+    }
+
+    /// Draws the `triangles`, as indices into `vertices`, into `target`.
+    pub fn draw<P, V, VS, FS>(
+        &self,
+        target: &mut RenderTarget<P>,
+        vertex_shader: &VS,
+        fragment_shader: &FS,
+        vertices: &[V],
+        triangles: &[[usize; 3]],
+    ) where
+        P: Pixel,
+        VS: VertexShader<V>,
+        FS: FragmentShader<VS::Varyings>,
+    {
+        let shaded: Vec<(Vec4<f64>, VS::Varyings)> =
+            vertices.iter().map(|v| vertex_shader.shade(v)).collect();
+        for triangle in triangles {
+            let corners = triangle.map(|i| shaded[i].clone());
+            let polygon = clip_polygon(corners.to_vec());
+            if polygon.len() < 3 {
+                continue;
+            }
+            // Normalized device coordinates, and their winding with `y` up.
+            let ndc: Vec<Vec4<f64>> = polygon
+                .iter()
+                .map(|(p, _)| vec4![p[0] / p[3], p[1] / p[3], p[2] / p[3], 1.0])
+                .collect();
+            let area: f64 = (0..ndc.len())
+                .map(|i| {
+                    let (a, b) = (&ndc[i], &ndc[(i + 1) % ndc.len()]);
+                    a[0] * b[1] - b[0] * a[1]
+                })
+                .sum();
+            let front = match self.front_face {
+                FrontFace::CounterClockwise => area > 0.0,
+                FrontFace::Clockwise => area < 0.0,
+            };
+            let culled = match self.cull {
+                CullMode::None => false,
+                CullMode::Front => front,
+                CullMode::Back => !front,
+            };
+            if culled || area == 0.0 {
+                continue;
+            }
+            let screen: Vec<Vec4<f64>> = ndc
+                .iter()
+                .zip(&polygon)
+                .map(|(n, (p, _))| {
+                    let s = self.viewport.mul_vec(n);
+                    vec4![s[0], s[1], s[2], p[3]]
+                })
+                .collect();
+            for i in 1..polygon.len() - 1 {
+                let fan = [0, i, i + 1];
+                let positions = fan.map(|k| screen[k].clone());
+                let varyings = fan.map(|k| polygon[k].1.clone());
+                self.draw_triangle(target, fragment_shader, &positions, &varyings);
+            }
+        }
+    }
+
+    fn draw_triangle<P: Pixel, V: Varyings, FS: FragmentShader<V>>(
+        &self,
+        target: &mut RenderTarget<P>,
+        fragment_shader: &FS,
+        positions: &[Vec4<f64>; 3],
+        varyings: &[V; 3],
+    ) {
+        let (width, height) = (target.width(), target.height());
+        rasterize_triangle(positions, width, height, |f| {
+            let index = f.y * width + f.x;
+            if let Some(compare) = self.depth_compare {
+                if !compare.test(f.depth, target.depth.values[index]) {
+                    return;
+                }
+            }
+            let Some(color) = fragment_shader.shade(&f.interpolate(varyings), f) else {
+                return;
+            };
+            if self.depth_compare.is_some() && self.depth_write {
+                target.depth.values[index] = f.depth;
+            }
+            let pixel = &mut target.color.pixels_mut()[index];
+            let color = match &self.blend {
+                Some(blend) => blend.apply(&color, &pixel.to_rgba()),
+                None => color,
+            };
+            *pixel = P::from_rgba(color);
+        });
+    }
+}
+
+/// Clips a convex polygon of shaded vertices to `-w <= x, y, z <= w`, one plane at a time.
+fn clip_polygon<V: Varyings + Clone>(mut polygon: Vec<(Vec4<f64>, V)>) -> Vec<(Vec4<f64>, V)> {
+    for axis in 0..3 {
+        for sign in [1.0, -1.0] {
+            // Inside where `w - sign * p[axis]` is not negative.
+            let distance = |p: &Vec4<f64>| p[3] - sign * p[axis];
+            let mut clipped = Vec::with_capacity(polygon.len() + 1);
+            for i in 0..polygon.len() {
+                let (a, b) = (&polygon[i], &polygon[(i + 1) % polygon.len()]);
+                let (da, db) = (distance(&a.0), distance(&b.0));
+                if da >= 0.0 {
+                    clipped.push(a.clone());
+                }
+                if (da >= 0.0) != (db >= 0.0) {
+                    let t = da / (da - db);
+                    let weights = [1.0 - t, t, 0.0];
+                    let position =
+                        Vec4::interpolate(&[a.0.clone(), b.0.clone(), a.0.clone()], weights);
+                    let varyings =
+                        V::interpolate(&[a.1.clone(), b.1.clone(), a.1.clone()], weights);
+                    clipped.push((position, varyings));
+                }
+            }
+            polygon = clipped;
+            if polygon.is_empty() {
+                return polygon;
+            }
+        }
+    }
+    polygon
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::{Rgba32F, Rgba8};
+    use crate::vec::Vec3;
+    use std::cell::Cell;
+
+    /// Position and colour of a vertex.
+    type Vertex = ([f64; 3], [f64; 4]);
+
+    struct Transform(Mat4<f64>);
+
+    impl VertexShader<Vertex> for Transform {
+        type Varyings = (Vec3<f64>, [f64; 4]);
+
+        fn shade(&self, vertex: &Vertex) -> (Vec4<f64>, Self::Varyings) {
+            let [x, y, z] = vertex.0;
+            let clip = self.0.mul_vec(&vec4![x, y, z, 1.0]);
+            (clip, (vec3![x, y, z], vertex.1))
+        }
+    }
+
+    /// Outputs the vertex colour, counting the fragments it sees.
+    #[derive(Default)]
+    struct Colored {
+        fragments: Cell<usize>,
+    }
+
+    impl FragmentShader<(Vec3<f64>, [f64; 4])> for Colored {
+        fn shade(&self, varyings: &(Vec3<f64>, [f64; 4]), _: &Fragment) -> Option<[f32; 4]> {
+            self.fragments.set(self.fragments.get() + 1);
+            Some(varyings.1.map(|c| c as f32))
+        }
+    }
+
+    fn quad(min: [f64; 2], max: [f64; 2], z: f64, color: [f64; 4]) -> Vec<Vertex> {
+        vec![
+            ([min[0], min[1], z], color),
+            ([max[0], min[1], z], color),
+            ([max[0], max[1], z], color),
+            ([min[0], max[1], z], color),
+        ]
+    }
+
+    const QUAD: [[usize; 3]; 2] = [[0, 1, 2], [0, 2, 3]];
+
+    fn rgba(target: &RenderTarget<Rgba32F>, x: i32, y: i32) -> [f32; 4] {
+        target.color.get(x, y).unwrap().0
+    }
+
+    #[test]
+    fn test_depth() {
+        let near = quad([-1.0, -1.0], [0.5, 0.5], -0.5, [0.0, 1.0, 0.0, 1.0]);
+        let far = quad([-0.5, -0.5], [1.0, 1.0], 0.5, [1.0, 0.0, 0.0, 1.0]);
+        let shader = Transform(Mat4::identity());
+        let green = [0.0, 1.0, 0.0, 1.0];
+        let red = [1.0, 0.0, 0.0, 1.0];
+        for (compare, overlap) in [
+            (Some(CompareOp::Less), green),
+            (Some(CompareOp::Greater), red),
+            (Some(CompareOp::Always), red),
+            (None, red),
+        ] {
+            let mut pipeline = Pipeline::new(8, 8);
+            pipeline.depth_compare = compare;
+            // Drawn in either order where the depth test decides.
+            let orders: &[[&Vec<Vertex>; 2]] = match compare {
+                Some(CompareOp::Less) | Some(CompareOp::Greater) => &[[&near, &far], [&far, &near]],
+                _ => &[[&near, &far]],
+            };
+            for order in orders {
+                let mut target = RenderTarget::new(8, 8);
+                if compare == Some(CompareOp::Greater) {
+                    target.depth.clear(0.0);
+                }
+                let colored = Colored::default();
+                for vertices in order {
+                    pipeline.draw(&mut target, &shader, &colored, vertices, &QUAD);
+                }
+                // The overlap is the middle of the target; `y` points down on screen.
+                assert_eq!(rgba(&target, 3, 3), overlap);
+                assert_eq!(rgba(&target, 0, 7), green);
+                assert_eq!(rgba(&target, 7, 0), red);
+                let depth = target.depth.get(3, 3).unwrap();
+                match compare {
+                    Some(CompareOp::Less) => assert_eq!(depth, 0.25),
+                    Some(_) => assert_eq!(depth, 0.75),
+                    None => assert_eq!(depth, 1.0),
+                }
+            }
+        }
+        // Nothing passes a test against a cleared buffer it never beats.
+        let mut pipeline = Pipeline::new(8, 8);
+        pipeline.depth_compare = Some(CompareOp::Greater);
+        let mut target = RenderTarget::<Rgba32F>::new(8, 8);
+        let colored = Colored::default();
+        pipeline.draw(&mut target, &shader, &colored, &near, &QUAD);
+        assert_eq!(colored.fragments.get(), 0);
+    }
+
+    #[test]
+    fn test_culling() {
+        let triangle = vec![
+            ([-0.5, -0.5, 0.0], [1.0; 4]),
+            ([0.5, -0.5, 0.0], [1.0; 4]),
+            ([0.0, 0.5, 0.0], [1.0; 4]),
+        ];
+        let shader = Transform(Mat4::identity());
+        for (cull, front_face, drawn) in [
+            (CullMode::Back, FrontFace::CounterClockwise, [true, false]),
+            (CullMode::Front, FrontFace::CounterClockwise, [false, true]),
+            (CullMode::Back, FrontFace::Clockwise, [false, true]),
+            (CullMode::None, FrontFace::CounterClockwise, [true, true]),
+        ] {
+            let mut pipeline = Pipeline::new(16, 16);
+            pipeline.cull = cull;
+            pipeline.front_face = front_face;
+            for (indices, drawn) in [[0, 1, 2], [0, 2, 1]].into_iter().zip(drawn) {
+                let mut target = RenderTarget::<Rgba32F>::new(16, 16);
+                let colored = Colored::default();
+                pipeline.draw(&mut target, &shader, &colored, &triangle, &[indices]);
+                assert_eq!(colored.fragments.get() > 0, drawn);
+            }
+        }
+    }
+
+    /// Checks that the interpolated positions project back onto the pixel centres.
+    struct Reprojected {
+        view_projection: Mat4<f64>,
+        viewport: Mat4<f64>,
+        fragments: Cell<usize>,
+    }
+
+    impl FragmentShader<(Vec3<f64>, [f64; 4])> for Reprojected {
+        fn shade(&self, varyings: &(Vec3<f64>, [f64; 4]), f: &Fragment) -> Option<[f32; 4]> {
+            let p = &varyings.0;
+            let clip = self.view_projection.mul_vec(&vec4![p[0], p[1], p[2], 1.0]);
+            let screen = self.viewport.mul_vec(&(clip.clone() / clip[3]));
+            assert!(clip[3] > 0.0);
+            // Off by no more than the snapping of the vertices to sub-pixels.
+            assert!((screen[0] - (f.x as f64 + 0.5)).abs() < 1e-2);
+            assert!((screen[1] - (f.y as f64 + 0.5)).abs() < 1e-2);
+            assert!((screen[2] - f.depth).abs() < 1e-6);
+            self.fragments.set(self.fragments.get() + 1);
+            // Every other square of a checkerboard is cut out.
+            let even = (p[0].floor() + p[2].floor()) as i64 % 2 == 0;
+            even.then_some([1.0, 1.0, 1.0, 1.0])
+        }
+    }
+
+    #[test]
+    fn test_clipping() {
+        // A ground plane stretching from behind the camera to beyond the far plane.
+        let (width, height) = (64, 48);
+        let projection = Mat4::perspective(1.2, width as f64 / height as f64, 0.1, 5000.0);
+        let mut view = Mat4::identity();
+        view[1][3] = -1.5;
+        let view_projection = projection.mul_mat(&view);
+        let ground = quad([-1000.0, -1000.0], [1000.0, 1000.0], 0.0, [1.0; 4])
+            .into_iter()
+            .map(|([x, y, _], c)| ([x, 0.0, y], c))
+            .collect::<Vec<_>>();
+        let pipeline = Pipeline::new(width, height);
+        let shader = Reprojected {
+            view_projection: view_projection.clone(),
+            viewport: pipeline.viewport.clone(),
+            fragments: Cell::new(0),
+        };
+        let mut target = RenderTarget::<Rgba32F>::new(width, height);
+        let mut culled = pipeline.clone();
+        culled.cull = CullMode::None;
+        culled.draw(
+            &mut target,
+            &Transform(view_projection),
+            &shader,
+            &ground,
+            &QUAD,
+        );
+        // Everything below the horizon, which is the middle row, and nothing above it.
+        assert_eq!(shader.fragments.get(), width * height / 2);
+        // Discarded fragments write neither colour nor depth.
+        let half = width * height / 2;
+        let below = target.color.pixels()[half..]
+            .iter()
+            .zip(&target.depth.values()[half..]);
+        let mut discarded = 0;
+        for (pixel, &depth) in below {
+            assert_eq!(pixel.0[3] == 0.0, depth == 1.0);
+            discarded += (depth == 1.0) as usize;
+        }
+        assert!(discarded > 0 && discarded < half);
+
+        // A quad far larger than the view covers each pixel once.
+        let mut target = RenderTarget::<Rgba32F>::new(width, height);
+        let mut additive = Pipeline::new(width, height);
+        additive.blend = Some(Blend::ADDITIVE);
+        additive.depth_compare = None;
+        let big = quad([-50.0, -50.0], [50.0, 50.0], 0.0, [0.25, 0.0, 0.0, 0.25]);
+        let colored = Colored::default();
+        additive.draw(
+            &mut target,
+            &Transform(Mat4::identity()),
+            &colored,
+            &big,
+            &QUAD,
+        );
+        assert!(target
+            .color
+            .pixels()
+            .iter()
+            .all(|p| p.0 == [0.25, 0.0, 0.0, 0.25]));
+
+        // Behind the camera or past the far plane, nothing is drawn.
+        let colored = Colored::default();
+        for z in [2.0, -6000.0] {
+            let vertices = quad([-1.0, -1.0], [1.0, 1.0], z, [1.0; 4]);
+            pipeline.draw(
+                &mut target,
+                &Transform(projection.clone()),
+                &colored,
+                &vertices,
+                &QUAD,
+            );
+        }
+        assert_eq!(colored.fragments.get(), 0);
+    }
+
+    #[test]
+    fn test_blending() {
+        let src = [1.0, 0.0, 0.0, 0.5];
+        let dst = [0.0, 0.0, 1.0, 1.0];
+        assert_eq!(Blend::ALPHA.apply(&src, &dst), [0.5, 0.0, 0.5, 1.0]);
+        let additive = Blend::ADDITIVE.apply(&src, &dst);
+        assert_eq!(additive, [1.0, 0.0, 1.0, 1.5]);
+        assert_eq!(Rgba8::from_rgba(additive), Rgba8::new(255, 0, 255, 255));
+        let darken = Blend {
+            color_op: BlendOp::Min,
+            alpha_op: BlendOp::Max,
+            ..Blend::ALPHA
+        };
+        assert_eq!(darken.apply(&src, &dst), [0.0, 0.0, 0.0, 1.0]);
+        let subtract = Blend {
+            color_op: BlendOp::ReverseSubtract,
+            ..Blend::ADDITIVE
+        };
+        assert_eq!(
+            subtract.apply(&src, &[0.5, 0.5, 0.5, 1.0]),
+            [-0.5, 0.5, 0.5, 1.5]
+        );
+
+        // A half-transparent quad over an opaque one, through the pipeline.
+        let mut pipeline = Pipeline::new(4, 4);
+        pipeline.blend = Some(Blend::ALPHA);
+        pipeline.depth_compare = None;
+        let mut target = RenderTarget::new(4, 4);
+        target.clear(Rgba32F::new(0.0, 0.0, 1.0, 1.0), 1.0);
+        let vertices = quad([-1.0, -1.0], [1.0, 1.0], 0.0, [1.0, 0.0, 0.0, 0.5]);
+        let colored = Colored::default();
+        pipeline.draw(
+            &mut target,
+            &Transform(Mat4::identity()),
+            &colored,
+            &vertices,
+            &QUAD,
+        );
+        assert_eq!(rgba(&target, 2, 1), [0.5, 0.0, 0.5, 1.0]);
+    }
+}
@@ -1,7 +1,7 @@
 #![allow(dead_code)]
 
 use crate::vec::{Vec2, Vec3};
-use std::ops::{Add, AddAssign, Index, IndexMut};
+use std::ops::{Add, AddAssign, Index, IndexMut, Sub, SubAssign};
 use std::slice::SliceIndex;
 
 
@@ -19,33 +19,42 @@ macro_rules! point3 {
     };
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct Point2(pub [f64; 2]);
 
 impl Point2 {
-    fn new(x: f64, y: f64) -> Point2 {
+    pub fn new(x: f64, y: f64) -> Point2 {
         Point2([x, y])
     }
 
-    fn x(&self) -> f64 {
+    pub fn x(&self) -> f64 {
         self[0]
     }
 
-    fn y(&self) -> f64 {
+    pub fn y(&self) -> f64 {
         self[1]
     }
 
-    fn set_x(&mut self, x: f64) {
+    pub fn set_x(&mut self, x: f64) {
         self[0] = x;
     }
 
-    fn set_y(&mut self, y: f64) {
+    pub fn set_y(&mut self, y: f64) {
         self[1] = y;
     }
 
-    fn set(&mut self, x: f64, y: f64) {
+    pub fn set(&mut self, x: f64, y: f64) {
         self[0] = x;
         self[1] = y;
     }
+
+    pub fn to_vec(&self) -> Vec2<f64> {
+        Vec2(self.0)
+    }
+
+    pub fn from_vec(v: Vec2<f64>) -> Point2 {
+        Point2(v.0)
+    }
 }
 
 impl<I: SliceIndex<[f64]>> Index<I> for Point2 {
@@ -77,43 +86,75 @@ impl AddAssign<Vec2<f64>> for Point2 {
     }
 }
 
+impl Sub<Vec2<f64>> for Point2 {
+    type Output = Self;
 
+    fn sub(self, rhs: Vec2<f64>) -> Self::Output {
+        point2![self[0] - rhs[0], self[1] - rhs[1]]
+    }
+}
+
+impl SubAssign<Vec2<f64>> for Point2 {
+    fn sub_assign(&mut self, rhs: Vec2<f64>) {
+        self[0] -= rhs[0];
+        self[1] -= rhs[1];
+    }
+}
+
+impl Sub<Point2> for Point2 {
+    type Output = Vec2<f64>;
+
+    fn sub(self, rhs: Point2) -> Self::Output {
+        Vec2([self[0] - rhs[0], self[1] - rhs[1]])
+    }
+}
+
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct Point3(pub [f64; 3]);
 
 impl Point3 {
-    fn new(x: f64, y: f64, z: f64) -> Point3 {
+    pub fn new(x: f64, y: f64, z: f64) -> Point3 {
         Point3([x, y, z])
     }
 
-    fn x(&self) -> f64 {
+    pub fn x(&self) -> f64 {
         self[0]
     }
 
-    fn y(&self) -> f64 {
+    pub fn y(&self) -> f64 {
         self[1]
     }
 
-    fn z(&self) -> f64 {
+    pub fn z(&self) -> f64 {
         self[2]
     }
 
-    fn set_x(&mut self, x: f64) {
+    pub fn set_x(&mut self, x: f64) {
         self[0] = x;
     }
 
-    fn set_y(&mut self, y: f64) {
+    pub fn set_y(&mut self, y: f64) {
         self[1] = y;
     }
 
-    fn set_z(&mut self, z: f64) {
+    pub fn set_z(&mut self, z: f64) {
         self[2] = z;
     }
 
-    fn set(&mut self, x: f64, y: f64, z: f64) {
+    pub fn set(&mut self, x: f64, y: f64, z: f64) {
         self[0] = x;
         self[1] = y;
         self[2] = z;
     }
+
+    pub fn to_vec(&self) -> Vec3<f64> {
+        Vec3(self.0)
+    }
+
+    pub fn from_vec(v: Vec3<f64>) -> Point3 {
+        Point3(v.0)
+    }
 }
 
 impl<I: SliceIndex<[f64]>> Index<I> for Point3 {
@@ -144,4 +185,28 @@ impl AddAssign<Vec3<f64>> for Point3 {
         self[1] += rhs[1];
         self[2] += rhs[2];
     }
-}
\ No newline at end of file
+}
+
+impl Sub<Vec3<f64>> for Point3 {
+    type Output = Self;
+
+    fn sub(self, rhs: Vec3<f64>) -> Self::Output {
+        Point3::new(self[0] - rhs[0], self[1] - rhs[1], self[2] - rhs[2])
+    }
+}
+
+impl SubAssign<Vec3<f64>> for Point3 {
+    fn sub_assign(&mut self, rhs: Vec3<f64>) {
+        self[0] -= rhs[0];
+        self[1] -= rhs[1];
+        self[2] -= rhs[2];
+    }
+}
+
+impl Sub<Point3> for Point3 {
+    type Output = Vec3<f64>;
+
+    fn sub(self, rhs: Point3) -> Self::Output {
+        Vec3([self[0] - rhs[0], self[1] - rhs[1], self[2] - rhs[2]])
+    }
+}
@@ -0,0 +1,437 @@
+use crate::aabb::Aabb2;
+use crate::point::Point2;
+use crate::segment::{Segment2, SegmentIntersection};
+
+/// Rule deciding which points are inside a set of possibly overlapping rings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    /// Inside when a ray from the point crosses the boundary an odd number of times.
+    EvenOdd,
+    /// Inside when the winding number around the point is non-zero.
+    NonZero,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    CounterClockwise,
+    Clockwise,
+    Collinear,
+}
+
+/// Identifies one edge of a polygon. Ring 0 is the exterior and ring `i + 1` is hole `i`;
+/// edge `index` runs from vertex `index` to the next vertex of that ring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdgeRef {
+    pub ring: usize,
+    pub index: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelfIntersection {
+    pub first: EdgeRef,
+    pub second: EdgeRef,
+    pub intersection: SegmentIntersection,
+}
+
+/// Signed area of a closed ring by the shoelace formula; positive when counter-clockwise.
+pub fn ring_signed_area(ring: &[Point2]) -> f64 {
+    let n = ring.len();
+    let mut sum = 0.0;
+    for i in 0..n {
+        let p = ring[i];
+        let q = ring[(i + 1) % n];
+        sum += p[0] * q[1] - q[0] * p[1];
+    }
+    sum * 0.5
+}
+
+pub fn ring_orientation(ring: &[Point2]) -> Orientation {
+    let area = ring_signed_area(ring);
+    if area > 0.0 {
+        Orientation::CounterClockwise
+    } else if area < 0.0 {
+        Orientation::Clockwise
+    } else {
+        Orientation::Collinear
+    }
+}
+
+/// Winding number of the ring around `p` (Sunday's crossing rule).
+pub fn ring_winding_number(ring: &[Point2], p: &Point2) -> i32 {
+    let n = ring.len();
+    let mut winding = 0;
+    for i in 0..n {
+        let a = ring[i];
+        let b = ring[(i + 1) % n];
+        let side = (b - a).perp_dot(&(*p - a));
+        if a[1] <= p[1] {
+            if b[1] > p[1] && side > 0.0 {
+                winding += 1;
+            }
+        } else if b[1] <= p[1] && side < 0.0 {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+/// Even-odd crossing test of `p` against a single ring.
+fn ring_crosses(ring: &[Point2], p: &Point2) -> bool {
+    let n = ring.len();
+    let mut inside = false;
+    for i in 0..n {
+        let a = ring[i];
+        let b = ring[(i + 1) % n];
+        if (a[1] > p[1]) != (b[1] > p[1]) {
+            let x = a[0] + (p[1] - a[1]) / (b[1] - a[1]) * (b[0] - a[0]);
+            if p[0] < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// Signed area and the area-weighted centroid sums of a ring.
+fn ring_moments(ring: &[Point2]) -> (f64, f64, f64) {
+    let n = ring.len();
+    let (mut a, mut cx, mut cy) = (0.0, 0.0, 0.0);
+    for i in 0..n {
+        let p = ring[i];
+        let q = ring[(i + 1) % n];
+        let cross = p[0] * q[1] - q[0] * p[1];
+        a += cross;
+        cx += (p[0] + q[0]) * cross;
+        cy += (p[1] + q[1]) * cross;
+    }
+    (a * 0.5, cx / 6.0, cy / 6.0)
+}
+
+/// A polygon made of an outer ring and zero or more holes. Rings are implicitly
+/// closed: the last vertex connects back to the first and is not repeated.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Polygon2 {
+    pub exterior: Vec<Point2>,
+    pub holes: Vec<Vec<Point2>>,
+}
+
+impl Polygon2 {
+    pub fn new(exterior: Vec<Point2>) -> Polygon2 {
+        Polygon2 {
+            exterior,
+            holes: vec![],
+        }
+    }
+
+    pub fn with_holes(exterior: Vec<Point2>, holes: Vec<Vec<Point2>>) -> Polygon2 {
+        Polygon2 { exterior, holes }
+    }
+
+    /// The exterior followed by the holes, numbered as in `EdgeRef::ring`.
+    pub fn rings(&self) -> impl Iterator<Item = &[Point2]> {
+        std::iter::once(self.exterior.as_slice()).chain(self.holes.iter().map(|h| h.as_slice()))
+    }
+
+    pub fn ring(&self, index: usize) -> &[Point2] {
+        if index == 0 {
+            &self.exterior
+        } else {
+            &self.holes[index - 1]
+        }
+    }
+
+    pub fn edge(&self, edge: EdgeRef) -> Segment2 {
+        let ring = self.ring(edge.ring);
+        Segment2::new(ring[edge.index], ring[(edge.index + 1) % ring.len()])
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (EdgeRef, Segment2)> + '_ {
+        self.rings().enumerate().flat_map(|(r, ring)| {
+            let n = ring.len();
+            (0..n).map(move |i| {
+                (
+                    EdgeRef { ring: r, index: i },
+                    Segment2::new(ring[i], ring[(i + 1) % n]),
+                )
+            })
+        })
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.rings().map(|r| r.len()).sum()
+    }
+
+    /// Sum of the signed ring areas. Once orientation is normalized this is the
+    /// enclosed area, positive for a counter-clockwise exterior.
+    pub fn signed_area(&self) -> f64 {
+        self.rings().map(ring_signed_area).sum()
+    }
+
+    /// Exterior area minus hole areas, independent of ring orientation.
+    pub fn area(&self) -> f64 {
+        ring_signed_area(&self.exterior).abs()
+            - self
+                .holes
+                .iter()
+                .map(|h| ring_signed_area(h).abs())
+                .sum::<f64>()
+    }
+
+    /// Orientation of the exterior ring.
+    pub fn orientation(&self) -> Orientation {
+        ring_orientation(&self.exterior)
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        self.edges().map(|(_, e)| e.length()).sum()
+    }
+
+    /// Area centroid, with holes removed. Returns `None` for a zero-area polygon.
+    pub fn centroid(&self) -> Option<Point2> {
+        let (mut a, mut cx, mut cy) = (0.0, 0.0, 0.0);
+        for (r, ring) in self.rings().enumerate() {
+            let (ra, rx, ry) = ring_moments(ring);
+            // Flip every ring to counter-clockwise, then subtract the holes.
+            let sign = ra.signum() * if r == 0 { 1.0 } else { -1.0 };
+            a += ra * sign;
+            cx += rx * sign;
+            cy += ry * sign;
+        }
+        if a == 0.0 {
+            None
+        } else {
+            Some(Point2::new(cx / a, cy / a))
+        }
+    }
+
+    pub fn aabb(&self) -> Aabb2 {
+        Aabb2::from_points(self.exterior.iter().copied())
+    }
+
+    /// Sum of the winding numbers of every ring around `p`.
+    pub fn winding_number(&self, p: &Point2) -> i32 {
+        self.rings().map(|r| ring_winding_number(r, p)).sum()
+    }
+
+    /// Point-in-polygon test. Under `NonZero` holes only subtract when they wind
+    /// opposite to the exterior, as `normalize_orientation` arranges.
+    pub fn contains(&self, p: &Point2, rule: FillRule) -> bool {
+        match rule {
+            FillRule::EvenOdd => self.rings().filter(|r| ring_crosses(r, p)).count() % 2 == 1,
+            FillRule::NonZero => self.winding_number(p) != 0,
+        }
+    }
+
+    /// True for a hole-free polygon whose exterior turns the same way at every
+    /// vertex and winds exactly once. Collinear vertices are allowed.
+    pub fn is_convex(&self) -> bool {
+        if !self.holes.is_empty() {
+            return false;
+        }
+        let ring = &self.exterior;
+        let n = ring.len();
+        if n < 3 {
+            return false;
+        }
+        let mut sign = 0.0;
+        let mut total_turn = 0.0;
+        for i in 0..n {
+            let d0 = ring[(i + 1) % n] - ring[i];
+            let d1 = ring[(i + 2) % n] - ring[(i + 1) % n];
+            let cross = d0.perp_dot(&d1);
+            if cross != 0.0 {
+                if sign != 0.0 && cross.signum() != sign {
+                    return false;
+                }
+                sign = cross.signum();
+            }
+            total_turn += cross.atan2(d0.dot(&d1));
+        }
+        sign != 0.0 && (total_turn.abs() - std::f64::consts::TAU).abs() < 1e-6
+    }
+
+    /// Every pair of edges that touch or cross, other than neighbouring edges meeting at
+    /// their shared vertex. Edges are swept in order of their minimum x so that only
+    /// pairs with overlapping x-ranges are tested.
+    pub fn self_intersections(&self) -> Vec<SelfIntersection> {
+        let mut edges: Vec<(EdgeRef, Segment2, Aabb2)> =
+            self.edges().map(|(r, e)| (r, e, e.aabb())).collect();
+        edges.sort_by(|a, b| a.2.min[0].total_cmp(&b.2.min[0]));
+
+        let mut found = vec![];
+        let mut active: Vec<usize> = vec![];
+        for i in 0..edges.len() {
+            let (ref_i, seg_i, box_i) = edges[i];
+            active.retain(|&j| edges[j].2.max[0] >= box_i.min[0]);
+            for &j in &active {
+                let (ref_j, seg_j, box_j) = edges[j];
+                if !box_i.overlaps(&box_j) {
+                    continue;
+                }
+                let Some(hit) = seg_j.intersection(&seg_i) else {
+                    continue;
+                };
+                // Neighbours always meet at their shared vertex; only a fold back along
+                // the same line counts. The computed point may differ from the vertex by
+                // rounding, so any single-point contact is skipped.
+                if self.are_neighbours(ref_j, ref_i) && matches!(hit, SegmentIntersection::Point(_))
+                {
+                    continue;
+                }
+                let (first, second) = if (ref_j.ring, ref_j.index) < (ref_i.ring, ref_i.index) {
+                    (ref_j, ref_i)
+                } else {
+                    (ref_i, ref_j)
+                };
+                found.push(SelfIntersection {
+                    first,
+                    second,
+                    intersection: hit,
+                });
+            }
+            active.push(i);
+        }
+        found
+    }
+
+    /// Whether two edges are consecutive in the same ring.
+    fn are_neighbours(&self, a: EdgeRef, b: EdgeRef) -> bool {
+        if a.ring != b.ring {
+            return false;
+        }
+        let n = self.ring(a.ring).len();
+        (a.index + 1) % n == b.index || (b.index + 1) % n == a.index
+    }
+
+    pub fn is_simple(&self) -> bool {
+        self.self_intersections().is_empty()
+    }
+
+    /// Makes the exterior counter-clockwise and every hole clockwise.
+    pub fn normalize_orientation(&mut self) {
+        if ring_signed_area(&self.exterior) < 0.0 {
+            self.exterior.reverse();
+        }
+        for hole in &mut self.holes {
+            if ring_signed_area(hole) > 0.0 {
+                hole.reverse();
+            }
+        }
+    }
+}
+
+impl From<Vec<Point2>> for Polygon2 {
+    fn from(exterior: Vec<Point2>) -> Polygon2 {
+        Polygon2::new(exterior)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ring;
+
+    fn square_with_hole() -> Polygon2 {
+        Polygon2::with_holes(
+            ring(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]),
+            vec![ring(&[(1.0, 1.0), (2.0, 1.0), (2.0, 2.0), (1.0, 2.0)])],
+        )
+    }
+
+    #[test]
+    fn test_area_orientation() {
+        let mut p = square_with_hole();
+        assert_eq!(p.area(), 15.0);
+        assert_eq!(p.orientation(), Orientation::CounterClockwise);
+        assert_eq!(p.signed_area(), 17.0);
+        p.normalize_orientation();
+        assert_eq!(p.signed_area(), 15.0);
+        assert_eq!(ring_orientation(&p.holes[0]), Orientation::Clockwise);
+        assert_eq!(p.perimeter(), 20.0);
+    }
+
+    #[test]
+    fn test_centroid() {
+        let square = Polygon2::new(ring(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]));
+        assert_eq!(square.centroid(), Some(Point2::new(1.0, 1.0)));
+
+        let c = square_with_hole().centroid().unwrap();
+        // (16 * (2, 2) - 1 * (1.5, 1.5)) / 15
+        assert!((c - Point2::new(30.5 / 15.0, 30.5 / 15.0)).length() < 1e-12);
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut p = square_with_hole();
+        for rule in [FillRule::EvenOdd, FillRule::NonZero] {
+            p.normalize_orientation();
+            assert!(p.contains(&Point2::new(3.0, 3.0), rule));
+            assert!(!p.contains(&Point2::new(1.5, 1.5), rule));
+            assert!(!p.contains(&Point2::new(5.0, 1.0), rule));
+        }
+
+        // A pentagram: the centre is covered twice.
+        let star = Polygon2::new(ring(&[
+            (0.0, 3.0),
+            (1.76, -2.43),
+            (-2.85, 0.93),
+            (2.85, 0.93),
+            (-1.76, -2.43),
+        ]));
+        let centre = Point2::new(0.0, 0.0);
+        assert!(!star.contains(&centre, FillRule::EvenOdd));
+        assert!(star.contains(&centre, FillRule::NonZero));
+        assert_eq!(star.winding_number(&centre).abs(), 2);
+    }
+
+    #[test]
+    fn test_convex() {
+        let square = Polygon2::new(ring(&[
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 2.0),
+            (0.0, 2.0),
+        ]));
+        assert!(square.is_convex());
+        let notch = Polygon2::new(ring(&[
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (1.0, 0.5),
+            (2.0, 2.0),
+            (0.0, 2.0),
+        ]));
+        assert!(!notch.is_convex());
+        let star = Polygon2::new(ring(&[
+            (0.0, 3.0),
+            (1.76, -2.43),
+            (-2.85, 0.93),
+            (2.85, 0.93),
+            (-1.76, -2.43),
+        ]));
+        assert!(!star.is_convex());
+        assert!(!square_with_hole().is_convex());
+    }
+
+    #[test]
+    fn test_self_intersections() {
+        assert!(square_with_hole().is_simple());
+
+        let bowtie = Polygon2::new(ring(&[(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 2.0)]));
+        let hits = bowtie.self_intersections();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].first, EdgeRef { ring: 0, index: 0 });
+        assert_eq!(hits[0].second, EdgeRef { ring: 0, index: 2 });
+        assert_eq!(
+            hits[0].intersection,
+            SegmentIntersection::Point(Point2::new(1.0, 1.0))
+        );
+
+        // A hole touching the exterior is reported.
+        let touching = Polygon2::with_holes(
+            ring(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]),
+            vec![ring(&[(0.0, 2.0), (1.0, 1.0), (1.0, 3.0)])],
+        );
+        assert!(!touching.is_simple());
+    }
+}
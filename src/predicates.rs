@@ -0,0 +1,405 @@
+//! Robust geometric predicates after Shewchuk, "Adaptive Precision Floating-Point
+//! Arithmetic and Fast Robust Geometric Predicates" (1997).
+//!
+//! Each predicate first evaluates its determinant in plain floating point and returns
+//! it when the result is larger than Shewchuk's forward error bound, so its sign is
+//! certain. Otherwise the determinant is recomputed exactly with floating-point
+//! expansions, and the sign of the returned value is always correct.
+
+use crate::point::{Point2, Point3};
+
+/// Half an ulp of 1.0, the relative rounding error of one operation.
+const EPSILON: f64 = f64::EPSILON * 0.5;
+const CCW_ERRBOUND_A: f64 = (3.0 + 16.0 * EPSILON) * EPSILON;
+const O3D_ERRBOUND_A: f64 = (7.0 + 56.0 * EPSILON) * EPSILON;
+const ICC_ERRBOUND_A: f64 = (10.0 + 96.0 * EPSILON) * EPSILON;
+const ISP_ERRBOUND_A: f64 = (16.0 + 224.0 * EPSILON) * EPSILON;
+
+/// `a + b` as a rounded sum and its exact rounding error.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let bv = x - a;
+    let av = x - bv;
+    (x, (a - av) + (b - bv))
+}
+
+/// `two_sum` for `|a| >= |b|`.
+fn fast_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    (x, b - (x - a))
+}
+
+/// `a * b` as a rounded product and its exact rounding error.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    (x, a.mul_add(b, -x))
+}
+
+/// A floating-point expansion: an exact sum of non-overlapping components ordered by
+/// increasing magnitude, with zeros removed.
+#[derive(Debug, Clone)]
+struct Expansion(Vec<f64>);
+
+impl Expansion {
+    /// The exact difference `a - b`.
+    fn diff(a: f64, b: f64) -> Expansion {
+        let (x, y) = two_sum(a, -b);
+        Expansion([y, x].into_iter().filter(|&c| c != 0.0).collect())
+    }
+
+    /// Adds one component (Shewchuk's Grow-Expansion).
+    fn grow(&self, b: f64) -> Expansion {
+        let mut h = Vec::with_capacity(self.0.len() + 1);
+        let mut q = b;
+        for &e in &self.0 {
+            let (sum, err) = two_sum(q, e);
+            if err != 0.0 {
+                h.push(err);
+            }
+            q = sum;
+        }
+        if q != 0.0 {
+            h.push(q);
+        }
+        Expansion(h)
+    }
+
+    fn add(&self, rhs: &Expansion) -> Expansion {
+        rhs.0.iter().fold(self.clone(), |acc, &c| acc.grow(c))
+    }
+
+    fn sub(&self, rhs: &Expansion) -> Expansion {
+        rhs.0.iter().fold(self.clone(), |acc, &c| acc.grow(-c))
+    }
+
+    /// Multiplies by one component (Shewchuk's Scale-Expansion).
+    fn scale(&self, b: f64) -> Expansion {
+        let mut h = Vec::with_capacity(2 * self.0.len());
+        let Some((&first, rest)) = self.0.split_first() else {
+            return Expansion(h);
+        };
+        let (mut q, err) = two_product(first, b);
+        h.push(err);
+        for &e in rest {
+            let (product, product_err) = two_product(e, b);
+            let (sum, err) = two_sum(q, product_err);
+            h.push(err);
+            let (next, err) = fast_two_sum(product, sum);
+            h.push(err);
+            q = next;
+        }
+        h.push(q);
+        h.retain(|&c| c != 0.0);
+        Expansion(h)
+    }
+
+    fn mul(&self, rhs: &Expansion) -> Expansion {
+        rhs.0
+            .iter()
+            .fold(Expansion(vec![]), |acc, &c| acc.add(&self.scale(c)))
+    }
+
+    /// The most significant component, which carries the sign of the exact value.
+    fn approximate(&self) -> f64 {
+        self.0.last().copied().unwrap_or(0.0)
+    }
+}
+
+/// Positive when `a`, `b`, `c` run counter-clockwise, negative when clockwise and zero
+/// when they are collinear. The magnitude approximates twice the triangle's area.
+pub fn orient2d(a: &Point2, b: &Point2, c: &Point2) -> f64 {
+    let det_left = (a[0] - c[0]) * (b[1] - c[1]);
+    let det_right = (a[1] - c[1]) * (b[0] - c[0]);
+    let det = det_left - det_right;
+
+    let det_sum = if det_left > 0.0 {
+        if det_right <= 0.0 {
+            return det;
+        }
+        det_left + det_right
+    } else if det_left < 0.0 {
+        if det_right >= 0.0 {
+            return det;
+        }
+        -det_left - det_right
+    } else {
+        return det;
+    };
+    let bound = CCW_ERRBOUND_A * det_sum;
+    if det >= bound || -det >= bound {
+        return det;
+    }
+
+    let acx = Expansion::diff(a[0], c[0]);
+    let bcx = Expansion::diff(b[0], c[0]);
+    let acy = Expansion::diff(a[1], c[1]);
+    let bcy = Expansion::diff(b[1], c[1]);
+    acx.mul(&bcy).sub(&acy.mul(&bcx)).approximate()
+}
+
+/// Positive when `d` lies below the plane through `a`, `b`, `c`, where "below" means
+/// `a`, `b`, `c` appear counter-clockwise seen from above; zero when coplanar. The
+/// magnitude approximates six times the tetrahedron's volume.
+pub fn orient3d(a: &Point3, b: &Point3, c: &Point3, d: &Point3) -> f64 {
+    let (adx, ady, adz) = (a[0] - d[0], a[1] - d[1], a[2] - d[2]);
+    let (bdx, bdy, bdz) = (b[0] - d[0], b[1] - d[1], b[2] - d[2]);
+    let (cdx, cdy, cdz) = (c[0] - d[0], c[1] - d[1], c[2] - d[2]);
+
+    let (bdxcdy, cdxbdy) = (bdx * cdy, cdx * bdy);
+    let (cdxady, adxcdy) = (cdx * ady, adx * cdy);
+    let (adxbdy, bdxady) = (adx * bdy, bdx * ady);
+    let det = adz * (bdxcdy - cdxbdy) + bdz * (cdxady - adxcdy) + cdz * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * adz.abs()
+        + (cdxady.abs() + adxcdy.abs()) * bdz.abs()
+        + (adxbdy.abs() + bdxady.abs()) * cdz.abs();
+    let bound = O3D_ERRBOUND_A * permanent;
+    if det > bound || -det > bound {
+        return det;
+    }
+
+    let [adx, ady, adz] = [0, 1, 2].map(|k| Expansion::diff(a[k], d[k]));
+    let [bdx, bdy, bdz] = [0, 1, 2].map(|k| Expansion::diff(b[k], d[k]));
+    let [cdx, cdy, cdz] = [0, 1, 2].map(|k| Expansion::diff(c[k], d[k]));
+    let bc = bdx.mul(&cdy).sub(&cdx.mul(&bdy));
+    let ca = cdx.mul(&ady).sub(&adx.mul(&cdy));
+    let ab = adx.mul(&bdy).sub(&bdx.mul(&ady));
+    adz.mul(&bc)
+        .add(&bdz.mul(&ca))
+        .add(&cdz.mul(&ab))
+        .approximate()
+}
+
+/// Positive when `d` lies inside the circle through `a`, `b`, `c`, negative outside and
+/// zero on it. `a`, `b`, `c` must run counter-clockwise, or the sign is reversed.
+pub fn incircle(a: &Point2, b: &Point2, c: &Point2, d: &Point2) -> f64 {
+    let (adx, ady) = (a[0] - d[0], a[1] - d[1]);
+    let (bdx, bdy) = (b[0] - d[0], b[1] - d[1]);
+    let (cdx, cdy) = (c[0] - d[0], c[1] - d[1]);
+
+    let (bdxcdy, cdxbdy) = (bdx * cdy, cdx * bdy);
+    let alift = adx * adx + ady * ady;
+    let (cdxady, adxcdy) = (cdx * ady, adx * cdy);
+    let blift = bdx * bdx + bdy * bdy;
+    let (adxbdy, bdxady) = (adx * bdy, bdx * ady);
+    let clift = cdx * cdx + cdy * cdy;
+
+    let det = alift * (bdxcdy - cdxbdy) + blift * (cdxady - adxcdy) + clift * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * alift
+        + (cdxady.abs() + adxcdy.abs()) * blift
+        + (adxbdy.abs() + bdxady.abs()) * clift;
+    let bound = ICC_ERRBOUND_A * permanent;
+    if det > bound || -det > bound {
+        return det;
+    }
+
+    let [adx, ady] = [0, 1].map(|k| Expansion::diff(a[k], d[k]));
+    let [bdx, bdy] = [0, 1].map(|k| Expansion::diff(b[k], d[k]));
+    let [cdx, cdy] = [0, 1].map(|k| Expansion::diff(c[k], d[k]));
+    let alift = adx.mul(&adx).add(&ady.mul(&ady));
+    let blift = bdx.mul(&bdx).add(&bdy.mul(&bdy));
+    let clift = cdx.mul(&cdx).add(&cdy.mul(&cdy));
+    let bc = bdx.mul(&cdy).sub(&cdx.mul(&bdy));
+    let ca = cdx.mul(&ady).sub(&adx.mul(&cdy));
+    let ab = adx.mul(&bdy).sub(&bdx.mul(&ady));
+    alift
+        .mul(&bc)
+        .add(&blift.mul(&ca))
+        .add(&clift.mul(&ab))
+        .approximate()
+}
+
+/// Positive when `e` lies inside the sphere through `a`, `b`, `c`, `d`, negative outside
+/// and zero on it. The four points must have positive `orient3d`, or the sign is
+/// reversed.
+pub fn insphere(a: &Point3, b: &Point3, c: &Point3, d: &Point3, e: &Point3) -> f64 {
+    let (aex, aey, aez) = (a[0] - e[0], a[1] - e[1], a[2] - e[2]);
+    let (bex, bey, bez) = (b[0] - e[0], b[1] - e[1], b[2] - e[2]);
+    let (cex, cey, cez) = (c[0] - e[0], c[1] - e[1], c[2] - e[2]);
+    let (dex, dey, dez) = (d[0] - e[0], d[1] - e[1], d[2] - e[2]);
+
+    let (aexbey, bexaey) = (aex * bey, bex * aey);
+    let (bexcey, cexbey) = (bex * cey, cex * bey);
+    let (cexdey, dexcey) = (cex * dey, dex * cey);
+    let (dexaey, aexdey) = (dex * aey, aex * dey);
+    let (aexcey, cexaey) = (aex * cey, cex * aey);
+    let (bexdey, dexbey) = (bex * dey, dex * bey);
+    let ab = aexbey - bexaey;
+    let bc = bexcey - cexbey;
+    let cd = cexdey - dexcey;
+    let da = dexaey - aexdey;
+    let ac = aexcey - cexaey;
+    let bd = bexdey - dexbey;
+
+    let abc = aez * bc - bez * ac + cez * ab;
+    let bcd = bez * cd - cez * bd + dez * bc;
+    let cda = cez * da + dez * ac + aez * cd;
+    let dab = dez * ab + aez * bd + bez * da;
+    let alift = aex * aex + aey * aey + aez * aez;
+    let blift = bex * bex + bey * bey + bez * bez;
+    let clift = cex * cex + cey * cey + cez * cez;
+    let dlift = dex * dex + dey * dey + dez * dez;
+    let det = (dlift * abc - clift * dab) + (blift * cda - alift * bcd);
+
+    let (aez, bez, cez, dez) = (aez.abs(), bez.abs(), cez.abs(), dez.abs());
+    let [aexbey, bexaey, bexcey, cexbey, cexdey, dexcey, dexaey, aexdey, aexcey, cexaey, bexdey, dexbey] =
+        [
+            aexbey, bexaey, bexcey, cexbey, cexdey, dexcey, dexaey, aexdey, aexcey, cexaey, bexdey,
+            dexbey,
+        ]
+        .map(f64::abs);
+    let permanent = ((cexdey + dexcey) * bez + (dexbey + bexdey) * cez + (bexcey + cexbey) * dez)
+        * alift
+        + ((dexaey + aexdey) * cez + (aexcey + cexaey) * dez + (cexdey + dexcey) * aez) * blift
+        + ((aexbey + bexaey) * dez + (bexdey + dexbey) * aez + (dexaey + aexdey) * bez) * clift
+        + ((bexcey + cexbey) * aez + (cexaey + aexcey) * bez + (aexbey + bexaey) * cez) * dlift;
+    let bound = ISP_ERRBOUND_A * permanent;
+    if det > bound || -det > bound {
+        return det;
+    }
+
+    let [aex, aey, aez] = [0, 1, 2].map(|k| Expansion::diff(a[k], e[k]));
+    let [bex, bey, bez] = [0, 1, 2].map(|k| Expansion::diff(b[k], e[k]));
+    let [cex, cey, cez] = [0, 1, 2].map(|k| Expansion::diff(c[k], e[k]));
+    let [dex, dey, dez] = [0, 1, 2].map(|k| Expansion::diff(d[k], e[k]));
+    let cross =
+        |p: &Expansion, q: &Expansion, r: &Expansion, s: &Expansion| p.mul(q).sub(&r.mul(s));
+    let ab = cross(&aex, &bey, &bex, &aey);
+    let bc = cross(&bex, &cey, &cex, &bey);
+    let cd = cross(&cex, &dey, &dex, &cey);
+    let da = cross(&dex, &aey, &aex, &dey);
+    let ac = cross(&aex, &cey, &cex, &aey);
+    let bd = cross(&bex, &dey, &dex, &bey);
+    let abc = aez.mul(&bc).sub(&bez.mul(&ac)).add(&cez.mul(&ab));
+    let bcd = bez.mul(&cd).sub(&cez.mul(&bd)).add(&dez.mul(&bc));
+    let cda = cez.mul(&da).add(&dez.mul(&ac)).add(&aez.mul(&cd));
+    let dab = dez.mul(&ab).add(&aez.mul(&bd)).add(&bez.mul(&da));
+    let lift = |x: &Expansion, y: &Expansion, z: &Expansion| x.mul(x).add(&y.mul(y)).add(&z.mul(z));
+    let alift = lift(&aex, &aey, &aez);
+    let blift = lift(&bex, &bey, &bez);
+    let clift = lift(&cex, &cey, &cez);
+    let dlift = lift(&dex, &dey, &dez);
+    dlift
+        .mul(&abc)
+        .sub(&clift.mul(&dab))
+        .add(&blift.mul(&cda))
+        .sub(&alift.mul(&bcd))
+        .approximate()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ULP: f64 = f64::EPSILON * 0.5;
+
+    #[test]
+    fn test_orient2d_near_collinear() {
+        // Kettner et al.'s classroom example: `p` sweeps a 256 x 256 grid of adjacent
+        // floats around (0.5, 0.5), and the exact sign of orient2d(p, q, r) for q, r on
+        // the line y = x is sign(py - px).
+        let (q, r) = (Point2::new(12.0, 12.0), Point2::new(24.0, 24.0));
+        let mut naive_wrong = 0;
+        for i in 0..256i32 {
+            for j in 0..256 {
+                let p = Point2::new(0.5 + i as f64 * ULP, 0.5 + j as f64 * ULP);
+                let expected = (j - i).signum();
+                let det = orient2d(&p, &q, &r);
+                if expected == 0 {
+                    assert_eq!(det, 0.0);
+                } else {
+                    assert_eq!(det.signum() as i32, expected);
+                }
+                let naive = (q[0] - p[0]) * (r[1] - p[1]) - (q[1] - p[1]) * (r[0] - p[0]);
+                let naive = if naive == 0.0 { 0 } else { naive.signum() as i32 };
+                if naive != expected {
+                    naive_wrong += 1;
+                }
+            }
+        }
+        assert!(naive_wrong > 0);
+    }
+
+    #[test]
+    fn test_orient3d_near_coplanar() {
+        // The plane x = y, with `d` swept over adjacent floats around it.
+        let (a, b, c) = (
+            Point3::new(12.0, 12.0, 0.0),
+            Point3::new(24.0, 24.0, 0.0),
+            Point3::new(0.0, 0.0, 12.0),
+        );
+        let side = orient3d(&a, &b, &c, &Point3::new(1.0, 0.0, 0.0)).signum();
+        for i in 0..64 {
+            for j in 0..64 {
+                let d = Point3::new(0.5 + i as f64 * ULP, 0.5 + j as f64 * ULP, 0.5);
+                let det = orient3d(&a, &b, &c, &d);
+                match i.cmp(&j) {
+                    std::cmp::Ordering::Equal => assert_eq!(det, 0.0),
+                    std::cmp::Ordering::Greater => assert_eq!(det.signum(), side),
+                    std::cmp::Ordering::Less => assert_eq!(det.signum(), -side),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_incircle_near_cocircular() {
+        // The circle of radius 1 about (2^30, 2^30), where the lifted coordinates need
+        // about 120 bits.
+        let o = (1u64 << 30) as f64;
+        let a = Point2::new(o + 1.0, o);
+        let b = Point2::new(o, o + 1.0);
+        let c = Point2::new(o - 1.0, o);
+        let ulp = o * f64::EPSILON;
+        assert_eq!(incircle(&a, &b, &c, &Point2::new(o, o - 1.0)), 0.0);
+        assert!(incircle(&a, &b, &c, &Point2::new(o, o - 1.0 + ulp)) > 0.0);
+        assert!(incircle(&a, &b, &c, &Point2::new(o, o - 1.0 - ulp)) < 0.0);
+        // Reversing the triangle's orientation flips the sign.
+        assert!(incircle(&c, &b, &a, &Point2::new(o, o - 1.0 + ulp)) < 0.0);
+
+        // Lattice points on a circle of radius 5.
+        let (a, b, c) = (
+            Point2::new(5.0, 0.0),
+            Point2::new(3.0, 4.0),
+            Point2::new(-4.0, 3.0),
+        );
+        assert_eq!(incircle(&a, &b, &c, &Point2::new(0.0, -5.0)), 0.0);
+        assert!(incircle(&a, &b, &c, &Point2::new(4.0 - ULP * 4.0, -3.0)) > 0.0);
+    }
+
+    #[test]
+    fn test_insphere_near_cospherical() {
+        let o = (1u64 << 26) as f64;
+        let p = |x: f64, y: f64, z: f64| Point3::new(o + x, o + y, o + z);
+        let (mut a, mut b) = (p(1.0, 0.0, 0.0), p(0.0, 1.0, 0.0));
+        let (c, d) = (p(0.0, 0.0, 1.0), p(-1.0, 0.0, 0.0));
+        if orient3d(&a, &b, &c, &d) < 0.0 {
+            std::mem::swap(&mut a, &mut b);
+        }
+        let ulp = o * f64::EPSILON;
+        assert_eq!(insphere(&a, &b, &c, &d, &p(0.0, -1.0, 0.0)), 0.0);
+        assert_eq!(insphere(&a, &b, &c, &d, &p(0.0, 0.0, -1.0)), 0.0);
+        assert!(insphere(&a, &b, &c, &d, &p(0.0, 0.0, -1.0 + ulp)) > 0.0);
+        assert!(insphere(&a, &b, &c, &d, &p(0.0, 0.0, -1.0 - ulp)) < 0.0);
+        assert!(insphere(&a, &b, &c, &d, &p(0.0, 0.0, 0.0)) > 0.0);
+    }
+
+    #[test]
+    fn test_well_conditioned() {
+        let (a, b, c) = (
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(0.0, 1.0),
+        );
+        assert_eq!(orient2d(&a, &b, &c), 1.0);
+        assert_eq!(orient2d(&a, &c, &b), -1.0);
+        assert!(incircle(&a, &b, &c, &Point2::new(0.5, 0.5)) > 0.0);
+        let origin = Point3::new(0.0, 0.0, 0.0);
+        let (x, y, z) = (
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        );
+        // `z` is above the counter-clockwise triangle `origin, x, y`.
+        assert!(orient3d(&origin, &x, &y, &z) < 0.0);
+    }
+}
@@ -0,0 +1,34 @@
+use crate::point::{Point2, Point3};
+use crate::vec::{Vec2, Vec3};
+
+#[derive(Debug, Clone)]
+pub struct Ray2 {
+    pub origin: Point2,
+    pub direction: Vec2<f64>,
+}
+
+impl Ray2 {
+    pub fn new(origin: Point2, direction: Vec2<f64>) -> Ray2 {
+        Ray2 { origin, direction }
+    }
+
+    pub fn at(&self, t: f64) -> Point2 {
+        self.origin + self.direction.clone() * t
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Ray3 {
+    pub origin: Point3,
+    pub direction: Vec3<f64>,
+}
+
+impl Ray3 {
+    pub fn new(origin: Point3, direction: Vec3<f64>) -> Ray3 {
+        Ray3 { origin, direction }
+    }
+
+    pub fn at(&self, t: f64) -> Point3 {
+        self.origin + self.direction.clone() * t
+    }
+}
@@ -0,0 +1,422 @@
+use crate::image::{Image, Pixel};
+use crate::path::Path2;
+use crate::point::Point2;
+use crate::polygon::FillRule;
+
+/// Scanline rasterizer for outlines made of line segments, computing exact anti-aliased
+/// coverage the way font rasterizers do.
+///
+/// Each edge adds, to every pixel it passes through, the signed area it covers to the right of
+/// itself within the pixel's row, and hands the rest of its height to the next pixel. A running
+/// sum along each row then gives every pixel the winding number averaged over its area, which
+/// the fill rule turns into coverage. Pixel `(x, y)` covers `[x, x + 1] x [y, y + 1]`.
+#[derive(Debug, Clone)]
+pub struct Rasterizer {
+    width: usize,
+    height: usize,
+    /// Signed area per pixel, two columns wider than the image so edges on its right border
+    /// have somewhere to put their remainder.
+    cells: Vec<f64>,
+}
+
+impl Rasterizer {
+    pub fn new(width: usize, height: usize) -> Rasterizer {
+        Rasterizer {
+            width,
+            height,
+            cells: vec![0.0; (width + 2) * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Removes all edges added so far.
+    pub fn clear(&mut self) {
+        self.cells.fill(0.0);
+    }
+
+    /// Adds the edge from `a` to `b`. Edges may run outside the image; the parts left of it
+    /// still count towards the winding of the pixels to their right.
+    pub fn add_line(&mut self, a: &Point2, b: &Point2) {
+        let (w, h) = (self.width as f64, self.height as f64);
+        // Rows above and below the image are never seen.
+        let (t0, t1) = if a[1] == b[1] {
+            return;
+        } else {
+            let ta = (0.0 - a[1]) / (b[1] - a[1]);
+            let tb = (h - a[1]) / (b[1] - a[1]);
+            (ta.min(tb).max(0.0), ta.max(tb).min(1.0))
+        };
+        if t0 >= t1 {
+            return;
+        }
+        let at = |t: f64| {
+            if t == 0.0 {
+                *a
+            } else if t == 1.0 {
+                *b
+            } else {
+                Point2::new(a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t)
+            }
+        };
+        // Split where the edge crosses the left and right borders, then flatten the pieces
+        // outside onto them: the winding inside is unchanged.
+        let mut ts = vec![t0, t1];
+        if a[0] != b[0] {
+            for x in [0.0, w] {
+                let t = (x - a[0]) / (b[0] - a[0]);
+                if t0 < t && t < t1 {
+                    ts.push(t);
+                }
+            }
+        }
+        ts.sort_by(f64::total_cmp);
+        for pair in ts.windows(2) {
+            let clamp = |p: Point2| Point2::new(p[0].clamp(0.0, w), p[1].clamp(0.0, h));
+            self.accumulate(clamp(at(pair[0])), clamp(at(pair[1])));
+        }
+    }
+
+    /// Adds a closed ring; the edge back to the first point is implied.
+    pub fn add_polygon(&mut self, ring: &[Point2]) {
+        for (i, a) in ring.iter().enumerate() {
+            self.add_line(a, &ring[(i + 1) % ring.len()]);
+        }
+    }
+
+    /// Adds the outline of `path`, flattened to within `tolerance`. Open subpaths are closed,
+    /// as filling does.
+    pub fn add_path(&mut self, path: &Path2, tolerance: f64) {
+        for polyline in path.flatten(tolerance) {
+            self.add_polygon(&polyline.points);
+        }
+    }
+
+    /// Adds the signed area of an edge lying within the image, one row at a time.
+    fn accumulate(&mut self, a: Point2, b: Point2) {
+        if a[1] == b[1] {
+            return;
+        }
+        let (sign, top, bottom) = if a[1] < b[1] {
+            (1.0, a, b)
+        } else {
+            (-1.0, b, a)
+        };
+        let stride = self.width + 2;
+        let dxdy = (bottom[0] - top[0]) / (bottom[1] - top[1]);
+        let (x_min, x_max) = (a[0].min(b[0]), a[0].max(b[0]));
+        let mut x = top[0];
+        let first = top[1].floor() as usize;
+        let end = (bottom[1].ceil() as usize).min(self.height);
+        for y in first..end {
+            let row = &mut self.cells[y * stride..(y + 1) * stride];
+            let y_next = (y as f64 + 1.0).min(bottom[1]);
+            let dy = y_next - (y as f64).max(top[1]);
+            // Stepping `x` would drift; off the edge's extent it could even leave the image.
+            let x_next = if y_next == bottom[1] {
+                bottom[0]
+            } else {
+                (top[0] + dxdy * (y_next - top[1])).clamp(x_min, x_max)
+            };
+            let d = dy * sign;
+            let (x0, x1) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let x0_floor = x0.floor();
+            let i0 = x0_floor as usize;
+            let i1 = x1.ceil() as usize;
+            if i1 <= i0 + 1 {
+                // Within one pixel: the part left of the edge's middle goes to the next one.
+                let mid = 0.5 * (x + x_next) - x0_floor;
+                row[i0] += d - d * mid;
+                row[i0 + 1] += d * mid;
+            } else {
+                // Across several pixels: the trapezoid right of the edge in each of them.
+                let s = 1.0 / (x1 - x0);
+                let f0 = x0 - x0_floor;
+                let a0 = 0.5 * s * (1.0 - f0) * (1.0 - f0);
+                let f1 = x1 - x1.ceil() + 1.0;
+                let a_end = 0.5 * s * f1 * f1;
+                row[i0] += d * a0;
+                if i1 == i0 + 2 {
+                    row[i0 + 1] += d * (1.0 - a0 - a_end);
+                } else {
+                    let a1 = s * (1.5 - f0);
+                    row[i0 + 1] += d * (a1 - a0);
+                    for cell in &mut row[i0 + 2..i1 - 1] {
+                        *cell += d * s;
+                    }
+                    let a2 = a1 + (i1 - i0 - 3) as f64 * s;
+                    row[i1 - 1] += d * (1.0 - a2 - a_end);
+                }
+                row[i1] += d * a_end;
+            }
+            x = x_next;
+        }
+    }
+
+    /// Coverage of every pixel under `rule`, row by row. Where edges of different rings pass
+    /// through the same pixel the average winding stands in for the exact coverage.
+    pub fn coverage(&self, rule: FillRule) -> Vec<f32> {
+        let stride = self.width + 2;
+        let mut coverage = Vec::with_capacity(self.width * self.height);
+        for row in self.cells.chunks(stride) {
+            let mut w = 0.0f64;
+            for &cell in &row[..self.width] {
+                w += cell;
+                let c = match rule {
+                    FillRule::NonZero => w.abs().min(1.0),
+                    FillRule::EvenOdd => {
+                        let c = w.abs() % 2.0;
+                        if c > 1.0 {
+                            2.0 - c
+                        } else {
+                            c
+                        }
+                    }
+                };
+                coverage.push(c as f32);
+            }
+        }
+        coverage
+    }
+
+    /// Blends `color` into `image` by the coverage under `rule`. The image is expected to be
+    /// the rasterizer's size; pixels outside either are skipped.
+    pub fn fill<P: Pixel>(&self, image: &mut Image<P>, rule: FillRule, color: P) {
+        let coverage = self.coverage(rule);
+        let width = self.width.min(image.width());
+        let height = self.height.min(image.height());
+        for y in 0..height {
+            for x in 0..width {
+                let c = coverage[y * self.width + x];
+                if c > 0.0 {
+                    image.blend(x as i32, y as i32, color, c);
+                }
+            }
+        }
+    }
+}
+
+/// Fills the rings of a polygon into `image` with anti-aliasing.
+pub fn fill_polygon<P: Pixel>(
+    image: &mut Image<P>,
+    rings: &[Vec<Point2>],
+    rule: FillRule,
+    color: P,
+) {
+    let mut rasterizer = Rasterizer::new(image.width(), image.height());
+    for ring in rings {
+        rasterizer.add_polygon(ring);
+    }
+    rasterizer.fill(image, rule, color);
+}
+
+/// Fills `path` into `image` with anti-aliasing, flattening curves to within `tolerance`.
+pub fn fill_path<P: Pixel>(
+    image: &mut Image<P>,
+    path: &Path2,
+    rule: FillRule,
+    color: P,
+    tolerance: f64,
+) {
+    let mut rasterizer = Rasterizer::new(image.width(), image.height());
+    rasterizer.add_path(path, tolerance);
+    rasterizer.fill(image, rule, color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hull::convex_hull_2d;
+    use crate::image::{Canvas, Rgba32F, Rgba8};
+    use crate::path::StrokeStyle;
+    use crate::polygon::ring_signed_area;
+    use crate::test_util::{assert_close, random_values};
+
+    /// The part of a convex polygon inside an axis-aligned box, by clipping against each side.
+    fn clip_to_box(ring: &[Point2], min: [f64; 2], max: [f64; 2]) -> Vec<Point2> {
+        let mut ring = ring.to_vec();
+        for (axis, bound, keep_below) in [
+            (0, min[0], false),
+            (0, max[0], true),
+            (1, min[1], false),
+            (1, max[1], true),
+        ] {
+            let inside = |p: &Point2| (p[axis] <= bound) == keep_below || p[axis] == bound;
+            let mut clipped = Vec::new();
+            for (i, p) in ring.iter().enumerate() {
+                let q = &ring[(i + 1) % ring.len()];
+                if inside(p) {
+                    clipped.push(*p);
+                }
+                if inside(p) != inside(q) {
+                    let t = (bound - p[axis]) / (q[axis] - p[axis]);
+                    clipped.push(Point2::new(
+                        p[0] + (q[0] - p[0]) * t,
+                        p[1] + (q[1] - p[1]) * t,
+                    ));
+                }
+            }
+            ring = clipped;
+            if ring.is_empty() {
+                break;
+            }
+        }
+        ring
+    }
+
+    fn render(rasterizer: &Rasterizer, rule: FillRule) -> String {
+        let mut text = String::new();
+        for row in rasterizer.coverage(rule).chunks(rasterizer.width()) {
+            for &c in row {
+                text.push(match (c * 10.0).round() as u32 {
+                    0 => '.',
+                    10 => '#',
+                    n => char::from(b'0' + n as u8),
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    #[test]
+    fn test_rectangle() {
+        let ring = vec![
+            Point2::new(1.25, 1.5),
+            Point2::new(4.75, 1.5),
+            Point2::new(4.75, 3.0),
+            Point2::new(1.25, 3.0),
+        ];
+        let mut rasterizer = Rasterizer::new(6, 4);
+        rasterizer.add_polygon(&ring);
+        let expected = "......\n.4554.\n.8##8.\n......\n";
+        assert_eq!(render(&rasterizer, FillRule::NonZero), expected);
+
+        // Either orientation gives the same coverage.
+        let mut reversed = Rasterizer::new(6, 4);
+        reversed.add_polygon(&ring.iter().rev().copied().collect::<Vec<_>>());
+        assert_eq!(
+            rasterizer.coverage(FillRule::NonZero),
+            reversed.coverage(FillRule::NonZero)
+        );
+        let c = rasterizer.coverage(FillRule::EvenOdd);
+        assert_close(c[6 + 1] as f64, 0.75 * 0.5, 1e-6);
+        assert_close(c[2 * 6 + 4] as f64, 0.75, 1e-6);
+    }
+
+    #[test]
+    fn test_exact_coverage() {
+        // Each pixel of a convex polygon is covered by exactly the area of the polygon inside
+        // it, including polygons reaching off the image.
+        let values = random_values(2 * 8 * 60, 5);
+        for v in values.chunks(16) {
+            let points: Vec<Point2> = v
+                .chunks(2)
+                .map(|p| Point2::new(p[0] * 14.0 - 2.0, p[1] * 10.0 - 2.0))
+                .collect();
+            let hull = convex_hull_2d(&points);
+            let mut rasterizer = Rasterizer::new(10, 7);
+            rasterizer.add_polygon(&hull);
+            let coverage = rasterizer.coverage(FillRule::NonZero);
+            for y in 0..7 {
+                for x in 0..10 {
+                    let (x0, y0) = (x as f64, y as f64);
+                    let part = clip_to_box(&hull, [x0, y0], [x0 + 1.0, y0 + 1.0]);
+                    let area = ring_signed_area(&part).abs();
+                    assert_close(coverage[y * 10 + x] as f64, area, 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fill_rules() {
+        let square = |min: f64, max: f64| {
+            vec![
+                Point2::new(min, min),
+                Point2::new(max, min),
+                Point2::new(max, max),
+                Point2::new(min, max),
+            ]
+        };
+        let mut rasterizer = Rasterizer::new(8, 8);
+        rasterizer.add_polygon(&square(1.0, 7.0));
+        rasterizer.add_polygon(&square(2.5, 5.5));
+        let expected_even_odd = "\
+            ........\n\
+            .######.\n\
+            .#8558#.\n\
+            .#5..5#.\n\
+            .#5..5#.\n\
+            .#8558#.\n\
+            .######.\n\
+            ........\n";
+        assert_eq!(render(&rasterizer, FillRule::EvenOdd), expected_even_odd);
+        let expected_non_zero = "\
+            ........\n\
+            .######.\n\
+            .######.\n\
+            .######.\n\
+            .######.\n\
+            .######.\n\
+            .######.\n\
+            ........\n";
+        assert_eq!(render(&rasterizer, FillRule::NonZero), expected_non_zero);
+
+        // A hole wound the other way is empty under both rules.
+        rasterizer.clear();
+        rasterizer.add_polygon(&square(1.0, 7.0));
+        let hole: Vec<Point2> = square(2.5, 5.5).into_iter().rev().collect();
+        rasterizer.add_polygon(&hole);
+        assert_eq!(render(&rasterizer, FillRule::NonZero), expected_even_odd);
+
+        // A pentagram has a hole in the middle only under even-odd.
+        let star: Vec<Point2> = (0..5)
+            .map(|i| {
+                let angle = i as f64 * 4.0 * std::f64::consts::PI / 5.0;
+                Point2::new(16.0 + 15.0 * angle.sin(), 16.0 - 15.0 * angle.cos())
+            })
+            .collect();
+        let mut image = Canvas::new(32, 32);
+        let rings = [star];
+        fill_polygon(&mut image, &rings, FillRule::EvenOdd, Rgba8::WHITE);
+        assert_eq!(image.get(16, 16).unwrap().0[3], 0);
+        assert_eq!(image.get(16, 4).unwrap().0[3], 255);
+        fill_polygon(&mut image, &rings, FillRule::NonZero, Rgba8::WHITE);
+        assert_eq!(image.get(16, 16).unwrap().0[3], 255);
+    }
+
+    #[test]
+    fn test_fill_path() {
+        // A circle's total coverage is its area.
+        let mut path = Path2::new();
+        path.move_to(Point2::new(30.0, 20.0));
+        path.arc_to(12.5, 12.5, 0.0, false, true, Point2::new(5.0, 20.0));
+        path.arc_to(12.5, 12.5, 0.0, false, true, Point2::new(30.0, 20.0));
+        path.close();
+        let mut image = Image::filled(40, 40, Rgba32F::default());
+        let color = Rgba32F::new(1.0, 1.0, 1.0, 1.0);
+        fill_path(&mut image, &path, FillRule::NonZero, color, 1e-4);
+        let total: f64 = image.pixels().iter().map(|p| p.0[3] as f64).sum();
+        assert_close(total, std::f64::consts::PI * 12.5 * 12.5, 1e-2);
+
+        // The rings of a stroke fill as the stroke.
+        let mut line = Path2::new();
+        line.move_to(Point2::new(2.0, 5.0));
+        line.line_to(Point2::new(14.0, 5.0));
+        let mut rasterizer = Rasterizer::new(16, 10);
+        for ring in line.stroke(&StrokeStyle::new(4.0), 1e-3) {
+            rasterizer.add_polygon(&ring);
+        }
+        let coverage = rasterizer.coverage(FillRule::NonZero);
+        let total: f32 = coverage.iter().sum();
+        assert_close(total as f64, 12.0 * 4.0, 1e-4);
+        assert_eq!(coverage[5 * 16 + 8], 1.0);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::aabb::Aabb2;
+use crate::point::Point2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentIntersection {
+    /// The segments cross or touch at a single point.
+    Point(Point2),
+    /// The segments are collinear and share the sub-segment between the two points.
+    Overlap(Point2, Point2),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment2 {
+    pub a: Point2,
+    pub b: Point2,
+}
+
+impl Segment2 {
+    pub fn new(a: Point2, b: Point2) -> Segment2 {
+        Segment2 { a, b }
+    }
+
+    pub fn length(&self) -> f64 {
+        (self.b - self.a).length()
+    }
+
+    pub fn at(&self, t: f64) -> Point2 {
+        self.a + (self.b - self.a) * t
+    }
+
+    pub fn aabb(&self) -> Aabb2 {
+        Aabb2::from_points([self.a, self.b])
+    }
+
+    /// Parameter of the point on the segment closest to `p`, clamped to `[0, 1]`.
+    pub fn closest_parameter(&self, p: &Point2) -> f64 {
+        let d = self.b - self.a;
+        let len2 = d.square_length();
+        if len2 == 0.0 {
+            return 0.0;
+        }
+        ((*p - self.a).dot(&d) / len2).clamp(0.0, 1.0)
+    }
+
+    pub fn closest_point(&self, p: &Point2) -> Point2 {
+        self.at(self.closest_parameter(p))
+    }
+
+    pub fn distance(&self, p: &Point2) -> f64 {
+        (self.closest_point(p) - *p).length()
+    }
+
+    pub fn intersection(&self, rhs: &Segment2) -> Option<SegmentIntersection> {
+        let r = self.b - self.a;
+        let s = rhs.b - rhs.a;
+        let qp = rhs.a - self.a;
+        let denom = r.perp_dot(&s);
+
+        if denom == 0.0 {
+            if qp.perp_dot(&r) != 0.0 {
+                // Parallel but not collinear.
+                return None;
+            }
+            let r2 = r.square_length();
+            if r2 == 0.0 {
+                // `self` is a single point.
+                return if rhs.closest_point(&self.a) == self.a {
+                    Some(SegmentIntersection::Point(self.a))
+                } else {
+                    None
+                };
+            }
+            let t0 = qp.dot(&r) / r2;
+            let t1 = t0 + s.dot(&r) / r2;
+            let (lo, hi) = (t0.min(t1).max(0.0), t0.max(t1).min(1.0));
+            if lo > hi {
+                return None;
+            }
+            return if lo == hi {
+                Some(SegmentIntersection::Point(self.at(lo)))
+            } else {
+                Some(SegmentIntersection::Overlap(self.at(lo), self.at(hi)))
+            };
+        }
+
+        let t = qp.perp_dot(&s) / denom;
+        let u = qp.perp_dot(&r) / denom;
+        if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+            Some(SegmentIntersection::Point(self.at(t)))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg(ax: f64, ay: f64, bx: f64, by: f64) -> Segment2 {
+        Segment2::new(Point2::new(ax, ay), Point2::new(bx, by))
+    }
+
+    #[test]
+    fn test_crossing() {
+        let a = seg(0.0, 0.0, 2.0, 2.0);
+        let b = seg(0.0, 2.0, 2.0, 0.0);
+        assert_eq!(
+            a.intersection(&b),
+            Some(SegmentIntersection::Point(Point2::new(1.0, 1.0)))
+        );
+        assert_eq!(a.intersection(&seg(3.0, 0.0, 3.0, 5.0)), None);
+    }
+
+    #[test]
+    fn test_collinear() {
+        let a = seg(0.0, 0.0, 2.0, 0.0);
+        assert_eq!(
+            a.intersection(&seg(3.0, 0.0, 1.0, 0.0)),
+            Some(SegmentIntersection::Overlap(
+                Point2::new(1.0, 0.0),
+                Point2::new(2.0, 0.0)
+            ))
+        );
+        assert_eq!(
+            a.intersection(&seg(2.0, 0.0, 4.0, 0.0)),
+            Some(SegmentIntersection::Point(Point2::new(2.0, 0.0)))
+        );
+        assert_eq!(a.intersection(&seg(0.0, 1.0, 2.0, 1.0)), None);
+    }
+
+    #[test]
+    fn test_closest_point() {
+        let a = seg(0.0, 0.0, 2.0, 0.0);
+        assert_eq!(
+            a.closest_point(&Point2::new(1.0, 5.0)),
+            Point2::new(1.0, 0.0)
+        );
+        assert_eq!(
+            a.closest_point(&Point2::new(-1.0, 1.0)),
+            Point2::new(0.0, 0.0)
+        );
+        assert_eq!(a.distance(&Point2::new(3.0, 0.0)), 1.0);
+    }
+}
@@ -0,0 +1,570 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::aabb::{Aabb2, Aabb3};
+use crate::point::{Point2, Point3};
+use crate::vec::{Vec2, Vec3};
+
+/// Box types a `SpatialHash` can bucket, with integer cell keys of matching dimension.
+pub trait GridBounds: Copy {
+    type Point: Copy;
+    type Cell: Clone + Eq + Hash;
+
+    fn from_point(p: &Self::Point) -> Self;
+    fn overlaps(&self, rhs: &Self) -> bool;
+    fn is_finite(&self) -> bool;
+    /// The cell containing `p`: its coordinates divided by `cell_size`, rounded down.
+    fn cell_of(p: &Self::Point, cell_size: f64) -> Self::Cell;
+    /// The lowest and highest cells the box touches.
+    fn cell_range(&self, cell_size: f64) -> (Self::Cell, Self::Cell);
+    /// Every cell in the inclusive range `min..=max`.
+    fn cells_between(min: &Self::Cell, max: &Self::Cell) -> Vec<Self::Cell>;
+    /// Number of cells in the inclusive range `min..=max`, saturating.
+    fn cell_count(min: &Self::Cell, max: &Self::Cell) -> u64;
+    /// Componentwise maximum of two cells.
+    fn cell_max(a: &Self::Cell, b: &Self::Cell) -> Self::Cell;
+}
+
+impl GridBounds for Aabb2 {
+    type Point = Point2;
+    type Cell = Vec2<i32>;
+
+    fn from_point(p: &Point2) -> Aabb2 {
+        Aabb2::new(*p, *p)
+    }
+
+    fn overlaps(&self, rhs: &Aabb2) -> bool {
+        Aabb2::overlaps(self, rhs)
+    }
+
+    fn is_finite(&self) -> bool {
+        (0..2).all(|i| self.min[i].is_finite() && self.max[i].is_finite())
+    }
+
+    fn cell_of(p: &Point2, cell_size: f64) -> Vec2<i32> {
+        vec2![
+            (p[0] / cell_size).floor() as i32,
+            (p[1] / cell_size).floor() as i32
+        ]
+    }
+
+    fn cell_range(&self, cell_size: f64) -> (Vec2<i32>, Vec2<i32>) {
+        (
+            Aabb2::cell_of(&self.min, cell_size),
+            Aabb2::cell_of(&self.max, cell_size),
+        )
+    }
+
+    fn cells_between(min: &Vec2<i32>, max: &Vec2<i32>) -> Vec<Vec2<i32>> {
+        let mut cells = Vec::new();
+        for y in min[1]..=max[1] {
+            for x in min[0]..=max[0] {
+                cells.push(vec2![x, y]);
+            }
+        }
+        cells
+    }
+
+    fn cell_count(min: &Vec2<i32>, max: &Vec2<i32>) -> u64 {
+        (0..2).fold(1, |count: u64, i| {
+            count.saturating_mul((i64::from(max[i]) - i64::from(min[i]) + 1).max(0) as u64)
+        })
+    }
+
+    fn cell_max(a: &Vec2<i32>, b: &Vec2<i32>) -> Vec2<i32> {
+        vec2![a[0].max(b[0]), a[1].max(b[1])]
+    }
+}
+
+impl GridBounds for Aabb3 {
+    type Point = Point3;
+    type Cell = Vec3<i32>;
+
+    fn from_point(p: &Point3) -> Aabb3 {
+        Aabb3::new(*p, *p)
+    }
+
+    fn overlaps(&self, rhs: &Aabb3) -> bool {
+        Aabb3::overlaps(self, rhs)
+    }
+
+    fn is_finite(&self) -> bool {
+        (0..3).all(|i| self.min[i].is_finite() && self.max[i].is_finite())
+    }
+
+    fn cell_of(p: &Point3, cell_size: f64) -> Vec3<i32> {
+        vec3![
+            (p[0] / cell_size).floor() as i32,
+            (p[1] / cell_size).floor() as i32,
+            (p[2] / cell_size).floor() as i32
+        ]
+    }
+
+    fn cell_range(&self, cell_size: f64) -> (Vec3<i32>, Vec3<i32>) {
+        (
+            Aabb3::cell_of(&self.min, cell_size),
+            Aabb3::cell_of(&self.max, cell_size),
+        )
+    }
+
+    fn cells_between(min: &Vec3<i32>, max: &Vec3<i32>) -> Vec<Vec3<i32>> {
+        let mut cells = Vec::new();
+        for z in min[2]..=max[2] {
+            for y in min[1]..=max[1] {
+                for x in min[0]..=max[0] {
+                    cells.push(vec3![x, y, z]);
+                }
+            }
+        }
+        cells
+    }
+
+    fn cell_count(min: &Vec3<i32>, max: &Vec3<i32>) -> u64 {
+        (0..3).fold(1, |count: u64, i| {
+            count.saturating_mul((i64::from(max[i]) - i64::from(min[i]) + 1).max(0) as u64)
+        })
+    }
+
+    fn cell_max(a: &Vec3<i32>, b: &Vec3<i32>) -> Vec3<i32> {
+        vec3![a[0].max(b[0]), a[1].max(b[1]), a[2].max(b[2])]
+    }
+}
+
+/// Objects touching more cells than this are kept in a separate list that every query
+/// checks, so that huge bounds cannot make inserts and queries walk unbounded ranges.
+const MAX_CELLS_PER_OBJECT: u64 = 4096;
+
+#[derive(Debug, Clone)]
+struct Entry<B: GridBounds, T> {
+    bounds: B,
+    value: T,
+    min_cell: B::Cell,
+    max_cell: B::Cell,
+}
+
+/// Uniform grid hashed by integer cell coordinates, for broad-phase collision. Each
+/// object is listed in every cell its bounds touch, so with a cell size close to the
+/// typical object size, inserts, updates and pair enumeration cost O(1) per object.
+///
+/// Objects are identified by the id returned from `insert`. Ids of removed objects are
+/// reused by later insertions. Bounds must be finite; objects spanning too many cells are
+/// checked against every query instead of being listed in cells.
+#[derive(Debug, Clone)]
+pub struct SpatialHash<B: GridBounds, T> {
+    cell_size: f64,
+    cells: HashMap<B::Cell, Vec<usize>>,
+    entries: Vec<Option<Entry<B, T>>>,
+    oversized: Vec<usize>,
+    free_ids: Vec<usize>,
+    len: usize,
+}
+
+pub type SpatialHash2<T> = SpatialHash<Aabb2, T>;
+pub type SpatialHash3<T> = SpatialHash<Aabb3, T>;
+
+impl<B: GridBounds, T> SpatialHash<B, T> {
+    pub fn new(cell_size: f64) -> SpatialHash<B, T> {
+        assert!(cell_size > 0.0, "cell size must be positive");
+        SpatialHash {
+            cell_size,
+            cells: HashMap::new(),
+            entries: Vec::new(),
+            oversized: Vec::new(),
+            free_ids: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn cell_size(&self) -> f64 {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Key of the cell containing `p`.
+    pub fn cell(&self, p: &B::Point) -> B::Cell {
+        B::cell_of(p, self.cell_size)
+    }
+
+    /// Ids listed in a single cell.
+    pub fn cell_items(&self, cell: &B::Cell) -> &[usize] {
+        self.cells.get(cell).map_or(&[], |items| items)
+    }
+
+    pub fn insert(&mut self, bounds: B, value: T) -> usize {
+        assert!(bounds.is_finite(), "bounds must be finite");
+        let (min_cell, max_cell) = bounds.cell_range(self.cell_size);
+        let entry = Some(Entry {
+            bounds,
+            value,
+            min_cell,
+            max_cell,
+        });
+        let id = match self.free_ids.pop() {
+            Some(id) => {
+                self.entries[id] = entry;
+                id
+            }
+            None => {
+                self.entries.push(entry);
+                self.entries.len() - 1
+            }
+        };
+        self.len += 1;
+        self.link(id);
+        id
+    }
+
+    pub fn insert_point(&mut self, p: &B::Point, value: T) -> usize {
+        self.insert(B::from_point(p), value)
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<T> {
+        self.entries.get(id)?.as_ref()?;
+        self.unlink(id);
+        self.free_ids.push(id);
+        self.len -= 1;
+        self.entries[id].take().map(|e| e.value)
+    }
+
+    /// Moves an object to new bounds, touching the grid only when the set of covered
+    /// cells changes. Returns false for unknown ids.
+    pub fn update(&mut self, id: usize, bounds: B) -> bool {
+        let cell_size = self.cell_size;
+        let Some(entry) = self.entries.get_mut(id).and_then(|e| e.as_mut()) else {
+            return false;
+        };
+        assert!(bounds.is_finite(), "bounds must be finite");
+        entry.bounds = bounds;
+        let (min_cell, max_cell) = bounds.cell_range(cell_size);
+        if min_cell == entry.min_cell && max_cell == entry.max_cell {
+            return true;
+        }
+        self.unlink(id);
+        let entry = self.entries[id].as_mut().unwrap();
+        entry.min_cell = min_cell;
+        entry.max_cell = max_cell;
+        self.link(id);
+        true
+    }
+
+    pub fn update_point(&mut self, id: usize, p: &B::Point) -> bool {
+        self.update(id, B::from_point(p))
+    }
+
+    pub fn get(&self, id: usize) -> Option<&T> {
+        self.entry(id).map(|e| &e.value)
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut T> {
+        self.entries
+            .get_mut(id)
+            .and_then(|e| e.as_mut())
+            .map(|e| &mut e.value)
+    }
+
+    pub fn bounds(&self, id: usize) -> Option<B> {
+        self.entry(id).map(|e| e.bounds)
+    }
+
+    /// All objects as `(id, bounds, value)` in id order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &B, &T)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(id, e)| e.as_ref().map(|e| (id, &e.bounds, &e.value)))
+    }
+
+    /// Ids of the objects whose bounds overlap `range`, each reported once.
+    pub fn query(&self, range: &B) -> Vec<usize> {
+        let (min_cell, max_cell) = range.cell_range(self.cell_size);
+        if B::cell_count(&min_cell, &max_cell) > MAX_CELLS_PER_OBJECT {
+            return self
+                .iter()
+                .filter(|(_, bounds, _)| bounds.overlaps(range))
+                .map(|(id, _, _)| id)
+                .collect();
+        }
+        let mut result: Vec<usize> = self
+            .oversized
+            .iter()
+            .copied()
+            .filter(|&id| self.entries[id].as_ref().unwrap().bounds.overlaps(range))
+            .collect();
+        for cell in B::cells_between(&min_cell, &max_cell) {
+            for &id in self.cell_items(&cell) {
+                let entry = self.entries[id].as_ref().unwrap();
+                // Report an object only from the first cell it shares with the range.
+                let first = B::cell_max(&min_cell, &entry.min_cell);
+                if first == cell && entry.bounds.overlaps(range) {
+                    result.push(id);
+                }
+            }
+        }
+        result
+    }
+
+    /// Ids of the other objects whose bounds overlap those of `id`.
+    pub fn neighbours(&self, id: usize) -> Vec<usize> {
+        match self.entry(id) {
+            Some(entry) => {
+                let mut result = self.query(&entry.bounds);
+                result.retain(|&other| other != id);
+                result
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Every pair of objects with overlapping bounds, as `(a, b)` with `a < b`. Each pair
+    /// is reported once, from the lowest cell both objects touch, in no particular order.
+    pub fn candidate_pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for (cell, items) in &self.cells {
+            for (k, &a) in items.iter().enumerate() {
+                let ea = self.entries[a].as_ref().unwrap();
+                for &b in &items[k + 1..] {
+                    let eb = self.entries[b].as_ref().unwrap();
+                    if B::cell_max(&ea.min_cell, &eb.min_cell) == *cell
+                        && ea.bounds.overlaps(&eb.bounds)
+                    {
+                        pairs.push((a.min(b), a.max(b)));
+                    }
+                }
+            }
+        }
+        // Oversized objects against everything else, and against each other once.
+        for (k, &a) in self.oversized.iter().enumerate() {
+            let ea = self.entries[a].as_ref().unwrap();
+            for (b, bounds, _) in self.iter() {
+                let eb = self.entries[b].as_ref().unwrap();
+                if b != a
+                    && (!Self::is_oversized(eb) || self.oversized[..k].contains(&b))
+                    && ea.bounds.overlaps(bounds)
+                {
+                    pairs.push((a.min(b), a.max(b)));
+                }
+            }
+        }
+        pairs
+    }
+
+    fn entry(&self, id: usize) -> Option<&Entry<B, T>> {
+        self.entries.get(id).and_then(|e| e.as_ref())
+    }
+
+    fn is_oversized(entry: &Entry<B, T>) -> bool {
+        B::cell_count(&entry.min_cell, &entry.max_cell) > MAX_CELLS_PER_OBJECT
+    }
+
+    fn link(&mut self, id: usize) {
+        let entry = self.entries[id].as_ref().unwrap();
+        if Self::is_oversized(entry) {
+            self.oversized.push(id);
+            return;
+        }
+        for cell in B::cells_between(&entry.min_cell, &entry.max_cell) {
+            self.cells.entry(cell).or_default().push(id);
+        }
+    }
+
+    fn unlink(&mut self, id: usize) {
+        let entry = self.entries[id].as_ref().unwrap();
+        if Self::is_oversized(entry) {
+            let position = self.oversized.iter().position(|&i| i == id).unwrap();
+            self.oversized.swap_remove(position);
+            return;
+        }
+        for cell in B::cells_between(&entry.min_cell, &entry.max_cell) {
+            let items = self.cells.get_mut(&cell).unwrap();
+            let position = items.iter().position(|&i| i == id).unwrap();
+            items.swap_remove(position);
+            if items.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::random_values;
+
+    /// Particles of radius up to 1 scattered over `[-50, 50]^2`.
+    fn random_particles(n: usize, seed: u64) -> Vec<Aabb2> {
+        random_values(3 * n, seed)
+            .chunks(3)
+            .map(|c| {
+                let p = Point2::new(c[0] * 100.0 - 50.0, c[1] * 100.0 - 50.0);
+                Aabb2::new(p, p).expand(c[2])
+            })
+            .collect()
+    }
+
+    fn brute_force_pairs(boxes: &[Option<Aabb2>]) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for a in 0..boxes.len() {
+            for b in a + 1..boxes.len() {
+                if let (Some(ba), Some(bb)) = (boxes[a], boxes[b]) {
+                    if ba.overlaps(&bb) {
+                        pairs.push((a, b));
+                    }
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn test_cells() {
+        let grid: SpatialHash2<()> = SpatialHash::new(2.0);
+        assert_eq!(grid.cell(&Point2::new(0.0, 3.9)), vec2![0, 1]);
+        assert_eq!(grid.cell(&Point2::new(-0.1, -2.0)), vec2![-1, -1]);
+        let grid: SpatialHash3<()> = SpatialHash::new(0.5);
+        assert_eq!(grid.cell(&Point3::new(1.2, -1.2, 0.0)), vec3![2, -3, 0]);
+    }
+
+    #[test]
+    fn test_candidate_pairs() {
+        let mut boxes: Vec<Option<Aabb2>> =
+            random_particles(2000, 1).into_iter().map(Some).collect();
+        let mut grid = SpatialHash2::new(2.0);
+        for (i, b) in boxes.iter().enumerate() {
+            assert_eq!(grid.insert(b.unwrap(), i), i);
+        }
+        let mut pairs = grid.candidate_pairs();
+        pairs.sort();
+        assert_eq!(pairs, brute_force_pairs(&boxes));
+        assert!(!pairs.is_empty());
+
+        // Jitter everything, teleport some and remove others; the pairs must follow.
+        let moves = random_values(4000, 2);
+        for (i, c) in moves.chunks(2).enumerate() {
+            let b = boxes[i].unwrap();
+            let offset = if i % 10 == 0 {
+                vec2![c[0] * 60.0 - 30.0, c[1] * 60.0 - 30.0]
+            } else {
+                vec2![c[0] * 0.4 - 0.2, c[1] * 0.4 - 0.2]
+            };
+            let moved = Aabb2::new(b.min + offset.clone(), b.max + offset);
+            assert!(grid.update(i, moved));
+            boxes[i] = Some(moved);
+        }
+        for i in (0..2000).step_by(7) {
+            assert_eq!(grid.remove(i), Some(i));
+            assert_eq!(grid.remove(i), None);
+            boxes[i] = None;
+        }
+        let mut pairs = grid.candidate_pairs();
+        pairs.sort();
+        assert_eq!(pairs, brute_force_pairs(&boxes));
+        assert_eq!(grid.len(), boxes.iter().flatten().count());
+        assert!(!grid.update(0, Aabb2::empty()));
+    }
+
+    #[test]
+    fn test_queries() {
+        let mut grid = SpatialHash3::new(1.0);
+        let a = grid.insert_point(&Point3::new(0.5, 0.5, 0.5), "a");
+        let big = Aabb3::new(Point3::new(-2.0, -2.0, -2.0), Point3::new(2.5, 0.7, 0.7));
+        let b = grid.insert(big, "b");
+        let c = grid.insert_point(&Point3::new(5.0, 5.0, 5.0), "c");
+        assert_eq!(grid.cell_items(&vec3![0, 0, 0]).len(), 2);
+        assert!(grid.cell_items(&vec3![9, 9, 9]).is_empty());
+
+        // The big box spans many cells but is reported once.
+        let range = Aabb3::new(Point3::new(-3.0, -3.0, -3.0), Point3::new(3.0, 3.0, 3.0));
+        let mut found = grid.query(&range);
+        found.sort();
+        assert_eq!(found, vec![a, b]);
+        assert_eq!(grid.neighbours(a), vec![b]);
+        assert!(grid.neighbours(c).is_empty());
+        assert_eq!(grid.candidate_pairs(), vec![(a, b)]);
+
+        assert!(grid.update_point(c, &Point3::new(0.5, 0.5, 0.5)));
+        let mut found = grid.neighbours(c);
+        found.sort();
+        assert_eq!(found, vec![a, b]);
+        *grid.get_mut(c).unwrap() = "d";
+        assert_eq!(grid.get(c), Some(&"d"));
+        assert_eq!(grid.bounds(b), Some(big));
+        assert_eq!(grid.iter().count(), 3);
+
+        // Ids are reused and emptied cells are dropped.
+        assert_eq!(grid.remove(b), Some("b"));
+        assert_eq!(grid.insert_point(&Point3::new(-1.0, 0.0, 0.0), "e"), b);
+        grid.remove(a);
+        grid.remove(b);
+        grid.remove(c);
+        assert!(grid.is_empty());
+        assert!(grid.cells.is_empty());
+    }
+
+    #[test]
+    fn test_oversized() {
+        let mut boxes: Vec<Option<Aabb2>> =
+            random_particles(200, 3).into_iter().map(Some).collect();
+        // Far wider than the cap on cells, beyond the range of the cell keys, and far
+        // from the origin.
+        boxes.push(Some(Aabb2::new(
+            Point2::new(-1e12, -0.5),
+            Point2::new(1e12, 0.5),
+        )));
+        boxes.push(Some(Aabb2::new(
+            Point2::new(-200.0, -200.0),
+            Point2::new(200.0, 200.0),
+        )));
+        boxes.push(Some(Aabb2::new(
+            Point2::new(1e15, 1e15),
+            Point2::new(1e15 + 1.0, 1e15 + 1.0),
+        )));
+        let mut grid = SpatialHash2::new(0.5);
+        for (i, b) in boxes.iter().enumerate() {
+            grid.insert(b.unwrap(), i);
+        }
+        assert_eq!(grid.oversized.len(), 2);
+        let mut pairs = grid.candidate_pairs();
+        pairs.sort();
+        assert_eq!(pairs, brute_force_pairs(&boxes));
+
+        let small = Aabb2::new(Point2::new(3.0, -0.1), Point2::new(3.2, 0.1));
+        let mut found = grid.query(&small);
+        found.sort();
+        let expected: Vec<usize> = (0..boxes.len())
+            .filter(|&i| boxes[i].unwrap().overlaps(&small))
+            .collect();
+        assert_eq!(found, expected);
+        assert!(found.contains(&200) && found.contains(&201));
+        let everything = Aabb2::new(Point2::new(-1e18, -1e18), Point2::new(1e18, 1e18));
+        assert_eq!(grid.query(&everything).len(), boxes.len());
+
+        // Moving in and out of the oversized list.
+        assert!(grid.update(200, small));
+        assert!(grid.update(0, boxes[200].unwrap()));
+        assert_eq!(grid.oversized.len(), 2);
+        boxes[0] = boxes[200];
+        boxes[200] = Some(small);
+        let mut pairs = grid.candidate_pairs();
+        pairs.sort();
+        assert_eq!(pairs, brute_force_pairs(&boxes));
+        assert_eq!(grid.remove(0), Some(0));
+        assert_eq!(grid.oversized, vec![201]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bounds must be finite")]
+    fn test_infinite_bounds() {
+        let mut grid = SpatialHash2::new(1.0);
+        grid.insert(
+            Aabb2::new(Point2::new(0.0, 0.0), Point2::new(f64::INFINITY, 1.0)),
+            (),
+        );
+    }
+}
@@ -0,0 +1,285 @@
+use crate::aabb::Aabb3;
+use crate::mat::Mat3;
+use crate::point::Point3;
+use crate::vec::Vec3;
+
+/// Relative slack used when testing points against a sphere during construction.
+const CONTAINS_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sphere {
+    pub center: Point3,
+    pub radius: f64,
+}
+
+impl Sphere {
+    pub fn new(center: Point3, radius: f64) -> Sphere {
+        Sphere { center, radius }
+    }
+
+    /// Approximate bounding sphere using Ritter's two-pass algorithm.
+    /// The result is typically 5-20% larger than the minimal sphere.
+    pub fn from_points_ritter(points: &[Point3]) -> Sphere {
+        if points.is_empty() {
+            return Sphere::new(Point3::default(), 0.0);
+        }
+
+        // Seed with the most distant pair among the axis-extreme points.
+        let mut best = (points[0], points[0]);
+        let mut best_dist = -1.0;
+        for axis in 0..3 {
+            let mut lo = points[0];
+            let mut hi = points[0];
+            for p in points {
+                if p[axis] < lo[axis] {
+                    lo = *p;
+                }
+                if p[axis] > hi[axis] {
+                    hi = *p;
+                }
+            }
+            let d = (hi - lo).square_length();
+            if d > best_dist {
+                best_dist = d;
+                best = (lo, hi);
+            }
+        }
+        let mut sphere = Sphere::from_diameter(&best.0, &best.1);
+
+        for p in points {
+            sphere.include(p);
+        }
+        sphere
+    }
+
+    /// Minimal bounding sphere using Welzl's algorithm with the move-to-front heuristic.
+    pub fn from_points_welzl(points: &[Point3]) -> Sphere {
+        if points.is_empty() {
+            return Sphere::new(Point3::default(), 0.0);
+        }
+        let mut points = points.to_vec();
+        let end = points.len();
+        let mut boundary = Vec::with_capacity(4);
+        welzl_mtf(&mut points, end, &mut boundary)
+    }
+
+    fn from_diameter(a: &Point3, b: &Point3) -> Sphere {
+        let center = *a + (*b - *a) * 0.5;
+        Sphere::new(center, (*b - *a).length() * 0.5)
+    }
+
+    /// Smallest sphere passing through every point of `boundary` (at most four points).
+    fn from_boundary(boundary: &[Point3]) -> Sphere {
+        match boundary.len() {
+            0 => Sphere::new(Point3::default(), -1.0),
+            1 => Sphere::new(boundary[0], 0.0),
+            2 => Sphere::from_diameter(&boundary[0], &boundary[1]),
+            3 => circumsphere3(&boundary[0], &boundary[1], &boundary[2])
+                .unwrap_or_else(|| Sphere::enclosing_subsets(boundary)),
+            _ => circumsphere4(&boundary[0], &boundary[1], &boundary[2], &boundary[3])
+                .unwrap_or_else(|| Sphere::enclosing_subsets(boundary)),
+        }
+    }
+
+    /// Fallback for degenerate (collinear or coplanar) boundaries: the smallest sphere
+    /// through a proper subset that still encloses every boundary point.
+    fn enclosing_subsets(boundary: &[Point3]) -> Sphere {
+        let n = boundary.len();
+        let mut best: Option<Sphere> = None;
+        for mask in 1..(1u32 << n) - 1 {
+            let subset: Vec<Point3> = (0..n)
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| boundary[i])
+                .collect();
+            let s = Sphere::from_boundary(&subset);
+            if boundary.iter().all(|p| s.contains_with_slack(p))
+                && best.is_none_or(|b| s.radius < b.radius)
+            {
+                best = Some(s);
+            }
+        }
+        best.unwrap_or_else(|| Sphere::new(boundary[0], 0.0))
+    }
+
+    fn contains_with_slack(&self, p: &Point3) -> bool {
+        let slack = CONTAINS_EPSILON * self.radius.max(1.0);
+        (*p - self.center).length() <= self.radius + slack
+    }
+
+    pub fn contains(&self, p: &Point3) -> bool {
+        (*p - self.center).square_length() <= self.radius * self.radius
+    }
+
+    pub fn contains_sphere(&self, rhs: &Sphere) -> bool {
+        (rhs.center - self.center).length() + rhs.radius <= self.radius
+    }
+
+    /// Grows the sphere just enough to contain `p`, moving the center towards it.
+    pub fn include(&mut self, p: &Point3) {
+        let d = *p - self.center;
+        let dist = d.length();
+        if dist > self.radius {
+            let new_radius = (self.radius + dist) * 0.5;
+            self.center += d * ((new_radius - self.radius) / dist);
+            self.radius = new_radius;
+        }
+    }
+
+    /// Smallest sphere enclosing both spheres.
+    pub fn merge(&self, rhs: &Sphere) -> Sphere {
+        let d = rhs.center - self.center;
+        let dist = d.length();
+        if dist + rhs.radius <= self.radius {
+            return *self;
+        }
+        if dist + self.radius <= rhs.radius {
+            return *rhs;
+        }
+        let radius = (dist + self.radius + rhs.radius) * 0.5;
+        let center = self.center + d * ((radius - self.radius) / dist);
+        Sphere::new(center, radius)
+    }
+
+    pub fn overlaps(&self, rhs: &Sphere) -> bool {
+        let r = self.radius + rhs.radius;
+        (rhs.center - self.center).square_length() <= r * r
+    }
+
+    pub fn overlaps_aabb(&self, aabb: &Aabb3) -> bool {
+        let closest = aabb.closest_point(&self.center);
+        (closest - self.center).square_length() <= self.radius * self.radius
+    }
+
+    pub fn closest_point(&self, p: &Point3) -> Point3 {
+        let d = *p - self.center;
+        let dist = d.length();
+        if dist <= self.radius {
+            *p
+        } else {
+            self.center + d * (self.radius / dist)
+        }
+    }
+
+    pub fn aabb(&self) -> Aabb3 {
+        Aabb3::new(self.center, self.center).expand(self.radius)
+    }
+
+    pub fn volume(&self) -> f64 {
+        4.0 / 3.0 * std::f64::consts::PI * self.radius * self.radius * self.radius
+    }
+}
+
+/// Gärtner's move-to-front formulation of Welzl; recursion depth is bounded by the boundary size.
+fn welzl_mtf(points: &mut [Point3], end: usize, boundary: &mut Vec<Point3>) -> Sphere {
+    let mut sphere = Sphere::from_boundary(boundary);
+    if boundary.len() == 4 {
+        return sphere;
+    }
+    for i in 0..end {
+        let p = points[i];
+        if sphere.radius < 0.0 || !sphere.contains_with_slack(&p) {
+            boundary.push(p);
+            sphere = welzl_mtf(points, i, boundary);
+            boundary.pop();
+            points[..=i].rotate_right(1);
+        }
+    }
+    sphere
+}
+
+fn circumsphere3(a: &Point3, b: &Point3, c: &Point3) -> Option<Sphere> {
+    let u = *a - *c;
+    let v = *b - *c;
+    let n = u.cross(&v);
+    let n2 = n.square_length();
+    if n2 <= f64::EPSILON * u.square_length() * v.square_length() {
+        return None;
+    }
+    let offset = (v.clone() * u.square_length() - u * v.square_length()).cross(&n) / (2.0 * n2);
+    let radius = offset.length();
+    Some(Sphere::new(*c + offset, radius))
+}
+
+fn circumsphere4(a: &Point3, b: &Point3, c: &Point3, d: &Point3) -> Option<Sphere> {
+    let u = *b - *a;
+    let v = *c - *a;
+    let w = *d - *a;
+    let m = Mat3::new([u.0, v.0, w.0]) * 2.0;
+    let scale = u.square_length() * v.square_length() * w.square_length();
+    if m.determinant().abs() <= 1e-12 * scale.sqrt() {
+        return None;
+    }
+    let rhs: Vec3<f64> = vec3![u.square_length(), v.square_length(), w.square_length()];
+    let offset = m.inverse()?.mul_vec(&rhs);
+    let radius = offset.length();
+    Some(Sphere::new(*a + offset, radius))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube_corners() -> Vec<Point3> {
+        let mut points = vec![];
+        for &x in &[-1.0, 1.0] {
+            for &y in &[-1.0, 1.0] {
+                for &z in &[-1.0, 1.0] {
+                    points.push(Point3::new(x, y, z));
+                }
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn test_ritter_contains_all() {
+        let points = cube_corners();
+        let s = Sphere::from_points_ritter(&points);
+        assert!(points.iter().all(|p| s.contains_with_slack(p)));
+        assert!(s.radius >= 3f64.sqrt() - 1e-12);
+    }
+
+    #[test]
+    fn test_welzl_minimal() {
+        let mut points = cube_corners();
+        points.push(Point3::new(0.0, 0.0, 0.0));
+        points.push(Point3::new(0.5, -0.2, 0.1));
+        let s = Sphere::from_points_welzl(&points);
+        assert!((s.radius - 3f64.sqrt()).abs() < 1e-9);
+        assert!((s.center - Point3::default()).length() < 1e-9);
+        assert!(points.iter().all(|p| s.contains_with_slack(p)));
+    }
+
+    #[test]
+    fn test_welzl_degenerate() {
+        let collinear: Vec<Point3> = (0..5).map(|i| Point3::new(i as f64, 0.0, 0.0)).collect();
+        let s = Sphere::from_points_welzl(&collinear);
+        assert!((s.radius - 2.0).abs() < 1e-9);
+        assert!((s.center - Point3::new(2.0, 0.0, 0.0)).length() < 1e-9);
+
+        let s = Sphere::from_points_welzl(&[Point3::new(1.0, 1.0, 1.0); 3]);
+        assert_eq!(s.radius, 0.0);
+    }
+
+    #[test]
+    fn test_merge() {
+        let a = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0);
+        let b = Sphere::new(Point3::new(4.0, 0.0, 0.0), 1.0);
+        let m = a.merge(&b);
+        assert_eq!(m, Sphere::new(Point3::new(2.0, 0.0, 0.0), 3.0));
+        assert!(m.contains_sphere(&a) && m.contains_sphere(&b));
+        let inner = Sphere::new(Point3::new(0.5, 0.0, 0.0), 0.1);
+        assert_eq!(a.merge(&inner), a);
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let a = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0);
+        assert!(a.overlaps(&Sphere::new(Point3::new(1.5, 0.0, 0.0), 0.6)));
+        assert!(!a.overlaps(&Sphere::new(Point3::new(1.5, 0.0, 0.0), 0.4)));
+
+        let aabb = Aabb3::new(Point3::new(1.0, 1.0, 1.0), Point3::new(2.0, 2.0, 2.0));
+        assert!(!a.overlaps_aabb(&aabb));
+        assert!(Sphere::new(Point3::default(), 1.8).overlaps_aabb(&aabb));
+    }
+}
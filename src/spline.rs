@@ -0,0 +1,662 @@
+use crate::bezier::CubicBezier;
+use crate::curve::{Curve, CurvePoint};
+
+/// Interpolates all of `points` with parametrisation exponent `alpha` (Barry and Goldman's
+/// pyramid): 0 is uniform, 0.5 centripetal and 1 chordal. Centripetal curves neither cusp
+/// nor self-intersect within a segment. Segment `i` spans parameters `i..i + 1`, and the
+/// end segments use control points mirrored through the end points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatmullRom<P> {
+    pub points: Vec<P>,
+    pub alpha: f64,
+}
+
+/// A B-spline of the given degree over a non-decreasing knot vector with
+/// `control.len() + degree + 1` entries, defined between `knots[degree]` and
+/// `knots[control.len()]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BSpline<P> {
+    degree: usize,
+    control: Vec<P>,
+    knots: Vec<f64>,
+}
+
+/// A rational B-spline: a `BSpline` whose control points pull with the given weights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nurbs<P> {
+    degree: usize,
+    control: Vec<P>,
+    weights: Vec<f64>,
+    knots: Vec<f64>,
+}
+
+/// Piecewise cubic through `points` with the given `tangents`, which are derivatives with
+/// respect to the parameter. Segment `i` spans parameters `i..i + 1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hermite<P: CurvePoint> {
+    pub points: Vec<P>,
+    pub tangents: Vec<P::Vector>,
+}
+
+/// Segment index and local parameter of `t` for a curve made of `segments` unit segments.
+fn segment(t: f64, segments: usize) -> (usize, f64) {
+    let i = (t.max(0.0).floor() as usize).min(segments - 1);
+    (i, t - i as f64)
+}
+
+/// One level of the Barry-Goldman pyramid: blends two points, with their derivatives with
+/// respect to the knot parameter, over `[ta, tb]`.
+fn blend<P: CurvePoint>(
+    (a, da): (P, P::Vector),
+    (b, db): (P, P::Vector),
+    ta: f64,
+    tb: f64,
+    t: f64,
+) -> (P, P::Vector) {
+    let span = tb - ta;
+    let s = (t - ta) / span;
+    let d = (b - a) * (1.0 / span) + da * (1.0 - s) + db * s;
+    (a.lerp(&b, s), d)
+}
+
+impl<P: CurvePoint> CatmullRom<P> {
+    /// A centripetal spline through `points`.
+    pub fn new(points: Vec<P>) -> CatmullRom<P> {
+        CatmullRom::with_alpha(points, 0.5)
+    }
+
+    pub fn with_alpha(points: Vec<P>, alpha: f64) -> CatmullRom<P> {
+        assert!(points.len() >= 2, "need at least two points");
+        CatmullRom { points, alpha }
+    }
+
+    /// Position and derivative with respect to the global parameter.
+    fn evaluate(&self, t: f64) -> (P, P::Vector) {
+        let n = self.points.len();
+        let (i, u) = segment(t, n - 1);
+        let p1 = self.points[i];
+        let p2 = self.points[i + 1];
+        let p0 = if i > 0 {
+            self.points[i - 1]
+        } else {
+            p1.lerp(&p2, -1.0)
+        };
+        let p3 = if i + 2 < n {
+            self.points[i + 2]
+        } else {
+            p2.lerp(&p1, -1.0)
+        };
+
+        // Coincident points would give empty knot intervals.
+        let interval = |a: P, b: P| {
+            let d = b - a;
+            P::dot(&d, &d).powf(self.alpha / 2.0).max(1e-12)
+        };
+        let t0 = 0.0;
+        let t1 = t0 + interval(p0, p1);
+        let t2 = t1 + interval(p1, p2);
+        let t3 = t2 + interval(p2, p3);
+        let t = t1 + u * (t2 - t1);
+
+        let zero = P::Vector::default();
+        let a1 = blend((p0, zero.clone()), (p1, zero.clone()), t0, t1, t);
+        let a2 = blend((p1, zero.clone()), (p2, zero.clone()), t1, t2, t);
+        let a3 = blend((p2, zero.clone()), (p3, zero), t2, t3, t);
+        let b1 = blend(a1, a2.clone(), t0, t2, t);
+        let b2 = blend(a2, a3, t1, t3, t);
+        let (p, d) = blend(b1, b2, t1, t2, t);
+        (p, d * (t2 - t1))
+    }
+}
+
+impl<P: CurvePoint> Curve for CatmullRom<P> {
+    type Point = P;
+
+    fn at(&self, t: f64) -> P {
+        self.evaluate(t).0
+    }
+
+    fn derivative(&self, t: f64) -> P::Vector {
+        self.evaluate(t).1
+    }
+
+    fn domain(&self) -> (f64, f64) {
+        (0.0, (self.points.len() - 1) as f64)
+    }
+}
+
+fn check_knots(degree: usize, points: usize, knots: &[f64]) {
+    assert!(points > degree, "need more control points than the degree");
+    assert_eq!(knots.len(), points + degree + 1, "wrong number of knots");
+    assert!(
+        knots.windows(2).all(|w| w[0] <= w[1]),
+        "knots must not decrease"
+    );
+    assert!(knots[degree] < knots[points], "empty parameter domain");
+}
+
+/// Knots `0, 1, 2, ...`, or with `degree + 1` repeated end knots when `clamped` so that the
+/// curve starts and ends at its end control points. Clamped knots run from 0 to 1.
+fn uniform_knots(degree: usize, points: usize, clamped: bool) -> Vec<f64> {
+    let count = points + degree + 1;
+    if !clamped {
+        return (0..count).map(|i| i as f64).collect();
+    }
+    let spans = (points - degree) as f64;
+    (0..count)
+        .map(|i| (i.saturating_sub(degree) as f64 / spans).min(1.0))
+        .collect()
+}
+
+/// Index `k` of the knot span `knots[k]..knots[k + 1]` that holds `t`, clamped to the
+/// domain so that its upper end belongs to the last span.
+fn find_span(degree: usize, points: usize, knots: &[f64], t: f64) -> usize {
+    if t >= knots[points] {
+        return (degree..points)
+            .rev()
+            .find(|&k| knots[k] < knots[k + 1])
+            .unwrap();
+    }
+    let upper = knots[degree + 1..=points].partition_point(|&u| u <= t);
+    (degree + upper).min(points - 1)
+}
+
+/// De Boor's algorithm, on points carried with weights so that rational curves stay in
+/// affine form. Returns the point and its derivative.
+fn de_boor<P: CurvePoint>(
+    degree: usize,
+    control: &[P],
+    weights: Option<&[f64]>,
+    knots: &[f64],
+    t: f64,
+) -> (P, P::Vector) {
+    let k = find_span(degree, control.len(), knots, t);
+    let t = t.clamp(knots[degree], knots[control.len()]);
+    let mut d: Vec<P> = control[k - degree..=k].to_vec();
+    let mut w: Vec<f64> = match weights {
+        Some(weights) => weights[k - degree..=k].to_vec(),
+        None => vec![1.0; degree + 1],
+    };
+    let mut last = None;
+    for r in 1..=degree {
+        if r == degree {
+            last = Some((d[degree - 1], w[degree - 1], d[degree], w[degree]));
+        }
+        for j in (r..=degree).rev() {
+            let lo = knots[j + k - degree];
+            let hi = knots[j + 1 + k - r];
+            let alpha = if hi > lo { (t - lo) / (hi - lo) } else { 0.0 };
+            let weight = (1.0 - alpha) * w[j - 1] + alpha * w[j];
+            d[j] = d[j - 1].lerp(&d[j], alpha * w[j] / weight);
+            w[j] = weight;
+        }
+    }
+    let p = d[degree];
+    let derivative = match last {
+        Some((a, wa, b, wb)) => {
+            let scale = degree as f64 / (knots[k + 1] - knots[k]) / w[degree];
+            ((b - p) * wb - (a - p) * wa) * scale
+        }
+        None => P::Vector::default(),
+    };
+    (p, derivative)
+}
+
+/// Boehm's knot insertion, in the same weighted affine form as `de_boor`.
+fn insert_knot<P: CurvePoint>(
+    degree: usize,
+    control: &mut Vec<P>,
+    weights: &mut Vec<f64>,
+    knots: &mut Vec<f64>,
+    u: f64,
+) {
+    let n = control.len();
+    assert!(
+        knots[degree] <= u && u <= knots[n],
+        "knot outside the parameter domain"
+    );
+    let k = find_span(degree, control.len(), knots, u);
+    let mut points = control[..=k - degree].to_vec();
+    let mut new_weights = weights[..=k - degree].to_vec();
+    for i in k - degree + 1..=k {
+        let span = knots[i + degree] - knots[i];
+        let alpha = if span > 0.0 {
+            (u - knots[i]) / span
+        } else {
+            0.0
+        };
+        let weight = (1.0 - alpha) * weights[i - 1] + alpha * weights[i];
+        points.push(control[i - 1].lerp(&control[i], alpha * weights[i] / weight));
+        new_weights.push(weight);
+    }
+    points.extend_from_slice(&control[k..]);
+    new_weights.extend_from_slice(&weights[k..]);
+    *control = points;
+    *weights = new_weights;
+    knots.insert(k + 1, u);
+}
+
+impl<P: CurvePoint> BSpline<P> {
+    pub fn new(degree: usize, control: Vec<P>, knots: Vec<f64>) -> BSpline<P> {
+        check_knots(degree, control.len(), &knots);
+        BSpline {
+            degree,
+            control,
+            knots,
+        }
+    }
+
+    /// Uniform knots `0, 1, 2, ...`: the curve starts near, but not at, the first control
+    /// point.
+    pub fn uniform(degree: usize, control: Vec<P>) -> BSpline<P> {
+        let knots = uniform_knots(degree, control.len(), false);
+        BSpline::new(degree, control, knots)
+    }
+
+    /// Uniform interior knots with clamped ends, so the curve runs from the first control
+    /// point to the last over `0..1`.
+    pub fn clamped(degree: usize, control: Vec<P>) -> BSpline<P> {
+        let knots = uniform_knots(degree, control.len(), true);
+        BSpline::new(degree, control, knots)
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    pub fn control_points(&self) -> &[P] {
+        &self.control
+    }
+
+    pub fn knots(&self) -> &[f64] {
+        &self.knots
+    }
+
+    /// Inserts the knot `u`, which must lie in the parameter domain, without changing the
+    /// shape of the curve, adding a control point.
+    pub fn insert_knot(&mut self, u: f64) {
+        let mut weights = vec![1.0; self.control.len()];
+        insert_knot(
+            self.degree,
+            &mut self.control,
+            &mut weights,
+            &mut self.knots,
+            u,
+        );
+    }
+}
+
+impl<P: CurvePoint> Curve for BSpline<P> {
+    type Point = P;
+
+    fn at(&self, t: f64) -> P {
+        de_boor(self.degree, &self.control, None, &self.knots, t).0
+    }
+
+    fn derivative(&self, t: f64) -> P::Vector {
+        de_boor(self.degree, &self.control, None, &self.knots, t).1
+    }
+
+    fn domain(&self) -> (f64, f64) {
+        (self.knots[self.degree], self.knots[self.control.len()])
+    }
+}
+
+impl<P: CurvePoint> Nurbs<P> {
+    pub fn new(degree: usize, control: Vec<P>, weights: Vec<f64>, knots: Vec<f64>) -> Nurbs<P> {
+        check_knots(degree, control.len(), &knots);
+        assert_eq!(weights.len(), control.len(), "one weight per control point");
+        assert!(weights.iter().all(|&w| w > 0.0), "weights must be positive");
+        Nurbs {
+            degree,
+            control,
+            weights,
+            knots,
+        }
+    }
+
+    /// Clamped uniform knots over `0..1`, as for `BSpline::clamped`.
+    pub fn clamped(degree: usize, control: Vec<P>, weights: Vec<f64>) -> Nurbs<P> {
+        let knots = uniform_knots(degree, control.len(), true);
+        Nurbs::new(degree, control, weights, knots)
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    pub fn control_points(&self) -> &[P] {
+        &self.control
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    pub fn knots(&self) -> &[f64] {
+        &self.knots
+    }
+
+    /// Inserts the knot `u`, which must lie in the parameter domain, without changing the
+    /// shape of the curve, adding a control point.
+    pub fn insert_knot(&mut self, u: f64) {
+        insert_knot(
+            self.degree,
+            &mut self.control,
+            &mut self.weights,
+            &mut self.knots,
+            u,
+        );
+    }
+}
+
+impl<P: CurvePoint> Curve for Nurbs<P> {
+    type Point = P;
+
+    fn at(&self, t: f64) -> P {
+        let weights = Some(&self.weights[..]);
+        de_boor(self.degree, &self.control, weights, &self.knots, t).0
+    }
+
+    fn derivative(&self, t: f64) -> P::Vector {
+        let weights = Some(&self.weights[..]);
+        de_boor(self.degree, &self.control, weights, &self.knots, t).1
+    }
+
+    fn domain(&self) -> (f64, f64) {
+        (self.knots[self.degree], self.knots[self.control.len()])
+    }
+}
+
+impl<P: CurvePoint> Hermite<P> {
+    pub fn new(points: Vec<P>, tangents: Vec<P::Vector>) -> Hermite<P> {
+        assert!(points.len() >= 2, "need at least two points");
+        assert_eq!(points.len(), tangents.len(), "one tangent per point");
+        Hermite { points, tangents }
+    }
+
+    /// Cardinal spline tangents: `(1 - tension)` times half the difference of the
+    /// neighbours, one-sided at the ends. Zero tension gives a uniform Catmull-Rom spline.
+    pub fn cardinal(points: Vec<P>, tension: f64) -> Hermite<P> {
+        let n = points.len();
+        assert!(n >= 2, "need at least two points");
+        let tangents = (0..n)
+            .map(|i| {
+                let (a, b) = (i.saturating_sub(1), (i + 1).min(n - 1));
+                let scale = if b - a == 2 { 0.5 } else { 1.0 };
+                (points[b] - points[a]) * ((1.0 - tension) * scale)
+            })
+            .collect();
+        Hermite::new(points, tangents)
+    }
+
+    /// Segment `i` as a cubic Bezier curve over `0..1`.
+    pub fn to_bezier(&self, i: usize) -> CubicBezier<P> {
+        let (p0, p3) = (self.points[i], self.points[i + 1]);
+        CubicBezier::new(
+            p0,
+            p0 + self.tangents[i].clone() * (1.0 / 3.0),
+            p3 + self.tangents[i + 1].clone() * (-1.0 / 3.0),
+            p3,
+        )
+    }
+}
+
+impl<P: CurvePoint> Curve for Hermite<P> {
+    type Point = P;
+
+    fn at(&self, t: f64) -> P {
+        let (i, u) = segment(t, self.points.len() - 1);
+        let (u2, u3) = (u * u, u * u * u);
+        let chord = self.points[i + 1] - self.points[i];
+        self.points[i]
+            + chord * (3.0 * u2 - 2.0 * u3)
+            + self.tangents[i].clone() * (u3 - 2.0 * u2 + u)
+            + self.tangents[i + 1].clone() * (u3 - u2)
+    }
+
+    fn derivative(&self, t: f64) -> P::Vector {
+        let (i, u) = segment(t, self.points.len() - 1);
+        let u2 = u * u;
+        let chord = self.points[i + 1] - self.points[i];
+        chord * (6.0 * u - 6.0 * u2)
+            + self.tangents[i].clone() * (3.0 * u2 - 4.0 * u + 1.0)
+            + self.tangents[i + 1].clone() * (3.0 * u2 - 2.0 * u)
+    }
+
+    fn domain(&self) -> (f64, f64) {
+        (0.0, (self.points.len() - 1) as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::{Point2, Point3};
+    use crate::test_util::assert_close;
+    use crate::vec::{Vec2, Vec3};
+
+    /// Compares the derivative with central differences across the domain, away from the
+    /// joints where the speed of piecewise curves may jump.
+    fn check_derivative<C: Curve<Point = Point2>>(curve: &C) {
+        let (t0, t1) = curve.domain();
+        let h = 1e-6;
+        for i in 0..40 {
+            let t = t0 + (t1 - t0) * (i as f64 + 0.5) / 40.0;
+            let numeric = (curve.at(t + h) - curve.at(t - h)) * (0.5 / h);
+            let exact = curve.derivative(t);
+            assert!(
+                (exact.clone() - numeric.clone()).length() < 1e-5 * exact.length().max(1.0),
+                "{t}: {exact:?} != {numeric:?}"
+            );
+        }
+    }
+
+    /// Basis function `N_{i,p}(t)` by the Cox-de Boor recursion.
+    fn basis(i: usize, p: usize, knots: &[f64], t: f64) -> f64 {
+        if p == 0 {
+            return (knots[i] <= t && t < knots[i + 1]) as u8 as f64;
+        }
+        let left = knots[i + p] - knots[i];
+        let right = knots[i + p + 1] - knots[i + 1];
+        let mut value = 0.0;
+        if left > 0.0 {
+            value += (t - knots[i]) / left * basis(i, p - 1, knots, t);
+        }
+        if right > 0.0 {
+            value += (knots[i + p + 1] - t) / right * basis(i + 1, p - 1, knots, t);
+        }
+        value
+    }
+
+    fn control() -> Vec<Point2> {
+        vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 3.0),
+            Point2::new(3.0, 4.0),
+            Point2::new(4.0, 1.0),
+            Point2::new(6.0, 0.0),
+            Point2::new(7.0, 2.0),
+            Point2::new(9.0, 2.0),
+        ]
+    }
+
+    #[test]
+    fn test_catmull_rom() {
+        let points = control();
+        let spline = CatmullRom::new(points.clone());
+        for (i, p) in points.iter().enumerate() {
+            assert!((spline.at(i as f64) - *p).length() < 1e-12);
+        }
+        check_derivative(&spline);
+        // Tangent directions agree across the joints.
+        for i in 1..points.len() - 1 {
+            let left = spline.derivative(i as f64 - 1e-12).normalize();
+            let right = spline.derivative(i as f64).normalize();
+            assert!((left - right).length() < 1e-6);
+        }
+
+        // Evenly spaced collinear points give uniform motion.
+        let line = CatmullRom::with_alpha(
+            (0..4)
+                .map(|i| Point3::new(i as f64, 2.0 * i as f64, 0.0))
+                .collect(),
+            0.0,
+        );
+        assert!((line.at(1.25) - Point3::new(1.25, 2.5, 0.0)).length() < 1e-12);
+        assert!((line.derivative(2.5) - vec3![1.0, 2.0, 0.0]).length() < 1e-12);
+
+        // A centripetal spline through a tight corner does not loop.
+        let corner = CatmullRom::new(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(10.0, 0.0),
+            Point2::new(10.0, 0.1),
+            Point2::new(0.0, 0.1),
+        ]);
+        for i in 0..=100 {
+            assert!(corner.at(1.0 + i as f64 / 100.0)[0] <= 10.0 + 0.1);
+        }
+    }
+
+    #[test]
+    fn test_bspline() {
+        let points = control();
+        let spline = BSpline::new(
+            3,
+            points.clone(),
+            vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.5, 3.0, 4.0, 4.0, 4.0, 4.0],
+        );
+        assert_eq!(spline.domain(), (0.0, 4.0));
+        assert_eq!(spline.at(0.0), points[0]);
+        assert!((spline.at(4.0) - points[6]).length() < 1e-12);
+        for i in 0..=40 {
+            let t = i as f64 / 10.0;
+            let expected = (0..points.len()).fold(Vec2::all(0.0), |acc, j| {
+                // The last span is closed on the right.
+                let t = t.min(4.0 - 1e-12);
+                acc + points[j].to_vec() * basis(j, 3, spline.knots(), t)
+            });
+            assert!((spline.at(t).to_vec() - expected).length() < 1e-9);
+        }
+        check_derivative(&spline);
+
+        // Each uniform cubic segment starts at (P0 + 4 P1 + P2) / 6.
+        let uniform = BSpline::uniform(3, points.clone());
+        assert_eq!(uniform.domain(), (3.0, 7.0));
+        let expected = (points[1].to_vec() + points[2].to_vec() * 4.0 + points[3].to_vec()) / 6.0;
+        assert!((uniform.at(4.0).to_vec() - expected).length() < 1e-12);
+        check_derivative(&uniform);
+
+        let clamped = BSpline::clamped(2, points.clone());
+        assert_eq!(clamped.knots()[..3], [0.0; 3]);
+        assert_eq!(clamped.knots()[7..], [1.0; 3]);
+        assert!((clamped.at(1.0) - points[6]).length() < 1e-12);
+
+        let mut refined = spline.clone();
+        for u in [0.5, 1.5, 1.5, 3.9] {
+            refined.insert_knot(u);
+        }
+        assert_eq!(refined.control_points().len(), points.len() + 4);
+        assert_eq!(refined.knots().len(), spline.knots().len() + 4);
+        for i in 0..=80 {
+            let t = i as f64 / 20.0;
+            assert!((refined.at(t) - spline.at(t)).length() < 1e-12);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "knot outside the parameter domain")]
+    fn test_insert_knot_outside_domain() {
+        let points = (0..7)
+            .map(|i| Point2::new(i as f64, (i % 2) as f64))
+            .collect();
+        // The uniform cubic's domain is 3..7, although its knots run from 0 to 10.
+        BSpline::uniform(3, points).insert_knot(2.0);
+    }
+
+    #[test]
+    fn test_nurbs() {
+        let h = 0.5f64.sqrt();
+        // The unit circle as four rational quadratic arcs.
+        let circle = Nurbs::new(
+            2,
+            vec![
+                Point2::new(1.0, 0.0),
+                Point2::new(1.0, 1.0),
+                Point2::new(0.0, 1.0),
+                Point2::new(-1.0, 1.0),
+                Point2::new(-1.0, 0.0),
+                Point2::new(-1.0, -1.0),
+                Point2::new(0.0, -1.0),
+                Point2::new(1.0, -1.0),
+                Point2::new(1.0, 0.0),
+            ],
+            vec![1.0, h, 1.0, h, 1.0, h, 1.0, h, 1.0],
+            vec![0.0, 0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 3.0, 3.0, 4.0, 4.0, 4.0],
+        );
+        for i in 0..=80 {
+            let t = i as f64 / 20.0;
+            assert_close(circle.at(t).to_vec().length(), 1.0, 1e-12);
+        }
+        check_derivative(&circle);
+        assert_close(circle.length(), 2.0 * std::f64::consts::PI, 1e-9);
+
+        // Arc-length samples are evenly spaced in angle.
+        let samples = circle.sample_by_length(13);
+        for (i, p) in samples.iter().enumerate() {
+            let angle = std::f64::consts::PI * i as f64 / 6.0;
+            assert!((*p - Point2::new(angle.cos(), angle.sin())).length() < 1e-9);
+        }
+
+        let mut refined = circle.clone();
+        refined.insert_knot(0.5);
+        refined.insert_knot(2.25);
+        for i in 0..=80 {
+            let t = i as f64 / 20.0;
+            assert!((refined.at(t) - circle.at(t)).length() < 1e-12);
+        }
+
+        // Unit weights reduce to the polynomial spline.
+        let points = control();
+        let plain = BSpline::clamped(3, points.clone());
+        let rational = Nurbs::clamped(3, points, vec![1.0; 7]);
+        for i in 0..=20 {
+            let t = i as f64 / 20.0;
+            assert!((plain.at(t) - rational.at(t)).length() < 1e-12);
+            assert!((plain.derivative(t) - rational.derivative(t)).length() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_hermite() {
+        let points = control();
+        let spline = Hermite::cardinal(points.clone(), 0.0);
+        for (i, p) in points.iter().enumerate() {
+            assert!((spline.at(i as f64) - *p).length() < 1e-12);
+            assert!((spline.derivative(i as f64) - spline.tangents[i].clone()).length() < 1e-12);
+        }
+        assert_eq!(spline.tangents[2], (points[3] - points[1]) * 0.5);
+        assert_eq!(spline.tangents[0], points[1] - points[0]);
+        check_derivative(&spline);
+
+        for i in 0..points.len() - 1 {
+            let bezier = spline.to_bezier(i);
+            for j in 0..=10 {
+                let u = j as f64 / 10.0;
+                assert!((bezier.at(u) - spline.at(i as f64 + u)).length() < 1e-12);
+            }
+        }
+
+        // Full tension stops at every point.
+        let tight = Hermite::cardinal(points, 1.0);
+        assert_eq!(tight.derivative(3.0), Vec2::all(0.0));
+
+        let arc_length = spline.sample_by_length(20);
+        let step = spline.length() / 19.0;
+        for pair in arc_length.windows(2) {
+            let a = spline.closest_parameter(&pair[0]);
+            let b = spline.closest_parameter(&pair[1]);
+            assert_close(spline.length_between(a, b), step, 1e-6);
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Helpers shared by the unit tests.
+
+use crate::point::Point2;
+use crate::polygon::Polygon2;
+
+/// Deterministic values in `[0, 1)` from a 64-bit linear congruential generator.
+pub fn random_values(n: usize, seed: u64) -> Vec<f64> {
+    let mut state = seed;
+    (0..n)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        })
+        .collect()
+}
+
+pub fn assert_close<T: Into<f64> + Copy>(a: T, b: T, tolerance: T) {
+    let (a, b): (f64, f64) = (a.into(), b.into());
+    assert!((a - b).abs() <= tolerance.into(), "{a} != {b}");
+}
+
+pub fn ring(coords: &[(f64, f64)]) -> Vec<Point2> {
+    coords.iter().map(|&(x, y)| Point2::new(x, y)).collect()
+}
+
+pub fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Polygon2 {
+    Polygon2::new(ring(&[(x0, y0), (x1, y0), (x1, y1), (x0, y1)]))
+}
+
+pub fn total_area(polygons: &[Polygon2]) -> f64 {
+    polygons.iter().map(|p| p.area()).sum()
+}
@@ -0,0 +1,578 @@
+use crate::image::{Image, Pixel};
+use crate::vec::Vec2;
+
+/// How texels are combined into a sample within one mipmap level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    /// The texel containing the sample point.
+    Nearest,
+    /// The four texels around the sample point, weighted by distance.
+    Linear,
+}
+
+/// How mipmap levels are chosen for minified samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MipmapMode {
+    /// Always the full-size level.
+    None,
+    /// The level nearest the level of detail.
+    Nearest,
+    /// A blend of the two levels around the level of detail.
+    Linear,
+}
+
+/// How texture coordinates outside `[0, 1]` map onto the texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    Repeat,
+    ClampToEdge,
+    MirroredRepeat,
+}
+
+impl WrapMode {
+    /// The texel `index` maps to in a row or column of `size` texels.
+    pub fn wrap(self, index: i64, size: usize) -> usize {
+        let n = size as i64;
+        let i = match self {
+            WrapMode::Repeat => index.rem_euclid(n),
+            WrapMode::ClampToEdge => index.clamp(0, n - 1),
+            WrapMode::MirroredRepeat => {
+                let i = index.rem_euclid(2 * n);
+                if i < n {
+                    i
+                } else {
+                    2 * n - 1 - i
+                }
+            }
+        };
+        i as usize
+    }
+}
+
+/// Filter used to shrink each mipmap level into the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MipmapFilter {
+    /// Averages the texels each smaller texel covers.
+    Box,
+    /// Kaiser-windowed sinc, sharper than the box while still suppressing aliasing.
+    Kaiser,
+}
+
+/// How a texture is sampled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sampler {
+    /// Filter for magnified samples, whose level of detail is at most zero.
+    pub mag_filter: Filter,
+    /// Filter within a level for minified samples.
+    pub min_filter: Filter,
+    pub mipmap: MipmapMode,
+    pub wrap_u: WrapMode,
+    pub wrap_v: WrapMode,
+    /// Most samples taken along the longer axis of a stretched footprint; one turns
+    /// anisotropic filtering off.
+    pub max_anisotropy: u32,
+    /// Added to the level of detail worked out from the derivatives.
+    pub lod_bias: f32,
+}
+
+impl Sampler {
+    pub fn nearest() -> Sampler {
+        Sampler {
+            mag_filter: Filter::Nearest,
+            min_filter: Filter::Nearest,
+            mipmap: MipmapMode::None,
+            wrap_u: WrapMode::Repeat,
+            wrap_v: WrapMode::Repeat,
+            max_anisotropy: 1,
+            lod_bias: 0.0,
+        }
+    }
+
+    pub fn bilinear() -> Sampler {
+        Sampler {
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Linear,
+            mipmap: MipmapMode::Nearest,
+            ..Sampler::nearest()
+        }
+    }
+
+    pub fn trilinear() -> Sampler {
+        Sampler {
+            mipmap: MipmapMode::Linear,
+            ..Sampler::bilinear()
+        }
+    }
+
+    pub fn with_wrap(self, wrap: WrapMode) -> Sampler {
+        Sampler {
+            wrap_u: wrap,
+            wrap_v: wrap,
+            ..self
+        }
+    }
+
+    pub fn with_anisotropy(self, max_anisotropy: u32) -> Sampler {
+        Sampler {
+            max_anisotropy: max_anisotropy.max(1),
+            ..self
+        }
+    }
+}
+
+impl Default for Sampler {
+    fn default() -> Sampler {
+        Sampler::trilinear()
+    }
+}
+
+/// An image with its chain of mipmap levels, sampled at texture coordinates where `(0, 0)`
+/// is the top-left corner of the first texel and `(1, 1)` the bottom-right of the last.
+/// Samples are straight-alpha RGBA.
+#[derive(Debug, Clone)]
+pub struct Texture2D<P: Pixel> {
+    levels: Vec<Image<P>>,
+}
+
+impl<P: Pixel> Texture2D<P> {
+    /// A texture of `image` alone, without smaller levels.
+    pub fn new(image: Image<P>) -> Texture2D<P> {
+        assert!(image.width() > 0 && image.height() > 0, "empty texture");
+        Texture2D {
+            levels: vec![image],
+        }
+    }
+
+    /// A texture of `image` with every mipmap level down to one texel, each level half the
+    /// size of the one before, rounded down.
+    pub fn with_mipmaps(image: Image<P>, filter: MipmapFilter) -> Texture2D<P> {
+        let mut texture = Texture2D::new(image);
+        loop {
+            let last = texture.levels.last().unwrap();
+            if last.width() == 1 && last.height() == 1 {
+                return texture;
+            }
+            let next = downsample(last, filter);
+            texture.levels.push(next);
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.levels[0].width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.levels[0].height()
+    }
+
+    pub fn levels(&self) -> &[Image<P>] {
+        &self.levels
+    }
+
+    pub fn level(&self, index: usize) -> &Image<P> {
+        &self.levels[index]
+    }
+
+    /// Samples the full-size level, as when magnified.
+    pub fn sample(&self, sampler: &Sampler, uv: &Vec2<f32>) -> [f32; 4] {
+        self.sample_lod(sampler, uv, 0.0)
+    }
+
+    /// Samples at a given level of detail, the base-two logarithm of how many texels of the
+    /// full-size level a pixel spans.
+    pub fn sample_lod(&self, sampler: &Sampler, uv: &Vec2<f32>, lod: f32) -> [f32; 4] {
+        let lod = lod + sampler.lod_bias;
+        if lod <= 0.0 {
+            return self.sample_level(sampler, sampler.mag_filter, 0, uv);
+        }
+        let filter = sampler.min_filter;
+        let last = (self.levels.len() - 1) as f32;
+        match sampler.mipmap {
+            MipmapMode::None => self.sample_level(sampler, filter, 0, uv),
+            MipmapMode::Nearest => {
+                let level = lod.round().min(last) as usize;
+                self.sample_level(sampler, filter, level, uv)
+            }
+            MipmapMode::Linear => {
+                let lod = lod.min(last);
+                let (level, t) = (lod.floor() as usize, lod.fract());
+                let a = self.sample_level(sampler, filter, level, uv);
+                if t == 0.0 {
+                    return a;
+                }
+                let b = self.sample_level(sampler, filter, level + 1, uv);
+                std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t)
+            }
+        }
+    }
+
+    /// Samples with the level of detail worked out from how far the texture coordinates move
+    /// from one pixel to the next along `x` (`ddx`) and `y` (`ddy`). With anisotropic
+    /// filtering a stretched footprint is covered by several samples along its longer axis,
+    /// each at the level of detail of the shorter one.
+    pub fn sample_grad(
+        &self,
+        sampler: &Sampler,
+        uv: &Vec2<f32>,
+        ddx: &Vec2<f32>,
+        ddy: &Vec2<f32>,
+    ) -> [f32; 4] {
+        let (x_len, y_len) = (self.texel_length(ddx), self.texel_length(ddy));
+        let (major, major_len, minor_len) = if x_len >= y_len {
+            (ddx, x_len, y_len)
+        } else {
+            (ddy, y_len, x_len)
+        };
+        let max = sampler.max_anisotropy.max(1) as f32;
+        let count = if minor_len > 0.0 {
+            (major_len / minor_len).ceil().clamp(1.0, max)
+        } else if major_len > 0.0 {
+            max
+        } else {
+            1.0
+        };
+        if count <= 1.0 {
+            return self.sample_lod(sampler, uv, major_len.log2());
+        }
+        let lod = (major_len / count).log2();
+        let mut total = [0.0; 4];
+        for i in 0..count as usize {
+            let offset = (i as f32 + 0.5) / count - 0.5;
+            let p = vec2![uv[0] + major[0] * offset, uv[1] + major[1] * offset];
+            let s = self.sample_lod(sampler, &p, lod);
+            for c in 0..4 {
+                total[c] += s[c] / count;
+            }
+        }
+        total
+    }
+
+    /// Level of detail of an isotropic sample for the given derivatives.
+    pub fn lod(&self, ddx: &Vec2<f32>, ddy: &Vec2<f32>) -> f32 {
+        self.texel_length(ddx).max(self.texel_length(ddy)).log2()
+    }
+
+    /// Length of a step in texture coordinates, measured in texels of the full-size level.
+    fn texel_length(&self, d: &Vec2<f32>) -> f32 {
+        let (x, y) = (d[0] * self.width() as f32, d[1] * self.height() as f32);
+        (x * x + y * y).sqrt()
+    }
+
+    fn sample_level(
+        &self,
+        sampler: &Sampler,
+        filter: Filter,
+        level: usize,
+        uv: &Vec2<f32>,
+    ) -> [f32; 4] {
+        let image = &self.levels[level];
+        let (w, h) = (image.width(), image.height());
+        let texel = |x: i64, y: i64| {
+            let (x, y) = (sampler.wrap_u.wrap(x, w), sampler.wrap_v.wrap(y, h));
+            image.pixels()[y * w + x].to_rgba()
+        };
+        let (x, y) = (uv[0] as f64 * w as f64, uv[1] as f64 * h as f64);
+        match filter {
+            Filter::Nearest => texel(x.floor() as i64, y.floor() as i64),
+            Filter::Linear => {
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = ((x - x0) as f32, (y - y0) as f32);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let (a, b) = (texel(x0, y0), texel(x0 + 1, y0));
+                let (c, d) = (texel(x0, y0 + 1), texel(x0 + 1, y0 + 1));
+                std::array::from_fn(|i| {
+                    let top = a[i] + (b[i] - a[i]) * fx;
+                    let bottom = c[i] + (d[i] - c[i]) * fx;
+                    top + (bottom - top) * fy
+                })
+            }
+        }
+    }
+}
+
+/// Kaiser window shape parameter; larger is smoother with a wider main lobe.
+const KAISER_BETA: f64 = 4.0;
+
+/// Radius of the Kaiser-windowed sinc, in texels of the smaller level.
+const KAISER_RADIUS: f64 = 3.0;
+
+/// The next mipmap level, half the size rounded down, filtered one axis at a time. Texels
+/// beyond the edges repeat the edge ones.
+fn downsample<P: Pixel>(image: &Image<P>, filter: MipmapFilter) -> Image<P> {
+    let (w, h) = (image.width(), image.height());
+    let (nw, nh) = ((w / 2).max(1), (h / 2).max(1));
+    let source: Vec<[f32; 4]> = image.pixels().iter().map(|p| p.to_rgba()).collect();
+    let columns = weights(w, nw, filter);
+    let rows = weights(h, nh, filter);
+    let mut wide = vec![[0.0f32; 4]; nw * h];
+    for y in 0..h {
+        for (x, taps) in columns.iter().enumerate() {
+            wide[y * nw + x] = weigh(taps, |i| &source[y * w + i]);
+        }
+    }
+    let mut pixels = Vec::with_capacity(nw * nh);
+    for taps in &rows {
+        for x in 0..nw {
+            let c = weigh(taps, |i| &wide[i * nw + x]);
+            pixels.push(P::from_rgba(c));
+        }
+    }
+    Image::from_pixels(nw, nh, pixels)
+}
+
+/// The weighted sum of the taps, kept within the range of the tapped values so that the
+/// negative lobes of the Kaiser filter do not ring past the source.
+fn weigh<'a>(taps: &[(usize, f32)], value: impl Fn(usize) -> &'a [f32; 4]) -> [f32; 4] {
+    let mut sum = [0.0; 4];
+    let mut lo = [f32::INFINITY; 4];
+    let mut hi = [f32::NEG_INFINITY; 4];
+    for &(i, weight) in taps {
+        let v = value(i);
+        for c in 0..4 {
+            sum[c] += v[c] * weight;
+            lo[c] = lo[c].min(v[c]);
+            hi[c] = hi[c].max(v[c]);
+        }
+    }
+    for c in 0..4 {
+        sum[c] = sum[c].clamp(lo[c], hi[c]);
+    }
+    sum
+}
+
+/// Source texels and their weights for each of `to` texels shrunk from `from`.
+fn weights(from: usize, to: usize, filter: MipmapFilter) -> Vec<Vec<(usize, f32)>> {
+    let scale = from as f64 / to as f64;
+    (0..to)
+        .map(|i| {
+            let mut taps: Vec<(usize, f64)> = match filter {
+                MipmapFilter::Box => {
+                    // How much of each source texel lies in this texel's footprint.
+                    let (lo, hi) = (i as f64 * scale, (i + 1) as f64 * scale);
+                    (lo.floor() as usize..(hi.ceil() as usize).min(from))
+                        .map(|j| (j, (hi.min(j as f64 + 1.0) - lo.max(j as f64)).max(0.0)))
+                        .collect()
+                }
+                MipmapFilter::Kaiser => {
+                    let center = (i as f64 + 0.5) * scale;
+                    let radius = KAISER_RADIUS * scale;
+                    let first = (center - radius).floor() as i64;
+                    let last = (center + radius).ceil() as i64;
+                    (first..=last)
+                        .map(|j| {
+                            let d = (j as f64 + 0.5 - center) / scale;
+                            let index = WrapMode::ClampToEdge.wrap(j, from);
+                            (index, sinc(d) * kaiser(d / KAISER_RADIUS))
+                        })
+                        .collect()
+                }
+            };
+            let total: f64 = taps.iter().map(|t| t.1).sum();
+            taps.retain(|t| t.1 != 0.0);
+            taps.into_iter()
+                .map(|(j, weight)| (j, (weight / total) as f32))
+                .collect()
+        })
+        .collect()
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        let x = x * std::f64::consts::PI;
+        x.sin() / x
+    }
+}
+
+/// The Kaiser window over `[-1, 1]`.
+fn kaiser(x: f64) -> f64 {
+    if x.abs() > 1.0 {
+        0.0
+    } else {
+        bessel_i0(KAISER_BETA * (1.0 - x * x).sqrt()) / bessel_i0(KAISER_BETA)
+    }
+}
+
+/// Modified Bessel function of the first kind of order zero, by its power series.
+fn bessel_i0(x: f64) -> f64 {
+    let (mut sum, mut term) = (1.0, 1.0);
+    let q = x * x / 4.0;
+    for k in 1..50 {
+        term *= q / (k * k) as f64;
+        sum += term;
+        if term < sum * 1e-16 {
+            break;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::Rgba32F;
+    use crate::test_util::assert_close;
+
+    fn gray(v: f32) -> Rgba32F {
+        Rgba32F::new(v, v, v, 1.0)
+    }
+
+    /// A texture whose texel `(x, y)` has red `x` and green `y`.
+    fn coordinates(width: usize, height: usize) -> Texture2D<Rgba32F> {
+        let pixels = (0..width * height)
+            .map(|i| Rgba32F::new((i % width) as f32, (i / width) as f32, 0.0, 1.0))
+            .collect();
+        Texture2D::new(Image::from_pixels(width, height, pixels))
+    }
+
+    #[test]
+    fn test_wrap_and_nearest() {
+        let wrapped = |mode: WrapMode| (-4..8).map(|i| mode.wrap(i, 3)).collect::<Vec<_>>();
+        assert_eq!(
+            wrapped(WrapMode::Repeat),
+            [2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1]
+        );
+        assert_eq!(
+            wrapped(WrapMode::ClampToEdge),
+            [0, 0, 0, 0, 0, 1, 2, 2, 2, 2, 2, 2]
+        );
+        assert_eq!(
+            wrapped(WrapMode::MirroredRepeat),
+            [2, 2, 1, 0, 0, 1, 2, 2, 1, 0, 0, 1]
+        );
+
+        let texture = coordinates(4, 2);
+        let at = |sampler: &Sampler, u: f32, v: f32| {
+            let c = texture.sample(sampler, &vec2![u, v]);
+            [c[0], c[1]]
+        };
+        let nearest = Sampler::nearest();
+        assert_eq!(at(&nearest, 0.3, 0.2), [1.0, 0.0]);
+        assert_eq!(at(&nearest, 0.99, 0.75), [3.0, 1.0]);
+        assert_eq!(at(&nearest, 1.1, -0.2), [0.0, 1.0]);
+        let clamp = nearest.with_wrap(WrapMode::ClampToEdge);
+        assert_eq!(at(&clamp, 1.1, -0.2), [3.0, 0.0]);
+        let mirror = nearest.with_wrap(WrapMode::MirroredRepeat);
+        assert_eq!(at(&mirror, 1.1, -0.2), [3.0, 0.0]);
+        assert_eq!(at(&mirror, -0.3, 1.7), [1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_bilinear() {
+        let texture = Texture2D::new(Image::from_pixels(2, 1, vec![gray(0.0), gray(1.0)]));
+        let sampler = Sampler::bilinear().with_wrap(WrapMode::ClampToEdge);
+        let red = |sampler: &Sampler, u: f32| texture.sample(sampler, &vec2![u, 0.5])[0];
+        // Texel centres are exact, and between them the value is linear.
+        assert_close(red(&sampler, 0.25), 0.0, 1e-6);
+        assert_close(red(&sampler, 0.75), 1.0, 1e-6);
+        assert_close(red(&sampler, 0.5), 0.5, 1e-6);
+        assert_close(red(&sampler, 0.4), 0.3, 1e-6);
+        assert_close(red(&sampler, 0.0), 0.0, 1e-6);
+        // Repeating blends the edge with the texel on the far side.
+        let repeat = sampler.with_wrap(WrapMode::Repeat);
+        assert_close(red(&repeat, 0.0), 0.5, 1e-6);
+        assert_close(red(&repeat, 1.125), 0.25, 1e-6);
+
+        let texture = coordinates(3, 3);
+        let c = texture.sample(&Sampler::bilinear(), &vec2![0.5, 0.5 + 1.0 / 12.0]);
+        assert_close(c[0], 1.0, 1e-6);
+        assert_close(c[1], 1.25, 1e-6);
+    }
+
+    #[test]
+    fn test_mipmaps() {
+        // A one-texel checkerboard of odd size.
+        let pixels = (0..7 * 5)
+            .map(|i| gray(((i % 7 + i / 7) % 2) as f32))
+            .collect();
+        let image = Image::from_pixels(7, 5, pixels);
+        let mean = |image: &Image<Rgba32F>| {
+            let sum: f32 = image.pixels().iter().map(|p| p.0[0]).sum();
+            sum / image.pixels().len() as f32
+        };
+        let boxed = Texture2D::with_mipmaps(image.clone(), MipmapFilter::Box);
+        let sizes: Vec<(usize, usize)> = boxed
+            .levels()
+            .iter()
+            .map(|l| (l.width(), l.height()))
+            .collect();
+        assert_eq!(sizes, [(7, 5), (3, 2), (1, 1)]);
+        // The box keeps the mean of every level.
+        for level in boxed.levels() {
+            assert_close(mean(level), mean(&image), 1e-5);
+        }
+        // The Kaiser filter takes the checkerboard, which is above the smaller level's
+        // highest frequency, to an even gray, and leaves an even colour as it is.
+        let kaiser = Texture2D::with_mipmaps(image, MipmapFilter::Kaiser);
+        assert_eq!(kaiser.levels().len(), 3);
+        for p in kaiser.level(1).pixels() {
+            assert_close(p.0[0], 0.5, 0.05);
+        }
+        let flat = Image::filled(16, 16, Rgba32F::new(0.2, 0.4, 0.6, 0.8));
+        let flat = Texture2D::with_mipmaps(flat, MipmapFilter::Kaiser);
+        assert_eq!(flat.levels().len(), 5);
+        for level in flat.levels() {
+            for p in level.pixels() {
+                for (c, v) in p.0.iter().zip([0.2, 0.4, 0.6, 0.8]) {
+                    assert_close(*c, v, 1e-5);
+                }
+            }
+        }
+        // Values outside [0, 1] survive, and a hard edge does not ring past either side.
+        let pixels = (0..16 * 16)
+            .map(|i| gray(if i % 16 < 5 { 4.0 } else { -1.0 }))
+            .collect();
+        let edge =
+            Texture2D::with_mipmaps(Image::from_pixels(16, 16, pixels), MipmapFilter::Kaiser);
+        let level = edge.level(1);
+        let red: Vec<f32> = level.pixels().iter().map(|p| p.0[0]).collect();
+        assert!(red.iter().any(|&v| v > 1.0) && red.iter().any(|&v| v < 0.0));
+        for p in level.pixels() {
+            assert!((-1.0..=4.0).contains(&p.0[0]));
+        }
+    }
+
+    #[test]
+    fn test_lod_and_anisotropy() {
+        // Rows alternate between black and white.
+        let pixels = (0..64 * 64).map(|i| gray(((i / 64) % 2) as f32)).collect();
+        let image = Image::from_pixels(64, 64, pixels);
+        let texture = Texture2D::with_mipmaps(image, MipmapFilter::Box);
+        let texel = 1.0 / 64.0;
+        assert_close(
+            texture.lod(&vec2![texel, 0.0], &vec2![0.0, texel]),
+            0.0,
+            1e-6,
+        );
+        assert_close(
+            texture.lod(&vec2![4.0 * texel, 0.0], &vec2![0.0, texel]),
+            2.0,
+            1e-6,
+        );
+
+        // Trilinear blends the two nearest levels, and magnification reads the full size.
+        let sampler = Sampler::trilinear();
+        // On a white row.
+        let uv = vec2![0.3, 33.5 * texel];
+        let one = texture.sample_lod(&sampler, &uv, 1.0);
+        let two = texture.sample_lod(&sampler, &uv, 2.0);
+        let between = texture.sample_lod(&sampler, &uv, 1.25);
+        assert_close(between[0], 0.75 * one[0] + 0.25 * two[0], 1e-6);
+        assert_eq!(texture.sample_lod(&sampler, &uv, -1.0), [1.0; 4]);
+
+        // A footprint stretched along the rows: the isotropic sample blurs the rows together,
+        // while the anisotropic one stays on the full-size level and keeps them apart.
+        let (ddx, ddy) = (vec2![8.0 * texel, 0.0], vec2![0.0, texel]);
+        let blurred = texture.sample_grad(&sampler, &uv, &ddx, &ddy);
+        assert_close(blurred[0], 0.5, 1e-6);
+        let sharp = texture.sample_grad(&sampler.with_anisotropy(8), &uv, &ddx, &ddy);
+        assert_close(sharp[0], 1.0, 1e-6);
+        // Too few samples for the stretch still reach a level where the rows are blurred.
+        let limited = texture.sample_grad(&sampler.with_anisotropy(2), &uv, &ddx, &ddy);
+        assert_close(limited[0], 0.5, 1e-6);
+        let no_motion = texture.sample_grad(&sampler, &uv, &vec2![0.0, 0.0], &vec2![0.0, 0.0]);
+        assert_eq!(no_motion, [1.0; 4]);
+    }
+}
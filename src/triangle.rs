@@ -0,0 +1,559 @@
+use crate::aabb::{Aabb2, Aabb3};
+use crate::point::{Point2, Point3};
+use crate::ray::Ray3;
+use crate::vec::Vec3;
+
+/// Which faces a ray query ignores. Front faces wind counter-clockwise around
+/// the normal `(b - a) x (c - a)` when seen from the ray origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Culling {
+    None,
+    Backface,
+}
+
+/// Result of a ray-triangle query. `u` and `v` are the barycentric weights of `b` and `c`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TriangleHit {
+    pub t: f64,
+    pub u: f64,
+    pub v: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle2 {
+    pub a: Point2,
+    pub b: Point2,
+    pub c: Point2,
+}
+
+impl Triangle2 {
+    pub fn new(a: Point2, b: Point2, c: Point2) -> Triangle2 {
+        Triangle2 { a, b, c }
+    }
+
+    /// Positive when the vertices wind counter-clockwise.
+    pub fn signed_area(&self) -> f64 {
+        (self.b - self.a).cross(&(self.c - self.a))[2] * 0.5
+    }
+
+    pub fn area(&self) -> f64 {
+        self.signed_area().abs()
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        (self.b - self.a).length() + (self.c - self.b).length() + (self.a - self.c).length()
+    }
+
+    pub fn aabb(&self) -> Aabb2 {
+        Aabb2::from_points([self.a, self.b, self.c])
+    }
+
+    /// Barycentric weights `(u, v, w)` of `p` for `a`, `b` and `c`, or `None` for a degenerate triangle.
+    pub fn barycentric(&self, p: &Point2) -> Option<Vec3<f64>> {
+        let v0 = self.b - self.a;
+        let v1 = self.c - self.a;
+        let v2 = *p - self.a;
+        let d00 = v0.dot(&v0);
+        let d01 = v0.dot(&v1);
+        let d11 = v1.dot(&v1);
+        let d20 = v2.dot(&v0);
+        let d21 = v2.dot(&v1);
+        let denom = d00 * d11 - d01 * d01;
+        if denom == 0.0 {
+            return None;
+        }
+        let v = (d11 * d20 - d01 * d21) / denom;
+        let w = (d00 * d21 - d01 * d20) / denom;
+        Some(vec3![1.0 - v - w, v, w])
+    }
+
+    pub fn from_barycentric(&self, uvw: &Vec3<f64>) -> Point2 {
+        Point2::from_vec(
+            self.a.to_vec() * uvw[0] + self.b.to_vec() * uvw[1] + self.c.to_vec() * uvw[2],
+        )
+    }
+
+    /// Inclusive of the boundary, for either winding.
+    pub fn contains(&self, p: &Point2) -> bool {
+        let d0 = (self.b - self.a).cross(&(*p - self.a))[2];
+        let d1 = (self.c - self.b).cross(&(*p - self.b))[2];
+        let d2 = (self.a - self.c).cross(&(*p - self.c))[2];
+        let has_neg = d0 < 0.0 || d1 < 0.0 || d2 < 0.0;
+        let has_pos = d0 > 0.0 || d1 > 0.0 || d2 > 0.0;
+        !(has_neg && has_pos)
+    }
+
+    /// Closest point on (or in) the triangle, using Ericson's Voronoi-region method.
+    pub fn closest_point(&self, p: &Point2) -> Point2 {
+        let (a, b, c) = (self.a, self.b, self.c);
+        let ab = b - a;
+        let ac = c - a;
+        let ap = *p - a;
+        let d1 = ab.dot(&ap);
+        let d2 = ac.dot(&ap);
+        if d1 <= 0.0 && d2 <= 0.0 {
+            return a;
+        }
+
+        let bp = *p - b;
+        let d3 = ab.dot(&bp);
+        let d4 = ac.dot(&bp);
+        if d3 >= 0.0 && d4 <= d3 {
+            return b;
+        }
+
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+            return a + ab * (d1 / (d1 - d3));
+        }
+
+        let cp = *p - c;
+        let d5 = ab.dot(&cp);
+        let d6 = ac.dot(&cp);
+        if d6 >= 0.0 && d5 <= d6 {
+            return c;
+        }
+
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+            return a + ac * (d2 / (d2 - d6));
+        }
+
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+            return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+        }
+
+        let denom = 1.0 / (va + vb + vc);
+        a + ab * (vb * denom) + ac * (vc * denom)
+    }
+
+    /// Circle through all three vertices, or `None` when they are collinear.
+    pub fn circumcircle(&self) -> Option<(Point2, f64)> {
+        let (a, b, c) = (self.a, self.b, self.c);
+        let d = 2.0 * (a[0] * (b[1] - c[1]) + b[0] * (c[1] - a[1]) + c[0] * (a[1] - b[1]));
+        if d == 0.0 {
+            return None;
+        }
+        let a2 = a.to_vec().square_length();
+        let b2 = b.to_vec().square_length();
+        let c2 = c.to_vec().square_length();
+        let center = Point2::new(
+            (a2 * (b[1] - c[1]) + b2 * (c[1] - a[1]) + c2 * (a[1] - b[1])) / d,
+            (a2 * (c[0] - b[0]) + b2 * (a[0] - c[0]) + c2 * (b[0] - a[0])) / d,
+        );
+        Some((center, (a - center).length()))
+    }
+
+    /// Largest circle inside the triangle.
+    pub fn incircle(&self) -> (Point2, f64) {
+        let la = (self.c - self.b).length();
+        let lb = (self.a - self.c).length();
+        let lc = (self.b - self.a).length();
+        let perimeter = la + lb + lc;
+        if perimeter == 0.0 {
+            return (self.a, 0.0);
+        }
+        let center = self.from_barycentric(&(vec3![la, lb, lc] / perimeter));
+        (center, 2.0 * self.area() / perimeter)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle3 {
+    pub a: Point3,
+    pub b: Point3,
+    pub c: Point3,
+}
+
+impl Triangle3 {
+    pub fn new(a: Point3, b: Point3, c: Point3) -> Triangle3 {
+        Triangle3 { a, b, c }
+    }
+
+    pub fn area(&self) -> f64 {
+        (self.b - self.a).cross(&(self.c - self.a)).length() * 0.5
+    }
+
+    /// Unnormalized normal, with length twice the area.
+    pub fn scaled_normal(&self) -> Vec3<f64> {
+        (self.b - self.a).cross(&(self.c - self.a))
+    }
+
+    /// Unit normal following the right-hand rule over `a`, `b`, `c`.
+    pub fn normal(&self) -> Vec3<f64> {
+        self.scaled_normal().normalize()
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        Point3::from_vec((self.a.to_vec() + self.b.to_vec() + self.c.to_vec()) / 3.0)
+    }
+
+    pub fn aabb(&self) -> Aabb3 {
+        Aabb3::from_points([self.a, self.b, self.c])
+    }
+
+    /// Barycentric weights `(u, v, w)` for `a`, `b` and `c` of `p` projected onto the
+    /// triangle's plane, or `None` for a degenerate triangle.
+    pub fn barycentric(&self, p: &Point3) -> Option<Vec3<f64>> {
+        let v0 = self.b - self.a;
+        let v1 = self.c - self.a;
+        let v2 = *p - self.a;
+        let d00 = v0.dot(&v0);
+        let d01 = v0.dot(&v1);
+        let d11 = v1.dot(&v1);
+        let d20 = v2.dot(&v0);
+        let d21 = v2.dot(&v1);
+        let denom = d00 * d11 - d01 * d01;
+        if denom == 0.0 {
+            return None;
+        }
+        let v = (d11 * d20 - d01 * d21) / denom;
+        let w = (d00 * d21 - d01 * d20) / denom;
+        Some(vec3![1.0 - v - w, v, w])
+    }
+
+    pub fn from_barycentric(&self, uvw: &Vec3<f64>) -> Point3 {
+        Point3::from_vec(
+            self.a.to_vec() * uvw[0] + self.b.to_vec() * uvw[1] + self.c.to_vec() * uvw[2],
+        )
+    }
+
+    /// Closest point on the triangle, using Ericson's Voronoi-region method.
+    pub fn closest_point(&self, p: &Point3) -> Point3 {
+        let (a, b, c) = (self.a, self.b, self.c);
+        let ab = b - a;
+        let ac = c - a;
+        let ap = *p - a;
+        let d1 = ab.dot(&ap);
+        let d2 = ac.dot(&ap);
+        if d1 <= 0.0 && d2 <= 0.0 {
+            return a;
+        }
+
+        let bp = *p - b;
+        let d3 = ab.dot(&bp);
+        let d4 = ac.dot(&bp);
+        if d3 >= 0.0 && d4 <= d3 {
+            return b;
+        }
+
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+            return a + ab * (d1 / (d1 - d3));
+        }
+
+        let cp = *p - c;
+        let d5 = ab.dot(&cp);
+        let d6 = ac.dot(&cp);
+        if d6 >= 0.0 && d5 <= d6 {
+            return c;
+        }
+
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+            return a + ac * (d2 / (d2 - d6));
+        }
+
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+            return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+        }
+
+        let denom = 1.0 / (va + vb + vc);
+        a + ab * (vb * denom) + ac * (vc * denom)
+    }
+
+    /// Circle through all three vertices, lying in the triangle's plane.
+    pub fn circumcircle(&self) -> Option<(Point3, f64)> {
+        let u = self.a - self.c;
+        let v = self.b - self.c;
+        let n = u.cross(&v);
+        let n2 = n.square_length();
+        if n2 == 0.0 {
+            return None;
+        }
+        let offset = (v.clone() * u.square_length() - u * v.square_length()).cross(&n) / (2.0 * n2);
+        let radius = offset.length();
+        Some((self.c + offset, radius))
+    }
+
+    /// Largest circle inside the triangle.
+    pub fn incircle(&self) -> (Point3, f64) {
+        let la = (self.c - self.b).length();
+        let lb = (self.a - self.c).length();
+        let lc = (self.b - self.a).length();
+        let perimeter = la + lb + lc;
+        if perimeter == 0.0 {
+            return (self.a, 0.0);
+        }
+        let center = self.from_barycentric(&(vec3![la, lb, lc] / perimeter));
+        (center, 2.0 * self.area() / perimeter)
+    }
+
+    /// Möller–Trumbore ray intersection. Only hits with `t >= 0` are reported.
+    pub fn ray_intersection(&self, ray: &Ray3, culling: Culling) -> Option<TriangleHit> {
+        const EPSILON: f64 = 1e-12;
+        let e1 = self.b - self.a;
+        let e2 = self.c - self.a;
+        let pvec = ray.direction.cross(&e2);
+        let det = e1.dot(&pvec);
+        match culling {
+            Culling::Backface if det < EPSILON => return None,
+            Culling::None if det.abs() < EPSILON => return None,
+            _ => {}
+        }
+        let inv_det = 1.0 / det;
+
+        let tvec = ray.origin - self.a;
+        let u = tvec.dot(&pvec) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let qvec = tvec.cross(&e1);
+        let v = ray.direction.dot(&qvec) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = e2.dot(&qvec) * inv_det;
+        if t < 0.0 {
+            return None;
+        }
+        Some(TriangleHit { t, u, v })
+    }
+
+    /// Watertight ray intersection (Woop, Benthin and Wald, 2013). Rays through a shared
+    /// edge or vertex of a closed mesh hit at least one of the adjacent triangles.
+    pub fn ray_intersection_watertight(&self, ray: &Ray3, culling: Culling) -> Option<TriangleHit> {
+        let dir = &ray.direction;
+
+        // Permute axes so that the dominant direction component becomes z.
+        let kz = if dir[0].abs() > dir[1].abs() {
+            if dir[0].abs() > dir[2].abs() {
+                0
+            } else {
+                2
+            }
+        } else if dir[1].abs() > dir[2].abs() {
+            1
+        } else {
+            2
+        };
+        let mut kx = (kz + 1) % 3;
+        let mut ky = (kx + 1) % 3;
+        if dir[kz] < 0.0 {
+            std::mem::swap(&mut kx, &mut ky);
+        }
+
+        // Shear so that the ray points along +z.
+        let sx = dir[kx] / dir[kz];
+        let sy = dir[ky] / dir[kz];
+        let sz = 1.0 / dir[kz];
+
+        let a = self.a - ray.origin;
+        let b = self.b - ray.origin;
+        let c = self.c - ray.origin;
+        let ax = a[kx] - sx * a[kz];
+        let ay = a[ky] - sy * a[kz];
+        let bx = b[kx] - sx * b[kz];
+        let by = b[ky] - sy * b[kz];
+        let cx = c[kx] - sx * c[kz];
+        let cy = c[ky] - sy * c[kz];
+
+        let u = cx * by - cy * bx;
+        let v = ax * cy - ay * cx;
+        let w = bx * ay - by * ax;
+
+        match culling {
+            Culling::Backface if u < 0.0 || v < 0.0 || w < 0.0 => return None,
+            Culling::None if (u < 0.0 || v < 0.0 || w < 0.0) && (u > 0.0 || v > 0.0 || w > 0.0) => {
+                return None
+            }
+            _ => {}
+        }
+
+        let det = u + v + w;
+        if det == 0.0 {
+            return None;
+        }
+
+        let az = sz * a[kz];
+        let bz = sz * b[kz];
+        let cz = sz * c[kz];
+        let t_scaled = u * az + v * bz + w * cz;
+        if (det > 0.0 && t_scaled < 0.0) || (det < 0.0 && t_scaled > 0.0) {
+            return None;
+        }
+
+        let inv_det = 1.0 / det;
+        Some(TriangleHit {
+            t: t_scaled * inv_det,
+            u: v * inv_det,
+            v: w * inv_det,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::assert_close;
+
+    fn unit_triangle2() -> Triangle2 {
+        Triangle2::new(
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(0.0, 1.0),
+        )
+    }
+
+    fn unit_triangle3() -> Triangle3 {
+        Triangle3::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn test_area_normal() {
+        assert_eq!(unit_triangle2().signed_area(), 0.5);
+        let t = unit_triangle2();
+        assert_eq!(Triangle2::new(t.a, t.c, t.b).signed_area(), -0.5);
+        assert_eq!(unit_triangle3().area(), 0.5);
+        assert_eq!(unit_triangle3().normal(), vec3![0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_barycentric_roundtrip() {
+        let t = Triangle3::new(
+            Point3::new(1.0, 2.0, 3.0),
+            Point3::new(4.0, -1.0, 0.0),
+            Point3::new(-2.0, 0.5, 1.0),
+        );
+        let p = t.from_barycentric(&vec3![0.2, 0.3, 0.5]);
+        let uvw = t.barycentric(&p).unwrap();
+        assert_close(uvw[0], 0.2, 1e-12);
+        assert_close(uvw[1], 0.3, 1e-12);
+        assert_close(uvw[2], 0.5, 1e-12);
+
+        let t2 = unit_triangle2();
+        assert_eq!(
+            t2.barycentric(&Point2::new(0.0, 0.0)),
+            Some(vec3![1.0, 0.0, 0.0])
+        );
+    }
+
+    #[test]
+    fn test_contains() {
+        let t = unit_triangle2();
+        assert!(t.contains(&Point2::new(0.25, 0.25)));
+        assert!(t.contains(&Point2::new(0.5, 0.5)));
+        assert!(!t.contains(&Point2::new(0.6, 0.6)));
+        let flipped = Triangle2::new(t.a, t.c, t.b);
+        assert!(flipped.contains(&Point2::new(0.25, 0.25)));
+    }
+
+    #[test]
+    fn test_closest_point() {
+        let t = unit_triangle3();
+        // Vertex, edge and face regions.
+        assert_eq!(t.closest_point(&Point3::new(-1.0, -1.0, 0.0)), t.a);
+        assert_eq!(
+            t.closest_point(&Point3::new(0.5, -1.0, 2.0)),
+            Point3::new(0.5, 0.0, 0.0)
+        );
+        assert_eq!(
+            t.closest_point(&Point3::new(1.0, 1.0, 0.0)),
+            Point3::new(0.5, 0.5, 0.0)
+        );
+        let q = t.closest_point(&Point3::new(0.2, 0.2, 5.0));
+        assert!((q - Point3::new(0.2, 0.2, 0.0)).length() < 1e-12);
+
+        let t2 = unit_triangle2();
+        assert_eq!(
+            t2.closest_point(&Point2::new(-1.0, 0.5)),
+            Point2::new(0.0, 0.5)
+        );
+        let q = t2.closest_point(&Point2::new(0.1, 0.1));
+        assert!((q - Point2::new(0.1, 0.1)).length() < 1e-12);
+    }
+
+    #[test]
+    fn test_circles() {
+        let t = Triangle2::new(
+            Point2::new(0.0, 0.0),
+            Point2::new(4.0, 0.0),
+            Point2::new(0.0, 3.0),
+        );
+        let (center, r) = t.circumcircle().unwrap();
+        assert_eq!(center, Point2::new(2.0, 1.5));
+        assert_eq!(r, 2.5);
+        let (center, r) = t.incircle();
+        assert!((center - Point2::new(1.0, 1.0)).length() < 1e-12);
+        assert_close(r, 1.0, 1e-12);
+
+        let collinear = Triangle2::new(
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(2.0, 2.0),
+        );
+        assert!(collinear.circumcircle().is_none());
+    }
+
+    #[test]
+    fn test_ray_intersection() {
+        let t = unit_triangle3();
+        let front = Ray3::new(Point3::new(0.2, 0.3, 1.0), vec3![0.0, 0.0, -1.0]);
+        let back = Ray3::new(Point3::new(0.2, 0.3, -1.0), vec3![0.0, 0.0, 1.0]);
+        let miss = Ray3::new(Point3::new(0.8, 0.8, 1.0), vec3![0.0, 0.0, -1.0]);
+
+        for hit in [
+            t.ray_intersection(&front, Culling::Backface),
+            t.ray_intersection_watertight(&front, Culling::Backface),
+        ] {
+            let hit = hit.unwrap();
+            assert_close(hit.t, 1.0, 1e-12);
+            assert_close(hit.u, 0.2, 1e-12);
+            assert_close(hit.v, 0.3, 1e-12);
+        }
+        assert!(t.ray_intersection(&back, Culling::Backface).is_none());
+        assert!(t
+            .ray_intersection_watertight(&back, Culling::Backface)
+            .is_none());
+        assert!(t.ray_intersection(&back, Culling::None).is_some());
+        assert!(t
+            .ray_intersection_watertight(&back, Culling::None)
+            .is_some());
+        assert!(t.ray_intersection(&miss, Culling::None).is_none());
+        assert!(t
+            .ray_intersection_watertight(&miss, Culling::None)
+            .is_none());
+    }
+
+    #[test]
+    fn test_watertight_shared_edge() {
+        // Two triangles sharing the diagonal of the unit square; a ray exactly on the
+        // diagonal must hit at least one of them.
+        let t0 = unit_triangle3();
+        let t1 = Triangle3::new(
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        );
+        for i in 1..10 {
+            let x = i as f64 / 10.0;
+            let ray = Ray3::new(Point3::new(x, 1.0 - x, 1.0), vec3![0.0, 0.0, -1.0]);
+            let hits = [&t0, &t1]
+                .iter()
+                .filter(|t| {
+                    t.ray_intersection_watertight(&ray, Culling::Backface)
+                        .is_some()
+                })
+                .count();
+            assert!(hits >= 1);
+        }
+    }
+}
@@ -0,0 +1,652 @@
+use crate::image::{Image, Pixel};
+use crate::vec::{Vec2, Vec3, Vec4};
+
+/// Bits of sub-pixel precision vertices are snapped to.
+pub const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL: i64 = 1 << SUBPIXEL_BITS;
+
+/// Vertices must lie within this many pixels of the origin, which keeps the fixed-point edge
+/// functions well inside `i64`. Triangles clipped to the view frustum always do.
+pub const GUARD_BAND: f64 = (1 << 20) as f64;
+
+/// Side of the square blocks of pixels tested against a triangle at once.
+pub const TILE_SIZE: usize = 8;
+
+/// Side of the square screen regions triangles are binned into for [`draw_triangles`].
+pub const BIN_SIZE: usize = 64;
+
+/// Values given per vertex and interpolated across a triangle.
+pub trait Varyings: Sized {
+    /// The sum of `values` weighted by `weights`, which add up to one.
+    fn interpolate(values: &[Self; 3], weights: [f64; 3]) -> Self;
+}
+
+impl Varyings for () {
+    fn interpolate(_: &[(); 3], _: [f64; 3]) {}
+}
+
+impl Varyings for f64 {
+    fn interpolate(values: &[f64; 3], weights: [f64; 3]) -> f64 {
+        values[0] * weights[0] + values[1] * weights[1] + values[2] * weights[2]
+    }
+}
+
+impl Varyings for f32 {
+    fn interpolate(values: &[f32; 3], weights: [f64; 3]) -> f32 {
+        f64::interpolate(&values.map(f64::from), weights) as f32
+    }
+}
+
+impl<V: Varyings + Copy, const N: usize> Varyings for [V; N] {
+    fn interpolate(values: &[[V; N]; 3], weights: [f64; 3]) -> [V; N] {
+        std::array::from_fn(|i| {
+            V::interpolate(&[values[0][i], values[1][i], values[2][i]], weights)
+        })
+    }
+}
+
+impl Varyings for Vec<f64> {
+    fn interpolate(values: &[Vec<f64>; 3], weights: [f64; 3]) -> Vec<f64> {
+        (0..values[0].len())
+            .map(|i| f64::interpolate(&[values[0][i], values[1][i], values[2][i]], weights))
+            .collect()
+    }
+}
+
+macro_rules! impl_varyings_vec {
+    ($vec:ident, $t:ty) => {
+        impl Varyings for $vec<$t> {
+            fn interpolate(values: &[$vec<$t>; 3], weights: [f64; 3]) -> $vec<$t> {
+                $vec(<_>::interpolate(
+                    &[values[0].0, values[1].0, values[2].0],
+                    weights,
+                ))
+            }
+        }
+    };
+}
+
+impl_varyings_vec!(Vec2, f64);
+impl_varyings_vec!(Vec3, f64);
+impl_varyings_vec!(Vec4, f64);
+impl_varyings_vec!(Vec2, f32);
+impl_varyings_vec!(Vec3, f32);
+impl_varyings_vec!(Vec4, f32);
+
+impl<A: Varyings + Clone, B: Varyings + Clone> Varyings for (A, B) {
+    fn interpolate(values: &[(A, B); 3], weights: [f64; 3]) -> (A, B) {
+        let a = values.clone().map(|v| v.0);
+        let b = values.clone().map(|v| v.1);
+        (A::interpolate(&a, weights), B::interpolate(&b, weights))
+    }
+}
+
+impl<A: Varyings + Clone, B: Varyings + Clone, C: Varyings + Clone> Varyings for (A, B, C) {
+    fn interpolate(values: &[(A, B, C); 3], weights: [f64; 3]) -> (A, B, C) {
+        let a = values.clone().map(|v| v.0);
+        let b = values.clone().map(|v| v.1);
+        let c = values.clone().map(|v| v.2);
+        (
+            A::interpolate(&a, weights),
+            B::interpolate(&b, weights),
+            C::interpolate(&c, weights),
+        )
+    }
+}
+
+/// A rectangle of pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl PixelRect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> PixelRect {
+        PixelRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// A pixel whose centre a triangle covers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fragment {
+    pub x: usize,
+    pub y: usize,
+    /// The vertices' `z` interpolated linearly across the screen.
+    pub depth: f64,
+    /// Perspective-correct weights of the three vertices, summing to one.
+    pub weights: [f64; 3],
+    /// Whether the vertices wind clockwise on screen, with `y` pointing down.
+    pub clockwise: bool,
+}
+
+impl Fragment {
+    /// The vertices' `values` interpolated to this pixel.
+    pub fn interpolate<V: Varyings>(&self, values: &[V; 3]) -> V {
+        V::interpolate(values, self.weights)
+    }
+}
+
+/// Edge function `(b - a) x (p - a)` in fixed point, positive inside the triangle.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    a: [i64; 2],
+    d: [i64; 2],
+    /// Zero for top and left edges, whose pixels the triangle owns, and minus one otherwise.
+    bias: i64,
+}
+
+impl Edge {
+    fn new(a: [i64; 2], b: [i64; 2]) -> Edge {
+        let d = [b[0] - a[0], b[1] - a[1]];
+        // With `y` down and the inside on the right, left edges go up and top edges go right.
+        let top_left = d[1] < 0 || (d[1] == 0 && d[0] > 0);
+        Edge {
+            a,
+            d,
+            bias: if top_left { 0 } else { -1 },
+        }
+    }
+
+    /// The edge function at the centre of pixel `(x, y)`.
+    fn at(&self, x: i64, y: i64) -> i64 {
+        let p = [x * SUBPIXEL + SUBPIXEL / 2, y * SUBPIXEL + SUBPIXEL / 2];
+        self.d[0] * (p[1] - self.a[1]) - self.d[1] * (p[0] - self.a[0])
+    }
+
+    /// Change of the edge function from one pixel to the next along `x` and `y`.
+    fn steps(&self) -> (i64, i64) {
+        (-self.d[1] * SUBPIXEL, self.d[0] * SUBPIXEL)
+    }
+}
+
+/// Rasterizes a triangle into a `width` by `height` target. See [`rasterize_triangle_in`].
+pub fn rasterize_triangle<F: FnMut(&Fragment)>(
+    positions: &[Vec4<f64>; 3],
+    width: usize,
+    height: usize,
+    fragment: F,
+) {
+    rasterize_triangle_in(positions, &PixelRect::new(0, 0, width, height), fragment);
+}
+
+/// Calls `fragment` for every pixel of `rect` whose centre the triangle covers.
+///
+/// Positions are in screen space: `x` and `y` in pixels, `z` the depth, and `w` the clip-space
+/// `w` the perspective divide used, which makes the weights perspective-correct. Vertices are
+/// snapped to [`SUBPIXEL_BITS`] of sub-pixel precision and pixels on an edge belong to the
+/// triangle only if it is a top or left edge, so triangles sharing edges cover each pixel
+/// once. Pixels are visited a [`TILE_SIZE`] block at a time, skipping blocks outside the
+/// triangle and taking those wholly inside without testing each pixel. Triangles with no area
+/// or vertices outside the [`GUARD_BAND`] are not drawn.
+pub fn rasterize_triangle_in<F: FnMut(&Fragment)>(
+    positions: &[Vec4<f64>; 3],
+    rect: &PixelRect,
+    mut fragment: F,
+) {
+    if positions
+        .iter()
+        .any(|p| !(p[0].abs() <= GUARD_BAND && p[1].abs() <= GUARD_BAND))
+    {
+        return;
+    }
+    let snapped = positions.clone().map(|p| {
+        [
+            (p[0] * SUBPIXEL as f64).round() as i64,
+            (p[1] * SUBPIXEL as f64).round() as i64,
+        ]
+    });
+    let [p0, p1, p2] = snapped;
+    let area = (p1[0] - p0[0]) * (p2[1] - p0[1]) - (p1[1] - p0[1]) * (p2[0] - p0[0]);
+    if area == 0 {
+        return;
+    }
+    let clockwise = area > 0;
+    // Walk the vertices so the inside is on the right of every edge. Edge `i` is opposite
+    // vertex `order[i]`, and its function divided by the area is that vertex's weight.
+    let order = if clockwise { [0, 1, 2] } else { [0, 2, 1] };
+    let edges: [Edge; 3] = std::array::from_fn(|i| {
+        Edge::new(snapped[order[(i + 1) % 3]], snapped[order[(i + 2) % 3]])
+    });
+    let area = area.abs() as f64;
+
+    // Pixels of `rect` whose centres lie within the triangle's bounds.
+    let range = |axis: usize, start: usize, len: usize| {
+        let lo = snapped.iter().map(|p| p[axis]).min().unwrap() - SUBPIXEL / 2;
+        let hi = snapped.iter().map(|p| p[axis]).max().unwrap() - SUBPIXEL / 2;
+        let first = (-(-lo).div_euclid(SUBPIXEL)).max(start as i64);
+        let last = hi.div_euclid(SUBPIXEL).min((start + len) as i64 - 1);
+        (first, last)
+    };
+    let (x0, x1) = range(0, rect.x, rect.width);
+    let (y0, y1) = range(1, rect.y, rect.height);
+    if x0 > x1 || y0 > y1 {
+        return;
+    }
+
+    let inverse_w = positions.clone().map(|p| 1.0 / p[3]);
+    let mut emit = |x: i64, y: i64, values: [i64; 3]| {
+        let mut screen = [0.0; 3];
+        for i in 0..3 {
+            screen[order[i]] = values[i] as f64 / area;
+        }
+        let depth = (0..3).map(|i| screen[i] * positions[i][2]).sum();
+        let perspective = [0, 1, 2].map(|i| screen[i] * inverse_w[i]);
+        let total: f64 = perspective.iter().sum();
+        fragment(&Fragment {
+            x: x as usize,
+            y: y as usize,
+            depth,
+            weights: perspective.map(|q| q / total),
+            clockwise,
+        });
+    };
+
+    let tile = TILE_SIZE as i64;
+    let steps = edges.map(|e| e.steps());
+    let mut ty = y0.div_euclid(tile) * tile;
+    while ty <= y1 {
+        let mut tx = x0.div_euclid(tile) * tile;
+        while tx <= x1 {
+            // Edge functions are linear, so their extremes over the block are at its corners.
+            let corners = [(tx, ty), (tx + tile - 1, ty), (tx, ty + tile - 1)];
+            let mut outside = false;
+            let mut inside = true;
+            for e in &edges {
+                let values = corners.map(|(x, y)| e.at(x, y) + e.bias);
+                let far = values[1] + values[2] - values[0];
+                let (lo, hi) = values
+                    .iter()
+                    .chain([&far])
+                    .fold((i64::MAX, i64::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+                outside |= hi < 0;
+                inside &= lo >= 0;
+            }
+            if !outside {
+                let (xa, xb) = (tx.max(x0), (tx + tile - 1).min(x1));
+                let (ya, yb) = (ty.max(y0), (ty + tile - 1).min(y1));
+                let mut row = edges.map(|e| e.at(xa, ya));
+                for y in ya..=yb {
+                    let mut values = row;
+                    for x in xa..=xb {
+                        if inside || (0..3).all(|i| values[i] + edges[i].bias >= 0) {
+                            emit(x, y, values);
+                        }
+                        for i in 0..3 {
+                            values[i] += steps[i].0;
+                        }
+                    }
+                    for i in 0..3 {
+                        row[i] += steps[i].1;
+                    }
+                }
+            }
+            tx += tile;
+        }
+        ty += tile;
+    }
+}
+
+/// Triangles sorted into the square screen regions their bounds touch, in the order given.
+#[derive(Debug, Clone)]
+pub struct TileBins {
+    width: usize,
+    height: usize,
+    size: usize,
+    columns: usize,
+    bins: Vec<Vec<usize>>,
+}
+
+impl TileBins {
+    /// Bins `triangles`, in screen-space positions, into regions `size` pixels square of a
+    /// `width` by `height` target. The triangles are split between `threads` threads, each
+    /// binning its share on its own, and their bins are joined in order.
+    pub fn new(
+        triangles: &[[Vec4<f64>; 3]],
+        width: usize,
+        height: usize,
+        size: usize,
+        threads: usize,
+    ) -> TileBins {
+        let columns = width.div_ceil(size);
+        let rows = height.div_ceil(size);
+        let bin_share = |offset: usize, share: &[[Vec4<f64>; 3]]| {
+            let mut bins = vec![Vec::new(); columns * rows];
+            for (i, t) in share.iter().enumerate() {
+                let Some((c0, c1, r0, r1)) = bin_range(t, width, height, size) else {
+                    continue;
+                };
+                for r in r0..=r1 {
+                    for c in c0..=c1 {
+                        bins[r * columns + c].push(offset + i);
+                    }
+                }
+            }
+            bins
+        };
+        let chunk = triangles.len().div_ceil(threads.max(1)).max(1);
+        let shares: Vec<Vec<Vec<usize>>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = triangles
+                .chunks(chunk)
+                .enumerate()
+                .map(|(i, share)| scope.spawn(move || bin_share(i * chunk, share)))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        let mut bins = vec![Vec::new(); columns * rows];
+        for share in shares {
+            for (bin, indices) in bins.iter_mut().zip(share) {
+                bin.extend(indices);
+            }
+        }
+        TileBins {
+            width,
+            height,
+            size,
+            columns,
+            bins,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bins.is_empty()
+    }
+
+    /// The pixels of bin `index`, clipped to the target.
+    pub fn rect(&self, index: usize) -> PixelRect {
+        let (x, y) = (
+            index % self.columns * self.size,
+            index / self.columns * self.size,
+        );
+        PixelRect::new(
+            x,
+            y,
+            self.size.min(self.width - x),
+            self.size.min(self.height - y),
+        )
+    }
+
+    /// Indices of the triangles touching bin `index`, in the order given.
+    pub fn triangles(&self, index: usize) -> &[usize] {
+        &self.bins[index]
+    }
+}
+
+/// Columns and rows of the bins a triangle's bounds touch.
+fn bin_range(
+    triangle: &[Vec4<f64>; 3],
+    width: usize,
+    height: usize,
+    size: usize,
+) -> Option<(usize, usize, usize, usize)> {
+    let range = |axis: usize, extent: usize| {
+        let lo = triangle
+            .iter()
+            .map(|p| p[axis])
+            .fold(f64::INFINITY, f64::min);
+        let hi = triangle
+            .iter()
+            .map(|p| p[axis])
+            .fold(f64::NEG_INFINITY, f64::max);
+        // Pixels sample their centres, so bounds snapped to an edge still reach only this far.
+        let lo = (lo - 0.5).floor().max(0.0);
+        let hi = (hi - 0.5).ceil().min(extent as f64 - 1.0);
+        (lo <= hi).then(|| (lo as usize / size, hi as usize / size))
+    };
+    let (c0, c1) = range(0, width)?;
+    let (r0, r1) = range(1, height)?;
+    Some((c0, c1, r0, r1))
+}
+
+/// Draws `triangles`, in screen-space positions, into `image` on `threads` threads. The
+/// triangles are binned into [`BIN_SIZE`] regions, and each thread takes whole regions,
+/// drawing their triangles in the order given into its own copy of the region's pixels.
+///
+/// `shade` is given the index of the triangle, the fragment and the pixel under it to update.
+pub fn draw_triangles<P, F>(
+    image: &mut Image<P>,
+    triangles: &[[Vec4<f64>; 3]],
+    threads: usize,
+    shade: F,
+) where
+    P: Pixel + Send + Sync,
+    F: Fn(usize, &Fragment, &mut P) + Sync,
+{
+    let bins = TileBins::new(triangles, image.width(), image.height(), BIN_SIZE, threads);
+    let next = std::sync::atomic::AtomicUsize::new(0);
+    let source = &*image;
+    let tiles: Vec<(PixelRect, Image<P>)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        if index >= bins.len() {
+                            return done;
+                        }
+                        if bins.triangles(index).is_empty() {
+                            continue;
+                        }
+                        let rect = bins.rect(index);
+                        let mut tile = source
+                            .view(rect.x, rect.y, rect.width, rect.height)
+                            .to_image();
+                        for &t in bins.triangles(index) {
+                            rasterize_triangle_in(&triangles[t], &rect, |f| {
+                                let (x, y) = ((f.x - rect.x) as i32, (f.y - rect.y) as i32);
+                                shade(t, f, tile.get_mut(x, y).unwrap());
+                            });
+                        }
+                        done.push((rect, tile));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    });
+    for (rect, tile) in tiles {
+        image.blit(&tile.as_view(), rect.x as i32, rect.y as i32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::Rgba8;
+    use crate::test_util::{assert_close, random_values};
+
+    fn screen(x: f64, y: f64) -> Vec4<f64> {
+        vec4![x, y, 0.0, 1.0]
+    }
+
+    fn render(width: usize, height: usize, triangles: &[[Vec4<f64>; 3]]) -> String {
+        let mut count = vec![0; width * height];
+        for t in triangles {
+            rasterize_triangle(t, width, height, |f| count[f.y * width + f.x] += 1);
+        }
+        count
+            .chunks(width)
+            .map(|row| {
+                let mut line: String = row
+                    .iter()
+                    .map(|&c| char::from_digit(c, 10).unwrap())
+                    .collect();
+                line.push('\n');
+                line
+            })
+            .collect::<String>()
+            .replace('0', ".")
+    }
+
+    #[test]
+    fn test_top_left_rule() {
+        // Two triangles splitting a square along its diagonal, both edges and the diagonal
+        // passing through pixel centres: each pixel is drawn once, and only the top and
+        // left sides of the square are.
+        let square = [
+            [screen(1.5, 1.5), screen(5.5, 1.5), screen(5.5, 5.5)],
+            [screen(1.5, 1.5), screen(1.5, 5.5), screen(5.5, 5.5)],
+        ];
+        let expected = "\
+            .......\n\
+            .1111..\n\
+            .1111..\n\
+            .1111..\n\
+            .1111..\n\
+            .......\n\
+            .......\n";
+        assert_eq!(render(7, 7, &square), expected);
+        let golden = "\
+            ..........\n\
+            ..111.....\n\
+            ..11111...\n\
+            ..1111111.\n\
+            ..11111...\n\
+            ..111.....\n\
+            ..........\n";
+        let arrow = [[screen(2.5, 0.5), screen(9.5, 3.5), screen(2.5, 6.5)]];
+        assert_eq!(render(10, 7, &arrow), golden);
+
+        // A fan of random triangles around a point, covering a large polygon with many
+        // vertices on pixel centres and edges, is drawn exactly once everywhere inside.
+        let values = random_values(2 * 24, 3);
+        let rim: Vec<Vec4<f64>> = values[..24]
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i as f64 + 0.5 * v) / 24.0 * std::f64::consts::TAU)
+            .zip(&values[24..])
+            .map(|(a, r)| {
+                let r = 20.0 + 10.0 * r;
+                screen(
+                    (24.0 + r * a.cos()).round() + 0.5,
+                    (24.0 + r * a.sin()).round() + 0.5,
+                )
+            })
+            .collect();
+        let center = screen(24.5, 24.5);
+        let fan: Vec<[Vec4<f64>; 3]> = (0..rim.len())
+            .map(|i| {
+                [
+                    center.clone(),
+                    rim[i].clone(),
+                    rim[(i + 1) % rim.len()].clone(),
+                ]
+            })
+            .collect();
+        let text = render(48, 48, &fan);
+        assert!(!text.contains('2'));
+        for y in 18..31 {
+            assert_eq!(&text[y * 49 + 18..y * 49 + 31], "1".repeat(13));
+        }
+        // The same fan wound the other way covers the same pixels.
+        let reversed: Vec<[Vec4<f64>; 3]> = fan
+            .iter()
+            .map(|[a, b, c]| [a.clone(), c.clone(), b.clone()])
+            .collect();
+        assert_eq!(render(48, 48, &reversed), text);
+    }
+
+    #[test]
+    fn test_perspective() {
+        // A triangle in camera space seen through a pinhole: interpolating the camera-space
+        // points gives points that project back onto the pixel centres.
+        let focal = 40.0;
+        let camera = [
+            vec3![-3.0, -2.0, 4.0],
+            vec3![5.0, -1.0, 12.0],
+            vec3![-1.0, 6.0, 7.0],
+        ];
+        let project = |p: &Vec3<f64>| {
+            vec4![
+                32.0 + focal * p[0] / p[2],
+                32.0 + focal * p[1] / p[2],
+                1.0 - 1.0 / p[2],
+                p[2]
+            ]
+        };
+        let positions = camera.clone().map(|p| project(&p));
+        let mut count = 0;
+        rasterize_triangle(&positions, 64, 64, |f| {
+            count += 1;
+            assert_close(f.weights.iter().sum(), 1.0, 1e-12);
+            let p = f.interpolate(&camera);
+            assert_close(32.0 + focal * p[0] / p[2], f.x as f64 + 0.5, 1e-2);
+            assert_close(32.0 + focal * p[1] / p[2], f.y as f64 + 0.5, 1e-2);
+            // Depth is linear on screen, so it matches the point too.
+            assert_close(f.depth, 1.0 - 1.0 / p[2], 1e-4);
+        });
+        assert!(count > 200);
+
+        // Varyings of several kinds at once.
+        let values = [
+            (1.0, vec2![0.0f32, 1.0]),
+            (2.0, vec2![1.0f32, 0.0]),
+            (4.0, vec2![1.0f32, 1.0]),
+        ];
+        let (a, uv) = <(f64, Vec2<f32>)>::interpolate(&values, [0.5, 0.25, 0.25]);
+        assert_close(a, 2.0, 1e-12);
+        assert_eq!(uv, vec2![0.5, 0.75]);
+    }
+
+    #[test]
+    fn test_bins_and_threads() {
+        let (width, height) = (150, 100);
+        let values = random_values(6 * 120, 8);
+        let triangles: Vec<[Vec4<f64>; 3]> = values
+            .chunks(6)
+            .map(|v| {
+                let (cx, cy) = (v[0] * 190.0 - 20.0, v[1] * 140.0 - 20.0);
+                [
+                    screen(cx, cy),
+                    screen(cx + v[2] * 60.0 - 30.0, cy + v[3] * 60.0 - 30.0),
+                    screen(cx + v[4] * 60.0 - 30.0, cy + v[5] * 60.0 - 30.0),
+                ]
+            })
+            .collect();
+
+        let bins = TileBins::new(&triangles, width, height, BIN_SIZE, 3);
+        assert_eq!(bins.len(), 3 * 2);
+        assert_eq!(bins.rect(5), PixelRect::new(128, 64, 22, 36));
+        for index in 0..bins.len() {
+            let rect = bins.rect(index);
+            for (t, triangle) in triangles.iter().enumerate() {
+                let mut touches = false;
+                rasterize_triangle_in(triangle, &rect, |_| touches = true);
+                if touches {
+                    assert!(bins.triangles(index).contains(&t));
+                }
+            }
+            assert!(bins.triangles(index).windows(2).all(|w| w[0] < w[1]));
+        }
+
+        // Drawn in order one at a time, or binned on any number of threads, the result is the
+        // same.
+        let color = |t: usize| Rgba8::new(t as u8, (t * 7) as u8, 255 - t as u8, 255);
+        let mut expected = Image::filled(width, height, Rgba8::BLACK);
+        for (t, triangle) in triangles.iter().enumerate() {
+            rasterize_triangle(triangle, width, height, |f| {
+                expected.set(f.x as i32, f.y as i32, color(t));
+            });
+        }
+        for threads in [1, 4] {
+            let mut image = Image::filled(width, height, Rgba8::BLACK);
+            draw_triangles(&mut image, &triangles, threads, |t, _, p| *p = color(t));
+            assert_eq!(image.pixels(), expected.pixels());
+        }
+    }
+}
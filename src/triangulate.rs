@@ -0,0 +1,537 @@
+use std::fmt;
+
+use crate::delaunay::Triangulation;
+use crate::point::Point2;
+use crate::polygon::{ring_signed_area, Polygon2};
+use crate::predicates::orient2d;
+use crate::segment::Segment2;
+
+/// Why a polygon failed to triangulate: one of its holes lies outside the exterior, so
+/// no bridge edge can join it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriangulateError {
+    /// Index of the hole among the polygon's rings, counting the exterior as 0.
+    pub ring: usize,
+}
+
+impl fmt::Display for TriangulateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "hole {} cannot be bridged to the exterior", self.ring)
+    }
+}
+
+impl std::error::Error for TriangulateError {}
+
+/// A vertex of the ring being clipped, linked to its neighbours.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    index: usize,
+    point: Point2,
+    prev: usize,
+    next: usize,
+}
+
+/// Doubly linked rings of polygon vertices, stored in one arena.
+struct Rings {
+    nodes: Vec<Node>,
+}
+
+impl Rings {
+    /// Links `indices` into a ring and returns its first node.
+    fn push_ring(
+        &mut self,
+        points: &[Point2],
+        indices: impl Iterator<Item = usize>,
+    ) -> Option<usize> {
+        let first = self.nodes.len();
+        for index in indices {
+            let point = points[index];
+            if self.nodes.len() > first && self.nodes.last().unwrap().point == point {
+                continue;
+            }
+            let n = self.nodes.len();
+            self.nodes.push(Node {
+                index,
+                point,
+                prev: n.wrapping_sub(1),
+                next: n + 1,
+            });
+        }
+        while self.nodes.len() > first
+            && self.nodes.last().unwrap().point == self.nodes[first].point
+        {
+            self.nodes.pop();
+        }
+        if self.nodes.len() - first < 3 {
+            self.nodes.truncate(first);
+            return None;
+        }
+        let last = self.nodes.len() - 1;
+        self.nodes[first].prev = last;
+        self.nodes[last].next = first;
+        Some(first)
+    }
+
+    fn point(&self, n: usize) -> Point2 {
+        self.nodes[n].point
+    }
+
+    fn prev(&self, n: usize) -> usize {
+        self.nodes[n].prev
+    }
+
+    fn next(&self, n: usize) -> usize {
+        self.nodes[n].next
+    }
+
+    fn remove(&mut self, n: usize) {
+        let Node { prev, next, .. } = self.nodes[n];
+        self.nodes[prev].next = next;
+        self.nodes[next].prev = prev;
+    }
+
+    /// Signed turn at `n`; positive when the ring turns left (a convex vertex).
+    fn turn(&self, n: usize) -> f64 {
+        orient2d(
+            &self.point(self.prev(n)),
+            &self.point(n),
+            &self.point(self.next(n)),
+        )
+    }
+
+    /// Whether the direction from `n` towards `p` starts inside the ring.
+    fn locally_inside(&self, n: usize, p: &Point2) -> bool {
+        let (a, b, v) = (
+            self.point(self.prev(n)),
+            self.point(self.next(n)),
+            self.point(n),
+        );
+        if orient2d(&a, &v, &b) >= 0.0 {
+            orient2d(&v, &b, p) >= 0.0 && orient2d(&a, &v, p) >= 0.0
+        } else {
+            orient2d(&v, &b, p) >= 0.0 || orient2d(&a, &v, p) >= 0.0
+        }
+    }
+
+    /// Whether clipping `e` leaves no other vertex inside or on the cut-off triangle.
+    fn is_ear(&self, e: usize) -> bool {
+        let (a, c) = (self.prev(e), self.next(e));
+        let [pa, pe, pc] = [a, e, c].map(|n| self.point(n));
+        let mut p = self.next(c);
+        while p != a {
+            let q = self.point(p);
+            // Only reflex or flat vertices can poke into an ear; copies of the ear's own
+            // corners come from bridges and never do.
+            if q != pa
+                && q != pe
+                && q != pc
+                && self.turn(p) <= 0.0
+                && orient2d(&pa, &pe, &q) >= 0.0
+                && orient2d(&pe, &pc, &q) >= 0.0
+                && orient2d(&pc, &pa, &q) >= 0.0
+            {
+                return false;
+            }
+            p = self.next(p);
+        }
+        true
+    }
+
+    /// Joins the hole starting at `hole` into the ring containing `outer`, through a
+    /// bridge from the hole's rightmost vertex to a visible vertex on its right (Eberly).
+    /// Returns false, leaving the rings as they were, when no outer edge lies on its right.
+    fn bridge_hole(&mut self, outer: usize, hole: usize) -> bool {
+        let mut m = hole;
+        let mut n = self.next(hole);
+        while n != hole {
+            let (q, best) = (self.point(n), self.point(m));
+            if q[0] > best[0] || (q[0] == best[0] && q[1] < best[1]) {
+                m = n;
+            }
+            n = self.next(n);
+        }
+        let pm = self.point(m);
+
+        // Nearest edge crossed by the ray from `m` towards +x, entered from inside.
+        let mut hit: Option<(f64, usize)> = None;
+        let mut p = outer;
+        loop {
+            let q = self.next(p);
+            let (a, b) = (self.point(p), self.point(q));
+            if a[1] <= pm[1] && pm[1] <= b[1] && a[1] < b[1] {
+                let x = a[0] + (pm[1] - a[1]) * (b[0] - a[0]) / (b[1] - a[1]);
+                if x >= pm[0] && hit.is_none_or(|(best, _)| x < best) {
+                    let endpoint = if a[0] > b[0] { p } else { q };
+                    let endpoint = if x == a[0] && pm[1] == a[1] {
+                        p
+                    } else if x == b[0] && pm[1] == b[1] {
+                        q
+                    } else {
+                        endpoint
+                    };
+                    hit = Some((x, endpoint));
+                }
+            }
+            p = q;
+            if p == outer {
+                break;
+            }
+        }
+        let Some((x, mut bridge)) = hit else {
+            return false;
+        };
+
+        // A reflex vertex inside the triangle `m, hit, bridge` would hide the bridge;
+        // take the one closest in angle to the ray instead.
+        let pi = Point2::new(x, pm[1]);
+        let pb = self.point(bridge);
+        if pb != pi {
+            let (lo, hi) = if pb[1] < pm[1] { (pb, pi) } else { (pi, pb) };
+            let mut best_tan = f64::INFINITY;
+            let mut p = outer;
+            loop {
+                let q = self.point(p);
+                if p != bridge
+                    && q[0] >= pm[0]
+                    && self.turn(p) <= 0.0
+                    && orient2d(&pm, &lo, &q) >= 0.0
+                    && orient2d(&lo, &hi, &q) >= 0.0
+                    && orient2d(&hi, &pm, &q) >= 0.0
+                    && q != pm
+                {
+                    let tan = (q[1] - pm[1]).abs() / (q[0] - pm[0]);
+                    if tan < best_tan || (tan == best_tan && q[0] < self.point(bridge)[0]) {
+                        best_tan = tan;
+                        bridge = p;
+                    }
+                }
+                p = self.next(p);
+                if p == outer {
+                    break;
+                }
+            }
+        }
+
+        // Earlier bridges duplicate vertices; use the copy whose corner faces the hole.
+        let target = self.point(bridge);
+        let mut p = outer;
+        loop {
+            if self.point(p) == target && self.locally_inside(p, &pm) {
+                bridge = p;
+                break;
+            }
+            p = self.next(p);
+            if p == outer {
+                break;
+            }
+        }
+
+        // Splice: bridge -> m -> ..hole.. -> m' -> bridge' -> rest of the outer ring.
+        let (after, before_m) = (self.next(bridge), self.prev(m));
+        let m2 = self.nodes.len();
+        let b2 = m2 + 1;
+        self.nodes.push(Node {
+            prev: before_m,
+            next: b2,
+            ..self.nodes[m]
+        });
+        self.nodes.push(Node {
+            prev: m2,
+            next: after,
+            ..self.nodes[bridge]
+        });
+        self.nodes[bridge].next = m;
+        self.nodes[m].prev = bridge;
+        self.nodes[before_m].next = m2;
+        self.nodes[after].prev = b2;
+        true
+    }
+}
+
+/// Triangulates a polygon with holes by ear clipping.
+///
+/// The result's points are the polygon's rings concatenated (exterior first) and its
+/// triangles index into them, wound counter-clockwise. Holes are joined to the exterior
+/// by bridge edges, and flat or collinear vertices are skipped instead of producing
+/// zero-area triangles. Self-intersecting rings still triangulate, with intersections
+/// cured locally where possible and otherwise by clipping the next convex corner. Fails
+/// when a hole has no part of the exterior on its right to bridge to.
+pub fn triangulate(polygon: &Polygon2) -> Result<Triangulation, TriangulateError> {
+    let points: Vec<Point2> = polygon.rings().flatten().copied().collect();
+    let mut triangles = vec![];
+    let mut rings = Rings { nodes: vec![] };
+
+    // Exterior counter-clockwise, holes clockwise, whatever the input orientation.
+    let mut offset = 0;
+    let mut starts = vec![];
+    for (k, ring) in polygon.rings().enumerate() {
+        let range = offset..offset + ring.len();
+        offset += ring.len();
+        let reverse = (ring_signed_area(ring) < 0.0) == (k == 0);
+        let start = if reverse {
+            rings.push_ring(&points, range.rev())
+        } else {
+            rings.push_ring(&points, range)
+        };
+        match start {
+            Some(start) => starts.push((k, start)),
+            None if k == 0 => return Ok(Triangulation { points, triangles }),
+            None => {}
+        }
+    }
+
+    let outer = starts[0].1;
+    let mut holes = starts[1..].to_vec();
+    let max_x = |rings: &Rings, start: usize| {
+        let mut x = rings.point(start)[0];
+        let mut n = rings.next(start);
+        while n != start {
+            x = x.max(rings.point(n)[0]);
+            n = rings.next(n);
+        }
+        x
+    };
+    holes.sort_by(|&(_, a), &(_, b)| max_x(&rings, b).total_cmp(&max_x(&rings, a)));
+    for (ring, hole) in holes {
+        if !rings.bridge_hole(outer, hole) {
+            return Err(TriangulateError { ring });
+        }
+    }
+
+    let mut remaining = 1;
+    let mut n = rings.next(outer);
+    while n != outer {
+        remaining += 1;
+        n = rings.next(n);
+    }
+
+    let mut ear = outer;
+    let mut stop = ear;
+    while remaining >= 3 {
+        let (a, c) = (rings.prev(ear), rings.next(ear));
+        let turn = rings.turn(ear);
+        if turn == 0.0 || (turn > 0.0 && rings.is_ear(ear)) {
+            if turn != 0.0 {
+                triangles.push([a, ear, c].map(|n| rings.nodes[n].index));
+            }
+            rings.remove(ear);
+            remaining -= 1;
+            ear = c;
+            stop = c;
+            continue;
+        }
+
+        ear = c;
+        if ear == stop {
+            // A full lap without an ear: the ring intersects itself.
+            ear = cure_local_intersection(&mut rings, ear, &mut triangles, &mut remaining)
+                .unwrap_or_else(|| force_clip(&mut rings, ear, &mut triangles, &mut remaining));
+            stop = ear;
+        }
+    }
+    Ok(Triangulation { points, triangles })
+}
+
+/// Clips a vertex pair that forms a small bow-tie loop, returning where to resume.
+fn cure_local_intersection(
+    rings: &mut Rings,
+    start: usize,
+    triangles: &mut Vec<[usize; 3]>,
+    remaining: &mut usize,
+) -> Option<usize> {
+    let mut p = start;
+    loop {
+        let a = rings.prev(p);
+        let q = rings.next(p);
+        let b = rings.next(q);
+        let [pa, pp, pq, pb] = [a, p, q, b].map(|n| rings.point(n));
+        if *remaining > 3
+            && pa != pb
+            && orient2d(&pa, &pp, &pb) > 0.0
+            && Segment2::new(pa, pp)
+                .intersection(&Segment2::new(pq, pb))
+                .is_some()
+            && rings.locally_inside(a, &pb)
+            && rings.locally_inside(b, &pa)
+        {
+            triangles.push([a, p, b].map(|n| rings.nodes[n].index));
+            rings.remove(p);
+            rings.remove(q);
+            *remaining -= 2;
+            return Some(b);
+        }
+        p = q;
+        if p == start {
+            return None;
+        }
+    }
+}
+
+/// Last resort: clips the first convex corner regardless of what it covers, or drops
+/// a vertex when none is convex.
+fn force_clip(
+    rings: &mut Rings,
+    start: usize,
+    triangles: &mut Vec<[usize; 3]>,
+    remaining: &mut usize,
+) -> usize {
+    let mut p = start;
+    loop {
+        if rings.turn(p) > 0.0 {
+            let (a, c) = (rings.prev(p), rings.next(p));
+            triangles.push([a, p, c].map(|n| rings.nodes[n].index));
+            break;
+        }
+        p = rings.next(p);
+        if p == start {
+            break;
+        }
+    }
+    let next = rings.next(p);
+    rings.remove(p);
+    *remaining -= 1;
+    next
+}
+
+impl Polygon2 {
+    pub fn triangulate(&self) -> Result<Triangulation, TriangulateError> {
+        triangulate(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ring;
+
+    /// Total area of the triangles, asserting each is counter-clockwise and non-degenerate.
+    fn covered_area(t: &Triangulation) -> f64 {
+        t.triangles
+            .iter()
+            .map(|tri| {
+                let [a, b, c] = tri.map(|i| t.points[i]);
+                let area = orient2d(&a, &b, &c) * 0.5;
+                assert!(area > 0.0);
+                area
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_convex_and_concave() {
+        let square = Polygon2::new(ring(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]));
+        let t = square.triangulate().unwrap();
+        assert_eq!(t.triangles.len(), 2);
+        assert_eq!(covered_area(&t), 4.0);
+
+        // A comb with three teeth, given clockwise.
+        let mut comb = ring(&[
+            (0.0, 0.0),
+            (5.0, 0.0),
+            (5.0, 3.0),
+            (4.0, 3.0),
+            (4.0, 1.0),
+            (3.0, 1.0),
+            (3.0, 3.0),
+            (2.0, 3.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 3.0),
+            (0.0, 3.0),
+        ]);
+        comb.reverse();
+        let comb = Polygon2::new(comb);
+        let t = comb.triangulate().unwrap();
+        // Clipping can leave straight runs behind, which are skipped.
+        assert!(t.triangles.len() <= 10);
+        assert!((covered_area(&t) - comb.area()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_collinear_and_duplicates() {
+        let polygon = Polygon2::new(ring(&[
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (3.0, 0.0),
+            (3.0, 3.0),
+            (3.0, 1.5),
+            (3.0, 3.0),
+            (0.0, 3.0),
+            (0.0, 0.0),
+        ]));
+        let t = polygon.triangulate().unwrap();
+        assert_eq!(t.points.len(), 10);
+        assert!((covered_area(&t) - 9.0).abs() < 1e-12);
+
+        // A sliver ear next to a nearly straight run.
+        let sliver = Polygon2::new(ring(&[
+            (0.0, 0.0),
+            (1.0, 1e-12),
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (0.0, 1.0),
+        ]));
+        let t = sliver.triangulate().unwrap();
+        assert!((covered_area(&t) - sliver.area()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_holes() {
+        let polygon = Polygon2::with_holes(
+            ring(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]),
+            vec![
+                ring(&[(2.0, 2.0), (4.0, 2.0), (4.0, 4.0), (2.0, 4.0)]),
+                ring(&[(6.0, 6.0), (8.0, 6.0), (7.0, 8.0)]),
+                // Shares its rightmost x with the first hole.
+                ring(&[(2.0, 6.0), (4.0, 6.0), (3.0, 8.0)]),
+            ],
+        );
+        let t = polygon.triangulate().unwrap();
+        // n + 2h - 2 triangles for n vertices and h holes.
+        assert_eq!(t.triangles.len(), 14 + 6 - 2);
+        assert!((covered_area(&t) - polygon.area()).abs() < 1e-9);
+        for tri in &t.triangles {
+            let [a, b, c] = tri.map(|i| t.points[i]);
+            let centroid = Point2::new((a[0] + b[0] + c[0]) / 3.0, (a[1] + b[1] + c[1]) / 3.0);
+            assert_eq!(polygon.winding_number(&centroid), 1);
+        }
+
+        // A hole right of the exterior has nothing to bridge to.
+        let stray = Polygon2::with_holes(
+            ring(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]),
+            vec![
+                ring(&[(2.0, 2.0), (4.0, 2.0), (4.0, 4.0), (2.0, 4.0)]),
+                ring(&[(12.0, 2.0), (14.0, 2.0), (13.0, 4.0)]),
+            ],
+        );
+        assert_eq!(
+            stray.triangulate().unwrap_err(),
+            TriangulateError { ring: 2 }
+        );
+    }
+
+    #[test]
+    fn test_self_intersecting() {
+        // A bow tie: both lobes are covered, the clockwise one by the fallback.
+        let bow_tie = Polygon2::new(ring(&[(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 2.0)]));
+        let t = bow_tie.triangulate().unwrap();
+        assert!(!t.triangles.is_empty());
+        covered_area(&t);
+
+        let figure_eight = Polygon2::new(ring(&[
+            (0.0, 0.0),
+            (4.0, 0.0),
+            (4.0, 2.0),
+            (1.0, 2.0),
+            (1.0, 3.0),
+            (3.0, 3.0),
+            (3.0, 1.0),
+            (0.0, 1.0),
+        ]));
+        let t = figure_eight.triangulate().unwrap();
+        assert!(!t.triangles.is_empty());
+        covered_area(&t);
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 use std::cmp::{Eq, PartialEq};
+use std::hash::{Hash, Hasher};
 use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Sub, SubAssign};
 use std::slice::SliceIndex;
 
@@ -50,6 +51,12 @@ impl<T: PartialEq> PartialEq for Vec2<T> {
 
 impl<T: Eq> Eq for Vec2<T> {}
 
+impl<T: Hash> Hash for Vec2<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
 impl<T: Add<Output = T> + Copy, R: Into<T> + Copy> Add<R> for Vec2<T> {
     type Output = Self;
 
@@ -171,54 +178,59 @@ impl<T: DivAssign<T> + Copy> DivAssign<Vec2<T>> for Vec2<T> {
 }
 
 impl<T: Copy> Vec2<T> {
-    fn all(v: T) -> Vec2<T> {
+    pub fn all(v: T) -> Vec2<T> {
         vec2![v, v]
     }
 
-    fn x(&self) -> T {
+    pub fn x(&self) -> T {
         self[0]
     }
 
-    fn y(&self) -> T {
+    pub fn y(&self) -> T {
         self[1]
     }
 }
 
 impl<T: Copy + Add<T, Output = T> + Mul<T, Output = T>> Vec2<T> {
-    fn dot(&self, rhs: &Vec2<T>) -> T {
+    pub fn dot(&self, rhs: &Vec2<T>) -> T {
         self[0] * rhs[0] + self[1] * rhs[1]
     }
 }
 
 impl<T: Default + Copy + Sub<T, Output = T> + Mul<T, Output = T>> Vec2<T> {
-    fn cross(&self, rhs: &Vec2<T>) -> Vec3<T> {
+    pub fn cross(&self, rhs: &Vec2<T>) -> Vec3<T> {
         vec3![
             T::default(),
             T::default(),
             self[0] * rhs[1] - self[1] * rhs[0]
         ]
     }
+
+    /// The z component of `cross`, i.e. the signed area of the parallelogram.
+    pub fn perp_dot(&self, rhs: &Vec2<T>) -> T {
+        self[0] * rhs[1] - self[1] * rhs[0]
+    }
 }
 
 impl Vec2<f64> {
-    fn square_length(&self) -> f64 {
+    pub fn square_length(&self) -> f64 {
         self[0] * self[0] + self[1] * self[1]
     }
 
-    fn length(&self) -> f64 {
+    pub fn length(&self) -> f64 {
         self.square_length().sqrt()
     }
 
-    fn normalize(&self) -> Vec2<f64> {
+    pub fn normalize(&self) -> Vec2<f64> {
         let len = self.length();
         vec2![self[0] / len, self[1] / len]
     }
 
-    fn rotate_left_90(&self) -> Vec2<f64> {
+    pub fn rotate_left_90(&self) -> Vec2<f64> {
         vec2![-self[1], self[0]]
     }
 
-    fn rotate_right_90(&self) -> Vec2<f64> {
+    pub fn rotate_right_90(&self) -> Vec2<f64> {
         vec2![self[1], -self[0]]
     }
 }
@@ -248,6 +260,12 @@ impl<T: PartialEq> PartialEq for Vec3<T> {
 
 impl<T: Eq> Eq for Vec3<T> {}
 
+impl<T: Hash> Hash for Vec3<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
 impl<T: Add<Output = T> + Copy, R: Into<T> + Copy> Add<R> for Vec3<T> {
     type Output = Self;
 
@@ -393,41 +411,39 @@ impl<T: DivAssign<T> + Copy> DivAssign<Vec3<T>> for Vec3<T> {
 }
 
 impl<T: Copy> Vec3<T> {
-    fn all(v: T) -> Vec3<T> {
+    pub fn all(v: T) -> Vec3<T> {
         vec3![v, v, v]
     }
 
-    fn x(&self) -> T {
+    pub fn x(&self) -> T {
         self[0]
     }
 
-    fn y(&self) -> T {
+    pub fn y(&self) -> T {
         self[1]
     }
 
-    fn z(&self) -> T {
+    pub fn z(&self) -> T {
         self[2]
     }
 
-    fn xy(&self) -> Vec2<T> {
+    pub fn xy(&self) -> Vec2<T> {
         Vec2([self.x(), self.y()])
     }
 
-    fn yz(&self) -> Vec2<T> {
+    pub fn yz(&self) -> Vec2<T> {
         Vec2([self.y(), self.z()])
     }
 }
 
 impl<T: Copy + Add<T, Output = T> + Mul<T, Output = T>> Vec3<T> {
-    #[allow(dead_code)]
-    fn dot(&self, rhs: &Vec3<T>) -> T {
+    pub fn dot(&self, rhs: &Vec3<T>) -> T {
         self[0] * rhs[0] + self[1] * rhs[1] + self[2] * rhs[2]
     }
 }
 
 impl<T: Copy + Sub<T, Output = T> + Mul<T, Output = T>> Vec3<T> {
-    #[allow(dead_code)]
-    fn cross(&self, rhs: &Vec3<T>) -> Vec3<T> {
+    pub fn cross(&self, rhs: &Vec3<T>) -> Vec3<T> {
         vec3![
             self[1] * rhs[2] - self[2] * rhs[1],
             self[2] * rhs[0] - self[0] * rhs[2],
@@ -437,15 +453,15 @@ impl<T: Copy + Sub<T, Output = T> + Mul<T, Output = T>> Vec3<T> {
 }
 
 impl Vec3<f64> {
-    fn square_length(&self) -> f64 {
+    pub fn square_length(&self) -> f64 {
         self[0] * self[0] + self[1] * self[1] + self[2] * self[2]
     }
 
-    fn length(&self) -> f64 {
+    pub fn length(&self) -> f64 {
         self.square_length().sqrt()
     }
 
-    fn normalize(&self) -> Vec3<f64> {
+    pub fn normalize(&self) -> Vec3<f64> {
         let len = self.length();
         vec3![self[0] / len, self[1] / len, self[2] / len]
     }
@@ -476,6 +492,12 @@ impl<T: PartialEq> PartialEq for Vec4<T> {
 
 impl<T: Eq> Eq for Vec4<T> {}
 
+impl<T: Hash> Hash for Vec4<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
 impl<T: Add<Output = T> + Copy, R: Into<T> + Copy> Add<R> for Vec4<T> {
     type Output = Self;
 
@@ -653,64 +675,63 @@ impl<T: DivAssign<T> + Copy> DivAssign<Vec4<T>> for Vec4<T> {
 }
 
 impl<T: Copy> Vec4<T> {
-    fn all(v: T) -> Vec4<T> {
+    pub fn all(v: T) -> Vec4<T> {
         vec4![v, v, v, v]
     }
 
-    fn x(&self) -> T {
+    pub fn x(&self) -> T {
         self[0]
     }
 
-    fn y(&self) -> T {
+    pub fn y(&self) -> T {
         self[1]
     }
 
-    fn z(&self) -> T {
+    pub fn z(&self) -> T {
         self[2]
     }
 
-    fn w(&self) -> T {
+    pub fn w(&self) -> T {
         self[3]
     }
 
-    fn xy(&self) -> Vec2<T> {
+    pub fn xy(&self) -> Vec2<T> {
         vec2![self.x(), self.y()]
     }
 
-    fn yz(&self) -> Vec2<T> {
+    pub fn yz(&self) -> Vec2<T> {
         vec2![self.y(), self.z()]
     }
 
-    fn zw(&self) -> Vec2<T> {
+    pub fn zw(&self) -> Vec2<T> {
         vec2![self.z(), self.w()]
     }
 
-    fn xyz(&self) -> Vec3<T> {
+    pub fn xyz(&self) -> Vec3<T> {
         vec3![self.x(), self.y(), self.z()]
     }
 
-    fn yzw(&self) -> Vec3<T> {
+    pub fn yzw(&self) -> Vec3<T> {
         vec3![self.y(), self.z(), self.w()]
     }
 }
 
 impl<T: Copy + Add<T, Output = T> + Mul<T, Output = T>> Vec4<T> {
-    #[allow(dead_code)]
-    fn dot(&self, rhs: &Vec4<T>) -> T {
+    pub fn dot(&self, rhs: &Vec4<T>) -> T {
         self[0] * rhs[0] + self[1] * rhs[1] + self[2] * rhs[2] + self[3] * rhs[3]
     }
 }
 
 impl Vec4<f64> {
-    fn square_length(&self) -> f64 {
+    pub fn square_length(&self) -> f64 {
         self[0] * self[0] + self[1] * self[1] + self[2] * self[2] + self[3] * self[3]
     }
 
-    fn length(&self) -> f64 {
+    pub fn length(&self) -> f64 {
         self.square_length().sqrt()
     }
 
-    fn normalize(&self) -> Vec4<f64> {
+    pub fn normalize(&self) -> Vec4<f64> {
         let len = self.length();
         vec4![self[0] / len, self[1] / len, self[2] / len, self[3] / len]
     }